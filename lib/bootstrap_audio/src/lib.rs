@@ -1,3 +1,5 @@
+extern crate bootstrap_rs as bootstrap;
+
 #[cfg(windows)]
 #[path="windows.rs"]
 mod audio_impl;
@@ -6,4 +8,240 @@ mod audio_impl;
 #[path="linux.rs"]
 mod audio_impl;
 
-pub use audio_impl::{AudioSource, init};
+pub mod wav;
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+pub use audio_impl::{AudioSource, init, init_with_latency};
+pub use wav::{WavReader, WavError};
+
+/// The latency used by `init()`, in milliseconds.
+///
+/// This is short enough for interactive sound effects while still leaving the game a couple of
+/// frames to refill the buffer.
+pub const DEFAULT_LATENCY_MS: u64 = 30;
+
+/// The length of a reference time unit, the 100 nanosecond unit used by Windows for audio buffer
+/// durations.
+const NANOSECONDS_PER_REFERENCE_TIME: u64 = 100;
+
+/// Converts a duration to reference time units (100 nanosecond intervals), rounding up so that a
+/// non-zero duration never becomes zero. Durations too long to represent are saturated.
+pub fn duration_to_reference_time(duration: Duration) -> i64 {
+    let max_seconds = ::std::i64::MAX as u64 / (1_000_000_000 / NANOSECONDS_PER_REFERENCE_TIME);
+    if duration.as_secs() >= max_seconds {
+        return ::std::i64::MAX;
+    }
+
+    let units = duration.as_secs() * (1_000_000_000 / NANOSECONDS_PER_REFERENCE_TIME)
+        + (duration.subsec_nanos() as u64 + NANOSECONDS_PER_REFERENCE_TIME - 1) / NANOSECONDS_PER_REFERENCE_TIME;
+    units as i64
+}
+
+/// Calculates the buffer duration in reference time units to request from the device for the
+/// given latency.
+///
+/// # Details
+///
+/// A device can't use a buffer shorter than its minimum period, so a latency below
+/// `device_minimum` (also in reference time units) is clamped up to it rather than being
+/// treated as an error.
+pub fn buffer_duration(latency: Duration, device_minimum: i64) -> i64 {
+    ::std::cmp::max(duration_to_reference_time(latency), device_minimum)
+}
+
+/// A function invoked with the total number of underruns each time an underrun is detected.
+pub type UnderrunCallback = fn(usize);
+
+/// The ways in which initializing the audio subsystem can fail.
+///
+/// Variants that wrap a value contain the error code (an `HRESULT` on Windows) returned by the
+/// failing system call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioInitError {
+    /// COM could not be initialized.
+    ComInit(i32),
+
+    /// The audio device enumerator could not be created.
+    EnumeratorFailed(i32),
+
+    /// There is no default audio output device.
+    NoDevice,
+
+    /// The audio client could not be activated on the output device.
+    ActivateFailed(i32),
+
+    /// The output device doesn't support the requested format or anything similar to it.
+    UnsupportedFormat,
+
+    /// Querying the device for format support failed.
+    FormatQueryFailed(i32),
+
+    /// The size of the device buffer could not be retrieved.
+    BufferSizeFailed(i32),
+
+    /// The render client could not be retrieved from the audio client.
+    ServiceFailed(i32),
+}
+
+impl Display for AudioInitError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            AudioInitError::ComInit(code) => write!(formatter, "failed to initialize COM (error code 0x{:x})", code),
+            AudioInitError::EnumeratorFailed(code) => write!(formatter, "failed to create the audio device enumerator (error code 0x{:x})", code),
+            AudioInitError::NoDevice => write!(formatter, "no default audio output device was found"),
+            AudioInitError::ActivateFailed(code) => write!(formatter, "failed to activate the audio client (error code 0x{:x})", code),
+            AudioInitError::UnsupportedFormat => write!(formatter, "the audio format is not supported and no similar one can be found"),
+            AudioInitError::FormatQueryFailed(code) => write!(formatter, "failed to query supported audio formats (error code 0x{:x})", code),
+            AudioInitError::BufferSizeFailed(code) => write!(formatter, "failed to retrieve the audio buffer size (error code 0x{:x})", code),
+            AudioInitError::ServiceFailed(code) => write!(formatter, "failed to retrieve the audio render client (error code 0x{:x})", code),
+        }
+    }
+}
+
+impl Error for AudioInitError {
+    fn description(&self) -> &str {
+        match *self {
+            AudioInitError::ComInit(_) => "failed to initialize COM",
+            AudioInitError::EnumeratorFailed(_) => "failed to create the audio device enumerator",
+            AudioInitError::NoDevice => "no default audio output device was found",
+            AudioInitError::ActivateFailed(_) => "failed to activate the audio client",
+            AudioInitError::UnsupportedFormat => "the audio format is not supported",
+            AudioInitError::FormatQueryFailed(_) => "failed to query supported audio formats",
+            AudioInitError::BufferSizeFailed(_) => "failed to retrieve the audio buffer size",
+            AudioInitError::ServiceFailed(_) => "failed to retrieve the audio render client",
+        }
+    }
+}
+
+/// Detects when the audio device runs out of samples to play.
+///
+/// # Details
+///
+/// Each time samples are streamed to the device `record()` is given the amount of data still
+/// queued in the device buffer (the padding), the amount of space that was available, and the
+/// amount of new data written. If data was queued after the previous call but the padding is now
+/// zero the device played everything and then starved, which is counted as an underrun. Each
+/// drain-out is only counted once, and an empty buffer before anything has been written (e.g. the
+/// very first call) is not an underrun.
+///
+/// If the data source wrote less than there was space for it has run out of data, e.g. because
+/// the sound finished. The buffer draining after that is expected, so it isn't counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnderrunDetector {
+    pending: bool,
+    underrun_count: usize,
+}
+
+impl UnderrunDetector {
+    pub fn new() -> UnderrunDetector {
+        UnderrunDetector {
+            pending: false,
+            underrun_count: 0,
+        }
+    }
+
+    /// Records a call to stream data, returning `true` if an underrun occurred since the last call.
+    ///
+    /// `padding` is the number of frames queued in the device buffer before writing,
+    /// `frames_available` is the number of frames there was space to write, and `frames_written`
+    /// is the number of frames that were then written.
+    pub fn record(&mut self, padding: u32, frames_available: u32, frames_written: u32) -> bool {
+        let underrun = self.pending && padding == 0;
+        if underrun {
+            self.underrun_count += 1;
+        }
+
+        let source_exhausted = frames_written < frames_available;
+        self.pending = !source_exhausted && (padding > 0 || frames_written > 0);
+        underrun
+    }
+
+    /// The total number of underruns detected.
+    pub fn underrun_count(&self) -> usize {
+        self.underrun_count
+    }
+}
+
+#[test]
+fn underrun_detection() {
+    let mut detector = UnderrunDetector::new();
+
+    // An empty buffer before anything has been written isn't an underrun.
+    assert!(!detector.record(0, 0, 0));
+    assert!(!detector.record(0, 100, 100));
+
+    // The buffer is being kept fed.
+    assert!(!detector.record(40, 60, 60));
+    assert!(!detector.record(10, 90, 90));
+    assert!(!detector.record(100, 0, 0));
+    assert_eq!(detector.underrun_count(), 0);
+
+    // The buffer drained completely while data was pending.
+    assert!(detector.record(0, 100, 100));
+    assert_eq!(detector.underrun_count(), 1);
+
+    // Full again, but the source can't keep up with the device so it drains out again.
+    assert!(!detector.record(100, 0, 0));
+    assert!(detector.record(0, 100, 100));
+    assert_eq!(detector.underrun_count(), 2);
+}
+
+#[test]
+fn underrun_not_counted_when_sound_ends() {
+    let mut detector = UnderrunDetector::new();
+
+    // The sound is playing.
+    assert!(!detector.record(0, 100, 100));
+    assert!(!detector.record(50, 50, 50));
+
+    // The last of the sound only partially fills the buffer, then the buffer plays out and the
+    // source has nothing more to write.
+    assert!(!detector.record(40, 60, 20));
+    assert!(!detector.record(10, 90, 0));
+    assert!(!detector.record(0, 100, 0));
+    assert!(!detector.record(0, 100, 0));
+    assert_eq!(detector.underrun_count(), 0);
+
+    // A new sound starting afterwards is tracked as usual.
+    assert!(!detector.record(0, 100, 100));
+    assert!(detector.record(0, 100, 100));
+    assert_eq!(detector.underrun_count(), 1);
+}
+
+#[test]
+fn audio_init_error_display() {
+    assert_eq!(format!("{}", AudioInitError::ComInit(0x80010106u32 as i32)), "failed to initialize COM (error code 0x80010106)");
+    assert_eq!(format!("{}", AudioInitError::EnumeratorFailed(0x1)), "failed to create the audio device enumerator (error code 0x1)");
+    assert_eq!(format!("{}", AudioInitError::NoDevice), "no default audio output device was found");
+    assert_eq!(format!("{}", AudioInitError::ActivateFailed(0x2)), "failed to activate the audio client (error code 0x2)");
+    assert_eq!(format!("{}", AudioInitError::UnsupportedFormat), "the audio format is not supported and no similar one can be found");
+    assert_eq!(format!("{}", AudioInitError::FormatQueryFailed(0x3)), "failed to query supported audio formats (error code 0x3)");
+    assert_eq!(format!("{}", AudioInitError::BufferSizeFailed(0x4)), "failed to retrieve the audio buffer size (error code 0x4)");
+    assert_eq!(format!("{}", AudioInitError::ServiceFailed(0x5)), "failed to retrieve the audio render client (error code 0x5)");
+}
+
+#[test]
+fn reference_time_conversion() {
+    assert_eq!(duration_to_reference_time(Duration::new(0, 0)), 0);
+    assert_eq!(duration_to_reference_time(Duration::from_millis(DEFAULT_LATENCY_MS)), 300_000);
+    assert_eq!(duration_to_reference_time(Duration::new(1, 0)), 10_000_000);
+    assert_eq!(duration_to_reference_time(Duration::new(2, 500)), 20_000_005);
+
+    // Partial units round up.
+    assert_eq!(duration_to_reference_time(Duration::new(0, 1)), 1);
+    assert_eq!(duration_to_reference_time(Duration::new(0, 150)), 2);
+
+    assert_eq!(duration_to_reference_time(Duration::new(::std::u64::MAX, 0)), ::std::i64::MAX);
+}
+
+#[test]
+fn buffer_duration_clamps_to_device_minimum() {
+    let minimum = 30_000; // 3ms, a typical minimum device period.
+    assert_eq!(buffer_duration(Duration::from_millis(DEFAULT_LATENCY_MS), minimum), 300_000);
+    assert_eq!(buffer_duration(Duration::from_millis(3), minimum), 30_000);
+    assert_eq!(buffer_duration(Duration::from_millis(1), minimum), 30_000);
+    assert_eq!(buffer_duration(Duration::new(0, 0), minimum), 30_000);
+}
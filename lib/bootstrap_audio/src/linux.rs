@@ -1,13 +1,40 @@
+use std::time::Duration;
+
+use bootstrap::log;
+use AudioInitError;
+use UnderrunCallback;
+use DEFAULT_LATENCY_MS;
+
 #[derive(Debug, Clone)]
 pub struct AudioSource;
 
 impl AudioSource {
+    pub fn channels(&self) -> u32 {
+        2
+    }
+
+    /// The number of times the device has run out of samples to play.
+    ///
+    /// Audio output isn't implemented on Linux, so this is always zero.
+    pub fn underrun_count(&self) -> usize {
+        0
+    }
+
+    /// Sets a function to be invoked whenever an underrun is detected.
+    ///
+    /// Audio output isn't implemented on Linux, so the callback is never invoked.
+    pub fn set_underrun_callback(&mut self, _callback: Option<UnderrunCallback>) {}
+
     pub fn stream<T: Iterator<Item = u16>>(&self, _data_source: &mut T, _max_time: f32) -> usize {
         0
     }
 }
 
-pub fn init() -> Result<AudioSource, String> {
-    println!("bootstrap_audio::init() has not been implemented yet for linux");
+pub fn init() -> Result<AudioSource, AudioInitError> {
+    init_with_latency(Duration::from_millis(DEFAULT_LATENCY_MS))
+}
+
+pub fn init_with_latency(_latency: Duration) -> Result<AudioSource, AudioInitError> {
+    log::warn("bootstrap_audio::init() has not been implemented yet for linux");
     Ok(AudioSource)
 }
@@ -0,0 +1,242 @@
+//! Minimal parsing for in-memory WAV data.
+//!
+//! Only uncompressed 16 bit PCM data is supported, which is the format expected by
+//! `AudioSource::stream()`.
+
+use std::fmt::{self, Display, Formatter};
+
+const RIFF: &'static [u8] = b"RIFF";
+const WAVE: &'static [u8] = b"WAVE";
+const FMT:  &'static [u8] = b"fmt ";
+const DATA: &'static [u8] = b"data";
+
+const FORMAT_PCM: u16 = 1;
+const CHUNK_HEADER_SIZE: usize = 8;
+const FORMAT_CHUNK_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavError {
+    /// The data doesn't start with a RIFF header with the WAVE type.
+    NotWave,
+
+    /// The data ended in the middle of a required chunk.
+    UnexpectedEnd,
+
+    /// No "fmt " chunk was found before the "data" chunk.
+    MissingFormat,
+
+    /// No "data" chunk was found.
+    MissingData,
+
+    /// The audio data is compressed, the wrapped value is the format tag.
+    UnsupportedFormat(u16),
+
+    /// The samples aren't 16 bit, the wrapped value is the sample size in bits.
+    UnsupportedBitDepth(u16),
+}
+
+impl Display for WavError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            WavError::NotWave => write!(formatter, "data is not a RIFF WAVE file"),
+            WavError::UnexpectedEnd => write!(formatter, "WAV data ended in the middle of a chunk"),
+            WavError::MissingFormat => write!(formatter, "WAV data has no \"fmt \" chunk before the \"data\" chunk"),
+            WavError::MissingData => write!(formatter, "WAV data has no \"data\" chunk"),
+            WavError::UnsupportedFormat(format) => write!(formatter, "WAV format tag {} is not supported, only PCM (1) is supported", format),
+            WavError::UnsupportedBitDepth(bits) => write!(formatter, "{} bit samples are not supported, only 16 bit samples are supported", bits),
+        }
+    }
+}
+
+/// Reads 16 bit PCM samples from WAV data in memory.
+#[derive(Debug, Clone, Copy)]
+pub struct WavReader<'a> {
+    channels: u16,
+    sample_rate: u32,
+    data: &'a [u8],
+}
+
+impl<'a> WavReader<'a> {
+    /// Parses the WAV header, validating that the data is 16 bit PCM.
+    ///
+    /// # Details
+    ///
+    /// Chunks other than "fmt " and "data" are skipped. If the data chunk is truncated (i.e. its
+    /// header reports more bytes than are in `bytes`) only the samples that are actually present
+    /// will be read.
+    pub fn new(bytes: &'a [u8]) -> Result<WavReader<'a>, WavError> {
+        if bytes.len() < 12 || &bytes[0..4] != RIFF || &bytes[8..12] != WAVE {
+            return Err(WavError::NotWave);
+        }
+
+        let mut format = None;
+        let mut offset = 12;
+        while offset + CHUNK_HEADER_SIZE <= bytes.len() {
+            let id = &bytes[offset..offset + 4];
+            let chunk_size = read_u32(bytes, offset + 4) as usize;
+            let chunk_start = offset + CHUNK_HEADER_SIZE;
+
+            if id == FMT {
+                if chunk_size < FORMAT_CHUNK_SIZE || chunk_start + FORMAT_CHUNK_SIZE > bytes.len() {
+                    return Err(WavError::UnexpectedEnd);
+                }
+
+                let format_tag = read_u16(bytes, chunk_start);
+                let channels = read_u16(bytes, chunk_start + 2);
+                let sample_rate = read_u32(bytes, chunk_start + 4);
+                let bits_per_sample = read_u16(bytes, chunk_start + 14);
+
+                if format_tag != FORMAT_PCM {
+                    return Err(WavError::UnsupportedFormat(format_tag));
+                }
+
+                if bits_per_sample != 16 {
+                    return Err(WavError::UnsupportedBitDepth(bits_per_sample));
+                }
+
+                format = Some((channels, sample_rate));
+            } else if id == DATA {
+                let (channels, sample_rate) = match format {
+                    Some(format) => format,
+                    None => return Err(WavError::MissingFormat),
+                };
+
+                // Clamp a truncated data chunk to the available bytes and drop any trailing
+                // partial sample.
+                let data_end = ::std::cmp::min(chunk_start + chunk_size, bytes.len());
+                let data = &bytes[chunk_start..data_end];
+                let data = &data[..data.len() - data.len() % 2];
+
+                return Ok(WavReader {
+                    channels: channels,
+                    sample_rate: sample_rate,
+                    data: data,
+                });
+            }
+
+            // Chunks are padded to an even number of bytes.
+            offset = chunk_start + chunk_size + chunk_size % 2;
+        }
+
+        Err(WavError::MissingData)
+    }
+
+    /// The number of interleaved channels in the sample data.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// The number of sample frames per second.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns an iterator over the samples, with the samples for each channel interleaved.
+    pub fn samples(&self) -> Samples<'a> {
+        Samples {
+            data: self.data,
+        }
+    }
+}
+
+/// An iterator over the 16 bit samples in a WAV file.
+#[derive(Debug, Clone)]
+pub struct Samples<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Samples<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        if self.data.len() < 2 {
+            return None;
+        }
+
+        let sample = read_u16(self.data, 0);
+        self.data = &self.data[2..];
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.data.len() / 2;
+        (remaining, Some(remaining))
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    bytes[offset] as u16 | (bytes[offset + 1] as u16) << 8
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    read_u16(bytes, offset) as u32 | (read_u16(bytes, offset + 2) as u32) << 16
+}
+
+#[cfg(test)]
+fn build_wav(format_tag: u16, bits_per_sample: u16, samples: &[u16], data_size: u32) -> Vec<u8> {
+    fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+        bytes.push(value as u8);
+        bytes.push((value >> 8) as u8);
+    }
+
+    fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+        push_u16(bytes, value as u16);
+        push_u16(bytes, (value >> 16) as u16);
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend(RIFF);
+    push_u32(&mut bytes, 36 + data_size);
+    bytes.extend(WAVE);
+
+    bytes.extend(FMT);
+    push_u32(&mut bytes, 16);
+    push_u16(&mut bytes, format_tag);
+    push_u16(&mut bytes, 2);            // Channels.
+    push_u32(&mut bytes, 44100);        // Sample rate.
+    push_u32(&mut bytes, 44100 * 2 * 2);// Byte rate.
+    push_u16(&mut bytes, 4);            // Block align.
+    push_u16(&mut bytes, bits_per_sample);
+
+    bytes.extend(DATA);
+    push_u32(&mut bytes, data_size);
+    for sample in samples {
+        push_u16(&mut bytes, *sample);
+    }
+
+    bytes
+}
+
+#[test]
+fn read_samples() {
+    let bytes = build_wav(FORMAT_PCM, 16, &[1, 2, 0xFFFF, 0x1234], 8);
+    let reader = WavReader::new(&*bytes).unwrap();
+
+    assert_eq!(reader.channels(), 2);
+    assert_eq!(reader.sample_rate(), 44100);
+    assert_eq!(reader.samples().collect::<Vec<_>>(), vec![1, 2, 0xFFFF, 0x1234]);
+}
+
+#[test]
+fn truncated_data() {
+    // Header claims 8 samples but only 3 are present, with an extra half sample at the end.
+    let mut bytes = build_wav(FORMAT_PCM, 16, &[1, 2, 3], 16);
+    bytes.push(0xAB);
+
+    let reader = WavReader::new(&*bytes).unwrap();
+    assert_eq!(reader.samples().collect::<Vec<_>>(), vec![1, 2, 3]);
+}
+
+#[test]
+fn unsupported_formats() {
+    assert_eq!(WavReader::new(b"not a wav file").unwrap_err(), WavError::NotWave);
+
+    let bytes = build_wav(3, 16, &[1, 2], 4);
+    assert_eq!(WavReader::new(&*bytes).unwrap_err(), WavError::UnsupportedFormat(3));
+
+    let bytes = build_wav(FORMAT_PCM, 8, &[1, 2], 4);
+    assert_eq!(WavReader::new(&*bytes).unwrap_err(), WavError::UnsupportedBitDepth(8));
+
+    let bytes = build_wav(FORMAT_PCM, 16, &[], 0);
+    assert_eq!(WavReader::new(&bytes[..36]).unwrap_err(), WavError::MissingData);
+}
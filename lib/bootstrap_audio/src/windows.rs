@@ -3,9 +3,19 @@ extern crate ole32;
 
 use std::ptr;
 use std::mem;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
 
 use self::winapi::*;
 
+use bootstrap::log;
+use AudioInitError;
+use UnderrunCallback;
+use UnderrunDetector;
+use DEFAULT_LATENCY_MS;
+use buffer_duration;
+
 #[derive(Debug)]
 pub struct AudioSource {
     audio_client: *mut IAudioClient,
@@ -15,9 +25,38 @@ pub struct AudioSource {
     bytes_per_frame: u32,
     bytes_per_sample: u32,
     samples_per_second: u32,
+
+    /// Shared between clones since they all stream to the same device buffer.
+    underruns: Rc<Cell<UnderrunDetector>>,
+    on_underrun: Option<UnderrunCallback>,
+
+    /// Keeps COM initialized on this thread until the last clone of the audio source is dropped.
+    ///
+    /// Declared after the COM interfaces so that it's dropped after they are released.
+    _com: Option<Rc<ComApartment>>,
 }
 
 impl AudioSource {
+    /// The number of channels the output device expects in each frame.
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// The number of times the device has run out of samples to play.
+    ///
+    /// When this happens the device plays stale data or silence, causing audible artifacts. See
+    /// `UnderrunDetector` for details on what counts as an underrun.
+    pub fn underrun_count(&self) -> usize {
+        self.underruns.get().underrun_count()
+    }
+
+    /// Sets a function to be invoked from `stream()` whenever an underrun is detected.
+    ///
+    /// The callback is passed the total number of underruns so far.
+    pub fn set_underrun_callback(&mut self, callback: Option<UnderrunCallback>) {
+        self.on_underrun = callback;
+    }
+
     /// Stream samples to the audio buffer.
     ///
     /// # Params
@@ -32,16 +71,18 @@ impl AudioSource {
         let audio_client = &mut *self.audio_client;
         let render_client = &mut *self.render_client;
 
-        let frames_available = {
+        let padding = {
             let mut padding = mem::uninitialized();
             let hresult = audio_client.GetCurrentPadding(&mut padding);
             if hresult != S_OK {
                 panic!("IAudioClient::GetCurrentPadding() failed with code 0x{:x}", hresult);
             }
-            self.max_frames_in_buffer - padding
+            padding
         };
+        let frames_available = self.max_frames_in_buffer - padding;
 
         if frames_available == 0 {
+            self.record_underruns(padding, 0, 0);
             return 0
         }
 
@@ -82,8 +123,22 @@ impl AudioSource {
 
         audio_client.Start();
 
+        self.record_underruns(padding, frames_available, samples_written / self.channels);
+
         samples_written as usize
     } }
+
+    fn record_underruns(&self, padding: u32, frames_available: u32, frames_written: u32) {
+        let mut underruns = self.underruns.get();
+        let underrun = underruns.record(padding, frames_available, frames_written);
+        self.underruns.set(underruns);
+
+        if underrun {
+            if let Some(callback) = self.on_underrun {
+                callback(underruns.underrun_count());
+            }
+        }
+    }
 }
 
 impl Clone for AudioSource {
@@ -101,6 +156,9 @@ impl Clone for AudioSource {
             bytes_per_frame: self.bytes_per_frame,
             bytes_per_sample: self.bytes_per_sample,
             samples_per_second: self.samples_per_second,
+            underruns: self.underruns.clone(),
+            on_underrun: self.on_underrun,
+            _com: self._com.clone(),
         }
     }
 }
@@ -109,16 +167,47 @@ impl Drop for AudioSource {
     fn drop(&mut self) { unsafe {
         (&mut *self.audio_client).Release();
         (&mut *self.render_client).Release();
-        ole32::CoUninitialize();
+
+        // COM is uninitialized when `_com` is dropped, after the interfaces have been released.
     } }
 }
 
-pub fn init() -> Result<AudioSource, String> { unsafe {
+/// Balances a successful call to `CoInitializeEx()` with a call to `CoUninitialize()`.
+#[derive(Debug)]
+struct ComApartment;
+
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        unsafe { ole32::CoUninitialize(); }
+    }
+}
+
+/// Initializes audio output on the default device with a latency of `DEFAULT_LATENCY_MS`.
+pub fn init() -> Result<AudioSource, AudioInitError> {
+    init_with_latency(Duration::from_millis(DEFAULT_LATENCY_MS))
+}
+
+/// Initializes audio output on the default device.
+///
+/// # Details
+///
+/// `latency` sets the size of the device buffer. A lower latency makes sounds start sooner but
+/// gives the game less slack, so `AudioSource::stream()` must be called more often to avoid
+/// underruns. A latency below the device's minimum period is clamped to the minimum.
+pub fn init_with_latency(latency: Duration) -> Result<AudioSource, AudioInitError> { unsafe {
     // TODO: Initialize with multithreading support once for better performance.
+    //
+    // Both `S_OK` and `S_FALSE` (COM was already initialized on this thread) must be balanced by
+    // a call to `CoUninitialize()`. `RPC_E_CHANGED_MODE` means COM was already initialized with a
+    // different concurrency model by someone else, in which case we can still use it but must
+    // not uninitialize it. Creating the apartment guard immediately ensures that COM is
+    // uninitialized if any of the later steps fail.
     let hresult = ole32::CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED);
-    if hresult != S_OK {
-        return Err(format!("ole32::CoInitializeEx() failed with error code 0x{:x}", hresult))
-    }
+    let com = match hresult {
+        S_OK | S_FALSE => Some(Rc::new(ComApartment)),
+        RPC_E_CHANGED_MODE => None,
+        _ => return Err(AudioInitError::ComInit(hresult)),
+    };
 
     // Build the devices enumerator.
     let enumerator = {
@@ -133,7 +222,7 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
                 mem::transmute(&mut enumerator));
 
         if hresult != S_OK {
-           return Err(format!("ole32::CoCreateInstance() failed with error code 0x{:x}", hresult))
+           return Err(AudioInitError::EnumeratorFailed(hresult))
         }
         &mut *enumerator
     };
@@ -148,7 +237,7 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
             mem::transmute(&mut device));
 
         if hresult != S_OK {
-           return Err(format!("IMMDeviceEnumerator::GetDefaultAudioEndpoint() failed with error code 0x{:x}", hresult))
+           return Err(AudioInitError::NoDevice)
         }
         &mut *device
     };
@@ -164,7 +253,7 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
                              mem::transmute(&mut audio_client));
 
         if hresult != S_OK {
-            return Err(format!("IAudioClient::Activate() failed with error code 0x{:x}", hresult))
+            return Err(AudioInitError::ActivateFailed(hresult))
         }
         &mut *audio_client
     };
@@ -193,9 +282,9 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
         && hresult != S_FALSE
         {
             return if hresult == AUDCLNT_E_UNSUPPORTED_FORMAT {
-                Err(format!("The specified audio format is not supported and no similar one can be found"))
+                Err(AudioInitError::UnsupportedFormat)
             } else {
-                Err(format!("IAudioClient::IsFormatSupported() return failure code {:x}", hresult))
+                Err(AudioInitError::FormatQueryFailed(hresult))
             }
         }
 
@@ -207,11 +296,25 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
         };
         let format_copy = ptr::read(format);
 
+        // Clamp the buffer to the device's minimum period. If the period can't be retrieved the
+        // requested latency is used as-is and the device rounds it up if necessary.
+        let device_minimum = {
+            let mut default_period: REFERENCE_TIME = 0;
+            let mut minimum_period: REFERENCE_TIME = 0;
+            let hresult = audio_client.GetDevicePeriod(&mut default_period, &mut minimum_period);
+            if hresult == S_OK {
+                minimum_period
+            } else {
+                log::warn(&format!("IAudioClient::GetDevicePeriod() failed with hresult 0x{:x}", hresult));
+                0
+            }
+        };
+
         // Initialize the audio client with the chosen format.
         let hresult = audio_client.Initialize(
             AUDCLNT_SHAREMODE_SHARED,
             0,
-            10000000,
+            buffer_duration(latency, device_minimum),
             0,
             format,
             ptr::null());
@@ -223,7 +326,7 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
 
         match hresult {
             S_OK => {},
-            _ => println!("IAudioClient::Initialize() failed with hresult 0x{:x}", hresult),
+            _ => log::error(&format!("IAudioClient::Initialize() failed with hresult 0x{:x}", hresult)),
         }
 
         format_copy
@@ -233,7 +336,7 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
         let mut max_frames_in_buffer = mem::uninitialized();
         let hresult = audio_client.GetBufferSize(&mut max_frames_in_buffer);
         if hresult != S_OK {
-           return Err(format!("IAudioClient::GetBufferSize() failed with error code 0x{:x}", hresult))
+           return Err(AudioInitError::BufferSizeFailed(hresult))
         }
         max_frames_in_buffer
     };
@@ -243,7 +346,7 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
         let hresult = audio_client.GetService(&IID_IAudioRenderClient,
                         mem::transmute(&mut render_client));
         if hresult != S_OK {
-           return Err(format!("IAudioClient::GetService() failed with error code 0x{:x}", hresult))
+           return Err(AudioInitError::ServiceFailed(hresult))
         }
         &mut *render_client
     };
@@ -266,5 +369,25 @@ pub fn init() -> Result<AudioSource, String> { unsafe {
         bytes_per_frame: format.nBlockAlign as u32,
         bytes_per_sample: mem::size_of::<u16>() as u32,
         samples_per_second: format.nSamplesPerSec,
+        underruns: Rc::new(Cell::new(UnderrunDetector::new())),
+        on_underrun: None,
+        _com: com,
     })
 } }
+
+#[test]
+fn repeated_init() {
+    for _ in 0..4 {
+        let audio_source = init().unwrap();
+        let clone = audio_source.clone();
+        drop(audio_source);
+        drop(clone);
+    }
+
+    // Once every audio source has been dropped COM should be fully uninitialized, so initializing
+    // it again must report `S_OK` rather than `S_FALSE`.
+    unsafe {
+        assert_eq!(ole32::CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED), S_OK);
+        ole32::CoUninitialize();
+    }
+}
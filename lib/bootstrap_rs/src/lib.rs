@@ -13,6 +13,7 @@ pub mod linux;
 #[cfg(unix)]
 pub use linux::init::init;
 
+pub mod log;
 pub mod window;
 pub mod input;
 pub mod time;
@@ -87,33 +87,68 @@ impl Window {
         let mut event = mem::uninitialized::<xlib::XEvent>();
         while xlib::XPending(self.display) > 0 {
             xlib::XNextEvent(self.display, &mut event);
-            match event.get_type() {
-                xlib::KeyPress => {
-                    let key_press_event: &xlib::XKeyPressedEvent = mem::transmute(&event);
-
-                    let mut num_syms = 0;
-                    let ptr_key_sym = xlib::XGetKeyboardMapping(self.display, key_press_event.keycode as u8, 1, &mut num_syms);
-                    let syms_slice = slice::from_raw_parts(ptr_key_sym, num_syms as usize);
-
-                    let us_sym = syms_slice[0];
-                    return Some(Message::KeyDown(key_sym_to_scancode(us_sym)));
-                },
-                xlib::KeyRelease => {
-                    let key_release_event: &xlib::XKeyReleasedEvent = mem::transmute(&event);
-
-                    let mut num_syms = 0;
-                    let ptr_key_sym = xlib::XGetKeyboardMapping(self.display, key_release_event.keycode as u8, 1, &mut num_syms);
-                    let syms_slice = slice::from_raw_parts(ptr_key_sym, num_syms as usize);
-
-                    let us_sym = syms_slice[0];
-                    return Some(Message::KeyUp(key_sym_to_scancode(us_sym)));
-                },
-                _ => println!("unsupported event type: {}", event.get_type()),
+            if let Some(message) = self.convert_event(&event) {
+                return Some(message);
             }
         }
 
         None
     } }
+
+    /// Checks whether the window is currently minimized.
+    ///
+    /// TODO: Track the window's state, for now this always returns `false`.
+    pub fn is_minimized(&self) -> bool {
+        false
+    }
+
+    /// Blocks until a message is available, returning that message.
+    ///
+    /// # Details
+    ///
+    /// Unlike `next_message()` this method doesn't busy-poll for events, the thread sleeps until
+    /// the X server delivers an event to the window. This is useful for tools and editors that
+    /// don't need to redraw until there's user input. Use `next_message()` when continuously
+    /// updating.
+    pub fn wait_message(&mut self) -> Message { unsafe {
+        let mut event = mem::uninitialized::<xlib::XEvent>();
+        loop {
+            // `XNextEvent()` blocks until an event is received.
+            xlib::XNextEvent(self.display, &mut event);
+            if let Some(message) = self.convert_event(&event) {
+                return message;
+            }
+        }
+    } }
+
+    fn convert_event(&self, event: &xlib::XEvent) -> Option<Message> { unsafe {
+        match event.get_type() {
+            xlib::KeyPress => {
+                let key_press_event: &xlib::XKeyPressedEvent = mem::transmute(event);
+
+                let mut num_syms = 0;
+                let ptr_key_sym = xlib::XGetKeyboardMapping(self.display, key_press_event.keycode as u8, 1, &mut num_syms);
+                let syms_slice = slice::from_raw_parts(ptr_key_sym, num_syms as usize);
+
+                let us_sym = syms_slice[0];
+                Some(Message::KeyDown(key_sym_to_scancode(us_sym)))
+            },
+            xlib::KeyRelease => {
+                let key_release_event: &xlib::XKeyReleasedEvent = mem::transmute(event);
+
+                let mut num_syms = 0;
+                let ptr_key_sym = xlib::XGetKeyboardMapping(self.display, key_release_event.keycode as u8, 1, &mut num_syms);
+                let syms_slice = slice::from_raw_parts(ptr_key_sym, num_syms as usize);
+
+                let us_sym = syms_slice[0];
+                Some(Message::KeyUp(key_sym_to_scancode(us_sym)))
+            },
+            _ => {
+                println!("unsupported event type: {}", event.get_type());
+                None
+            },
+        }
+    } }
 }
 
 fn key_sym_to_scancode(key_sym: u64) -> ScanCode {
@@ -0,0 +1,132 @@
+//! A minimal logging facade so that the engine's diagnostics can be routed to the game's own log.
+//!
+//! Libraries report messages with `log()` or one of the per-level helpers, and the game installs
+//! a logger with `set_logger()` to decide where they go. Until a logger is set, warnings and
+//! errors are written to stderr, info messages to stdout, and trace messages are discarded.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+/// The severity of a log message, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Detailed diagnostics, such as profiling output.
+    Trace,
+
+    /// General information about what the engine is doing.
+    Info,
+
+    /// Something unexpected happened but the engine can continue.
+    Warn,
+
+    /// Something failed.
+    Error,
+}
+
+impl Display for Level {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let name = match *self {
+            Level::Trace => "TRACE",
+            Level::Info => "INFO",
+            Level::Warn => "WARNING",
+            Level::Error => "ERROR",
+        };
+        formatter.write_str(name)
+    }
+}
+
+/// A function that receives every log message.
+///
+/// Messages can be logged from any thread, so loggers must be `Send` and `Sync`.
+pub type Logger = Fn(Level, &str) + Send + Sync;
+
+/// The address of the logger installed with `set_logger()`, or 0 if the default logger is being
+/// used.
+///
+/// The logger is stored with release ordering and loaded with acquire ordering so that a thread
+/// that sees the pointer also sees the logger it points to.
+static LOGGER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Sets the logger that receives all log messages.
+///
+/// # Details
+///
+/// The logger is meant to be set once during startup. Replacing a logger doesn't free the
+/// previous one since another thread may still be using it.
+pub fn set_logger(logger: Box<Logger>) {
+    let logger = Box::into_raw(Box::new(logger));
+    LOGGER.store(logger as usize, Ordering::Release);
+}
+
+/// Goes back to using the default logger, see the module documentation.
+pub fn clear_logger() {
+    LOGGER.store(0, Ordering::Release);
+}
+
+/// Sends `message` to the current logger.
+pub fn log(level: Level, message: &str) {
+    let logger = LOGGER.load(Ordering::Acquire) as *const Box<Logger>;
+    if !logger.is_null() {
+        // Loggers are never freed, so the logger is still valid even if it has been replaced.
+        let logger = unsafe { &*logger };
+        logger(level, message);
+        return;
+    }
+
+    // Failing to write a log message isn't worth panicking over, so write errors are ignored.
+    match level {
+        Level::Trace => {},
+        Level::Info => { let _ = writeln!(io::stdout(), "{}", message); },
+        Level::Warn | Level::Error => { let _ = writeln!(io::stderr(), "{}: {}", level, message); },
+    }
+}
+
+pub fn trace(message: &str) {
+    log(Level::Trace, message);
+}
+
+pub fn info(message: &str) {
+    log(Level::Info, message);
+}
+
+pub fn warn(message: &str) {
+    log(Level::Warn, message);
+}
+
+pub fn error(message: &str) {
+    log(Level::Error, message);
+}
+
+#[test]
+fn custom_logger_captures_messages() {
+    use std::sync::{Arc, Mutex};
+
+    // The logger is global and tests run in parallel, so only messages from this test are kept
+    // in case another test logs while the logger is installed.
+    const PREFIX: &'static str = "custom_logger_captures_messages: ";
+
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let logger_captured = captured.clone();
+    set_logger(Box::new(move |level: Level, message: &str| {
+        if message.starts_with(PREFIX) {
+            logger_captured.lock().unwrap().push((level, message[PREFIX.len()..].to_string()));
+        }
+    }));
+
+    trace(&format!("{}trace message", PREFIX));
+    warn(&format!("{}low on memory", PREFIX));
+    log(Level::Error, &format!("{}device lost", PREFIX));
+    clear_logger();
+
+    // Messages logged after clearing go to the default logger instead.
+    trace(&format!("{}discarded", PREFIX));
+
+    assert_eq!(
+        *captured.lock().unwrap(),
+        vec![
+            (Level::Trace, "trace message".to_string()),
+            (Level::Warn, "low on memory".to_string()),
+            (Level::Error, "device lost".to_string()),
+        ]);
+}
@@ -4,9 +4,11 @@ pub use windows::window::Window;
 #[cfg(unix)]
 pub use linux::window::Window;
 
+use std::collections::VecDeque;
+
 use input::ScanCode;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Message {
     Activate,
     Close,
@@ -40,8 +42,181 @@ pub enum Message {
 
     /// Message signalling how much the mouse wheel has been scrolled.
     ///
-    /// This message is sent any time the mouse wheel is scrolled. The wrapped value
-    /// is the amount the mouse wheel was scrolled, though the scale of this value
-    /// is platform/driver dependent (I assume).
-    MouseWheel(i32),
+    /// This message is sent any time the mouse wheel is scrolled or tilted. The deltas are
+    /// measured in notches of a standard mouse wheel, with positive `delta_y` scrolling up (away
+    /// from the user) and positive `delta_x` scrolling right. High-resolution wheels and
+    /// touchpads report fractions of a notch.
+    MouseWheel {
+        delta_x: f32,
+        delta_y: f32,
+    },
+
+    /// Message signalling that the window's scale factor has changed.
+    ///
+    /// This message is sent when the window moves to a monitor with a different DPI or the user
+    /// changes the display scaling. The wrapped value is the new scale factor, see
+    /// `scale_factor_for_dpi()`.
+    ScaleFactorChanged(f32),
+
+    /// Message signalling that the user typed a character.
+    ///
+    /// The character is produced by the OS, so it respects the keyboard layout, modifier keys,
+    /// dead keys, and IME composition. This is sent in addition to the raw `KeyDown` and `KeyUp`
+    /// messages and should be used for text entry rather than reconstructing characters from
+    /// scancodes. Control characters (e.g. backspace and enter) are not sent as text input, use
+    /// the key messages to handle those.
+    TextInput(char),
+
+    /// Message signalling that the window has been minimized.
+    Minimized,
+
+    /// Message signalling that the window has been maximized.
+    Maximized,
+
+    /// Message signalling that the window has been restored from being minimized or maximized.
+    Restored,
+}
+
+/// Whether the window is minimized, maximized, or neither.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowState {
+    Normal,
+    Minimized,
+    Maximized,
+}
+
+/// Updates `state` to `new_state`, pushing the message for the change onto `messages`.
+///
+/// # Details
+///
+/// Nothing is pushed if the state hasn't changed, which lets backends report their current state
+/// every time the window is resized without producing redundant messages. Each change produces a
+/// message, so if the window is minimized and then restored before the messages are processed
+/// both `Minimized` and `Restored` are delivered, in that order.
+pub fn push_state_change(messages: &mut VecDeque<Message>, state: &mut WindowState, new_state: WindowState) {
+    if *state == new_state {
+        return;
+    }
+
+    *state = new_state;
+    messages.push_back(match new_state {
+        WindowState::Normal => Message::Restored,
+        WindowState::Minimized => Message::Minimized,
+        WindowState::Maximized => Message::Maximized,
+    });
+}
+
+/// The DPI that corresponds to a scale factor of 1.0.
+pub const DEFAULT_DPI: u32 = 96;
+
+/// Converts a DPI value to a scale factor, where `DEFAULT_DPI` is a scale factor of 1.0.
+pub fn scale_factor_for_dpi(dpi: u32) -> f32 {
+    dpi as f32 / DEFAULT_DPI as f32
+}
+
+/// Converts a size in physical pixels to logical pixels using the specified scale factor.
+///
+/// Sizes are rounded to the nearest pixel.
+pub fn physical_to_logical(size: (u32, u32), scale_factor: f32) -> (u32, u32) {
+    let (width, height) = size;
+    (
+        (width as f32 / scale_factor).round() as u32,
+        (height as f32 / scale_factor).round() as u32,
+    )
+}
+
+/// Converts a size in logical pixels to physical pixels using the specified scale factor.
+///
+/// Sizes are rounded to the nearest pixel.
+pub fn logical_to_physical(size: (u32, u32), scale_factor: f32) -> (u32, u32) {
+    let (width, height) = size;
+    (
+        (width as f32 * scale_factor).round() as u32,
+        (height as f32 * scale_factor).round() as u32,
+    )
+}
+
+/// Retrieves the next message from `messages`, blocking on `pump` until one is available.
+///
+/// # Details
+///
+/// `pump` is invoked any time `messages` is empty and is expected to block until the OS has
+/// delivered at least one event, pushing any resulting messages onto the queue. Not every OS event
+/// produces a `Message` so `pump` may be invoked multiple times before a message is returned.
+/// Messages already in the queue are always returned before `pump` is invoked, so a `Close`
+/// message is delivered as soon as the event that produced it has been processed.
+pub fn wait_message<P>(messages: &mut VecDeque<Message>, mut pump: P) -> Message
+    where P: FnMut(&mut VecDeque<Message>)
+{
+    loop {
+        if let Some(message) = messages.pop_front() {
+            return message;
+        }
+
+        pump(messages);
+    }
+}
+
+#[test]
+fn wait_message_pumps_until_message() {
+    let mut messages = VecDeque::new();
+    let mut pump_count = 0;
+    let message = wait_message(&mut messages, |messages| {
+        pump_count += 1;
+
+        // The first event doesn't produce a message, the second one closes the window.
+        if pump_count == 2 {
+            messages.push_back(Message::Close);
+        }
+    });
+
+    assert_eq!(message, Message::Close);
+    assert_eq!(pump_count, 2);
+}
+
+#[test]
+fn wait_message_uses_queued_messages_first() {
+    let mut messages = VecDeque::new();
+    messages.push_back(Message::Close);
+    messages.push_back(Message::Destroy);
+
+    let message = wait_message(&mut messages, |_| panic!("Pump invoked while messages were queued"));
+    assert_eq!(message, Message::Close);
+    assert_eq!(messages.len(), 1);
+}
+
+#[test]
+fn scale_factor_conversions() {
+    assert_eq!(scale_factor_for_dpi(DEFAULT_DPI), 1.0);
+    assert_eq!(scale_factor_for_dpi(144), 1.5);
+    assert_eq!(scale_factor_for_dpi(192), 2.0);
+
+    assert_eq!(physical_to_logical((1920, 1080), 1.5), (1280, 720));
+    assert_eq!(logical_to_physical((1280, 720), 1.5), (1920, 1080));
+    assert_eq!(physical_to_logical((800, 600), 1.0), (800, 600));
+
+    // Sizes that don't divide evenly are rounded to the nearest pixel.
+    assert_eq!(physical_to_logical((801, 601), 2.0), (401, 301));
+    assert_eq!(logical_to_physical((333, 333), 1.25), (416, 416));
+}
+
+#[test]
+fn window_state_changes() {
+    let mut messages = VecDeque::new();
+    let mut state = WindowState::Normal;
+
+    // Minimizing and restoring before the messages are processed delivers both, in order.
+    push_state_change(&mut messages, &mut state, WindowState::Minimized);
+    push_state_change(&mut messages, &mut state, WindowState::Normal);
+    assert_eq!(messages.drain(..).collect::<Vec<_>>(), vec![Message::Minimized, Message::Restored]);
+
+    // Ordinary resizes don't produce messages.
+    push_state_change(&mut messages, &mut state, WindowState::Normal);
+    assert!(messages.is_empty());
+
+    push_state_change(&mut messages, &mut state, WindowState::Maximized);
+    push_state_change(&mut messages, &mut state, WindowState::Maximized);
+    push_state_change(&mut messages, &mut state, WindowState::Minimized);
+    assert_eq!(messages.drain(..).collect::<Vec<_>>(), vec![Message::Maximized, Message::Minimized]);
+    assert_eq!(state, WindowState::Minimized);
 }
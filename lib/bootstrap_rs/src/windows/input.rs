@@ -115,8 +115,8 @@ pub fn handle_raw_input(window: &mut Window, lParam: LPARAM) {
         if button_flags & RI_MOUSE_BUTTON_5_UP != 0 {
             window.messages.push_back(MouseButtonReleased(4));
         }
-        if button_flags & RI_MOUSE_WHEEL != 0 {
-            window.messages.push_back(MouseWheel(raw_mouse.usButtonData as i32))
-        }
+
+        // Wheel input is handled through `WM_MOUSEWHEEL` and `WM_MOUSEHWHEEL` instead, which
+        // also cover touchpads that don't report through raw input.
     }
 }
@@ -1,5 +1,7 @@
 use std::mem;
 use std::ptr;
+use std::ffi::CString;
+use std::sync::{Once, ONCE_INIT};
 use std::collections::VecDeque;
 use std::ops::DerefMut;
 use std::rc::Rc;
@@ -10,7 +12,7 @@ use windows::user32;
 use windows::kernel32;
 use windows::winmm;
 use super::ToCU16Str;
-use window::Message;
+use window::{self, Message, WindowState};
 use window::Message::*;
 use input::ScanCode;
 
@@ -19,11 +21,56 @@ use super::input::{register_raw_input, handle_raw_input};
 static CLASS_NAME: &'static str = "bootstrap";
 static WINDOW_PROP: &'static str = "window";
 
+// Not defined by winapi.
+const WM_DPICHANGED: UINT = 0x02E0;
+const SIZE_RESTORED: WPARAM = 0;
+const SIZE_MINIMIZED: WPARAM = 1;
+const SIZE_MAXIMIZED: WPARAM = 2;
+const LOGPIXELSX: c_int = 88;
+const WM_MOUSEHWHEEL: UINT = 0x020E;
+
+/// The wheel delta reported by Windows for one notch of a standard mouse wheel.
+const WHEEL_DELTA: f32 = 120.0;
+
+/// Passed to `SetProcessDpiAwarenessContext()`, not defined by winapi.
+const DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2: isize = -4;
+
+/// Passed to `SetProcessDpiAwareness()`, not defined by winapi.
+const PROCESS_PER_MONITOR_DPI_AWARE: c_int = 2;
+
+type GetDpiForWindowFn = unsafe extern "system" fn(HWND) -> UINT;
+type SetProcessDpiAwarenessContextFn = unsafe extern "system" fn(HANDLE) -> BOOL;
+type SetProcessDpiAwarenessFn = unsafe extern "system" fn(c_int) -> HRESULT;
+
+// Not exported by the -sys crates.
+#[link(name = "gdi32")]
+extern "system" {
+    fn GetDeviceCaps(hdc: HDC, index: c_int) -> c_int;
+}
+
+#[link(name = "user32")]
+extern "system" {
+    fn SetProcessDPIAware() -> BOOL;
+}
+
+static DPI_AWARENESS: Once = ONCE_INIT;
+static GET_DPI_FOR_WINDOW_INIT: Once = ONCE_INIT;
+
+/// Only written inside `GET_DPI_FOR_WINDOW_INIT.call_once()`, so it's never written while being
+/// read.
+static mut GET_DPI_FOR_WINDOW: Option<GetDpiForWindowFn> = None;
+
 #[derive(Debug, Clone)]
 pub struct Window {
     pub handle: HWND,
     pub dc: HDC,
-    pub messages: VecDeque<Message>
+    pub messages: VecDeque<Message>,
+
+    /// The leading half of a UTF-16 surrogate pair received through `WM_CHAR`, waiting for the
+    /// trailing half.
+    high_surrogate: Option<u16>,
+
+    state: WindowState,
 }
 
 impl Window {
@@ -50,6 +97,10 @@ impl Window {
             user32::RegisterClassExW(&class_info);
         }
 
+        // Has to happen before the window is created, otherwise Windows treats the window as DPI
+        // unaware and scales it with a bitmap stretch instead of sending `WM_DPICHANGED`.
+        enable_dpi_awareness();
+
         let handle = unsafe {
             user32::CreateWindowExW(
                 0,
@@ -78,7 +129,9 @@ impl Window {
         let window = Rc::new(RefCell::new(Window {
             handle: handle,
             dc: dc,
-            messages: VecDeque::new()
+            messages: VecDeque::new(),
+            high_surrogate: None,
+            state: WindowState::Normal,
         }));
         let window_address = (window.borrow_mut().deref_mut() as *mut Window) as LPVOID;
 
@@ -119,6 +172,94 @@ impl Window {
 
         self.messages.pop_front()
     }
+
+    /// Checks whether the window is currently minimized.
+    ///
+    /// Nothing is visible while the window is minimized, so rendering can be skipped.
+    pub fn is_minimized(&self) -> bool {
+        self.state == WindowState::Minimized
+    }
+
+    /// Retrieves the DPI of the monitor the window is currently on.
+    ///
+    /// # Details
+    ///
+    /// Per-monitor DPI (`GetDpiForWindow()`) is only available on Windows 10 and later, on older
+    /// versions this falls back to the system DPI. `GetDpiForWindow()` is looked up at runtime
+    /// (once per process) so that the binary still runs on older versions of Windows.
+    pub fn dpi(&self) -> u32 {
+        unsafe {
+            if let Some(get_dpi_for_window) = get_dpi_for_window() {
+                let dpi = get_dpi_for_window(self.handle);
+                if dpi != 0 {
+                    return dpi;
+                }
+            }
+
+            let screen_dc = user32::GetDC(ptr::null_mut());
+            let dpi = GetDeviceCaps(screen_dc, LOGPIXELSX);
+            user32::ReleaseDC(ptr::null_mut(), screen_dc);
+            if dpi > 0 {
+                return dpi as u32;
+            }
+        }
+
+        window::DEFAULT_DPI
+    }
+
+    /// Retrieves the ratio between physical pixels and logical pixels for the window.
+    ///
+    /// A scale factor of 1.0 corresponds to 96 DPI.
+    pub fn scale_factor(&self) -> f32 {
+        window::scale_factor_for_dpi(self.dpi())
+    }
+
+    /// Retrieves the size of the window's client area in physical pixels.
+    ///
+    /// This is the size that should be used for the framebuffer.
+    pub fn physical_size(&self) -> (u32, u32) {
+        let mut rect = unsafe { mem::uninitialized::<RECT>() };
+        let result = unsafe { user32::GetClientRect(self.handle, &mut rect) };
+        assert!(result != 0, "user32::GetClientRect() failed for window {:?}", self.handle);
+
+        ((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32)
+    }
+
+    /// Retrieves the size of the window's client area in logical pixels.
+    ///
+    /// This is the size that should be used for laying out UI.
+    pub fn logical_size(&self) -> (u32, u32) {
+        window::physical_to_logical(self.physical_size(), self.scale_factor())
+    }
+
+    /// Blocks until a message is available, returning that message.
+    ///
+    /// # Details
+    ///
+    /// Unlike `next_message()` this method doesn't busy-poll the message queue, the thread sleeps
+    /// until Windows delivers an event to the window. This is useful for tools and editors that
+    /// don't need to redraw until there's user input. Use `next_message()` when continuously
+    /// updating.
+    pub fn wait_message(&mut self) -> Message {
+        let handle = self.handle;
+        window::wait_message(&mut self.messages, |_| {
+            let mut message = unsafe { mem::uninitialized::<MSG>() };
+
+            // `GetMessageW()` blocks until there is a message in the queue. The window callback will
+            // push any resulting message onto the message queue when the message is dispatched.
+            let result = unsafe {
+                user32::GetMessageW(&mut message, handle, 0, 0)
+            };
+            if result == -1 {
+                panic!("user32::GetMessageW() failed for window {:?}", handle);
+            }
+
+            unsafe {
+                user32::TranslateMessage(&message);
+                user32::DispatchMessageW(&message);
+            }
+        })
+    }
 }
 
 impl Drop for Window {
@@ -130,6 +271,65 @@ impl Drop for Window {
     }
 }
 
+/// Opts the process into per-monitor DPI awareness, using the newest API that's available.
+///
+/// # Details
+///
+/// `SetProcessDpiAwarenessContext()` is available on Windows 10 (1703) and later,
+/// `SetProcessDpiAwareness()` on Windows 8.1 and later, and on anything older only system DPI
+/// awareness (`SetProcessDPIAware()`) is supported. The awareness can only be set once per
+/// process, so this does nothing after the first call. Setting it fails harmlessly if the
+/// application manifest already specified the awareness.
+fn enable_dpi_awareness() {
+    DPI_AWARENESS.call_once(|| unsafe {
+        let set_awareness_context = load_function("user32.dll", "SetProcessDpiAwarenessContext");
+        if !set_awareness_context.is_null() {
+            let set_awareness_context: SetProcessDpiAwarenessContextFn = mem::transmute(set_awareness_context);
+            if set_awareness_context(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2 as HANDLE) != 0 {
+                return;
+            }
+        }
+
+        let set_awareness = load_function("shcore.dll", "SetProcessDpiAwareness");
+        if !set_awareness.is_null() {
+            let set_awareness: SetProcessDpiAwarenessFn = mem::transmute(set_awareness);
+            if set_awareness(PROCESS_PER_MONITOR_DPI_AWARE) >= 0 {
+                return;
+            }
+        }
+
+        SetProcessDPIAware();
+    });
+}
+
+/// Retrieves `GetDpiForWindow()`, if it's available on this version of Windows.
+fn get_dpi_for_window() -> Option<GetDpiForWindowFn> {
+    unsafe {
+        GET_DPI_FOR_WINDOW_INIT.call_once(|| {
+            let function = load_function("user32.dll", "GetDpiForWindow");
+            if !function.is_null() {
+                GET_DPI_FOR_WINDOW = Some(mem::transmute(function));
+            }
+        });
+
+        GET_DPI_FOR_WINDOW
+    }
+}
+
+/// Loads the specified function from a system DLL, returning null if it isn't available.
+///
+/// The DLL is left loaded so that the function stays valid, callers should only look up each
+/// function once.
+unsafe fn load_function(module: &str, name: &str) -> FARPROC {
+    let module = kernel32::LoadLibraryW(module.to_c_u16().as_ptr());
+    if module.is_null() {
+        return ptr::null();
+    }
+
+    let name = CString::new(name).unwrap();
+    kernel32::GetProcAddress(module, name.as_ptr())
+}
+
 #[allow(non_snake_case)]
 unsafe extern "system"
 fn message_callback(
@@ -153,9 +353,64 @@ fn message_callback(
                 let y_coord = ( ( lParam >> 16 ) as i16 ) as i32;
                 window.messages.push_back(MousePos(x_coord, y_coord));
             },
+            WM_MOUSEWHEEL => {
+                window.messages.push_back(MouseWheel {
+                    delta_x: 0.0,
+                    delta_y: wheel_delta(wParam),
+                });
+            },
+            WM_MOUSEHWHEEL => {
+                window.messages.push_back(MouseWheel {
+                    delta_x: wheel_delta(wParam),
+                    delta_y: 0.0,
+                });
+            },
             WM_INPUT => {
                 handle_raw_input(window, lParam);
             },
+            WM_CHAR => {
+                // `WM_CHAR` delivers UTF-16 code units, so characters outside the BMP arrive as
+                // two messages that must be combined.
+                let code_unit = wParam as u16;
+                if code_unit >= 0xD800 && code_unit < 0xDC00 {
+                    window.high_surrogate = Some(code_unit);
+                } else {
+                    let units = match window.high_surrogate.take() {
+                        Some(high) => vec![high, code_unit],
+                        None => vec![code_unit],
+                    };
+
+                    for character in ::std::char::decode_utf16(units.into_iter()) {
+                        match character {
+                            Ok(character) if !character.is_control() => {
+                                window.messages.push_back(TextInput(character));
+                            },
+                            _ => {},
+                        }
+                    }
+                }
+            },
+            WM_SIZE => {
+                if let Some(new_state) = size_state(wParam) {
+                    window::push_state_change(&mut window.messages, &mut window.state, new_state);
+                }
+            },
+            WM_DPICHANGED => {
+                // The X and Y DPI are always the same, so only the X DPI in the low word is used.
+                let dpi = (wParam & 0xFFFF) as u32;
+                window.messages.push_back(ScaleFactorChanged(window::scale_factor_for_dpi(dpi)));
+
+                // Resize the window to the rect suggested by Windows for the new DPI.
+                let suggested = &*(lParam as *const RECT);
+                user32::SetWindowPos(
+                    hwnd,
+                    ptr::null_mut(),
+                    suggested.left,
+                    suggested.top,
+                    suggested.right - suggested.left,
+                    suggested.bottom - suggested.top,
+                    SWP_NOZORDER | SWP_NOACTIVATE);
+            },
             _ => ()
         }
     }
@@ -163,6 +418,27 @@ fn message_callback(
     user32::DefWindowProcW(hwnd, uMsg, wParam, lParam)
 }
 
+/// Converts the wheel delta sent with `WM_MOUSEWHEEL` and `WM_MOUSEHWHEEL` to notches.
+///
+/// The delta is a signed value in the high word of `wParam`. Precision touchpads send deltas
+/// smaller than `WHEEL_DELTA`, so the result isn't rounded.
+fn wheel_delta(w_param: WPARAM) -> f32 {
+    ((w_param >> 16) as i16) as f32 / WHEEL_DELTA
+}
+
+/// Converts the resize type sent with `WM_SIZE` to the window's new state.
+///
+/// Returns `None` for the resize types that are sent when other windows change state.
+#[allow(non_snake_case)]
+fn size_state(wParam: WPARAM) -> Option<WindowState> {
+    match wParam {
+        SIZE_RESTORED => Some(WindowState::Normal),
+        SIZE_MINIMIZED => Some(WindowState::Minimized),
+        SIZE_MAXIMIZED => Some(WindowState::Maximized),
+        _ => None,
+    }
+}
+
 fn convert_windows_scancode(wParam: WPARAM, _: LPARAM) -> ScanCode {
     const A: u32 = 'A' as u32;
     const Z: u32 = 'Z' as u32;
@@ -185,3 +461,16 @@ fn convert_windows_scancode(wParam: WPARAM, _: LPARAM) -> ScanCode {
         }
     }
 }
+
+#[test]
+fn size_messages() {
+    let mut messages = VecDeque::new();
+    let mut state = WindowState::Normal;
+    for &size_type in &[SIZE_RESTORED, SIZE_MINIMIZED, SIZE_RESTORED, SIZE_MAXIMIZED, 4, SIZE_RESTORED] {
+        if let Some(new_state) = size_state(size_type) {
+            window::push_state_change(&mut messages, &mut state, new_state);
+        }
+    }
+
+    assert_eq!(messages.into_iter().collect::<Vec<_>>(), vec![Minimized, Restored, Maximized, Restored]);
+}
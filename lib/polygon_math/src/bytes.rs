@@ -0,0 +1,212 @@
+//! Reading and writing primitive values and math types as bytes, e.g. for saving and loading.
+//!
+//! Every value is stored in a fixed little-endian format regardless of the platform, without
+//! padding or type information, so data must be read back in the same order it was written.
+//! Points are stored as `x, y, z` (the `w` coordinate is always 1), vectors as their components
+//! in order, and quaternions as `w, x, y, z`.
+
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+
+use point::Point;
+use vector::{Vector2, Vector3};
+use quaternion::Quaternion;
+
+/// The error returned when a read would go past the end of the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnexpectedEnd {
+    /// The offset of the value that couldn't be read.
+    pub offset: usize,
+
+    /// The number of bytes the value needed.
+    pub needed: usize,
+}
+
+impl Display for UnexpectedEnd {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Data ended unexpectedly, {} bytes needed at offset {}", self.needed, self.offset)
+    }
+}
+
+/// Reads values sequentially from a byte slice.
+///
+/// Reads are bounds-checked, reading past the end of the data returns an error and leaves the
+/// reader where it was.
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader {
+            bytes: bytes,
+            offset: 0,
+        }
+    }
+
+    /// The number of bytes that have been read so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Checks if every byte has been read.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reads the next `count` bytes as a slice.
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], UnexpectedEnd> {
+        if self.remaining() < count {
+            return Err(UnexpectedEnd {
+                offset: self.offset,
+                needed: count,
+            });
+        }
+
+        let bytes = &self.bytes[self.offset..self.offset + count];
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, UnexpectedEnd> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, UnexpectedEnd> {
+        let bytes = try!(self.read_bytes(4));
+        Ok(bytes.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u32))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, UnexpectedEnd> {
+        self.read_u32().map(|value| value as i32)
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, UnexpectedEnd> {
+        let bits = try!(self.read_u32());
+        Ok(unsafe { mem::transmute(bits) })
+    }
+
+    pub fn read_vector2(&mut self) -> Result<Vector2, UnexpectedEnd> {
+        let mut reader = try!(self.sub_reader(2 * 4));
+        Ok(Vector2::new(try!(reader.read_f32()), try!(reader.read_f32())))
+    }
+
+    pub fn read_vector3(&mut self) -> Result<Vector3, UnexpectedEnd> {
+        let mut reader = try!(self.sub_reader(3 * 4));
+        Ok(Vector3::new(try!(reader.read_f32()), try!(reader.read_f32()), try!(reader.read_f32())))
+    }
+
+    pub fn read_point(&mut self) -> Result<Point, UnexpectedEnd> {
+        let mut reader = try!(self.sub_reader(3 * 4));
+        Ok(Point::new(try!(reader.read_f32()), try!(reader.read_f32()), try!(reader.read_f32())))
+    }
+
+    pub fn read_quaternion(&mut self) -> Result<Quaternion, UnexpectedEnd> {
+        let mut reader = try!(self.sub_reader(4 * 4));
+        Ok(Quaternion {
+            w: try!(reader.read_f32()),
+            x: try!(reader.read_f32()),
+            y: try!(reader.read_f32()),
+            z: try!(reader.read_f32()),
+        })
+    }
+
+    /// Reads the next `count` bytes as a separate reader, so that a multi-part value is either
+    /// read completely or not at all.
+    fn sub_reader(&mut self, count: usize) -> Result<ByteReader<'a>, UnexpectedEnd> {
+        self.read_bytes(count).map(ByteReader::new)
+    }
+}
+
+/// Writes values to a growable byte buffer in the format read by `ByteReader`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> ByteWriter {
+        ByteWriter {
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Creates a writer that appends to the end of `bytes`.
+    pub fn from_vec(bytes: Vec<u8>) -> ByteWriter {
+        ByteWriter {
+            bytes: bytes,
+        }
+    }
+
+    /// The bytes written so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &*self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend(bytes.iter().cloned());
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        for shift in 0..4 {
+            self.bytes.push((value >> (shift * 8)) as u8);
+        }
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_u32(value as u32);
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        let bits: u32 = unsafe { mem::transmute(value) };
+        self.write_u32(bits);
+    }
+
+    pub fn write_vector2(&mut self, value: Vector2) {
+        self.write_f32(value.x);
+        self.write_f32(value.y);
+    }
+
+    pub fn write_vector3(&mut self, value: Vector3) {
+        self.write_f32(value.x);
+        self.write_f32(value.y);
+        self.write_f32(value.z);
+    }
+
+    /// Writes the point's `x`, `y`, and `z` coordinates, `w` isn't stored.
+    pub fn write_point(&mut self, value: Point) {
+        self.write_f32(value.x);
+        self.write_f32(value.y);
+        self.write_f32(value.z);
+    }
+
+    pub fn write_quaternion(&mut self, value: Quaternion) {
+        self.write_f32(value.w);
+        self.write_f32(value.x);
+        self.write_f32(value.y);
+        self.write_f32(value.z);
+    }
+}
@@ -1,4 +1,4 @@
-use std::mem;
+use plain_data;
 
 pub const RED:   Color = Color { r: 1.0, b: 0.0, g: 0.0, a: 1.0 };
 pub const WHITE: Color = Color { r: 1.0, b: 1.0, g: 1.0, a: 1.0 };
@@ -23,6 +23,6 @@ impl Color {
     }
 
     pub fn as_array(&self) -> &[f32; 4] {
-        unsafe { mem::transmute(self) }
+        plain_data::cast(self)
     }
 }
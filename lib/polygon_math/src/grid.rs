@@ -0,0 +1,58 @@
+//! Conversions between world positions and the cells of a regular grid.
+
+use point::Point;
+use vector::Vector3;
+
+/// A regular 3D grid of cells, e.g. for a tilemap.
+///
+/// # Details
+///
+/// Cell `(0, 0, 0)` has its minimum corner at `origin` and extends `cell_size` along each axis.
+/// Cells extend infinitely in all directions, including negative cell coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    pub cell_size: Vector3,
+    pub origin: Point,
+}
+
+impl Grid {
+    pub fn new(cell_size: Vector3, origin: Point) -> Grid {
+        debug_assert!(
+            cell_size.x > 0.0 && cell_size.y > 0.0 && cell_size.z > 0.0,
+            "Grid cell size must be positive on all axes, got {:?}", cell_size);
+
+        Grid {
+            cell_size: cell_size,
+            origin: origin,
+        }
+    }
+
+    /// Retrieves the coordinates of the cell containing `point`.
+    ///
+    /// # Details
+    ///
+    /// Coordinates are floored rather than truncated, so points just below the origin are in
+    /// cell -1 rather than cell 0. A point exactly on the boundary between two cells belongs to
+    /// the cell with the higher coordinate.
+    pub fn world_to_cell(&self, point: Point) -> (i32, i32, i32) {
+        let offset = point - self.origin;
+        (
+            (offset.x / self.cell_size.x).floor() as i32,
+            (offset.y / self.cell_size.y).floor() as i32,
+            (offset.z / self.cell_size.z).floor() as i32,
+        )
+    }
+
+    /// Retrieves the world position of the center of the specified cell.
+    pub fn cell_to_world_center(&self, cell: (i32, i32, i32)) -> Point {
+        let (min, _) = self.cell_bounds(cell);
+        min + self.cell_size * 0.5
+    }
+
+    /// Retrieves the minimum and maximum corners of the specified cell.
+    pub fn cell_bounds(&self, cell: (i32, i32, i32)) -> (Point, Point) {
+        let (x, y, z) = cell;
+        let min = self.origin + Vector3::new(x as f32, y as f32, z as f32) * self.cell_size;
+        (min, min + self.cell_size)
+    }
+}
@@ -1,33 +1,72 @@
 #![feature(raw, augmented_assignments, op_assign_traits)]
 #![cfg_attr(test, feature(test))]
 
+pub mod bytes;
 pub mod point;
 pub mod vector;
 pub mod matrix;
+pub mod mesh;
+pub mod plane;
 pub mod color;
+pub mod grid;
 pub mod quaternion;
+pub mod random;
+pub mod ray;
+pub mod smoothing;
+pub mod spline;
+pub mod tween;
+pub mod vertex;
+
+mod plain_data;
 
 #[cfg(test)]
 mod test;
 
-pub use self::point::Point;
-pub use self::vector::{Vector2, Vector3};
-pub use self::matrix::{Matrix3, Matrix4};
+pub use self::point::{Point, QuantizedPoint};
+pub use self::vector::{Vector2, Vector3, QuantizedVector3};
+pub use self::matrix::{Matrix3, Matrix4, DepthRange};
 pub use self::color::Color;
+pub use self::plane::Plane;
 pub use self::quaternion::Quaternion;
+pub use self::ray::Ray;
 
+/// The default tolerance used by `IsZero::is_zero()`.
+///
+/// This affects all approximate comparisons in the library, including matrix equality and
+/// normalization checks.
 pub const EPSILON: f32 = 1e-6;
 
-pub trait IsZero {
-    fn is_zero(self) -> bool;
+/// The default number of decimal digits used when displaying vectors and points.
+pub const DISPLAY_PRECISION: usize = 4;
+
+pub trait IsZero: Sized {
+    /// Checks if the value is zero within the specified tolerance.
+    ///
+    /// A negative `epsilon` is treated as its absolute value.
+    fn is_zero_within(self, epsilon: f32) -> bool;
+
+    /// Checks if the value is zero within the default tolerance, `EPSILON`.
+    fn is_zero(self) -> bool {
+        self.is_zero_within(EPSILON)
+    }
 }
 
 impl IsZero for f32 {
-    fn is_zero(self) -> bool {
-        self.abs() < EPSILON
+    fn is_zero_within(self, epsilon: f32) -> bool {
+        self.abs() < epsilon.abs()
     }
 }
 
+/// Rounds `value` to the nearest integer multiple of `epsilon`, returning the multiple.
+///
+/// Values exactly halfway between two multiples are rounded away from zero.
+fn quantize(value: f32, epsilon: f32) -> i64 {
+    assert!(epsilon > 0.0, "Quantization epsilon must be positive but was {}", epsilon);
+    debug_assert!(value.is_finite(), "Cannot quantize non-finite value {}", value);
+
+    (value / epsilon).round() as i64
+}
+
 pub trait Clamp {
     fn clamp(self, min: Self, max: Self) -> Self;
 }
@@ -1,15 +1,32 @@
-use std::ops::{Index, IndexMut, Mul};
+use std::ops::{Index, IndexMut, Mul, MulAssign};
 use std::fmt::{Debug, Formatter, Error};
 use std::cmp::PartialEq;
 
 use vector::Vector3;
 use point::Point;
 use quaternion::Quaternion;
+use plane::Plane;
+use plain_data;
 use super::{IsZero, Dot};
 
+/// The range of normalized device coordinates that a projection matrix maps depth onto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthRange {
+    /// Depth is mapped onto `[-1, 1]`, the OpenGL convention.
+    NegativeOneToOne,
+
+    /// Depth is mapped onto `[0, 1]`, the DirectX convention.
+    ZeroToOne,
+}
+
 /// A 4x4 matrix that can be used to represent a combination of translation, rotation, and scale.
 ///
 /// Matrices are row-major.
+///
+/// Unless otherwise noted the constructors assume a right-handed coordinate system, matching the
+/// rest of the library. Rotations are counter-clockwise about the axis when looking down the axis
+/// towards the origin, and cameras look down the -Z axis. The `_lh` variants are for left-handed
+/// systems (e.g. DirectX-style rendering) where cameras look down the +Z axis.
 #[repr(C)] #[derive(Clone, Copy)]
 pub struct Matrix4 {
     data: [[f32; 4]; 4]
@@ -65,7 +82,8 @@ impl Matrix4 {
     /// Details
     /// -------
     ///
-    /// The resulting matrix will have the rotations applied in the order x -> y -> z.
+    /// The resulting matrix will have the rotations applied in the order x -> y -> z. Positive
+    /// angles rotate counter-clockwise in a right-handed system (clockwise in a left-handed one).
     pub fn rotation(x: f32, y: f32, z: f32) -> Matrix4 {
         let s1 = x.sin();
         let c1 = x.cos();
@@ -85,6 +103,8 @@ impl Matrix4 {
     }
 
     /// Creates a new rotation matrix from a quaternion.
+    ///
+    /// The rotation follows the same handedness rule as `rotation()`.
     pub fn from_quaternion(q: Quaternion) -> Matrix4 {
         Matrix4 {
             data: [
@@ -119,6 +139,186 @@ impl Matrix4 {
         }
     }
 
+    /// Creates a new shear matrix.
+    ///
+    /// # Details
+    ///
+    /// Each factor is named for the axis being sheared followed by the axis it's sheared along,
+    /// e.g. `xy` is how much a point's x coordinate is offset for each unit of its y coordinate:
+    ///
+    /// ```text
+    /// x' = x + xy * y + xz * z
+    /// y' = y + yx * x + yz * z
+    /// z' = z + zx * x + zy * y
+    /// ```
+    pub fn shear(xy: f32, xz: f32, yx: f32, yz: f32, zx: f32, zy: f32) -> Matrix4 {
+        Matrix4 {
+            data: [
+                [1.0, xy,  xz,  0.0],
+                [yx,  1.0, yz,  0.0],
+                [zx,  zy,  1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]
+        }
+    }
+
+    /// Creates a matrix that mirrors points across `plane`.
+    ///
+    /// # Details
+    ///
+    /// This is a Householder reflection `I - 2nn^T` about the plane's normal `n`, combined with a
+    /// translation so that the plane doesn't need to pass through the origin. Points on the plane
+    /// are left unchanged. Reflections flip handedness, so the resulting matrix has a negative
+    /// determinant and any triangle winding must be reversed when rendering with it.
+    pub fn reflection(plane: Plane) -> Matrix4 {
+        let Plane { normal: n, distance: d } = plane;
+        debug_assert!(n.is_normalized(), "Plane normal must be normalized: {:?}", n);
+
+        Matrix4 {
+            data: [
+                [1.0 - 2.0 * n.x * n.x,      -2.0 * n.x * n.y,      -2.0 * n.x * n.z, 2.0 * d * n.x],
+                [     -2.0 * n.y * n.x, 1.0 - 2.0 * n.y * n.y,      -2.0 * n.y * n.z, 2.0 * d * n.y],
+                [     -2.0 * n.z * n.x,      -2.0 * n.z * n.y, 1.0 - 2.0 * n.z * n.z, 2.0 * d * n.z],
+                [                  0.0,                   0.0,                   0.0,           1.0],
+            ]
+        }
+    }
+
+    /// Creates an orthographic projection matrix.
+    ///
+    /// # Details
+    ///
+    /// The matrix maps the box bounded by `left`, `right`, `bottom`, `top`, `-near`, and `-far`
+    /// in camera space onto the unit cube, following the OpenGL convention that the camera looks
+    /// down the -Z axis (i.e. a right-handed system) and depth is mapped onto `[-1, 1]`. `near`
+    /// and `far` are distances from the camera.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Matrix4 {
+        Matrix4 {
+            data: [
+                [2.0 / (right - left), 0.0,                  0.0,                 -(right + left) / (right - left)],
+                [0.0,                  2.0 / (top - bottom), 0.0,                 -(top + bottom) / (top - bottom)],
+                [0.0,                  0.0,                  -2.0 / (far - near), -(far + near) / (far - near)    ],
+                [0.0,                  0.0,                  0.0,                 1.0                             ],
+            ]
+        }
+    }
+
+    /// Creates a right-handed perspective projection matrix.
+    ///
+    /// # Details
+    ///
+    /// `fov` is the vertical field of view in radians and `aspect` is the ratio of the viewport's
+    /// width to its height. The camera looks down the -Z axis and `near` and `far` are distances
+    /// from the camera, which are mapped onto the depth range selected by `depth_range`.
+    pub fn perspective(fov: f32, aspect: f32, near: f32, far: f32, depth_range: DepthRange) -> Matrix4 {
+        let mut projection = Matrix4::perspective_lh(fov, aspect, near, far, depth_range);
+
+        // Flipping the Z axis turns the left-handed projection into a right-handed one.
+        projection[2][2] = -projection[2][2];
+        projection[3][2] = -projection[3][2];
+        projection
+    }
+
+    /// Creates a left-handed perspective projection matrix.
+    ///
+    /// # Details
+    ///
+    /// The same as `perspective()` except that the camera looks down the +Z axis.
+    pub fn perspective_lh(fov: f32, aspect: f32, near: f32, far: f32, depth_range: DepthRange) -> Matrix4 {
+        let (depth_scale, depth_offset) = match depth_range {
+            DepthRange::NegativeOneToOne => ((far + near) / (far - near), -2.0 * far * near / (far - near)),
+            DepthRange::ZeroToOne => (far / (far - near), -far * near / (far - near)),
+        };
+
+        Matrix4::perspective_from_depth(fov, aspect, depth_scale, depth_offset)
+    }
+
+    /// Creates a right-handed perspective projection matrix with the far plane at infinity.
+    ///
+    /// # Details
+    ///
+    /// This is the limit of `perspective()` as `far` goes to infinity, so nothing is ever clipped
+    /// for being too far away. Points approach the far end of the depth range as their distance
+    /// approaches infinity but never pass it. Moving the far plane to infinity costs very little
+    /// depth precision compared to a distant finite far plane.
+    pub fn perspective_infinite(fov: f32, aspect: f32, near: f32, depth_range: DepthRange) -> Matrix4 {
+        let mut projection = Matrix4::perspective_infinite_lh(fov, aspect, near, depth_range);
+
+        // Flipping the Z axis turns the left-handed projection into a right-handed one.
+        projection[2][2] = -projection[2][2];
+        projection[3][2] = -projection[3][2];
+        projection
+    }
+
+    /// Creates a left-handed perspective projection matrix with the far plane at infinity.
+    ///
+    /// # Details
+    ///
+    /// The same as `perspective_infinite()` except that the camera looks down the +Z axis.
+    pub fn perspective_infinite_lh(fov: f32, aspect: f32, near: f32, depth_range: DepthRange) -> Matrix4 {
+        let (depth_scale, depth_offset) = match depth_range {
+            DepthRange::NegativeOneToOne => (1.0, -2.0 * near),
+            DepthRange::ZeroToOne => (1.0, -near),
+        };
+
+        Matrix4::perspective_from_depth(fov, aspect, depth_scale, depth_offset)
+    }
+
+    /// Builds a left-handed perspective matrix that maps view depth `z` to `depth_scale + depth_offset / z`.
+    fn perspective_from_depth(fov: f32, aspect: f32, depth_scale: f32, depth_offset: f32) -> Matrix4 {
+        let focal_length = 1.0 / (fov * 0.5).tan();
+
+        Matrix4 {
+            data: [
+                [focal_length / aspect, 0.0,          0.0,         0.0         ],
+                [0.0,                   focal_length, 0.0,         0.0         ],
+                [0.0,                   0.0,          depth_scale, depth_offset],
+                [0.0,                   0.0,          1.0,         0.0         ],
+            ]
+        }
+    }
+
+    /// Creates a right-handed view matrix for a camera at `eye` looking towards `target`.
+    ///
+    /// # Details
+    ///
+    /// The direction from `eye` to `target` is mapped onto the -Z axis and `up` onto the +Y axis
+    /// as closely as possible. The result is degenerate if `eye` and `target` coincide or if the
+    /// view direction is parallel to `up`.
+    pub fn look_at(eye: Point, target: Point, up: Vector3) -> Matrix4 {
+        let forward = (target - eye).normalized();
+        let right = Vector3::cross(forward, up).normalized();
+        let up = Vector3::cross(right, forward);
+
+        Matrix4::view_from_basis(eye, right, up, -forward)
+    }
+
+    /// Creates a left-handed view matrix for a camera at `eye` looking towards `target`.
+    ///
+    /// # Details
+    ///
+    /// The same as `look_at()` except that the view direction is mapped onto the +Z axis.
+    pub fn look_at_lh(eye: Point, target: Point, up: Vector3) -> Matrix4 {
+        let forward = (target - eye).normalized();
+        let right = Vector3::cross(up, forward).normalized();
+        let up = Vector3::cross(forward, right);
+
+        Matrix4::view_from_basis(eye, right, up, forward)
+    }
+
+    /// Creates a view matrix that maps the camera's axes onto X, Y, and Z, and `eye` to the origin.
+    fn view_from_basis(eye: Point, x_axis: Vector3, y_axis: Vector3, z_axis: Vector3) -> Matrix4 {
+        let eye = eye.as_vector3();
+        Matrix4 {
+            data: [
+                [x_axis.x, x_axis.y, x_axis.z, -x_axis.dot(eye)],
+                [y_axis.x, y_axis.y, y_axis.z, -y_axis.dot(eye)],
+                [z_axis.x, z_axis.y, z_axis.z, -z_axis.dot(eye)],
+                [0.0,      0.0,      0.0,      1.0             ],
+            ]
+        }
+    }
+
     pub fn from_scale_vector(scale: Vector3) -> Matrix4 {
         Matrix4 {
             data: [
@@ -143,6 +343,57 @@ impl Matrix4 {
         transpose
     }
 
+    /// Calculates the inverse of the matrix.
+    ///
+    /// # Details
+    ///
+    /// The inverse is calculated using Gauss-Jordan elimination with partial pivoting, so it
+    /// works for any invertible matrix, not just affine transforms. Returns `None` if the matrix
+    /// is singular (e.g. a scale matrix with a zero component).
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut source = *self;
+        let mut inverse = Matrix4::identity();
+
+        for col in 0..4 {
+            // Find the row with the largest value in the current column to use as the pivot.
+            let mut pivot = col;
+            for row in (col + 1)..4 {
+                if source[row][col].abs() > source[pivot][col].abs() {
+                    pivot = row;
+                }
+            }
+
+            if source[pivot][col].is_zero() {
+                return None;
+            }
+
+            source.data.swap(col, pivot);
+            inverse.data.swap(col, pivot);
+
+            // Normalize the pivot row.
+            let scale = 1.0 / source[col][col];
+            for index in 0..4 {
+                source[col][index] *= scale;
+                inverse[col][index] *= scale;
+            }
+
+            // Eliminate the current column from all other rows.
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+
+                let factor = source[row][col];
+                for index in 0..4 {
+                    source[row][index] -= factor * source[col][index];
+                    inverse[row][index] -= factor * inverse[col][index];
+                }
+            }
+        }
+
+        Some(inverse)
+    }
+
     pub fn x_part(&self) -> Vector3 {
         Vector3::new(self[0][0], self[1][0], self[2][0])
     }
@@ -155,15 +406,81 @@ impl Matrix4 {
         Vector3::new(self[0][2], self[1][2], self[2][2])
     }
 
+    /// Extracts the scale from an affine transform matrix.
+    ///
+    /// # Details
+    ///
+    /// The magnitude of each scale component is the length of the corresponding basis vector.
+    /// If the matrix contains a reflection (i.e. the determinant of the upper 3x3 is negative)
+    /// the X component is negated, so that removing the extracted scale from the matrix leaves a
+    /// proper rotation. Any reflection can be represented this way, though it may not be the
+    /// same combination of negative components that the matrix was built with. A pure rotation
+    /// returns `(1, 1, 1)`.
+    ///
+    /// This is a standalone helper for when only the matrix is available. Code that already tracks
+    /// scale separately (such as a transform hierarchy) should use that instead, since it keeps
+    /// the exact sign of each component.
+    pub fn extract_scale(&self) -> Vector3 {
+        let x_part = self.x_part();
+        let y_part = self.y_part();
+        let z_part = self.z_part();
+
+        let determinant = x_part.dot(Vector3::cross(y_part, z_part));
+        let x_scale = if determinant < 0.0 { -x_part.magnitude() } else { x_part.magnitude() };
+
+        Vector3::new(x_scale, y_part.magnitude(), z_part.magnitude())
+    }
+
     pub fn translation_part(&self) -> Point {
         Point::new(self[0][3], self[1][3], self[2][3])
     }
 
+    /// Compares two matrices as affine transforms, ignoring the bottom row.
+    ///
+    /// # Details
+    ///
+    /// For affine transforms the bottom row is always `[0, 0, 0, 1]`, but float error from
+    /// operations like `inverse()` can leave noise there. This only compares the upper 3x4
+    /// portion of the matrices, using the same tolerance as `==`.
+    pub fn affine_eq(&self, other: &Matrix4) -> bool {
+        for row in 0..3 {
+            for col in 0..4 {
+                if !(self[row][col] - other[row][col]).is_zero() {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Transforms every point in `points` by the matrix, writing the results to `out`.
+    ///
+    /// `out` is cleared first, so it contains exactly one point for each point in `points`.
+    /// Reusing the same buffer avoids reallocating once it is large enough.
+    pub fn transform_points(&self, points: &[Point], out: &mut Vec<Point>) {
+        out.clear();
+        out.reserve(points.len());
+        for point in points {
+            out.push(*point * *self);
+        }
+    }
+
+    /// Transforms every point in `points` by the matrix in place.
+    pub fn transform_points_in_place(&self, points: &mut [Point]) {
+        for point in points {
+            *point = *point * *self;
+        }
+    }
+
+    /// Checks that none of the elements are NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.raw_data().iter().all(|element| element.is_finite())
+    }
+
     /// Get the matrix data as a raw array.
     pub fn raw_data(&self) -> &[f32; 16] {
-        // It's safe to transmute a pointer to data to a &[f32; 16]
-        // because the layout in memory is exactly the same.
-        unsafe { ::std::mem::transmute(&self.data) }
+        plain_data::cast(self)
     }
 }
 
@@ -242,6 +559,13 @@ impl Mul<Matrix4> for Matrix4 {
     }
 }
 
+impl MulAssign for Matrix4 {
+    fn mul_assign(&mut self, rhs: Matrix4) {
+        // `Mul` writes into a temporary, so this is safe even when `rhs` is a copy of `self`.
+        *self = *self * rhs;
+    }
+}
+
 impl Mul<Matrix4> for Point {
     type Output = Point;
 
@@ -356,6 +680,70 @@ impl Matrix3 {
     pub fn z_part(&self) -> Vector3 {
         Vector3::new(self[0][2], self[1][2], self[2][2])
     }
+
+    /// Calculates the determinant of the matrix.
+    pub fn determinant(&self) -> f32 {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+      - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+      + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Calculates the inverse of the matrix.
+    ///
+    /// # Details
+    ///
+    /// The inverse is calculated as the adjugate (the transposed matrix of cofactors) divided by
+    /// the determinant. Returns `None` if the matrix is singular, i.e. its determinant is zero
+    /// within `IsZero::is_zero()`.
+    pub fn inverse(&self) -> Option<Matrix3> {
+        let determinant = self.determinant();
+        if determinant.is_zero() {
+            return None;
+        }
+
+        let m = &self.0;
+        let inv_det = 1.0 / determinant;
+        Some(Matrix3([
+            [
+                (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv_det,
+                (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+                (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+            ],
+            [
+                (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv_det,
+                (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+                (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+            ],
+            [
+                (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv_det,
+                (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+                (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+            ],
+        ]))
+    }
+
+    /// Calculates the transpose of the inverse of the matrix.
+    ///
+    /// This is the matrix used to transform normals, since it keeps them perpendicular to their
+    /// surface even under non-uniform scale. Returns `None` if the matrix is singular.
+    pub fn inverse_transpose(&self) -> Option<Matrix3> {
+        self.inverse().map(|inverse| inverse.transpose())
+    }
+}
+
+impl PartialEq for Matrix3 {
+    fn eq(&self, other: &Matrix3) -> bool {
+        for row in 0..3 {
+            for col in 0..3 {
+                if !(self[row][col] - other[row][col]).is_zero() {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
 }
 
 impl Index<usize> for Matrix3 {
@@ -392,6 +780,13 @@ impl Mul for Matrix3 {
     }
 }
 
+impl MulAssign for Matrix3 {
+    fn mul_assign(&mut self, rhs: Matrix3) {
+        // `Mul` writes into a temporary, so this is safe even when `rhs` is a copy of `self`.
+        *self = *self * rhs;
+    }
+}
+
 impl Mul<Matrix3> for Point {
     type Output = Point;
 
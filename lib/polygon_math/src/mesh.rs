@@ -0,0 +1,347 @@
+//! Procedural generation of simple meshes, and per-vertex data derived from existing meshes.
+//!
+//! All generated meshes are centered on the origin, use counter-clockwise winding for front
+//! faces, and have one normal and one UV coordinate per vertex.
+
+use std::f32::consts::PI;
+
+use point::Point;
+use vector::{Vector2, Vector3};
+use super::{IsZero, Dot};
+
+/// The minimum number of segments around the equator of a UV sphere.
+pub const MIN_SPHERE_SEGMENTS: usize = 3;
+
+/// The minimum number of rings from pole to pole of a UV sphere.
+pub const MIN_SPHERE_RINGS: usize = 2;
+
+/// Vertex and index data for a triangle mesh.
+///
+/// # Details
+///
+/// `positions`, `normals`, and `uvs` all have one element per vertex. Every three elements of
+/// `indices` form a triangle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<Point>,
+    pub normals: Vec<Vector3>,
+    pub uvs: Vec<Vector2>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    pub fn new() -> MeshData {
+        MeshData {
+            positions: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn push_vertex(&mut self, position: Point, normal: Vector3, uv: Vector2) {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+    }
+
+    fn push_triangle(&mut self, first: usize, second: usize, third: usize) {
+        self.indices.push(first as u32);
+        self.indices.push(second as u32);
+        self.indices.push(third as u32);
+    }
+}
+
+/// Generates an axis-aligned cube with sides of length `size`.
+///
+/// # Details
+///
+/// Each face has its own four vertices so that the normals are flat, giving 24 vertices and 36
+/// indices in total. Each face is mapped to the full `[0, 1]` UV range.
+pub fn cube(size: f32) -> MeshData {
+    // The normal of each face and two axes along the face, chosen so that
+    // `cross(right, up) == normal`, which keeps the winding counter-clockwise.
+    let faces = [
+        (Vector3::new( 1.0,  0.0,  0.0), Vector3::new( 0.0,  0.0, -1.0), Vector3::new(0.0, 1.0,  0.0)),
+        (Vector3::new(-1.0,  0.0,  0.0), Vector3::new( 0.0,  0.0,  1.0), Vector3::new(0.0, 1.0,  0.0)),
+        (Vector3::new( 0.0,  1.0,  0.0), Vector3::new( 1.0,  0.0,  0.0), Vector3::new(0.0, 0.0, -1.0)),
+        (Vector3::new( 0.0, -1.0,  0.0), Vector3::new( 1.0,  0.0,  0.0), Vector3::new(0.0, 0.0,  1.0)),
+        (Vector3::new( 0.0,  0.0,  1.0), Vector3::new( 1.0,  0.0,  0.0), Vector3::new(0.0, 1.0,  0.0)),
+        (Vector3::new( 0.0,  0.0, -1.0), Vector3::new(-1.0,  0.0,  0.0), Vector3::new(0.0, 1.0,  0.0)),
+    ];
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+    let half_size = size * 0.5;
+    let mut mesh = MeshData::new();
+    for &(normal, right, up) in &faces {
+        let base = mesh.vertex_count();
+        for &(u, v) in &corners {
+            let offset = (normal + right * u + up * v) * half_size;
+            mesh.push_vertex(
+                Point::origin() + offset,
+                normal,
+                Vector2::new((u + 1.0) * 0.5, (v + 1.0) * 0.5));
+        }
+
+        mesh.push_triangle(base, base + 1, base + 2);
+        mesh.push_triangle(base, base + 2, base + 3);
+    }
+
+    mesh
+}
+
+/// Generates a sphere made of `rings` horizontal bands each divided into `segments` quads.
+///
+/// # Details
+///
+/// `segments` is clamped to at least `MIN_SPHERE_SEGMENTS` and `rings` to at least
+/// `MIN_SPHERE_RINGS`. U wraps once around the sphere and V runs from 1 at the top pole to 0
+/// at the bottom pole.
+///
+/// The first and last column of vertices are at the same positions but have U coordinates of 0
+/// and 1 respectively, so the texture doesn't wrap backwards across the seam. Each pole has one
+/// vertex per segment, with U offset to the middle of that segment, so that the texture isn't
+/// pinched towards one side. The bands touching the poles are made of triangles rather than
+/// quads since the quads would be degenerate.
+pub fn uv_sphere(radius: f32, segments: usize, rings: usize) -> MeshData {
+    let segments = ::std::cmp::max(segments, MIN_SPHERE_SEGMENTS);
+    let rings = ::std::cmp::max(rings, MIN_SPHERE_RINGS);
+
+    let mut mesh = MeshData::new();
+    for ring in 0..rings + 1 {
+        let polar_angle = PI * ring as f32 / rings as f32;
+        let is_pole = ring == 0 || ring == rings;
+
+        for segment in 0..segments + 1 {
+            let azimuth = 2.0 * PI * segment as f32 / segments as f32;
+
+            // Calculate the pole normals directly so they point exactly along the Y axis.
+            let normal = if ring == 0 {
+                Vector3::new(0.0, 1.0, 0.0)
+            } else if ring == rings {
+                Vector3::new(0.0, -1.0, 0.0)
+            } else {
+                Vector3::new(
+                    polar_angle.sin() * azimuth.cos(),
+                    polar_angle.cos(),
+                    polar_angle.sin() * azimuth.sin())
+            };
+
+            let u = if is_pole {
+                (segment as f32 + 0.5) / segments as f32
+            } else {
+                segment as f32 / segments as f32
+            };
+            let v = 1.0 - ring as f32 / rings as f32;
+
+            mesh.push_vertex(Point::origin() + normal * radius, normal, Vector2::new(u, v));
+        }
+    }
+
+    let row_length = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let upper = ring * row_length + segment;
+            let upper_next = upper + 1;
+            let lower = upper + row_length;
+            let lower_next = lower + 1;
+
+            // The upper vertices coincide at the top pole and the lower ones at the bottom pole.
+            if ring != 0 {
+                mesh.push_triangle(upper, upper_next, lower_next);
+            }
+
+            if ring != rings - 1 {
+                mesh.push_triangle(upper, lower_next, lower);
+            }
+        }
+    }
+
+    mesh
+}
+
+/// Generates a flat plane on the XZ plane facing up the +Y axis.
+///
+/// # Details
+///
+/// The plane is divided into `subdivisions` quads along each side, which is clamped to at least
+/// 1. The full `[0, 1]` UV range is stretched across the plane.
+pub fn plane(width: f32, depth: f32, subdivisions: usize) -> MeshData {
+    let subdivisions = ::std::cmp::max(subdivisions, 1);
+
+    let mut mesh = MeshData::new();
+    for row in 0..subdivisions + 1 {
+        let v = row as f32 / subdivisions as f32;
+        for column in 0..subdivisions + 1 {
+            let u = column as f32 / subdivisions as f32;
+            mesh.push_vertex(
+                Point::new((u - 0.5) * width, 0.0, (v - 0.5) * depth),
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector2::new(u, 1.0 - v));
+        }
+    }
+
+    let row_length = subdivisions + 1;
+    for row in 0..subdivisions {
+        for column in 0..subdivisions {
+            let back_left = row * row_length + column;
+            let back_right = back_left + 1;
+            let front_left = back_left + row_length;
+            let front_right = front_left + 1;
+
+            mesh.push_triangle(back_left, front_left, front_right);
+            mesh.push_triangle(back_left, front_right, back_right);
+        }
+    }
+
+    mesh
+}
+
+/// Calculates a tangent for each vertex, for use in normal mapping.
+///
+/// # Details
+///
+/// Uses Lengyel's method: the tangent of each triangle is the object space direction in which U
+/// increases, and each vertex accumulates the tangents of the triangles that use it. The result
+/// is then made orthogonal to the vertex's normal (Gram-Schmidt) and normalized. The bitangent
+/// can be reconstructed in the shader as `cross(normal, tangent)`, though this assumes that the
+/// UVs aren't mirrored.
+///
+/// Triangles with degenerate UVs (i.e. with zero area in UV space) don't have a well-defined
+/// tangent, so they don't contribute to their vertices. A vertex that receives no contribution
+/// is given an arbitrary tangent perpendicular to its normal.
+///
+/// # Panics
+///
+/// Panics if `positions`, `uvs`, and `normals` aren't the same length, or if `indices` contains
+/// an index out of range.
+pub fn compute_tangents(positions: &[Point], uvs: &[Vector2], indices: &[u32], normals: &[Vector3]) -> Vec<Vector3> {
+    assert!(
+        positions.len() == uvs.len() && positions.len() == normals.len(),
+        "Vertex data lengths don't match, {} positions, {} UVs, and {} normals",
+        positions.len(),
+        uvs.len(),
+        normals.len());
+
+    let mut tangents = vec![Vector3::zero(); positions.len()];
+    for triangle in indices.chunks(3) {
+        // Ignore any trailing indices that don't form a whole triangle.
+        if triangle.len() < 3 {
+            break;
+        }
+
+        let (first, second, third) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+        let edge_1 = positions[second] - positions[first];
+        let edge_2 = positions[third] - positions[first];
+        let (du_1, dv_1) = (uvs[second].x - uvs[first].x, uvs[second].y - uvs[first].y);
+        let (du_2, dv_2) = (uvs[third].x - uvs[first].x, uvs[third].y - uvs[first].y);
+
+        let determinant = du_1 * dv_2 - du_2 * dv_1;
+        if determinant.is_zero() {
+            continue;
+        }
+
+        let tangent = (edge_1 * dv_2 - edge_2 * dv_1) / determinant;
+        tangents[first] += tangent;
+        tangents[second] += tangent;
+        tangents[third] += tangent;
+    }
+
+    for (tangent, &normal) in tangents.iter_mut().zip(normals.iter()) {
+        let orthogonal = *tangent - normal * normal.dot(*tangent);
+        *tangent = if orthogonal.is_zero() {
+            any_perpendicular(normal)
+        } else {
+            orthogonal.normalized()
+        };
+    }
+
+    tangents
+}
+
+/// Reverses the winding of every triangle by swapping its second and third indices.
+///
+/// # Details
+///
+/// Use this to convert meshes exported with clockwise front faces to the counter-clockwise
+/// winding used by the rest of the library. Flipping twice gives back the original indices. Any
+/// trailing indices that don't form a whole triangle are left as-is.
+pub fn flip_winding(indices: &mut [u32]) {
+    for triangle in indices.chunks_mut(3) {
+        if triangle.len() == 3 {
+            triangle.swap(1, 2);
+        }
+    }
+}
+
+/// Computes smooth per-vertex normals from the triangles of a mesh.
+///
+/// # Details
+///
+/// Each triangle's face normal is the cross product of its edges, assuming counter-clockwise
+/// winding for front faces. The face normals are added to each of the triangle's vertices without
+/// being normalized first, so larger triangles have more influence, and then each vertex's sum is
+/// normalized.
+///
+/// If the face normals around a vertex cancel out (e.g. a vertex shared by two triangles facing
+/// opposite directions) the normal of the first non-degenerate triangle using the vertex is used
+/// instead. Vertices that aren't used by any non-degenerate triangle are given the +Y axis, so
+/// every returned normal is a valid unit vector.
+///
+/// # Panics
+///
+/// Panics if `indices` contains an index out of range.
+pub fn recompute_normals(positions: &[Point], indices: &[u32]) -> Vec<Vector3> {
+    let mut normals = vec![Vector3::zero(); positions.len()];
+    let mut first_face_normals: Vec<Option<Vector3>> = vec![None; positions.len()];
+    for triangle in indices.chunks(3) {
+        // Ignore any trailing indices that don't form a whole triangle.
+        if triangle.len() < 3 {
+            break;
+        }
+
+        let (first, second, third) = (triangle[0] as usize, triangle[1] as usize, triangle[2] as usize);
+        let face_normal = Vector3::cross(
+            positions[second] - positions[first],
+            positions[third] - positions[first]);
+        if face_normal.is_zero() {
+            continue;
+        }
+
+        for &vertex in &[first, second, third] {
+            normals[vertex] += face_normal;
+            if first_face_normals[vertex].is_none() {
+                first_face_normals[vertex] = Some(face_normal);
+            }
+        }
+    }
+
+    for (normal, first_face_normal) in normals.iter_mut().zip(first_face_normals.into_iter()) {
+        *normal = if !normal.is_zero() {
+            normal.normalized()
+        } else {
+            match first_face_normal {
+                Some(face_normal) => face_normal.normalized(),
+                None => Vector3::new(0.0, 1.0, 0.0),
+            }
+        };
+    }
+
+    normals
+}
+
+/// Returns an arbitrary unit vector perpendicular to `normal`.
+fn any_perpendicular(normal: Vector3) -> Vector3 {
+    // Cross with whichever axis is least aligned with the normal to avoid a degenerate result.
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+
+    Vector3::cross(normal, axis).normalized()
+}
@@ -0,0 +1,84 @@
+//! Reinterpreting math types as raw `f32` data.
+//!
+//! Graphics APIs want vertex and uniform data as flat arrays of floats, and most of the math
+//! types are laid out exactly that way. Rather than scattering `unsafe` casts across the crate
+//! every such conversion goes through the functions in this module, which check the layout
+//! requirements of the types involved.
+
+use std::mem;
+use std::slice;
+
+use matrix::{Matrix3, Matrix4};
+use point::Point;
+use vector::{Vector2, Vector3};
+use color::Color;
+
+/// Marker for types that are laid out as a tightly packed sequence of `f32`s.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` (or arrays) and contain nothing but `f32` fields, so that
+/// the type has no padding, the alignment of an `f32`, and a size that is a multiple of the size
+/// of an `f32`. The size and alignment are checked every time a cast is performed, but the lack
+/// of padding and the field types can't be, which is why implementing this trait is `unsafe`.
+pub unsafe trait PlainData: Copy {}
+
+unsafe impl PlainData for f32 {}
+unsafe impl PlainData for [f32; 2] {}
+unsafe impl PlainData for [f32; 3] {}
+unsafe impl PlainData for [f32; 4] {}
+unsafe impl PlainData for [f32; 9] {}
+unsafe impl PlainData for [f32; 16] {}
+unsafe impl PlainData for Vector2 {}
+unsafe impl PlainData for Vector3 {}
+unsafe impl PlainData for Point {}
+unsafe impl PlainData for Color {}
+unsafe impl PlainData for Matrix3 {}
+unsafe impl PlainData for Matrix4 {}
+
+/// The number of `f32`s in a `T`.
+///
+/// # Details
+///
+/// `size_of()` and `align_of()` can't be used in constant expressions, so the layout checks
+/// happen here at runtime instead. Both sides of each comparison are constant for a given `T`
+/// though, so the checks are optimized out of release builds.
+fn f32_count<T: PlainData>() -> usize {
+    assert!(
+        mem::align_of::<T>() == mem::align_of::<f32>(),
+        "Plain data type must have the alignment of f32");
+    assert!(
+        mem::size_of::<T>() % mem::size_of::<f32>() == 0 && mem::size_of::<T>() > 0,
+        "Plain data type must have a size that is a non-zero multiple of the size of f32");
+
+    mem::size_of::<T>() / mem::size_of::<f32>()
+}
+
+/// Reinterprets a slice of plain data as a slice of `f32`s without copying.
+pub fn as_f32_slice<T: PlainData>(data: &[T]) -> &[f32] {
+    let count = f32_count::<T>();
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const f32, data.len() * count) }
+}
+
+/// Reinterprets a slice of `f32`s as a slice of plain data without copying.
+///
+/// Panics if the length of `data` isn't a multiple of the number of `f32`s in a `T`.
+pub fn from_f32_slice<T: PlainData>(data: &[f32]) -> &[T] {
+    let count = f32_count::<T>();
+    assert!(
+        data.len() % count == 0,
+        "Cannot reinterpret {} f32s as a slice of values made of {} f32s", data.len(), count);
+
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const T, data.len() / count) }
+}
+
+/// Reinterprets a reference to one plain data type as another of the same size.
+///
+/// Panics if the two types have different sizes.
+pub fn cast<T: PlainData, U: PlainData>(value: &T) -> &U {
+    assert!(
+        f32_count::<T>() == f32_count::<U>(),
+        "Cannot cast between plain data types of different sizes");
+
+    unsafe { &*(value as *const T as *const U) }
+}
@@ -0,0 +1,58 @@
+//! Infinite planes in 3D space.
+
+use point::Point;
+use vector::Vector3;
+use super::{IsZero, Dot};
+
+/// An infinite plane, stored as a unit normal and the plane's signed distance from the origin
+/// along that normal.
+///
+/// A point `p` lies on the plane when `normal.dot(p) == distance`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// Creates a plane from a normal and a distance from the origin.
+    ///
+    /// # Details
+    ///
+    /// `normal` does not need to be normalized, `distance` is measured in multiples of `normal`
+    /// and is rescaled along with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `normal` is the zero vector.
+    pub fn new(normal: Vector3, distance: f32) -> Plane {
+        let magnitude = normal.magnitude();
+        assert!(!magnitude.is_zero(), "Cannot create a plane with a zero normal");
+
+        Plane {
+            normal: normal / magnitude,
+            distance: distance / magnitude,
+        }
+    }
+
+    /// Creates the plane that passes through `point` and faces along `normal`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `normal` is the zero vector.
+    pub fn from_point_normal(point: Point, normal: Vector3) -> Plane {
+        let normal = normal.normalized();
+        assert!(!normal.is_zero(), "Cannot create a plane with a zero normal");
+
+        Plane {
+            normal: normal,
+            distance: normal.dot(point.as_vector3()),
+        }
+    }
+
+    /// Returns the distance from the plane to `point`, which is positive if the point is on the
+    /// side the normal faces and negative if it's behind the plane.
+    pub fn signed_distance(&self, point: Point) -> f32 {
+        self.normal.dot(point.as_vector3()) - self.distance
+    }
+}
@@ -1,11 +1,11 @@
 use std::ops::{Sub, Add, AddAssign, Neg};
 use std::cmp::{PartialOrd, Ord, Ordering};
-use std::mem;
 use std::f32;
-use std::raw::Slice;
-use std::slice;
+use std::fmt::{self, Display, Formatter};
 
 use vector::Vector3;
+use plain_data;
+use super::{quantize, DISPLAY_PRECISION};
 
 /// A point in 3D space.
 ///
@@ -81,25 +81,95 @@ impl Point {
         Vector3::new(self.x, self.y, self.z)
     }
 
+    /// Checks that none of the components are NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite() && self.w.is_finite()
+    }
+
     pub fn as_array(&self) -> &[f32; 4] {
-        unsafe { mem::transmute(self) }
+        plain_data::cast(self)
     }
 
     pub fn as_ref(points: &[Point]) -> &[f32] {
-        unsafe {
-            let Slice { data, len } = mem::transmute(points);
-            slice::from_raw_parts(data, len * 4)
+        plain_data::as_f32_slice(points)
+    }
+
+    /// Gradually moves `current` towards `target` using a critically damped spring.
+    ///
+    /// See `Vector3::smooth_damp()` for more information.
+    pub fn smooth_damp(current: Point, target: Point, velocity: &mut Vector3, smooth_time: f32, dt: f32) -> Point {
+        let result = Vector3::smooth_damp(current.as_vector3(), target.as_vector3(), velocity, smooth_time, dt);
+        Point::new(result.x, result.y, result.z)
+    }
+
+    /// Moves `current` towards `target` by at most `max_distance_delta`.
+    ///
+    /// See `Vector3::move_towards()` for more information.
+    pub fn move_towards(current: Point, target: Point, max_distance_delta: f32) -> Point {
+        let offset = target - current;
+        let distance = offset.magnitude();
+        if distance <= max_distance_delta || distance == 0.0 {
+            target
+        } else {
+            current + offset * (max_distance_delta / distance)
+        }
+    }
+
+    /// Snaps the point to a grid with cells of size `epsilon`, returning a key that can be hashed.
+    ///
+    /// # Details
+    ///
+    /// Each component is rounded to the nearest integer multiple of `epsilon`, so two points that
+    /// quantize equal are within `epsilon` of each other on every axis, and points closer than
+    /// `epsilon / 2` on every axis usually quantize equal. Points that straddle the halfway mark
+    /// between two multiples quantize differently no matter how close together they are, so
+    /// welding vertices may need to also check the neighboring cells. The homogeneous coordinate
+    /// is ignored. Panics if `epsilon` isn't positive.
+    pub fn quantize(&self, epsilon: f32) -> QuantizedPoint {
+        QuantizedPoint {
+            x: quantize(self.x, epsilon),
+            y: quantize(self.y, epsilon),
+            z: quantize(self.z, epsilon),
         }
     }
 
+    /// Formats the point as `(x, y, z)` with the specified number of decimal digits.
+    pub fn fmt_precision(&self, digits: usize) -> String {
+        format!("{:.*}", digits, self)
+    }
+
     pub fn slice_from_f32_slice(raw: &[f32]) -> &[Point] {
         assert!(raw.len() % 4 == 0, "To convert a slice of f32 to a slice of Point it must have a length that is a multiple of 4");
 
-        unsafe {
-            slice::from_raw_parts(
-                raw.as_ptr() as *const Point,
-                raw.len() / 4)
-        }
+        plain_data::from_f32_slice(raw)
+    }
+}
+
+/// A point snapped to a grid, see `Point::quantize()`.
+///
+/// The components are the number of grid cells from the origin along each axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuantizedPoint {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl QuantizedPoint {
+    /// Converts back to a point, `epsilon` should be the same value used to quantize the point.
+    pub fn to_point(&self, epsilon: f32) -> Point {
+        Point::new(self.x as f32 * epsilon, self.y as f32 * epsilon, self.z as f32 * epsilon)
+    }
+}
+
+/// Formats the point as `(x, y, z)`, omitting the homogeneous coordinate.
+///
+/// If no precision is specified in the format string the components are printed with
+/// `DISPLAY_PRECISION` decimal digits.
+impl Display for Point {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let precision = formatter.precision().unwrap_or(DISPLAY_PRECISION);
+        write!(formatter, "({:.*}, {:.*}, {:.*})", precision, self.x, precision, self.y, precision, self.z)
     }
 }
 
@@ -1,9 +1,10 @@
-use std::ops::Mul;
+use std::ops::{Index, IndexMut, Mul};
 use std::f32::consts::PI;
+use std::fmt::{self, Display, Formatter};
 
 use vector::Vector3;
 use matrix::*;
-use super::{IsZero, Clamp, Dot};
+use super::{IsZero, Clamp, Dot, DISPLAY_PRECISION};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Quaternion {
@@ -46,35 +47,114 @@ impl Quaternion {
     }
 
     /// Creates a quaternion that rotates an object to look in the specified direction.
+    ///
+    /// # Details
+    ///
+    /// If `forward` and `up` are parallel then `up` can't be used to orient the rotation, in
+    /// which case the world axis least aligned with `forward` is used as the up vector instead.
+    /// Use `Quaternion::try_look_rotation()` to handle that case manually.
     pub fn look_rotation(forward: Vector3, up: Vector3) -> Quaternion {
         assert!(!forward.is_zero());
         assert!(!up.is_zero());
 
+        if let Some(rotation) = Quaternion::try_look_rotation(forward, up) {
+            return rotation;
+        }
+
+        let forward = forward.normalized();
+        let (x, y, z) = (forward.x.abs(), forward.y.abs(), forward.z.abs());
+        let alternate_up = if x <= y && x <= z {
+            Vector3::new(1.0, 0.0, 0.0)
+        } else if y <= z {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3::new(0.0, 0.0, 1.0)
+        };
+
+        Quaternion::try_look_rotation(forward, alternate_up)
+            .expect("Alternate up vector should never be parallel to forward")
+    }
+
+    /// Creates a quaternion that rotates an object to look in the specified direction.
+    ///
+    /// Returns `None` if either `forward` or `up` is the zero vector, or if `forward` and `up` are
+    /// parallel.
+    pub fn try_look_rotation(forward: Vector3, up: Vector3) -> Option<Quaternion> {
+        if forward.is_zero() || up.is_zero() {
+            return None;
+        }
+
         let source = Vector3::forward();
         let forward = forward.normalized();
         let up = up.normalized();
 
+        if Vector3::cross(forward, up).is_zero() {
+            return None;
+        }
+
         let dot = source.dot(forward);
 
         if (dot + 1.0).is_zero() {
             // vector a and b point exactly in the opposite direction,
             // so it is a 180 degrees turn around the up-axis
-            return Quaternion::axis_angle(up, PI)
+            return Some(Quaternion::axis_angle(up, PI));
         }
 
         if (dot - 1.0).is_zero() {
             // Vector a and b point exactly in the same direction
             // so we return the identity quaternion.
-            return Quaternion::identity()
+            return Some(Quaternion::identity());
         }
 
         let rot_angle = dot.acos();
         let rot_axis = Vector3::cross(source, forward).normalized();// source.cross(forward).normalized();
-        return Quaternion::axis_angle(rot_axis, rot_angle)
+        Some(Quaternion::axis_angle(rot_axis, rot_angle))
 
         // TODO: Correctly take the up vector into account.
     }
 
+    /// Creates the shortest rotation that rotates the direction `from` onto the direction `to`.
+    ///
+    /// # Details
+    ///
+    /// Neither `from` nor `to` need to be normalized, but neither may be the zero vector. If the
+    /// directions are the same the identity quaternion is returned. If they point in opposite
+    /// directions there is no unique shortest rotation, so a 180 degree rotation around an
+    /// arbitrary axis perpendicular to `from` is returned.
+    pub fn from_to_rotation(from: Vector3, to: Vector3) -> Quaternion {
+        assert!(!from.is_zero());
+        assert!(!to.is_zero());
+
+        let from = from.normalized();
+        let to = to.normalized();
+        let dot = from.dot(to);
+
+        if (dot + 1.0).is_zero() {
+            // Use whichever world axis is least aligned with `from` to build a perpendicular axis.
+            let (x, y, z) = (from.x.abs(), from.y.abs(), from.z.abs());
+            let other = if x <= y && x <= z {
+                Vector3::new(1.0, 0.0, 0.0)
+            } else if y <= z {
+                Vector3::new(0.0, 1.0, 0.0)
+            } else {
+                Vector3::new(0.0, 0.0, 1.0)
+            };
+
+            let axis = Vector3::cross(from, other).normalized();
+            return Quaternion::axis_angle(axis, PI);
+        }
+
+        // (1 + dot, cross) is the desired rotation scaled by 2 * cos(angle / 2), so normalizing it
+        // gives the shortest arc rotation without any trigonometry.
+        let axis = Vector3::cross(from, to);
+        Quaternion {
+            w: 1.0 + dot,
+            x: axis.x,
+            y: axis.y,
+            z: axis.z,
+        }.normalized()
+    }
+
     /// Creates a quaternion from a set of euler angles.
     pub fn from_eulers(x: f32, y: f32, z: f32) -> Quaternion {
         Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), x)
@@ -126,6 +206,57 @@ impl Quaternion {
         Vector3::new(x, y, z)
     }
 
+    /// Limits how far the rotation can turn `reference_forward` away from its original direction.
+    ///
+    /// # Details
+    ///
+    /// `reference_forward` is the direction the rotated object faces at rest (e.g. the barrel of
+    /// a turret). If rotating it by `self` moves it more than `max_angle` radians away from
+    /// itself the rotation is pulled back along the shortest arc so that it lies exactly on the
+    /// boundary of the cone. Any twist around the forward direction is preserved. Rotations that
+    /// are already within the cone are returned unchanged.
+    pub fn clamp_to_cone(&self, reference_forward: Vector3, max_angle: f32) -> Quaternion {
+        assert!(!reference_forward.is_zero());
+
+        let reference = reference_forward.normalized();
+        let forward = reference * self.as_matrix3();
+        let angle = reference.dot(forward).clamp(-1.0, 1.0).acos();
+        if angle <= max_angle {
+            return *self;
+        }
+
+        // Rotate the forward direction back towards the reference around the axis perpendicular
+        // to both. If they point in opposite directions any perpendicular axis will do.
+        let mut axis = Vector3::cross(reference, forward);
+        if axis.is_zero() {
+            axis = Vector3::cross(reference, Vector3::new(1.0, 0.0, 0.0));
+            if axis.is_zero() {
+                axis = Vector3::cross(reference, Vector3::new(0.0, 1.0, 0.0));
+            }
+        }
+
+        let correction = Quaternion::axis_angle(axis.normalized(), max_angle - angle);
+        correction * *self
+    }
+
+    /// Clamps each of the rotation's euler angles to the specified range.
+    ///
+    /// # Details
+    ///
+    /// The angles are the ones returned by `as_eulers()`, given as `(x, y, z)` in radians. The
+    /// clamped rotation is rebuilt in the order that `as_eulers()` decomposes rotations (z, then
+    /// y, then x), so a rotation within all limits is returned unchanged.
+    pub fn clamp_euler(&self, min: (f32, f32, f32), max: (f32, f32, f32)) -> Quaternion {
+        let eulers = self.as_eulers();
+        let x = eulers.x.clamp(min.0, max.0);
+        let y = eulers.y.clamp(min.1, max.1);
+        let z = eulers.z.clamp(min.2, max.2);
+
+        Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), z)
+      * Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), y)
+      * Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), x)
+    }
+
     /// Normalizes the quaternion to unit length.
     ///
     /// The quaternion must not have a length of zero when calling this method.
@@ -158,11 +289,114 @@ impl Quaternion {
         (mag_sqrd - 1.0).is_zero()
     }
 
+    /// Checks that none of the components are NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.w.is_finite() && self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    /// Formats the quaternion as `(w, x, y, z)` with the specified number of decimal digits.
+    pub fn fmt_precision(&self, digits: usize) -> String {
+        format!("{:.*}", digits, self)
+    }
+
+    /// Calculates the conjugate of the quaternion.
+    ///
+    /// For a normalized quaternion the conjugate is also its inverse, i.e. the rotation that
+    /// undoes it.
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
     pub fn repeat(&self, repeat: f32) -> Quaternion {
         let (axis, angle) = self.as_axis_angle();
         Quaternion::axis_angle(axis, angle * repeat)
     }
 
+    /// Calculates the natural logarithm of the quaternion.
+    ///
+    /// # Details
+    ///
+    /// For a unit quaternion rotating `angle` radians about `axis` the result is the pure
+    /// quaternion `(0, axis * angle / 2)`, so the log of the identity quaternion is the zero
+    /// quaternion. The result is generally not normalized and should only be used with `exp()`
+    /// or the quaternion arithmetic that builds on it, not as a rotation.
+    ///
+    /// A negative real quaternion (a full turn about any axis) has no unique logarithm, in which
+    /// case the rotation is taken to be about the X axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the quaternion has a length of zero.
+    pub fn log(&self) -> Quaternion {
+        assert!(!self.is_zero(), "Cannot take the log of a zero quaternion");
+
+        let magnitude = Quaternion::dot(*self, *self).sqrt();
+        let vector_magnitude = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+
+        let scale = if vector_magnitude == 0.0 && self.w < 0.0 {
+            return Quaternion {
+                w: magnitude.ln(),
+                x: PI,
+                y: 0.0,
+                z: 0.0,
+            };
+        } else if self.w > 0.0 && vector_magnitude.is_zero() {
+            // atan2(v, w) / v approaches 1 / w as v goes to zero, use the limit directly to
+            // avoid dividing by a (nearly) zero value.
+            1.0 / self.w
+        } else {
+            f32::atan2(vector_magnitude, self.w) / vector_magnitude
+        };
+
+        Quaternion {
+            w: magnitude.ln(),
+            x: self.x * scale,
+            y: self.y * scale,
+            z: self.z * scale,
+        }
+    }
+
+    /// Calculates the exponential of the quaternion, the inverse of `log()`.
+    ///
+    /// The exponential of a pure quaternion (one with a real part of zero) is always a unit
+    /// quaternion, and the exponential of the zero quaternion is the identity quaternion.
+    pub fn exp(&self) -> Quaternion {
+        let vector_magnitude = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        let scale = self.w.exp();
+
+        // sin(v) / v approaches 1 as v goes to zero.
+        let sinc = if vector_magnitude.is_zero() {
+            1.0
+        } else {
+            vector_magnitude.sin() / vector_magnitude
+        };
+
+        Quaternion {
+            w: scale * vector_magnitude.cos(),
+            x: scale * sinc * self.x,
+            y: scale * sinc * self.y,
+            z: scale * sinc * self.z,
+        }
+    }
+
+    /// Raises the quaternion to the power `t`.
+    ///
+    /// # Details
+    ///
+    /// For a unit quaternion this scales the angle of the rotation by `t` while keeping the same
+    /// axis, e.g. `pow(0.5)` gives the rotation halfway to `self`. Unlike `repeat()` the rotation
+    /// isn't converted to an axis and angle, so it's well-behaved for rotations close to the
+    /// identity. A quaternion with a negative `w` represents the longer way around, and its powers
+    /// follow that longer path.
+    pub fn pow(&self, t: f32) -> Quaternion {
+        self.log().mul(t).exp()
+    }
+
     /// Calculates the dot product of two quaternions.
     pub fn dot(first: Quaternion, second: Quaternion) -> f32 {
         (first.w * second.w
@@ -224,6 +458,53 @@ impl Quaternion {
         return (first.mul(theta.cos()).add(normal.mul(theta.sin()))).normalized();
     }
 
+    /// Interpolates along a smooth curve through a series of rotation keyframes using spherical
+    /// quadrangle interpolation.
+    ///
+    /// # Details
+    ///
+    /// Interpolates from `first` at `t = 0` to `second` at `t = 1`. `first_control` and
+    /// `second_control` shape the curve and are calculated with `Quaternion::squad_control()` from
+    /// the keyframes on either side of `first` and `second` respectively. Chaining segments
+    /// together this way produces an animation whose angular velocity is continuous across
+    /// keyframes, unlike chaining `slerp()`.
+    ///
+    /// Unlike `slerp()` this doesn't correct for `first` and `second` being in opposite
+    /// hemispheres, so the sign of each keyframe should be chosen so that it has a non-negative
+    /// dot product with the previous keyframe before calculating the control points.
+    pub fn squad(
+        first: Quaternion,
+        second: Quaternion,
+        first_control: Quaternion,
+        second_control: Quaternion,
+        t: f32,
+    ) -> Quaternion {
+        Quaternion::slerp_direct(
+            Quaternion::slerp_direct(first, second, t),
+            Quaternion::slerp_direct(first_control, second_control, t),
+            2.0 * t * (1.0 - t))
+    }
+
+    /// Calculates the control point for keyframe `current` used by `Quaternion::squad()`.
+    ///
+    /// # Details
+    ///
+    /// `previous` and `next` are the keyframes before and after `current`. For the first and last
+    /// keyframes of an animation use `current` in place of the missing neighbor.
+    pub fn squad_control(previous: Quaternion, current: Quaternion, next: Quaternion) -> Quaternion {
+        let inverse = current.conjugate();
+        let to_next = (inverse * next).log();
+        let to_previous = (inverse * previous).log();
+
+        current * to_next.add(to_previous).mul(-0.25).exp()
+    }
+
+    /// Interpolates along the great arc from `first` to `second` without picking the shorter
+    /// path.
+    fn slerp_direct(first: Quaternion, second: Quaternion, t: f32) -> Quaternion {
+        first * (first.conjugate() * second).pow(t)
+    }
+
     fn mul(self, rhs: f32) -> Quaternion {
         Quaternion {
             w: self.w * rhs,
@@ -270,11 +551,56 @@ impl Mul<Quaternion> for Quaternion {
 
 // TODO: impl Mul<Vector3> for Quaternion (or maybe other way around).
 
+/// The tolerance is compared against the squared magnitude of the quaternion.
 impl IsZero for Quaternion {
-    fn is_zero(self) -> bool {
+    fn is_zero_within(self, epsilon: f32) -> bool {
         (self.w * self.w
        + self.x * self.x
        + self.y * self.y
-       + self.z * self.z).is_zero()
+       + self.z * self.z).is_zero_within(epsilon)
+    }
+}
+
+/// Formats the quaternion as `(w, x, y, z)`.
+///
+/// If no precision is specified in the format string the components are printed with
+/// `DISPLAY_PRECISION` decimal digits.
+impl Display for Quaternion {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let precision = formatter.precision().unwrap_or(DISPLAY_PRECISION);
+        write!(
+            formatter,
+            "({:.*}, {:.*}, {:.*}, {:.*})",
+            precision, self.w,
+            precision, self.x,
+            precision, self.y,
+            precision, self.z)
+    }
+}
+
+/// Accesses the components of the quaternion in `(w, x, y, z)` order.
+impl Index<usize> for Quaternion {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &f32 {
+        match index {
+            0 => &self.w,
+            1 => &self.x,
+            2 => &self.y,
+            3 => &self.z,
+            _ => panic!("Index {} is out of bounds for Quaternion", index),
+        }
+    }
+}
+
+impl IndexMut<usize> for Quaternion {
+    fn index_mut(&mut self, index: usize) -> &mut f32 {
+        match index {
+            0 => &mut self.w,
+            1 => &mut self.x,
+            2 => &mut self.y,
+            3 => &mut self.z,
+            _ => panic!("Index {} is out of bounds for Quaternion", index),
+        }
     }
 }
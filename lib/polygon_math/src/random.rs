@@ -0,0 +1,103 @@
+//! A small, fast, seedable pseudo-random number generator for gameplay code.
+//!
+//! The generator is deterministic: seeding two generators with the same value produces the same
+//! sequence of values on every platform, which makes it suitable for replays and tests. It is NOT
+//! suitable for anything security related.
+
+use std::f32::consts::PI;
+
+use point::Point;
+use vector::Vector3;
+
+/// A pseudo-random number generator using the xorshift128+ algorithm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 2],
+}
+
+impl Rng {
+    /// Creates a new generator from a seed.
+    ///
+    /// # Details
+    ///
+    /// The seed is expanded into the generator's full state using SplitMix64, so any seed
+    /// (including zero) produces a well-mixed starting state.
+    pub fn new(seed: u64) -> Rng {
+        let mut seed = seed;
+        let first = split_mix(&mut seed);
+        let second = split_mix(&mut seed);
+
+        // xorshift128+ gets stuck at zero if the entire state is zero.
+        let state = if first == 0 && second == 0 { [1, 0] } else { [first, second] };
+        Rng {
+            state: state,
+        }
+    }
+
+    /// Generates a uniformly distributed 64 bit value.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut s1 = self.state[0];
+        let s0 = self.state[1];
+        self.state[0] = s0;
+        s1 ^= s1 << 23;
+        self.state[1] = s1 ^ s0 ^ (s1 >> 17) ^ (s0 >> 26);
+        self.state[1].wrapping_add(s0)
+    }
+
+    /// Generates a uniformly distributed 32 bit value.
+    pub fn next_u32(&mut self) -> u32 {
+        // The high bits of xorshift128+ are of better quality than the low bits.
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Generates a uniformly distributed value in the range `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        // Only use as many bits as an f32 mantissa can represent exactly so the result can never
+        // round up to 1.0.
+        (self.next_u32() >> 8) as f32 * (1.0 / (1 << 24) as f32)
+    }
+
+    /// Generates a uniformly distributed value in the range `[lo, hi)`.
+    ///
+    /// Because of floating point rounding the result may equal `hi` for very wide ranges.
+    pub fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        debug_assert!(lo <= hi, "Lower bound {} of range is greater than upper bound {}", lo, hi);
+
+        lo + (hi - lo) * self.next_f32()
+    }
+
+    /// Generates a random direction uniformly distributed over the unit sphere.
+    ///
+    /// # Details
+    ///
+    /// Choosing the height uniformly in `[-1, 1]` and the angle around the Y axis uniformly gives
+    /// a uniform distribution over the sphere (Archimedes' hat-box theorem). Picking two random
+    /// angles instead would cluster the results around the poles.
+    pub fn unit_vector(&mut self) -> Vector3 {
+        let y = self.range(-1.0, 1.0);
+        let angle = self.range(0.0, 2.0 * PI);
+        let radius = (1.0 - y * y).max(0.0).sqrt();
+
+        Vector3::new(radius * angle.cos(), y, radius * angle.sin())
+    }
+
+    /// Generates a random point uniformly distributed within the unit sphere centered at the
+    /// origin.
+    pub fn unit_point_in_sphere(&mut self) -> Point {
+        // The cube root makes the distance from the center account for the volume of the shell at
+        // that distance, otherwise points would cluster around the center.
+        let direction = self.unit_vector();
+        let distance = self.next_f32().cbrt();
+
+        Point::origin() + direction * distance
+    }
+}
+
+/// Advances `state` and returns the next SplitMix64 output.
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
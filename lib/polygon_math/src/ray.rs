@@ -0,0 +1,51 @@
+//! Rays for picking and line-of-sight queries.
+
+use point::Point;
+use vector::Vector3;
+use super::IsZero;
+
+/// A half-infinite line starting at `origin` and extending along `direction`.
+///
+/// Positions along the ray are given by a parameter `t`, where `origin + direction * t` is the
+/// point at `t`. Rays created with `new()` have a unit length direction so `t` is the distance
+/// from the origin, but the fields are public so a ray can also be built with a scaled direction,
+/// e.g. when transforming it into another space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: Vector3,
+}
+
+impl Ray {
+    /// Creates a ray starting at `origin` and pointing along `direction`.
+    ///
+    /// # Details
+    ///
+    /// `direction` doesn't need to be normalized, it's normalized automatically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `direction` is the zero vector.
+    pub fn new(origin: Point, direction: Vector3) -> Ray {
+        assert!(!direction.is_zero(), "Cannot create a ray with a zero direction");
+
+        Ray {
+            origin: origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    /// Creates the ray starting at `from` and pointing towards `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two points are coincident.
+    pub fn from_points(from: Point, to: Point) -> Ray {
+        Ray::new(from, to - from)
+    }
+
+    /// Returns the point at `t` along the ray.
+    pub fn point_at(&self, t: f32) -> Point {
+        self.origin + self.direction * t
+    }
+}
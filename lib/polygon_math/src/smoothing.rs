@@ -0,0 +1,46 @@
+//! Frame-rate independent smoothing functions.
+//!
+//! Unlike a simple lerp towards a target these functions take the elapsed time into account, so
+//! the motion looks the same regardless of frame rate, and they never overshoot the target.
+
+use super::IsZero;
+
+/// Gradually moves `current` towards `target` using a critically damped spring.
+///
+/// # Params
+///
+/// - current - The current value.
+/// - target - The value being moved towards.
+/// - velocity - The current rate of change of the value. This is updated each time the function
+///   is called and should be persisted between calls, starting at 0.0.
+/// - smooth_time - Roughly the time in seconds it takes to reach the target. If this is zero
+///   the target is returned immediately.
+/// - dt - The time in seconds since the last call.
+///
+/// # Details
+///
+/// This is the critically damped spring approximation from Game Programming Gems 4, chapter 1.10.
+pub fn smooth_damp(current: f32, target: f32, velocity: &mut f32, smooth_time: f32, dt: f32) -> f32 {
+    if smooth_time.is_zero() {
+        *velocity = 0.0;
+        return target;
+    }
+
+    let omega = 2.0 / smooth_time;
+    let x = omega * dt;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * exp;
+
+    let result = target + (change + temp) * exp;
+
+    // Prevent overshooting the target.
+    if (target - current > 0.0) == (result > target) {
+        *velocity = 0.0;
+        return target;
+    }
+
+    result
+}
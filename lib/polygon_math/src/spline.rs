@@ -0,0 +1,124 @@
+//! Smooth curves through a series of control points, e.g. for camera paths.
+
+use point::Point;
+use vector::Vector3;
+use super::Clamp;
+
+/// A Catmull-Rom spline, which passes through every one of its control points.
+///
+/// # Details
+///
+/// The path is made of one segment between each pair of consecutive control points, and each
+/// segment covers an equal share of the `[0, 1]` parameter range regardless of its length. The
+/// shape of each segment also depends on the control points on either side of it, so the first
+/// and last control points are duplicated to give the end segments a neighbor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRom {
+    pub points: Vec<Point>,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Point>) -> CatmullRom {
+        CatmullRom {
+            points: points,
+        }
+    }
+
+    /// Samples the position along the path at `t`.
+    ///
+    /// # Details
+    ///
+    /// `t` is clamped to `[0, 1]`, where 0 is the first control point and 1 is the last. A path
+    /// with a single control point always returns that point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path has no control points.
+    pub fn sample(&self, t: f32) -> Point {
+        let (points, t) = self.segment(t);
+        let (p0, p1, p2, p3) = (points[0], points[1], points[2], points[3]);
+
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let position =
+            (p1 * 2.0
+          + (p2 - p0) * t
+          + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+          + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5;
+
+        Point::origin() + position
+    }
+
+    /// Samples the tangent of the path at `t`.
+    ///
+    /// # Details
+    ///
+    /// The tangent is the derivative of the position with respect to `t`, so it points in the
+    /// direction of travel and isn't normalized. Its length is the rate at which the position
+    /// changes at `t`. `t` is clamped to `[0, 1]`, and a path with a single control point has a
+    /// zero tangent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path has no control points.
+    pub fn sample_tangent(&self, t: f32) -> Vector3 {
+        let (points, t) = self.segment(t);
+        let (p0, p1, p2, p3) = (points[0], points[1], points[2], points[3]);
+
+        let tangent =
+            ((p2 - p0)
+          + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (2.0 * t)
+          + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (3.0 * t * t)) * 0.5;
+
+        // Convert from the rate of change within the segment to the rate over the whole path.
+        tangent * self.segment_count() as f32
+    }
+
+    /// Approximates the length of the path by summing the distances between `samples` + 1 evenly
+    /// spaced points along it.
+    ///
+    /// More samples gives a more accurate length. `samples` is clamped to at least 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path has no control points.
+    pub fn length(&self, samples: usize) -> f32 {
+        let samples = ::std::cmp::max(samples, 1);
+
+        let mut length = 0.0;
+        let mut previous = self.sample(0.0);
+        for sample in 1..samples + 1 {
+            let current = self.sample(sample as f32 / samples as f32);
+            length += (current - previous).magnitude();
+            previous = current;
+        }
+
+        length
+    }
+
+    fn segment_count(&self) -> usize {
+        ::std::cmp::max(self.points.len(), 2) - 1
+    }
+
+    /// Retrieves the four control points that shape the segment at `t`, and the parameter
+    /// within that segment.
+    fn segment(&self, t: f32) -> ([Vector3; 4], f32) {
+        assert!(!self.points.is_empty(), "Cannot sample a spline with no control points");
+
+        let last = self.points.len() - 1;
+        let segment_count = self.segment_count();
+        let scaled = t.clamp(0.0, 1.0) * segment_count as f32;
+        let segment = ::std::cmp::min(scaled as usize, segment_count - 1);
+
+        // Duplicate the endpoints for the segments at either end of the path.
+        let point = |index: usize| self.points[::std::cmp::min(index, last)].as_vector3();
+        let points = [
+            point(segment.saturating_sub(1)),
+            point(segment),
+            point(segment + 1),
+            point(segment + 2),
+        ];
+
+        (points, scaled - segment as f32)
+    }
+}
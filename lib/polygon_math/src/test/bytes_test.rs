@@ -0,0 +1,58 @@
+use bytes::{ByteReader, ByteWriter, UnexpectedEnd};
+use point::Point;
+use quaternion::Quaternion;
+use vector::{Vector2, Vector3};
+
+#[test]
+fn round_trip() {
+    let mut writer = ByteWriter::new();
+    writer.write_u8(0xab);
+    writer.write_u32(0xdeadbeef);
+    writer.write_i32(-7);
+    writer.write_f32(-1.5);
+    writer.write_vector2(Vector2::new(0.25, -8.0));
+    writer.write_vector3(Vector3::new(1.0, 2.0, 3.0));
+    writer.write_point(Point::new(-4.0, 5.5, 1.0e10));
+    writer.write_quaternion(Quaternion { w: 0.5, x: -0.5, y: 0.5, z: -0.5 });
+    writer.write_bytes(b"end");
+    assert_eq!(writer.len(), 1 + 4 + 4 + 4 + 8 + 12 + 12 + 16 + 3);
+
+    let bytes = writer.into_bytes();
+    let mut reader = ByteReader::new(&bytes);
+    assert_eq!(reader.read_u8(), Ok(0xab));
+    assert_eq!(reader.read_u32(), Ok(0xdeadbeef));
+    assert_eq!(reader.read_i32(), Ok(-7));
+    assert_eq!(reader.read_f32(), Ok(-1.5));
+    assert_eq!(reader.read_vector2(), Ok(Vector2::new(0.25, -8.0)));
+    assert_eq!(reader.read_vector3(), Ok(Vector3::new(1.0, 2.0, 3.0)));
+    assert_eq!(reader.read_point(), Ok(Point::new(-4.0, 5.5, 1.0e10)));
+    assert_eq!(reader.read_quaternion(), Ok(Quaternion { w: 0.5, x: -0.5, y: 0.5, z: -0.5 }));
+    assert_eq!(reader.read_bytes(3), Ok(&b"end"[..]));
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn little_endian_layout() {
+    let mut writer = ByteWriter::from_vec(vec![0xff]);
+    writer.write_u32(0x01020304);
+    writer.write_f32(1.0);
+    assert_eq!(writer.as_bytes(), &[0xff, 0x04, 0x03, 0x02, 0x01, 0x00, 0x00, 0x80, 0x3f][..]);
+}
+
+#[test]
+fn read_past_end() {
+    let bytes = [1, 2, 3, 4, 5, 6];
+    let mut reader = ByteReader::new(&bytes);
+    assert_eq!(reader.read_u32(), Ok(0x04030201));
+
+    // A failed read reports where it started and doesn't consume anything.
+    assert_eq!(reader.read_u32(), Err(UnexpectedEnd { offset: 4, needed: 4 }));
+    assert_eq!(reader.read_vector3(), Err(UnexpectedEnd { offset: 4, needed: 12 }));
+    assert_eq!(reader.offset(), 4);
+    assert_eq!(reader.remaining(), 2);
+
+    assert_eq!(reader.read_u8(), Ok(5));
+    assert_eq!(reader.read_u8(), Ok(6));
+    assert_eq!(reader.read_u8(), Err(UnexpectedEnd { offset: 6, needed: 1 }));
+    assert_eq!(ByteReader::new(&[]).read_quaternion(), Err(UnexpectedEnd { offset: 0, needed: 16 }));
+}
@@ -0,0 +1,30 @@
+use std::f32::{INFINITY, NAN};
+
+use matrix::Matrix4;
+use point::Point;
+use quaternion::Quaternion;
+use vector::Vector3;
+
+#[test]
+fn finite_values() {
+    assert!(Vector3::new(1.0, -2.0, 3.0).is_finite());
+    assert!(Point::new(1.0, -2.0, 3.0).is_finite());
+    assert!(Quaternion::identity().is_finite());
+    assert!(Matrix4::translation(1.0, -2.0, 3.0).is_finite());
+}
+
+#[test]
+fn non_finite_values() {
+    for &bad in &[NAN, INFINITY, -INFINITY] {
+        assert!(!Vector3::new(1.0, bad, 3.0).is_finite());
+        assert!(!Point::new(1.0, -2.0, bad).is_finite());
+
+        let mut quaternion = Quaternion::identity();
+        quaternion.x = bad;
+        assert!(!quaternion.is_finite());
+
+        let mut matrix = Matrix4::identity();
+        matrix[3][2] = bad;
+        assert!(!matrix.is_finite());
+    }
+}
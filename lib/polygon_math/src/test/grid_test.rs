@@ -0,0 +1,35 @@
+use grid::Grid;
+use point::Point;
+use vector::Vector3;
+
+#[test]
+fn world_to_cell_negative() {
+    let grid = Grid::new(Vector3::new(2.0, 2.0, 2.0), Point::origin());
+
+    assert_eq!(grid.world_to_cell(Point::new(0.5, 1.5, 3.0)), (0, 0, 1));
+    assert_eq!(grid.world_to_cell(Point::new(-0.5, -1.5, -2.5)), (-1, -1, -2));
+    assert_eq!(grid.world_to_cell(Point::new(-4.5, 0.0, 0.0)), (-3, 0, 0));
+}
+
+#[test]
+fn world_to_cell_boundaries() {
+    let grid = Grid::new(Vector3::new(1.0, 1.0, 1.0), Point::new(0.5, 0.5, 0.5));
+
+    // Points on a boundary belong to the higher cell.
+    assert_eq!(grid.world_to_cell(Point::new(0.5, 0.5, 0.5)), (0, 0, 0));
+    assert_eq!(grid.world_to_cell(Point::new(1.5, -0.5, 2.5)), (1, -1, 2));
+}
+
+#[test]
+fn cell_to_world() {
+    let grid = Grid::new(Vector3::new(2.0, 1.0, 4.0), Point::new(1.0, 0.0, 0.0));
+
+    assert_eq!(grid.cell_to_world_center((0, 0, 0)), Point::new(2.0, 0.5, 2.0));
+    assert_eq!(grid.cell_to_world_center((-1, -2, 1)), Point::new(0.0, -1.5, 6.0));
+    assert_eq!(grid.cell_bounds((-1, 0, 0)), (Point::new(-1.0, 0.0, 0.0), Point::new(1.0, 1.0, 4.0)));
+
+    // The center of a cell maps back to the same cell.
+    for &cell in &[(0, 0, 0), (-1, -2, 1), (5, -7, -3)] {
+        assert_eq!(grid.world_to_cell(grid.cell_to_world_center(cell)), cell);
+    }
+}
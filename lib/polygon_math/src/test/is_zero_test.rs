@@ -0,0 +1,26 @@
+use vector::Vector3;
+use super::super::{IsZero, EPSILON};
+
+#[test]
+fn is_zero_within() {
+    assert!(1e-4f32.is_zero_within(1e-3));
+    assert!(!1e-4f32.is_zero_within(1e-5));
+    assert!((-1e-4f32).is_zero_within(1e-3));
+
+    // Negative tolerances are treated as positive.
+    assert!(1e-4f32.is_zero_within(-1e-3));
+    assert!(!1e-4f32.is_zero_within(-1e-5));
+
+    // The default tolerance is unchanged.
+    assert!(!1e-4f32.is_zero());
+    assert!((EPSILON * 0.5).is_zero());
+    assert!(!EPSILON.is_zero());
+}
+
+#[test]
+fn vector_is_zero_within() {
+    let vector = Vector3::new(1e-2, 0.0, 0.0);
+    assert!(vector.is_zero_within(1e-3));
+    assert!(!vector.is_zero_within(1e-5));
+    assert!(!vector.is_zero());
+}
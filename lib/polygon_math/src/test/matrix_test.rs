@@ -1,4 +1,7 @@
-use matrix::Matrix4;
+use matrix::{Matrix3, Matrix4, DepthRange};
+use plane::Plane;
+use point::Point;
+use vector::Vector3;
 use super::test::{Bencher, black_box};
 
 #[test]
@@ -14,6 +17,21 @@ fn matrix_equality()
     assert!(identity_1 != identity_2);
 }
 
+#[test]
+fn matrix_affine_equality() {
+    let matrix = Matrix4::translation(1.0, 2.0, 3.0) * Matrix4::rotation(0.5, 1.0, -0.25);
+    let mut perturbed = matrix;
+    perturbed[3][0] = 1e-3;
+    perturbed[3][3] = 1.001;
+
+    assert!(matrix.affine_eq(&matrix));
+    assert!(matrix.affine_eq(&perturbed));
+    assert!(matrix != perturbed);
+
+    perturbed[2][3] += 1.0;
+    assert!(!matrix.affine_eq(&perturbed));
+}
+
 #[test]
 #[should_panic(expected = "assertion failed")]
 fn matrix_index_bounds() {
@@ -28,6 +46,64 @@ fn matrix_mut_index_bounds() {
     _matrix[4][4];
 }
 
+#[test]
+fn matrix_inverse() {
+    let matrix =
+        Matrix4::translation(1.0, -2.0, 3.0)
+      * Matrix4::rotation(0.5, 1.0, -0.25)
+      * Matrix4::scale(2.0, 1.0, 0.5);
+    let inverse = matrix.inverse().unwrap();
+
+    assert_eq!(matrix * inverse, Matrix4::identity());
+    assert_eq!(inverse * matrix, Matrix4::identity());
+    assert_eq!(Matrix4::identity().inverse(), Some(Matrix4::identity()));
+
+    // Singular matrices have no inverse.
+    assert_eq!(Matrix4::scale(1.0, 0.0, 1.0).inverse(), None);
+    assert_eq!(Matrix4::new().inverse(), None);
+}
+
+#[test]
+fn matrix_extract_scale() {
+    use vector::Vector3;
+    use super::super::IsZero;
+
+    let rotation = Matrix4::rotation(0.5, 1.0, -0.25);
+    assert!((rotation.extract_scale() - Vector3::one()).is_zero());
+
+    let matrix = Matrix4::translation(1.0, 2.0, 3.0) * rotation * Matrix4::scale(2.0, 3.0, 4.0);
+    assert!((matrix.extract_scale() - Vector3::new(2.0, 3.0, 4.0)).is_zero());
+
+    // A negative scale introduces a reflection which must be preserved.
+    let mirrored = Matrix4::translation(1.0, 2.0, 3.0) * rotation * Matrix4::scale(-2.0, 3.0, 4.0);
+    assert!((mirrored.extract_scale() - Vector3::new(-2.0, 3.0, 4.0)).is_zero());
+}
+
+#[test]
+fn matrix_transform_points() {
+    use point::Point;
+
+    let matrix =
+        Matrix4::translation(1.0, -2.0, 3.0)
+      * Matrix4::rotation(0.5, 1.0, -0.25)
+      * Matrix4::scale(2.0, 1.0, 0.5);
+    let points = [
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 2.0, 3.0),
+        Point::new(-4.0, 0.5, 2.0),
+    ];
+    let expected: Vec<Point> = points.iter().map(|&point| point * matrix).collect();
+
+    // Stale data in the output buffer is discarded.
+    let mut out = vec![Point::origin(); 5];
+    matrix.transform_points(&points, &mut out);
+    assert_eq!(out, expected);
+
+    let mut in_place = points;
+    matrix.transform_points_in_place(&mut in_place);
+    assert_eq!(&in_place[..], &expected[..]);
+}
+
 #[test]
 fn matrix_identity() {
     let identity = Matrix4::identity();
@@ -58,6 +134,42 @@ fn matrix_translation()
     assert!(translation_2[3][3] == 1.0);
 }
 
+#[test]
+fn matrix_mul_assign() {
+    let first = Matrix4::translation(1.0, 2.0, 3.0) * Matrix4::rotation(0.5, 0.0, 0.0);
+    let second = Matrix4::scale(2.0, 3.0, 4.0) * Matrix4::rotation(0.0, 0.0, 1.0);
+
+    let mut result = first;
+    result *= second;
+    assert_eq!(result, first * second);
+
+    // Multiplying a matrix by itself must use the original values for both operands.
+    let mut result = first;
+    result *= result;
+    assert_eq!(result, first * first);
+
+    let first = Matrix3::rotation(0.5, 0.25, 0.0);
+    let second = Matrix3::rotation(0.0, 1.0, 0.5);
+
+    let mut result = first;
+    result *= second;
+    let expected = first * second;
+    for row in 0..3 {
+        for col in 0..3 {
+            assert_eq!(result[row][col], expected[row][col]);
+        }
+    }
+
+    let mut result = first;
+    result *= result;
+    let expected = first * first;
+    for row in 0..3 {
+        for col in 0..3 {
+            assert_eq!(result[row][col], expected[row][col]);
+        }
+    }
+}
+
 #[bench]
 fn bench_multiply(bencher: &mut Bencher) {
     let first = Matrix4::identity();
@@ -67,3 +179,146 @@ fn bench_multiply(bencher: &mut Bencher) {
         black_box(first * second);
     });
 }
+
+#[test]
+fn orthographic() {
+    use point::Point;
+
+    let projection = Matrix4::orthographic(-4.0, 4.0, -2.0, 2.0, 1.0, 3.0);
+    assert_eq!(Point::new(4.0, 2.0, -1.0) * projection, Point::new(1.0, 1.0, -1.0));
+    assert_eq!(Point::new(-4.0, -2.0, -3.0) * projection, Point::new(-1.0, -1.0, 1.0));
+    assert_eq!(Point::new(0.0, 0.0, -2.0) * projection, Point::origin());
+}
+
+#[test]
+fn matrix3_determinant() {
+    assert_eq!(Matrix3::identity().determinant(), 1.0);
+
+    let mut scale = Matrix3::identity();
+    scale[0][0] = 2.0;
+    scale[1][1] = 3.0;
+    scale[2][2] = -0.5;
+    assert_eq!(scale.determinant(), -3.0);
+
+    let mut singular = Matrix3::identity();
+    singular[2] = [1.0, 1.0, 0.0];
+    singular[1] = [2.0, 2.0, 0.0];
+    assert!(singular.inverse().is_none());
+    assert!(singular.inverse_transpose().is_none());
+}
+
+#[test]
+fn matrix3_inverse() {
+    // An orthonormal rotation matrix's inverse is its transpose.
+    let rotation = Matrix3::rotation(0.3, -1.2, 2.0);
+    assert_eq!(rotation.inverse().unwrap(), rotation.transpose());
+    assert_eq!(rotation.inverse_transpose().unwrap(), rotation);
+
+    let mut matrix = Matrix3::rotation(0.5, 0.25, -0.75);
+    matrix[0] = [2.0, 0.5, 0.0];
+    matrix[2][1] = 4.0;
+    let inverse = matrix.inverse().unwrap();
+    assert_eq!(matrix * inverse, Matrix3::identity());
+    assert_eq!(inverse * matrix, Matrix3::identity());
+    assert_eq!(matrix.inverse_transpose().unwrap(), inverse.transpose());
+}
+
+/// Transforms `point` by `matrix` and performs the perspective divide.
+fn project(matrix: Matrix4, point: Point) -> Point {
+    let clip = point * matrix;
+    Point::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+}
+
+#[test]
+fn look_at_handedness() {
+    let eye = Point::new(1.0, 2.0, 3.0);
+    let target = Point::new(1.0, 2.0, 8.0);
+    let up = Vector3::new(0.0, 1.0, 0.0);
+
+    // Left-handed views map the view direction onto +Z, right-handed ones onto -Z.
+    let left = Matrix4::look_at_lh(eye, target, up);
+    assert!((target * left - Point::new(0.0, 0.0, 5.0)).magnitude() < 1e-5);
+    assert!((eye * left - Point::origin()).magnitude() < 1e-5);
+    assert!((Point::new(1.0, 3.0, 3.0) * left - Point::new(0.0, 1.0, 0.0)).magnitude() < 1e-5);
+
+    let right = Matrix4::look_at(eye, target, up);
+    assert!((target * right - Point::new(0.0, 0.0, -5.0)).magnitude() < 1e-5);
+    assert!((Point::new(1.0, 3.0, 3.0) * right - Point::new(0.0, 1.0, 0.0)).magnitude() < 1e-5);
+}
+
+#[test]
+fn perspective_depth_ranges() {
+    let fov = ::std::f32::consts::PI * 0.5;
+    let (near, far) = (1.0, 10.0);
+
+    let left = Matrix4::perspective_lh(fov, 2.0, near, far, DepthRange::ZeroToOne);
+    assert!((project(left, Point::new(0.0, 0.0, near)).z - 0.0).abs() < 1e-5);
+    assert!((project(left, Point::new(0.0, 0.0, far)).z - 1.0).abs() < 1e-5);
+    assert!((project(left, Point::new(2.0, 1.0, near)) - Point::new(1.0, 1.0, 0.0)).magnitude() < 1e-5);
+
+    let left = Matrix4::perspective_lh(fov, 2.0, near, far, DepthRange::NegativeOneToOne);
+    assert!((project(left, Point::new(0.0, 0.0, near)).z + 1.0).abs() < 1e-5);
+    assert!((project(left, Point::new(0.0, 0.0, far)).z - 1.0).abs() < 1e-5);
+
+    let right = Matrix4::perspective(fov, 2.0, near, far, DepthRange::ZeroToOne);
+    assert!((project(right, Point::new(0.0, 0.0, -near)).z - 0.0).abs() < 1e-5);
+    assert!((project(right, Point::new(0.0, 0.0, -far)).z - 1.0).abs() < 1e-5);
+
+    let right = Matrix4::perspective(fov, 2.0, near, far, DepthRange::NegativeOneToOne);
+    assert!((project(right, Point::new(0.0, 0.0, -near)).z + 1.0).abs() < 1e-5);
+    assert!((project(right, Point::new(0.0, 0.0, -far)).z - 1.0).abs() < 1e-5);
+    assert!((project(right, Point::new(-2.0, 1.0, -near)) - Point::new(-1.0, 1.0, -1.0)).magnitude() < 1e-5);
+}
+
+#[test]
+fn perspective_infinite_far() {
+    let fov = ::std::f32::consts::PI * 0.5;
+    let near = 0.5;
+
+    for &depth_range in &[DepthRange::NegativeOneToOne, DepthRange::ZeroToOne] {
+        let near_depth = if depth_range == DepthRange::ZeroToOne { 0.0 } else { -1.0 };
+
+        let right = Matrix4::perspective_infinite(fov, 1.5, near, depth_range);
+        assert!((project(right, Point::new(0.0, 0.0, -near)).z - near_depth).abs() < 1e-5);
+
+        // Depth increases towards 1.0 with distance but never exceeds it.
+        let mut last_depth = near_depth;
+        for &distance in &[1.0, 10.0, 1000.0, 1.0e6, 1.0e12] {
+            let depth = project(right, Point::new(0.0, 0.0, -distance)).z;
+            assert!(depth > last_depth - 1e-6 && depth <= 1.0, "Depth {} at distance {}", depth, distance);
+            last_depth = depth;
+        }
+        assert!(last_depth > 0.999);
+
+        let left = Matrix4::perspective_infinite_lh(fov, 1.5, near, depth_range);
+        assert!((project(left, Point::new(0.0, 0.0, near)).z - near_depth).abs() < 1e-5);
+        assert!(project(left, Point::new(0.0, 0.0, 1.0e6)).z <= 1.0);
+    }
+}
+
+#[test]
+fn shear_offsets_along_axes() {
+    let sheared = Point::new(1.0, 2.0, 3.0) * Matrix4::shear(1.0, 0.0, 0.0, 0.5, 2.0, 0.0);
+    assert_eq!(sheared, Point::new(3.0, 3.5, 5.0));
+}
+
+#[test]
+fn reflection_across_planes() {
+    // Reflecting across the XY plane negates z.
+    let xy_plane = Plane::new(Vector3::new(0.0, 0.0, 1.0), 0.0);
+    let reflected = Point::new(1.0, 2.0, 3.0) * Matrix4::reflection(xy_plane);
+    assert_eq!(reflected, Point::new(1.0, 2.0, -3.0));
+
+    // An arbitrary plane, here x + y = 2 offset from the origin.
+    let plane = Plane::from_point_normal(Point::new(1.0, 1.0, 0.0), Vector3::new(1.0, 1.0, 0.0));
+    let reflection = Matrix4::reflection(plane);
+    let reflected = Point::new(0.0, 0.0, 5.0) * reflection;
+    assert!((reflected - Point::new(2.0, 2.0, 5.0)).magnitude() < 1e-5);
+    assert!((plane.signed_distance(reflected) + plane.signed_distance(Point::new(0.0, 0.0, 5.0))).abs() < 1e-5);
+
+    // Points on the plane don't move, and reflecting twice gets back the original point.
+    let on_plane = Point::new(3.0, -1.0, 7.0);
+    assert!((on_plane * reflection - on_plane).magnitude() < 1e-5);
+    let point = Point::new(-4.0, 0.5, 2.0);
+    assert!(((point * reflection) * reflection - point).magnitude() < 1e-5);
+}
@@ -0,0 +1,197 @@
+use mesh::{self, MeshData};
+use point::Point;
+use vector::{Vector2, Vector3};
+use super::super::Dot;
+
+/// Asserts that every triangle winds counter-clockwise when viewed from outside the mesh, i.e.
+/// that its face normal points away from the origin.
+fn assert_outward_winding(mesh: &MeshData) {
+    for triangle in mesh.indices.chunks(3) {
+        let first = mesh.positions[triangle[0] as usize];
+        let second = mesh.positions[triangle[1] as usize];
+        let third = mesh.positions[triangle[2] as usize];
+
+        let face_normal = Vector3::cross(second - first, third - first);
+        let center = (first.as_vector3() + second.as_vector3() + third.as_vector3()) / 3.0;
+        assert!(face_normal.dot(center) > 0.0, "Triangle {:?} winds the wrong way", triangle);
+    }
+}
+
+#[test]
+fn cube() {
+    let mesh = mesh::cube(2.0);
+    assert_eq!(mesh.vertex_count(), 24);
+    assert_eq!(mesh.normals.len(), 24);
+    assert_eq!(mesh.uvs.len(), 24);
+    assert_eq!(mesh.indices.len(), 36);
+
+    for (position, normal) in mesh.positions.iter().zip(mesh.normals.iter()) {
+        // Every vertex is a corner of the cube and lies on the face its normal points out of.
+        assert_eq!(position.x.abs(), 1.0);
+        assert_eq!(position.y.abs(), 1.0);
+        assert_eq!(position.z.abs(), 1.0);
+        assert_eq!(position.as_vector3().dot(*normal), 1.0);
+    }
+
+    assert_outward_winding(&mesh);
+}
+
+#[test]
+fn uv_sphere() {
+    let mesh = mesh::uv_sphere(2.0, 16, 8);
+    assert_eq!(mesh.vertex_count(), 17 * 9);
+
+    // Each of the 16 segments has one triangle in each polar band and two in the other 6 bands.
+    assert_eq!(mesh.indices.len(), 3 * 16 * (2 + 2 * 6));
+    assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertex_count()));
+
+    for (position, normal) in mesh.positions.iter().zip(mesh.normals.iter()) {
+        assert!((normal.magnitude() - 1.0).abs() < 1e-5, "{:?} is not normalized", normal);
+
+        // Normals point directly away from the center.
+        assert!((position.as_vector3() - *normal * 2.0).magnitude() < 1e-5);
+    }
+
+    for uv in &mesh.uvs {
+        assert!(uv.x >= 0.0 && uv.x <= 1.0 && uv.y >= 0.0 && uv.y <= 1.0, "{:?} is out of range", uv);
+    }
+
+    assert_outward_winding(&mesh);
+}
+
+#[test]
+fn uv_sphere_clamps_counts() {
+    let sphere = mesh::uv_sphere(1.0, 0, 0);
+    assert_eq!(sphere, mesh::uv_sphere(1.0, mesh::MIN_SPHERE_SEGMENTS, mesh::MIN_SPHERE_RINGS));
+    assert_eq!(sphere.indices.len(), 3 * 2 * mesh::MIN_SPHERE_SEGMENTS);
+    assert_outward_winding(&sphere);
+}
+
+#[test]
+fn plane() {
+    let mesh = mesh::plane(4.0, 2.0, 2);
+    assert_eq!(mesh.vertex_count(), 9);
+    assert_eq!(mesh.indices.len(), 24);
+    assert!(mesh.positions.contains(&Point::new(-2.0, 0.0, -1.0)));
+    assert!(mesh.positions.contains(&Point::new(2.0, 0.0, 1.0)));
+
+    // Every triangle faces up.
+    for triangle in mesh.indices.chunks(3) {
+        let first = mesh.positions[triangle[0] as usize];
+        let face_normal = Vector3::cross(
+            mesh.positions[triangle[1] as usize] - first,
+            mesh.positions[triangle[2] as usize] - first);
+        assert!(face_normal.y > 0.0);
+    }
+
+    assert_eq!(mesh::plane(1.0, 1.0, 0), mesh::plane(1.0, 1.0, 1));
+}
+
+#[test]
+fn compute_tangents() {
+    // A unit quad on the XY plane facing +Z, with U increasing along +X and V along +Y.
+    let positions = [
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 0.0, 0.0),
+        Point::new(1.0, 1.0, 0.0),
+        Point::new(0.0, 1.0, 0.0),
+    ];
+    let uvs = [
+        Vector2::new(0.0, 0.0),
+        Vector2::new(1.0, 0.0),
+        Vector2::new(1.0, 1.0),
+        Vector2::new(0.0, 1.0),
+    ];
+    let normals = [Vector3::new(0.0, 0.0, 1.0); 4];
+    let indices = [0, 1, 2, 0, 2, 3];
+
+    let tangents = mesh::compute_tangents(&positions, &uvs, &indices, &normals);
+    assert_eq!(tangents.len(), 4);
+    for tangent in &tangents {
+        assert!((*tangent - Vector3::new(1.0, 0.0, 0.0)).magnitude() < 1e-5, "{:?} isn't along U", tangent);
+    }
+
+    // Degenerate UVs contribute nothing, but the tangents are still valid.
+    let degenerate_uvs = [Vector2::new(0.5, 0.5); 4];
+    let tangents = mesh::compute_tangents(&positions, &degenerate_uvs, &indices, &normals);
+    for (tangent, normal) in tangents.iter().zip(normals.iter()) {
+        assert!(tangent.is_finite());
+        assert!((tangent.magnitude() - 1.0).abs() < 1e-5);
+        assert!(tangent.dot(*normal).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn flip_winding() {
+    let cube = mesh::cube(2.0);
+    let mut indices = cube.indices.clone();
+
+    mesh::flip_winding(&mut indices);
+    assert_eq!(indices[..6].to_vec(), vec![0, 2, 1, 0, 3, 2]);
+
+    let mut flipped = cube.clone();
+    flipped.indices = indices.clone();
+    for triangle in flipped.indices.chunks(3) {
+        let first = flipped.positions[triangle[0] as usize];
+        let face_normal = Vector3::cross(
+            flipped.positions[triangle[1] as usize] - first,
+            flipped.positions[triangle[2] as usize] - first);
+        let center = first.as_vector3();
+        assert!(face_normal.dot(center) < 0.0, "Triangle {:?} wasn't flipped", triangle);
+    }
+
+    // Flipping twice is the identity, and a partial trailing triangle is left alone.
+    mesh::flip_winding(&mut indices);
+    assert_eq!(indices, cube.indices);
+
+    let mut partial = [0, 1, 2, 3, 4];
+    mesh::flip_winding(&mut partial);
+    assert_eq!(partial, [0, 2, 1, 3, 4]);
+}
+
+#[test]
+fn recompute_normals() {
+    // The generated cube has separate vertices per face, so smooth normals are the face normals.
+    let cube = mesh::cube(2.0);
+    let normals = mesh::recompute_normals(&cube.positions, &cube.indices);
+    for (normal, expected) in normals.iter().zip(cube.normals.iter()) {
+        assert!((*normal - *expected).magnitude() < 1e-5, "{:?} != {:?}", normal, expected);
+    }
+
+    // Welding the corners together gives each corner a normal pointing diagonally outward.
+    let mut positions: Vec<Point> = Vec::new();
+    let indices: Vec<u32> = cube.indices.iter().map(|&index| {
+        let position = cube.positions[index as usize];
+        match positions.iter().position(|&other| other == position) {
+            Some(welded) => welded as u32,
+            None => {
+                positions.push(position);
+                (positions.len() - 1) as u32
+            },
+        }
+    }).collect();
+    assert_eq!(positions.len(), 8);
+
+    let normals = mesh::recompute_normals(&positions, &indices);
+    for (position, normal) in positions.iter().zip(normals.iter()) {
+        assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+        assert!(normal.dot(position.as_vector3()) > 0.0, "{:?} points inward at {:?}", normal, position);
+    }
+
+    // Opposing faces cancel out, but the shared vertices still get a valid normal, and unused
+    // vertices get a default one.
+    let positions = [
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 0.0, 0.0),
+        Point::new(0.0, 1.0, 0.0),
+        Point::new(5.0, 5.0, 5.0),
+    ];
+    let normals = mesh::recompute_normals(&positions, &[0, 1, 2, 0, 2, 1]);
+    assert_eq!(normals.len(), 4);
+    for normal in &normals {
+        assert!(normal.is_finite());
+        assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+    }
+    assert!((normals[0] - Vector3::new(0.0, 0.0, 1.0)).magnitude() < 1e-5);
+}
+
@@ -1,4 +1,18 @@
 extern crate test;
 
+mod bytes_test;
+mod finite_test;
+mod grid_test;
+mod is_zero_test;
 mod matrix_test;
+mod mesh_test;
+mod plain_data_test;
+mod point_test;
 mod quaternion_test;
+mod random_test;
+mod ray_test;
+mod smoothing_test;
+mod spline_test;
+mod tween_test;
+mod vector_test;
+mod vertex_test;
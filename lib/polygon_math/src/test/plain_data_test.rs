@@ -0,0 +1,54 @@
+use matrix::{Matrix3, Matrix4};
+use point::Point;
+use vector::{Vector2, Vector3};
+use plain_data::{as_f32_slice, from_f32_slice, cast};
+
+#[test]
+fn matrix_layout() {
+    let matrix = Matrix4::from_point(Point::new(1.0, 2.0, 3.0)) * Matrix4::from_scale_vector(Vector3::new(4.0, 5.0, 6.0));
+
+    // Elements are laid out row by row.
+    let mut expected = Vec::new();
+    for row in 0..4 {
+        for col in 0..4 {
+            expected.push(matrix[row][col]);
+        }
+    }
+
+    assert_eq!(&matrix.raw_data()[..], &*expected);
+    assert_eq!(as_f32_slice(&[matrix]), &*expected);
+
+    let matrices = [Matrix4::identity(), matrix];
+    let raw = as_f32_slice(&matrices);
+    assert_eq!(raw.len(), 32);
+    assert_eq!(&raw[..16], &Matrix4::identity().raw_data()[..]);
+    assert_eq!(&raw[16..], &*expected);
+
+    let rotation = Matrix3::rotation(0.5, 1.0, -0.25);
+    let raw: &[f32; 9] = cast(&rotation);
+    assert_eq!(raw[5], rotation[1][2]);
+}
+
+#[test]
+fn vector_and_point_layout() {
+    let vectors = [Vector3::new(1.0, 2.0, 3.0), Vector3::new(4.0, 5.0, 6.0)];
+    assert_eq!(Vector3::as_ref(&vectors), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let vectors = [Vector2::new(1.0, 2.0), Vector2::new(3.0, 4.0)];
+    assert_eq!(Vector2::as_ref(&vectors), &[1.0, 2.0, 3.0, 4.0]);
+
+    let point = Point::new(7.0, 8.0, 9.0);
+    assert_eq!(point.as_array(), &[7.0, 8.0, 9.0, 1.0]);
+
+    let raw = [1.0, 2.0, 3.0, 1.0, 4.0, 5.0, 6.0, 1.0];
+    let points: &[Point] = from_f32_slice(&raw);
+    assert_eq!(points, &[Point::new(1.0, 2.0, 3.0), Point::new(4.0, 5.0, 6.0)]);
+    assert_eq!(Point::as_ref(points), &raw);
+}
+
+#[test]
+#[should_panic]
+fn from_f32_slice_wrong_length() {
+    let raw = [1.0, 2.0, 3.0];
+    let _: &[Point] = from_f32_slice(&raw);
+}
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use point::Point;
+
+#[test]
+fn display() {
+    assert_eq!(format!("{}", Point::new(1.0, -2.5, 0.125)), "(1.0000, -2.5000, 0.1250)");
+    assert_eq!(format!("{:.2}", Point::origin()), "(0.00, 0.00, 0.00)");
+}
+
+#[test]
+fn fmt_precision() {
+    assert_eq!(Point::new(10.0, 0.55, -3.26).fmt_precision(1), "(10.0, 0.6, -3.3)");
+}
+
+#[test]
+fn move_towards() {
+    let target = Point::new(0.0, 0.0, 10.0);
+    let mut current = Point::origin();
+    for _ in 0..3 {
+        current = Point::move_towards(current, target, 3.0);
+    }
+    assert_eq!(current, Point::new(0.0, 0.0, 9.0));
+
+    current = Point::move_towards(current, target, 3.0);
+    assert_eq!(current, target);
+
+    assert_eq!(Point::move_towards(Point::origin(), target, 0.0), Point::origin());
+}
+
+#[test]
+fn quantize() {
+    let epsilon = 0.01;
+    let point = Point::new(1.0, -2.0, 0.5);
+
+    // Nearby points share a key, distant ones don't.
+    assert_eq!(point.quantize(epsilon), Point::new(1.003, -2.002, 0.4999).quantize(epsilon));
+    assert!(point.quantize(epsilon) != Point::new(1.02, -2.0, 0.5).quantize(epsilon));
+    assert!(point.quantize(epsilon) != Point::new(1.0, -2.0, 0.6).quantize(epsilon));
+
+    // Negative values round symmetrically and the homogeneous coordinate is ignored.
+    assert_eq!((-point).quantize(epsilon).x, -point.quantize(epsilon).x);
+    let mut homogeneous = point;
+    homogeneous.w = 0.0;
+    assert_eq!(homogeneous.quantize(epsilon), point.quantize(epsilon));
+
+    // Keys can be used to weld points.
+    let points = [point, Point::new(1.001, -2.0, 0.5), Point::new(1.0, -1.999, 0.501), Point::origin()];
+    let welded: HashSet<_> = points.iter().map(|point| point.quantize(epsilon)).collect();
+    assert_eq!(welded.len(), 2);
+
+    let snapped = Point::new(0.126, 0.0, -0.374).quantize(0.25).to_point(0.25);
+    assert_eq!(snapped, Point::new(0.25, 0.0, -0.25));
+}
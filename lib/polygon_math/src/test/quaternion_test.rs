@@ -3,6 +3,7 @@ use std::f32::consts::PI;
 use quaternion::Quaternion;
 use vector::Vector3;
 use matrix::Matrix4;
+use super::super::IsZero;
 
 #[test]
 fn multiplication() {
@@ -31,3 +32,233 @@ fn as_matrix() {
     assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5).as_matrix(), Matrix4::rotation(0.0, 0.5, 0.0));
     assert_eq!(Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.5).as_matrix(), Matrix4::rotation(0.0, 0.0, 0.5));
 }
+
+#[test]
+fn look_rotation_parallel_up() {
+    fn assert_valid_look(forward: Vector3, up: Vector3) {
+        assert!(Quaternion::try_look_rotation(forward, up).is_none());
+
+        let rotation = Quaternion::look_rotation(forward, up);
+        assert!(rotation.w.is_finite() && rotation.x.is_finite() && rotation.y.is_finite() && rotation.z.is_finite(),
+                "{:?} is not finite", rotation);
+        assert!(rotation.is_normalized(), "{:?} is not normalized", rotation);
+
+        let result = Vector3::forward() * rotation.as_matrix3();
+        assert!((result - forward).is_zero(), "Expected {:?}, got {:?}", forward, result);
+    }
+
+    // Looking straight up and down with world up as the up vector.
+    assert_valid_look(Vector3::up(), Vector3::up());
+    assert_valid_look(Vector3::down(), Vector3::up());
+
+    // Looking directly backwards with an up vector parallel to the view direction.
+    assert_valid_look(Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn display() {
+    let quat = Quaternion { w: 1.0, x: -0.5, y: 0.126, z: 0.0 };
+    assert_eq!(format!("{}", quat), "(1.0000, -0.5000, 0.1260, 0.0000)");
+    assert_eq!(format!("{:.1}", Quaternion::identity()), "(1.0, 0.0, 0.0, 0.0)");
+    assert_eq!(quat.fmt_precision(2), "(1.00, -0.50, 0.13, 0.00)");
+}
+
+#[test]
+fn index() {
+    let mut quat = Quaternion { w: 1.0, x: 2.0, y: 3.0, z: 4.0 };
+    assert_eq!(quat[0], 1.0);
+    assert_eq!(quat[1], 2.0);
+    assert_eq!(quat[2], 3.0);
+    assert_eq!(quat[3], 4.0);
+
+    quat[2] = 5.0;
+    assert_eq!(quat.y, 5.0);
+    assert_eq!(Quaternion::dot(quat, Quaternion::identity()), 1.0);
+}
+
+#[test]
+#[should_panic(expected = "Index 4 is out of bounds for Quaternion")]
+fn index_out_of_bounds() {
+    let quat = Quaternion::identity();
+    quat[4];
+}
+
+#[test]
+fn conjugate() {
+    let quat = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.25);
+    let product = quat * quat.conjugate();
+    assert!((Quaternion::dot(product, Quaternion::identity()) - 1.0).is_zero());
+}
+
+#[test]
+fn clamp_to_cone() {
+    use matrix::Matrix3;
+    use super::super::Dot;
+
+    let reference = Vector3::new(0.0, 0.0, -1.0);
+    let max_angle = PI / 6.0;
+
+    // Rotations within the cone are unchanged.
+    let inside = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI / 9.0);
+    assert_eq!(inside.clamp_to_cone(reference, max_angle), inside);
+
+    // Rotations outside the cone are pulled back to the boundary in the same plane.
+    let outside = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI / 3.0);
+    let clamped = outside.clamp_to_cone(reference, max_angle);
+    let forward = reference * Matrix3::from_quaternion(clamped);
+    assert!((reference.dot(forward).acos() - max_angle).abs() < 1e-4);
+    assert!(forward.y.is_zero());
+    assert!(forward.x < 0.0);
+
+    let expected = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), max_angle);
+    assert!((Quaternion::dot(clamped, expected).abs() - 1.0).is_zero());
+}
+
+#[test]
+fn clamp_euler() {
+    let rotation = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5);
+    let limits = (-1.0, -1.0, -1.0);
+
+    let unchanged = rotation.clamp_euler(limits, (1.0, 1.0, 1.0));
+    assert!((Quaternion::dot(unchanged, rotation).abs() - 1.0).is_zero());
+
+    let clamped = rotation.clamp_euler(limits, (1.0, 0.25, 1.0));
+    let expected = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.25);
+    assert!((Quaternion::dot(clamped, expected).abs() - 1.0).is_zero());
+}
+
+#[test]
+fn clamp_euler_combined_axes() {
+    // Builds a rotation from yaw (z), pitch (y), and roll (x), applied in the order `as_eulers()`
+    // decomposes them.
+    fn from_yaw_pitch_roll(x: f32, y: f32, z: f32) -> Quaternion {
+        Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), z)
+      * Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), y)
+      * Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), x)
+    }
+
+    fn assert_eulers(rotation: Quaternion, x: f32, y: f32, z: f32) {
+        let eulers = rotation.as_eulers();
+        assert!((eulers.x - x).abs() < 1e-3, "x was {}, expected {}", eulers.x, x);
+        assert!((eulers.y - y).abs() < 1e-3, "y was {}, expected {}", eulers.y, y);
+        assert!((eulers.z - z).abs() < 1e-3, "z was {}, expected {}", eulers.z, z);
+    }
+
+    let min = (-0.5, -1.0, -0.5);
+    let max = (0.5, 1.0, 0.5);
+
+    // Within every limit, so the rotation is unchanged.
+    let rotation = from_yaw_pitch_roll(0.4, -0.8, 0.3);
+    let clamped = rotation.clamp_euler(min, max);
+    assert!((Quaternion::dot(clamped, rotation).abs() - 1.0).abs() < 1e-5);
+
+    // Roll and yaw are both out of range, pitch is left alone.
+    let clamped = from_yaw_pitch_roll(1.2, -0.6, 0.9).clamp_euler(min, max);
+    assert_eulers(clamped, 0.5, -0.6, 0.5);
+    let expected = from_yaw_pitch_roll(0.5, -0.6, 0.5);
+    assert!((Quaternion::dot(clamped, expected).abs() - 1.0).abs() < 1e-5);
+
+    // Steep pitch combined with yaw, close to gimbal lock.
+    let clamped = from_yaw_pitch_roll(0.3, 1.4, -2.0).clamp_euler(min, max);
+    assert_eulers(clamped, 0.3, 1.0, -0.5);
+
+    // All three axes out of range.
+    let clamped = from_yaw_pitch_roll(-1.0, -1.3, 1.0).clamp_euler(min, max);
+    assert_eulers(clamped, -0.5, -1.0, 0.5);
+}
+
+#[test]
+fn from_to_rotation() {
+    use matrix::Matrix3;
+
+    let x_axis = Vector3::new(1.0, 0.0, 0.0);
+
+    let rotation = Quaternion::from_to_rotation(x_axis, Vector3::new(0.0, 2.0, 0.0));
+    assert!((x_axis * Matrix3::from_quaternion(rotation) - Vector3::new(0.0, 1.0, 0.0)).is_zero());
+    let expected = Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), PI * 0.5);
+    assert!((Quaternion::dot(rotation, expected).abs() - 1.0).is_zero());
+
+    assert_eq!(Quaternion::from_to_rotation(x_axis, x_axis), Quaternion::identity());
+
+    let opposite = Quaternion::from_to_rotation(x_axis, -x_axis);
+    assert!(opposite.is_finite());
+    assert!(opposite.w.is_zero());
+    assert!((x_axis * Matrix3::from_quaternion(opposite) + x_axis).is_zero());
+}
+
+fn assert_same_rotation(actual: Quaternion, expected: Quaternion) {
+    assert!(
+        (Quaternion::dot(actual, expected).abs() - 1.0).abs() < 1e-5,
+        "Expected {} to be the same rotation as {}", actual, expected);
+}
+
+#[test]
+fn log_exp_round_trip() {
+    let identity_log = Quaternion::identity().log();
+    assert_eq!(identity_log, Quaternion { w: 0.0, x: 0.0, y: 0.0, z: 0.0 });
+    assert_eq!(identity_log.exp(), Quaternion::identity());
+
+    let rotations = [
+        Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.5),
+        Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), -2.0),
+        Quaternion::axis_angle(Vector3::new(1.0, 2.0, -3.0).normalized(), 3.0),
+        Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), 1.9 * PI),
+        Quaternion::from_eulers(0.3, -1.2, 2.5),
+
+        // Tiny rotations must not produce NaN.
+        Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), 1e-7),
+    ];
+
+    for &rotation in &rotations {
+        let log = rotation.log();
+        assert!(log.is_finite());
+        assert!(log.w.is_zero());
+
+        let round_trip = log.exp();
+        for index in 0..4 {
+            assert!((round_trip[index] - rotation[index]).abs() < 1e-5, "{} != {}", round_trip, rotation);
+        }
+    }
+
+    // The log of a unit quaternion is its axis scaled by half its angle.
+    let log = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 1.0).log();
+    assert!((log.y - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn pow_scales_angle() {
+    let axis = Vector3::new(1.0, 1.0, 0.0).normalized();
+    let rotation = Quaternion::axis_angle(axis, 1.5);
+
+    assert_same_rotation(rotation.pow(0.5), Quaternion::axis_angle(axis, 0.75));
+    assert_same_rotation(rotation.pow(2.0), Quaternion::axis_angle(axis, 3.0));
+    assert_same_rotation(rotation.pow(1.0), rotation);
+    assert_same_rotation(rotation.pow(0.0), Quaternion::identity());
+
+    let (half_axis, half_angle) = rotation.pow(0.5).as_axis_angle();
+    assert!((half_angle - 0.75).abs() < 1e-4);
+    assert!((half_axis - axis).is_zero());
+}
+
+#[test]
+fn squad_interpolation() {
+    let axis = Vector3::new(0.0, 1.0, 0.0);
+    let keys: Vec<Quaternion> = (0..4).map(|index| Quaternion::axis_angle(axis, index as f32)).collect();
+    let controls: Vec<Quaternion> = (0..4).map(|index| {
+        let previous = keys[if index == 0 { 0 } else { index - 1 }];
+        let next = keys[if index == 3 { 3 } else { index + 1 }];
+        Quaternion::squad_control(previous, keys[index], next)
+    }).collect();
+
+    // The curve passes through the keyframes.
+    assert_same_rotation(Quaternion::squad(keys[1], keys[2], controls[1], controls[2], 0.0), keys[1]);
+    assert_same_rotation(Quaternion::squad(keys[1], keys[2], controls[1], controls[2], 1.0), keys[2]);
+
+    // Keyframes evenly spaced around a single axis rotate at a constant rate.
+    for step in 0..5 {
+        let t = step as f32 / 4.0;
+        let rotation = Quaternion::squad(keys[1], keys[2], controls[1], controls[2], t);
+        assert!(rotation.is_finite());
+        assert_same_rotation(rotation, Quaternion::axis_angle(axis, 1.0 + t));
+    }
+}
@@ -0,0 +1,71 @@
+use point::Point;
+use random::Rng;
+
+#[test]
+fn reproducible_from_seed() {
+    let mut first = Rng::new(12345);
+    let mut second = Rng::new(12345);
+    for _ in 0..100 {
+        assert_eq!(first.next_u32(), second.next_u32());
+    }
+
+    let mut first = Rng::new(12345);
+    let mut other = Rng::new(54321);
+    let first_values: Vec<u32> = (0..8).map(|_| first.next_u32()).collect();
+    let other_values: Vec<u32> = (0..8).map(|_| other.next_u32()).collect();
+    assert!(first_values != other_values);
+}
+
+#[test]
+fn zero_seed() {
+    let mut rng = Rng::new(0);
+    assert!((0..8).any(|_| rng.next_u64() != 0));
+}
+
+#[test]
+fn next_f32_in_range() {
+    let mut rng = Rng::new(7);
+    for _ in 0..10000 {
+        let value = rng.next_f32();
+        assert!(value >= 0.0 && value < 1.0, "{} is out of range", value);
+
+        let value = rng.range(-3.0, 5.0);
+        assert!(value >= -3.0 && value < 5.0, "{} is out of range", value);
+    }
+}
+
+#[test]
+fn unit_vector_uniform() {
+    let mut rng = Rng::new(42);
+    let count = 20000;
+
+    let mut near_poles = 0;
+    let mut positive_x = 0;
+    for _ in 0..count {
+        let vector = rng.unit_vector();
+        assert!((vector.magnitude() - 1.0).abs() < 1e-5, "{:?} is not normalized", vector);
+
+        // For a uniform distribution on the sphere the height is uniform in [-1, 1], so half the
+        // points should be more than 0.5 from the equator.
+        if vector.y.abs() > 0.5 {
+            near_poles += 1;
+        }
+        if vector.x > 0.0 {
+            positive_x += 1;
+        }
+    }
+
+    let near_poles = near_poles as f32 / count as f32;
+    let positive_x = positive_x as f32 / count as f32;
+    assert!((near_poles - 0.5).abs() < 0.02, "{} of the points were near the poles", near_poles);
+    assert!((positive_x - 0.5).abs() < 0.02, "{} of the points had a positive x", positive_x);
+}
+
+#[test]
+fn unit_point_in_sphere() {
+    let mut rng = Rng::new(3);
+    for _ in 0..1000 {
+        let point = rng.unit_point_in_sphere();
+        assert!(point.distance(&Point::origin()) <= 1.0 + 1e-6, "{:?} is outside the sphere", point);
+    }
+}
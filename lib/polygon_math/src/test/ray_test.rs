@@ -0,0 +1,22 @@
+use point::Point;
+use ray::Ray;
+use vector::Vector3;
+use super::super::IsZero;
+
+#[test]
+fn ray_direction_is_normalized() {
+    let ray = Ray::new(Point::new(1.0, 2.0, 3.0), Vector3::new(0.0, 0.0, -4.0));
+    assert_eq!(ray.direction, Vector3::new(0.0, 0.0, -1.0));
+    assert_eq!(ray.point_at(0.0), Point::new(1.0, 2.0, 3.0));
+    assert_eq!(ray.point_at(2.5), Point::new(1.0, 2.0, 0.5));
+
+    let ray = Ray::from_points(Point::origin(), Point::new(3.0, 4.0, 0.0));
+    assert!((ray.direction - Vector3::new(0.6, 0.8, 0.0)).is_zero());
+    assert!((ray.point_at(5.0) - Point::new(3.0, 4.0, 0.0)).is_zero());
+}
+
+#[test]
+#[should_panic]
+fn ray_zero_direction() {
+    Ray::new(Point::origin(), Vector3::zero());
+}
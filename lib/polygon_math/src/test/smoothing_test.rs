@@ -0,0 +1,39 @@
+use smoothing::smooth_damp;
+use point::Point;
+use vector::Vector3;
+use super::super::IsZero;
+
+#[test]
+fn smooth_damp_converges() {
+    let mut value = 0.0;
+    let mut velocity = 0.0;
+    for _ in 0..120 {
+        let next = smooth_damp(value, 10.0, &mut velocity, 0.25, 1.0 / 60.0);
+
+        // Value always moves towards the target and never overshoots it.
+        assert!(next >= value, "{} moved away from target from {}", next, value);
+        assert!(next <= 10.0, "{} overshot the target", next);
+        value = next;
+    }
+
+    assert!((value - 10.0).abs() < 0.01, "{} did not converge to the target", value);
+}
+
+#[test]
+fn smooth_damp_zero_time() {
+    let mut velocity = 5.0;
+    assert_eq!(smooth_damp(0.0, 10.0, &mut velocity, 0.0, 1.0 / 60.0), 10.0);
+    assert_eq!(velocity, 0.0);
+}
+
+#[test]
+fn smooth_damp_vector() {
+    let target = Point::new(1.0, -2.0, 3.0);
+    let mut position = Point::origin();
+    let mut velocity = Vector3::zero();
+    for _ in 0..120 {
+        position = Point::smooth_damp(position, target, &mut velocity, 0.25, 1.0 / 60.0);
+    }
+
+    assert!((position - target).is_zero(), "{:?} did not converge to {:?}", position, target);
+}
@@ -0,0 +1,59 @@
+use point::Point;
+use vector::Vector3;
+use spline::CatmullRom;
+use super::super::Dot;
+
+#[test]
+fn catmull_rom_endpoints() {
+    let spline = CatmullRom::new(vec![
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 2.0, 0.0),
+        Point::new(3.0, 2.0, 1.0),
+        Point::new(4.0, 0.0, 1.0),
+        Point::new(6.0, -1.0, 0.0),
+    ]);
+
+    assert!((spline.sample(0.0) - spline.points[0]).magnitude() < 1e-5);
+    assert!((spline.sample(1.0) - spline.points[4]).magnitude() < 1e-5);
+
+    // The path passes through every control point.
+    for (index, &point) in spline.points.iter().enumerate() {
+        assert!((spline.sample(index as f32 / 4.0) - point).magnitude() < 1e-5);
+    }
+
+    // Values outside of [0, 1] are clamped.
+    assert_eq!(spline.sample(-1.0), spline.sample(0.0));
+    assert_eq!(spline.sample(2.0), spline.sample(1.0));
+}
+
+#[test]
+fn catmull_rom_tangent() {
+    let spline = CatmullRom::new(vec![
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(1.0, 1.0, 0.0),
+        Point::new(2.0, 1.0, 0.0),
+        Point::new(3.0, 0.0, 0.0),
+    ]);
+
+    // The middle of a symmetric arch is at its peak, travelling along +X.
+    let tangent = spline.sample_tangent(0.5);
+    assert!(tangent.x > 0.0);
+    assert!(tangent.y.abs() < 1e-5 && tangent.z.abs() < 1e-5);
+
+    // The tangent points from one sample towards the next.
+    let step = spline.sample(0.3) - spline.sample(0.29);
+    assert!(spline.sample_tangent(0.29).normalized().dot(step.normalized()) > 0.99);
+}
+
+#[test]
+fn catmull_rom_short_paths() {
+    let single = CatmullRom::new(vec![Point::new(1.0, 2.0, 3.0)]);
+    assert_eq!(single.sample(0.5), Point::new(1.0, 2.0, 3.0));
+    assert_eq!(single.sample_tangent(0.5), Vector3::zero());
+    assert_eq!(single.length(10), 0.0);
+
+    // With only two points the duplicated endpoints make the path a straight line.
+    let line = CatmullRom::new(vec![Point::new(0.0, 0.0, 0.0), Point::new(4.0, 0.0, 3.0)]);
+    assert!((line.sample(0.5) - Point::new(2.0, 0.0, 1.5)).magnitude() < 1e-5);
+    assert!((line.length(16) - 5.0).abs() < 1e-4);
+}
@@ -0,0 +1,69 @@
+use color::Color;
+use point::Point;
+use quaternion::Quaternion;
+use vector::Vector3;
+use tween::{Tween, Easing, Lerp};
+use super::super::IsZero;
+
+const EASINGS: [Easing; 5] = [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut, Easing::ElasticOut];
+
+#[test]
+fn easing_endpoints() {
+    for &easing in &EASINGS {
+        assert!(easing.apply(0.0) == 0.0, "{:?} doesn't start at 0", easing);
+        assert!(easing.apply(1.0) == 1.0, "{:?} doesn't end at 1", easing);
+        assert_eq!(easing.apply(-1.0), 0.0);
+        assert_eq!(easing.apply(2.0), 1.0);
+    }
+
+    assert_eq!(Easing::Linear.apply(0.25), 0.25);
+    assert!(Easing::EaseIn.apply(0.25) < 0.25);
+    assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    assert!((Easing::EaseInOut.apply(0.5) - 0.5).is_zero());
+    assert!((0..100).any(|step| Easing::ElasticOut.apply(step as f32 / 100.0) > 1.0), "ElasticOut never overshoots");
+}
+
+#[test]
+fn tween_endpoints() {
+    for &easing in &EASINGS {
+        let mut tween = Tween::new(2.0, 10.0, 1.5, easing);
+        assert_eq!(tween.value(), 2.0);
+        assert_eq!(tween.update(0.0), 2.0);
+        assert!(!tween.is_finished());
+
+        tween.update(0.75);
+        assert!(!tween.is_finished());
+
+        assert!(tween.update(0.75) == 10.0, "{:?} doesn't reach the end value", easing);
+        assert!(tween.is_finished());
+
+        // Updating past the end stays clamped to the end value.
+        assert_eq!(tween.update(5.0), 10.0);
+        assert!(tween.is_finished());
+
+        tween.reset();
+        assert_eq!(tween.value(), 2.0);
+    }
+
+    let mut tween = Tween::new(1.0, 3.0, 0.0, Easing::Linear);
+    assert!(tween.is_finished());
+    assert_eq!(tween.update(0.1), 3.0);
+}
+
+#[test]
+fn tween_types() {
+    let mut tween = Tween::new(Point::origin(), Point::new(2.0, 4.0, -6.0), 2.0, Easing::Linear);
+    assert_eq!(tween.update(1.0), Point::new(1.0, 2.0, -3.0));
+    assert_eq!(tween.update(1.0), Point::new(2.0, 4.0, -6.0));
+
+    let halfway = Vector3::lerp(Vector3::zero(), Vector3::new(0.0, 10.0, 0.0), 0.5);
+    assert_eq!(halfway, Vector3::new(0.0, 5.0, 0.0));
+
+    let color = Color::lerp(Color::new(0.0, 0.0, 0.0, 1.0), Color::new(1.0, 0.5, 0.0, 0.0), 0.5);
+    assert_eq!(color.as_array(), &[0.5, 0.25, 0.0, 0.5]);
+
+    let end = Quaternion::axis_angle(Vector3::up(), 1.0);
+    let mut tween = Tween::new(Quaternion::identity(), end, 1.0, Easing::EaseInOut);
+    assert!(tween.update(0.5).is_normalized());
+    assert!((Quaternion::dot(tween.update(0.5), end) - 1.0).is_zero());
+}
@@ -0,0 +1,145 @@
+use vector::{Vector2, Vector3};
+use super::super::{IsZero, Dot};
+
+#[test]
+fn display() {
+    assert_eq!(format!("{}", Vector3::new(1.0, -2.5, 0.125)), "(1.0000, -2.5000, 0.1250)");
+    assert_eq!(format!("{:.1}", Vector3::new(1.0, -2.5, 0.125)), "(1.0, -2.5, 0.1)");
+    assert_eq!(format!("{}", Vector2::new(3.0, -0.5)), "(3.0000, -0.5000)");
+}
+
+#[test]
+fn fmt_precision() {
+    let vector = Vector3::new(1.23456, 0.0, -7.6);
+    assert_eq!(vector.fmt_precision(0), "(1, 0, -8)");
+    assert_eq!(vector.fmt_precision(2), "(1.23, 0.00, -7.60)");
+    assert_eq!(Vector2::new(0.333, 2.0).fmt_precision(1), "(0.3, 2.0)");
+}
+
+#[test]
+fn distance() {
+    let first = Vector3::new(1.0, 2.0, 3.0);
+    let second = Vector3::new(4.0, 6.0, 3.0);
+
+    assert_eq!(Vector3::distance(first, second), 5.0);
+    assert_eq!(Vector3::distance(second, first), 5.0);
+    assert_eq!(Vector3::distance_squared(first, second), 25.0);
+    assert_eq!(Vector3::distance(first, first), 0.0);
+    assert_eq!(Vector3::distance_squared(first, first), 0.0);
+}
+
+#[test]
+fn direction_to() {
+    let first = Vector3::new(1.0, 2.0, 3.0);
+    let second = Vector3::new(4.0, 6.0, 3.0);
+
+    let direction = Vector3::direction_to(first, second);
+    assert!((direction - Vector3::new(0.6, 0.8, 0.0)).is_zero());
+    assert!(direction.is_normalized());
+
+    // Coincident positions give the zero vector rather than NaN.
+    assert_eq!(Vector3::direction_to(first, first), Vector3::zero());
+}
+
+#[test]
+fn move_towards() {
+    let target = Vector3::new(3.0, 4.0, 0.0);
+    let mut current = Vector3::zero();
+
+    // Each step covers exactly the max distance until the target is reached.
+    for step in 1..5 {
+        let previous = current;
+        current = Vector3::move_towards(current, target, 2.0);
+
+        if step < 3 {
+            assert!((Vector3::distance(previous, current) - 2.0).abs() < 1e-5);
+        }
+
+        // Never overshoot the target.
+        assert!(Vector3::distance(current, target) <= Vector3::distance(previous, target));
+        assert!(current.x <= target.x && current.y <= target.y);
+    }
+
+    assert_eq!(current, target);
+
+    // Snaps exactly when within range.
+    assert_eq!(Vector3::move_towards(Vector3::new(2.9, 4.0, 0.0), target, 0.5), target);
+
+    // A zero step doesn't move.
+    let start = Vector3::new(1.0, 2.0, 3.0);
+    assert_eq!(Vector3::move_towards(start, target, 0.0), start);
+
+    // Already at the target.
+    assert_eq!(Vector3::move_towards(target, target, 0.0), target);
+}
+
+#[test]
+fn quantize() {
+    let epsilon = 0.001;
+    let vector = Vector3::new(0.25, 3.0, -7.5);
+
+    assert_eq!(vector.quantize(epsilon), Vector3::new(0.2502, 2.9998, -7.5003).quantize(epsilon));
+    assert!(vector.quantize(epsilon) != Vector3::new(0.25, 3.01, -7.5).quantize(epsilon));
+    assert_eq!(Vector3::new(-1.0, 0.0, 1.0).quantize(0.5).to_vector3(0.5), Vector3::new(-1.0, 0.0, 1.0));
+}
+
+#[test]
+fn orthonormal_basis() {
+    let directions = [
+        Vector3::new(0.0, 0.0, 1.0),
+        Vector3::new(0.0, 0.0, -1.0),
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.0, -1.0, 0.0),
+        Vector3::new(1.0, 2.0, 3.0).normalized(),
+        Vector3::new(-0.3, 0.8, -0.5).normalized(),
+
+        // Very close to the poles, where less careful methods lose precision.
+        Vector3::new(1e-4, -1e-4, 1.0).normalized(),
+        Vector3::new(-1e-4, 1e-4, -1.0).normalized(),
+        Vector3::new(0.0, 1e-7, -1.0).normalized(),
+    ];
+
+    for &direction in &directions {
+        let (first, second) = direction.orthonormal_basis();
+        assert!((first.magnitude() - 1.0).abs() < 1e-5, "{:?} gave non-unit {:?}", direction, first);
+        assert!((second.magnitude() - 1.0).abs() < 1e-5, "{:?} gave non-unit {:?}", direction, second);
+        assert!(first.dot(second).abs() < 1e-5, "{:?} gave non-perpendicular basis", direction);
+        assert!(first.dot(direction).abs() < 1e-5, "{:?} isn't perpendicular to {:?}", first, direction);
+        assert!(second.dot(direction).abs() < 1e-5, "{:?} isn't perpendicular to {:?}", second, direction);
+        assert!((Vector3::cross(first, second) - direction).is_zero_within(1e-5));
+    }
+
+    assert_eq!(Vector3::zero().orthonormal_basis(), (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)));
+}
+
+#[test]
+fn spherical_round_trip() {
+    use std::f32::consts::PI;
+
+    let vectors = [
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, -2.0),
+        Vector3::new(1.0, 2.0, 3.0),
+        Vector3::new(-0.3, 0.8, -0.5),
+        Vector3::new(-4.0, -1.0, 0.5),
+        Vector3::new(0.001, 10.0, -0.002),
+    ];
+
+    for &vector in &vectors {
+        let (radius, theta, phi) = vector.to_spherical();
+        assert!((radius - vector.magnitude()).abs() < 1e-5);
+        assert!(theta >= -PI && theta <= PI && phi >= 0.0 && phi <= PI, "{:?} gave ({}, {})", vector, theta, phi);
+        let round_trip = Vector3::from_spherical(radius, theta, phi);
+        assert!((round_trip - vector).is_zero_within(1e-4), "{:?} became {:?}", vector, round_trip);
+    }
+
+    // The angle conventions: theta starts at +Z and turns towards +X, phi is measured from +Y.
+    assert!((Vector3::from_spherical(2.0, 0.0, PI / 2.0) - Vector3::new(0.0, 0.0, 2.0)).is_zero_within(1e-5));
+    assert!((Vector3::from_spherical(2.0, PI / 2.0, PI / 2.0) - Vector3::new(2.0, 0.0, 0.0)).is_zero_within(1e-5));
+    assert!((Vector3::from_spherical(2.0, 1.0, 0.0) - Vector3::new(0.0, 2.0, 0.0)).is_zero_within(1e-5));
+
+    // Degenerate directions.
+    assert_eq!(Vector3::zero().to_spherical(), (0.0, 0.0, 0.0));
+    assert_eq!(Vector3::new(0.0, 3.0, 0.0).to_spherical(), (3.0, 0.0, 0.0));
+    assert_eq!(Vector3::new(0.0, -3.0, -0.0).to_spherical(), (3.0, 0.0, PI));
+}
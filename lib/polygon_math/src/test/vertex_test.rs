@@ -0,0 +1,32 @@
+use point::Point;
+use vector::{Vector2, Vector3};
+use vertex::{interleave, INTERLEAVED_LAYOUT};
+
+#[test]
+fn interleave_two_vertices() {
+    let positions = [Point::new(1.0, 2.0, 3.0), Point::new(4.0, 5.0, 6.0)];
+    let normals = [Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)];
+    let texcoords = [Vector2::new(0.25, 0.5), Vector2::new(0.75, 1.0)];
+
+    let vertex_data = interleave(&positions, &normals, &texcoords).unwrap();
+    assert_eq!(vertex_data, vec![
+        1.0, 2.0, 3.0, 0.0, 1.0, 0.0, 0.25, 0.5,
+        4.0, 5.0, 6.0, 0.0, 0.0, 1.0, 0.75, 1.0,
+    ]);
+
+    assert_eq!(INTERLEAVED_LAYOUT.stride, 8);
+    assert_eq!(vertex_data.len(), positions.len() * INTERLEAVED_LAYOUT.stride);
+    assert_eq!(vertex_data[INTERLEAVED_LAYOUT.stride + INTERLEAVED_LAYOUT.normal_offset + 2], 1.0);
+    assert_eq!(vertex_data[INTERLEAVED_LAYOUT.stride + INTERLEAVED_LAYOUT.texcoord_offset], 0.75);
+}
+
+#[test]
+fn interleave_mismatched_lengths() {
+    let positions = [Point::new(1.0, 2.0, 3.0), Point::new(4.0, 5.0, 6.0)];
+    let normals = [Vector3::new(0.0, 1.0, 0.0)];
+    let texcoords = [Vector2::new(0.25, 0.5), Vector2::new(0.75, 1.0)];
+
+    assert_eq!(interleave(&positions, &normals, &texcoords), None);
+    assert_eq!(interleave(&positions, &[], &[]), None);
+    assert_eq!(interleave(&[], &[], &[]), Some(Vec::new()));
+}
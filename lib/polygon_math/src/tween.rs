@@ -0,0 +1,163 @@
+//! Interpolating a value between two endpoints over time, e.g. for scripted animations.
+
+use std::f32::consts::PI;
+
+use color::Color;
+use point::Point;
+use quaternion::Quaternion;
+use vector::Vector3;
+use super::Clamp;
+
+/// Types that can be linearly interpolated.
+pub trait Lerp: Copy {
+    /// Interpolates between `from` and `to`, returning `from` when `t` is 0.0 and `to` when `t`
+    /// is 1.0.
+    ///
+    /// Values of `t` outside of [0, 1] extrapolate past the endpoints.
+    fn lerp(from: Self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(from: f32, to: f32, t: f32) -> f32 {
+        from + (to - from) * t
+    }
+}
+
+impl Lerp for Vector3 {
+    fn lerp(from: Vector3, to: Vector3, t: f32) -> Vector3 {
+        from + (to - from) * t
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(from: Point, to: Point, t: f32) -> Point {
+        from + (to - from) * t
+    }
+}
+
+/// Quaternions are interpolated with `Quaternion::nlerp()` so the result is always a valid
+/// rotation.
+impl Lerp for Quaternion {
+    fn lerp(from: Quaternion, to: Quaternion, t: f32) -> Quaternion {
+        Quaternion::nlerp(from, to, t)
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(from: Color, to: Color, t: f32) -> Color {
+        Color::new(
+            f32::lerp(from.r, to.r, t),
+            f32::lerp(from.g, to.g, t),
+            f32::lerp(from.b, to.b, t),
+            f32::lerp(from.a, to.a, t))
+    }
+}
+
+/// The curve used to map a tween's progress to the interpolation between its endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+
+    /// Starts slow and speeds up.
+    EaseIn,
+
+    /// Starts fast and slows down.
+    EaseOut,
+
+    /// Starts slow, speeds up, then slows down again.
+    EaseInOut,
+
+    /// Overshoots the end and springs back, settling on the end value.
+    ElasticOut,
+}
+
+impl Easing {
+    /// Maps the progress `t` to an interpolation factor.
+    ///
+    /// `t` is clamped to [0, 1]. Every curve returns exactly 0.0 for a `t` of 0.0 and exactly
+    /// 1.0 for a `t` of 1.0, though `ElasticOut` leaves that range in between.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    let inverse = -2.0 * t + 2.0;
+                    1.0 - inverse * inverse * 0.5
+                }
+            },
+            Easing::ElasticOut => {
+                // The formula only approximately reaches the endpoints.
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    const PERIOD: f32 = 2.0 * PI / 3.0;
+                    2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * PERIOD).sin() + 1.0
+                }
+            },
+        }
+    }
+}
+
+/// Animates a value from one endpoint to another over a fixed duration.
+///
+/// # Details
+///
+/// The tween only tracks the elapsed time, so the value at any point depends only on the total
+/// time passed to `update()` and not on how it was split between calls.
+#[derive(Debug, Clone, Copy)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Creates a tween from `from` to `to` that takes `duration` seconds.
+    ///
+    /// A tween with a duration of zero (or less) is finished immediately.
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Tween<T> {
+        Tween {
+            from: from,
+            to: to,
+            duration: duration,
+            elapsed: 0.0,
+            easing: easing,
+        }
+    }
+
+    /// Advances the tween by `dt` seconds and returns the new value.
+    ///
+    /// Once the duration has passed the tween is finished and always returns the end value.
+    pub fn update(&mut self, dt: f32) -> T {
+        self.elapsed = f32::min(self.elapsed + dt, self.duration);
+        self.value()
+    }
+
+    /// Retrieves the current value without advancing the tween.
+    pub fn value(&self) -> T {
+        if self.is_finished() {
+            return self.to;
+        }
+
+        let t = self.easing.apply(self.elapsed / self.duration);
+        T::lerp(self.from, self.to, t)
+    }
+
+    /// Returns true once the tween has reached the end value.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Rewinds the tween to the start value.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
@@ -1,6 +1,9 @@
 use std::ops::{Mul, MulAssign, Div, DivAssign, Neg, Add, AddAssign, Sub, SubAssign, Index, IndexMut};
+use std::fmt::{self, Display, Formatter};
 
-use super::{IsZero, Dot};
+use super::{IsZero, Dot, quantize, DISPLAY_PRECISION};
+use smoothing::smooth_damp;
+use plain_data;
 
 #[repr(C)] #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector3 {
@@ -57,6 +60,89 @@ impl Vector3 {
         }
     }
 
+    /// Calculates two unit vectors perpendicular to the vector and to each other.
+    ///
+    /// # Details
+    ///
+    /// Uses the branchless method from Duff et al. "Building an Orthonormal Basis, Revisited",
+    /// which picks its helper axis based on the sign of Z, so it stays accurate for every
+    /// direction including those near the poles. The result `(a, b)` forms a right-handed basis
+    /// with the vector, i.e. `cross(a, b) == self`.
+    ///
+    /// The vector must be normalized, which is checked in debug builds. As a special case the
+    /// zero vector returns the X and Y axes.
+    pub fn orthonormal_basis(&self) -> (Vector3, Vector3) {
+        if self.is_zero() {
+            return (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        }
+
+        debug_assert!(self.is_normalized(), "Cannot build an orthonormal basis around {:?} because it isn't normalized", self);
+
+        let sign = if self.z >= 0.0 { 1.0 } else { -1.0 };
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+
+        (
+            Vector3::new(1.0 + sign * self.x * self.x * a, sign * b, -sign * self.x),
+            Vector3::new(b, sign + self.y * self.y * a, -self.y),
+        )
+    }
+
+    /// Creates a vector from spherical coordinates.
+    ///
+    /// # Details
+    ///
+    /// `theta` is the azimuth in radians, measured around the Y axis starting from +Z towards +X,
+    /// and `phi` is the polar angle in radians measured from +Y, so a `phi` of 0 points straight
+    /// up and a `phi` of PI / 2 lies in the XZ plane. This is the inverse of `to_spherical()`.
+    pub fn from_spherical(radius: f32, theta: f32, phi: f32) -> Vector3 {
+        let horizontal = radius * phi.sin();
+        Vector3::new(horizontal * theta.sin(), radius * phi.cos(), horizontal * theta.cos())
+    }
+
+    /// Converts the vector to spherical coordinates, returning `(radius, theta, phi)`.
+    ///
+    /// # Details
+    ///
+    /// The angles follow the same convention as `from_spherical()`, with `theta` in the range
+    /// [-PI, PI] and `phi` in the range [0, PI]. The zero vector has no direction so it returns
+    /// all zeros. Vectors on the Y axis have a well defined `phi` but any azimuth is equally
+    /// valid, so `theta` is 0 for them.
+    pub fn to_spherical(&self) -> (f32, f32, f32) {
+        let radius = self.magnitude();
+        if radius == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+
+        let theta = if self.x == 0.0 && self.z == 0.0 { 0.0 } else { self.x.atan2(self.z) };
+        let phi = (self.y / radius).max(-1.0).min(1.0).acos();
+        (radius, theta, phi)
+    }
+
+    /// Calculates the distance between two positions.
+    pub fn distance(first: Vector3, second: Vector3) -> f32 {
+        (second - first).magnitude()
+    }
+
+    /// Calculates the squared distance between two positions.
+    ///
+    /// This is cheaper than `distance()` and should be preferred when only comparing distances.
+    pub fn distance_squared(first: Vector3, second: Vector3) -> f32 {
+        (second - first).magnitude_squared()
+    }
+
+    /// Calculates the normalized direction from `from` to `to`.
+    ///
+    /// If the two positions are coincident the zero vector is returned.
+    pub fn direction_to(from: Vector3, to: Vector3) -> Vector3 {
+        let offset = to - from;
+        if offset.is_zero() {
+            Vector3::zero()
+        } else {
+            offset.normalized()
+        }
+    }
+
     /// Normalizes the vector, returning the old length.
     ///
     /// If the vector is the zero vector it is not altered.
@@ -91,6 +177,11 @@ impl Vector3 {
         (self.dot(self) - 1.0).is_zero()
     }
 
+    /// Checks that none of the components are NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
     pub fn magnitude(&self) -> f32 {
         self.magnitude_squared().sqrt()
     }
@@ -102,10 +193,52 @@ impl Vector3 {
     // Safely reinterprets a slice of Vector3s to a slice of f32s. This is a cheap operation and
     // does not copy any data.
     pub fn as_ref(vectors: &[Vector3]) -> &[f32] {
-        unsafe {
-            ::std::slice::from_raw_parts(
-                vectors.as_ptr() as *const f32,
-                vectors.len() * 3)
+        plain_data::as_f32_slice(vectors)
+    }
+
+    /// Gradually moves `current` towards `target` using a critically damped spring.
+    ///
+    /// Each component is damped independently using `smoothing::smooth_damp()`, with the
+    /// components of `velocity` tracking the rate of change of each component.
+    pub fn smooth_damp(current: Vector3, target: Vector3, velocity: &mut Vector3, smooth_time: f32, dt: f32) -> Vector3 {
+        Vector3::new(
+            smooth_damp(current.x, target.x, &mut velocity.x, smooth_time, dt),
+            smooth_damp(current.y, target.y, &mut velocity.y, smooth_time, dt),
+            smooth_damp(current.z, target.z, &mut velocity.z, smooth_time, dt))
+    }
+
+    /// Moves `current` towards `target` by at most `max_distance_delta`.
+    ///
+    /// # Details
+    ///
+    /// Unlike `lerp()` the step is an absolute distance, so calling this every frame with
+    /// `speed * delta` moves at a constant speed regardless of how far away the target is. The
+    /// result never overshoots `target`, if it's within `max_distance_delta` then `target` is
+    /// returned exactly. A `max_distance_delta` of zero returns `current` unchanged.
+    pub fn move_towards(current: Vector3, target: Vector3, max_distance_delta: f32) -> Vector3 {
+        let offset = target - current;
+        let distance = offset.magnitude();
+        if distance <= max_distance_delta || distance == 0.0 {
+            target
+        } else {
+            current + offset * (max_distance_delta / distance)
+        }
+    }
+
+    /// Formats the vector as `(x, y, z)` with the specified number of decimal digits.
+    pub fn fmt_precision(&self, digits: usize) -> String {
+        format!("{:.*}", digits, self)
+    }
+
+    /// Snaps the vector to a grid with cells of size `epsilon`, returning a key that can be
+    /// hashed.
+    ///
+    /// See `Point::quantize()` for details on how the components are rounded.
+    pub fn quantize(&self, epsilon: f32) -> QuantizedVector3 {
+        QuantizedVector3 {
+            x: quantize(self.x, epsilon),
+            y: quantize(self.y, epsilon),
+            z: quantize(self.z, epsilon),
         }
     }
 
@@ -117,6 +250,23 @@ impl Vector3 {
     // }
 }
 
+/// A vector snapped to a grid, see `Vector3::quantize()`.
+///
+/// The components are the number of grid cells along each axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuantizedVector3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl QuantizedVector3 {
+    /// Converts back to a vector, `epsilon` should be the same value used to quantize the vector.
+    pub fn to_vector3(&self, epsilon: f32) -> Vector3 {
+        Vector3::new(self.x as f32 * epsilon, self.y as f32 * epsilon, self.z as f32 * epsilon)
+    }
+}
+
 impl Dot for Vector3 {
     type Output = f32;
 
@@ -258,9 +408,21 @@ impl Div<Vector3> for f32 {
     }
 }
 
+/// The tolerance is compared against the squared magnitude of the vector.
 impl IsZero for Vector3 {
-    fn is_zero(self) -> bool {
-        self.dot(self).is_zero()
+    fn is_zero_within(self, epsilon: f32) -> bool {
+        self.dot(self).is_zero_within(epsilon)
+    }
+}
+
+/// Formats the vector as `(x, y, z)`.
+///
+/// If no precision is specified in the format string the components are printed with
+/// `DISPLAY_PRECISION` decimal digits.
+impl Display for Vector3 {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let precision = formatter.precision().unwrap_or(DISPLAY_PRECISION);
+        write!(formatter, "({:.*}, {:.*}, {:.*})", precision, self.x, precision, self.y, precision, self.z)
     }
 }
 
@@ -291,7 +453,7 @@ impl IndexMut<usize> for Vector3 {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[repr(C)] #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32,
@@ -306,12 +468,22 @@ impl Vector2 {
     }
 
     pub fn as_ref(vectors: &[Vector2]) -> &[f32] {
-        use std::slice;
+        plain_data::as_f32_slice(vectors)
+    }
 
-        unsafe {
-            slice::from_raw_parts(
-                vectors.as_ptr() as *const f32,
-                vectors.len() * 2)
-        }
+    /// Formats the vector as `(x, y)` with the specified number of decimal digits.
+    pub fn fmt_precision(&self, digits: usize) -> String {
+        format!("{:.*}", digits, self)
+    }
+}
+
+/// Formats the vector as `(x, y)`.
+///
+/// If no precision is specified in the format string the components are printed with
+/// `DISPLAY_PRECISION` decimal digits.
+impl Display for Vector2 {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let precision = formatter.precision().unwrap_or(DISPLAY_PRECISION);
+        write!(formatter, "({:.*}, {:.*})", precision, self.x, precision, self.y)
     }
 }
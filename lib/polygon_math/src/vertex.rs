@@ -0,0 +1,62 @@
+//! Utilities for building vertex buffers in a layout ready to upload to the GPU.
+
+use point::Point;
+use vector::{Vector2, Vector3};
+
+/// Describes the layout of the vertex data produced by `interleave()`.
+///
+/// # Details
+///
+/// All values are measured in number of `f32` elements, not bytes. Multiply by
+/// `mem::size_of::<f32>()` to get byte offsets suitable for `glVertexAttribPointer()` and similar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexLayout {
+    /// The number of elements between the start of one vertex and the start of the next.
+    pub stride: usize,
+
+    /// The offset of the position attribute from the start of the vertex.
+    pub position_offset: usize,
+
+    /// The offset of the normal attribute from the start of the vertex.
+    pub normal_offset: usize,
+
+    /// The offset of the texcoord attribute from the start of the vertex.
+    pub texcoord_offset: usize,
+}
+
+/// The layout of vertex data produced by `interleave()`.
+pub const INTERLEAVED_LAYOUT: VertexLayout = VertexLayout {
+    stride: 8,
+    position_offset: 0,
+    normal_offset: 3,
+    texcoord_offset: 6,
+};
+
+/// Interleaves vertex positions, normals, and texcoords into a single buffer.
+///
+/// # Details
+///
+/// Each vertex is written as `[px, py, pz, nx, ny, nz, u, v]`, as described by
+/// `INTERLEAVED_LAYOUT`. Note that the `w` component of the positions is dropped. Returns `None`
+/// if the slices don't all have the same length.
+pub fn interleave(positions: &[Point], normals: &[Vector3], texcoords: &[Vector2]) -> Option<Vec<f32>> {
+    if positions.len() != normals.len() || positions.len() != texcoords.len() {
+        return None;
+    }
+
+    let position_data = Point::as_ref(positions);
+    let normal_data = Vector3::as_ref(normals);
+    let texcoord_data = Vector2::as_ref(texcoords);
+
+    let mut vertex_data = Vec::with_capacity(positions.len() * INTERLEAVED_LAYOUT.stride);
+    for ((position, normal), texcoord) in position_data.chunks(4)
+        .zip(normal_data.chunks(3))
+        .zip(texcoord_data.chunks(2))
+    {
+        vertex_data.extend(&position[..3]);
+        vertex_data.extend(normal);
+        vertex_data.extend(texcoord);
+    }
+
+    Some(vertex_data)
+}
@@ -1,34 +1,89 @@
 use math::Point;
+use math::Vector3;
+use math::Matrix3;
 use math::Matrix4;
+use math::DepthRange;
 use math::Quaternion;
 
+/// The projection used by a camera to convert camera space to clip space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ProjectionMode {
+    /// A perspective projection, where objects further from the camera appear smaller.
+    ///
+    /// `fov` is the vertical field of view in radians and `aspect` is the ratio of the viewport's
+    /// width to its height.
+    Perspective {
+        fov: f32,
+        aspect: f32,
+    },
+
+    /// An orthographic projection, where objects appear the same size regardless of distance.
+    ///
+    /// `size` is half the height of the view volume in world units and `aspect` is the ratio of
+    /// the viewport's width to its height.
+    Orthographic {
+        size: f32,
+        aspect: f32,
+    },
+}
+
 /// A camera in the scene.
 #[derive(Debug, Copy, Clone)]
 pub struct Camera
 {
-    pub fov: f32,
-    pub aspect: f32,
+    pub projection: ProjectionMode,
     pub near: f32,
     pub far: f32,
 
+    /// Whether the far plane is at infinity, see `set_infinite_far()`.
+    pub infinite_far: bool,
+
     pub position: Point,
     pub rotation: Quaternion,
 }
 
 impl Camera
 {
+    /// Creates a new camera with a perspective projection.
     pub fn new(fov: f32, aspect: f32, near: f32, far: f32) -> Camera {
+        Camera::with_projection(ProjectionMode::Perspective { fov: fov, aspect: aspect }, near, far)
+    }
+
+    /// Creates a new camera with an orthographic projection.
+    ///
+    /// See `ProjectionMode::Orthographic` for the meaning of `size`.
+    pub fn orthographic(size: f32, aspect: f32, near: f32, far: f32) -> Camera {
+        Camera::with_projection(ProjectionMode::Orthographic { size: size, aspect: aspect }, near, far)
+    }
+
+    pub fn with_projection(projection: ProjectionMode, near: f32, far: f32) -> Camera {
         Camera {
-            fov: fov,
-            aspect: aspect,
+            projection: projection,
             near: near,
             far: far,
+            infinite_far: false,
 
             position: Point::origin(),
             rotation: Quaternion::identity(),
         }
     }
 
+    /// Moves the far plane to infinity so that distant objects are never clipped.
+    ///
+    /// # Details
+    ///
+    /// Only perspective projections support an infinite far plane, orthographic projections
+    /// continue to use `far`. The value of `far` is kept so that `set_far()` can restore it.
+    pub fn set_infinite_far(&mut self) {
+        self.infinite_far = true;
+    }
+
+    /// Sets the distance to the far plane, switching back from an infinite far plane.
+    pub fn set_far(&mut self, far: f32) {
+        self.far = far;
+        self.infinite_far = false;
+    }
+
     /// Calculates the view transform for the camera.
     ///
     /// The view transform the matrix that converts from world coordinates
@@ -41,20 +96,139 @@ impl Camera
         Matrix4::from_point(self.position) * self.rotation.as_matrix4()
     }
 
+    /// Transforms a normal from world space to view space.
+    ///
+    /// # Details
+    ///
+    /// Normals are generally transformed by the inverse-transpose of a matrix's upper-left 3x3,
+    /// but the view matrix is built from only the camera's position and rotation, so that 3x3 is
+    /// a pure rotation and is its own inverse-transpose. This assumes the view matrix has no
+    /// scale, which holds as long as the camera's rotation is normalized. The normal's length is
+    /// preserved, so a zero normal stays zero.
+    pub fn world_to_view_normal(&self, normal: Vector3) -> Vector3 {
+        normal * Matrix3::from_matrix4(&self.view_matrix())
+    }
+
     /// Calculates the projection matrix for the camera.
     ///
     /// The projection matrix is the matrix that converts from camera space to
     /// clip space. This effectively converts the viewing frustrum into a unit cube.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clip planes would produce a degenerate projection, i.e. if `near` isn't
+    /// positive for a perspective projection, or if `far` isn't greater than `near` (unless the
+    /// far plane is infinite).
     pub fn projection_matrix(&self) -> Matrix4 {
-        let height = 2.0 * self.near * (self.fov * 0.5).tan();
-        let width = self.aspect * height;
+        let infinite_far = self.infinite_far && match self.projection {
+            ProjectionMode::Perspective { .. } => true,
+            ProjectionMode::Orthographic { .. } => false,
+        };
+        assert!(
+            infinite_far || self.far > self.near,
+            "Camera far plane ({}) must be further than its near plane ({})", self.far, self.near);
+
+        match self.projection {
+            ProjectionMode::Perspective { fov, aspect } => {
+                assert!(self.near > 0.0, "Perspective camera near plane must be positive but was {}", self.near);
 
-        let mut projection = Matrix4::new();
-        projection[0][0] = 2.0 * self.near / width;
-        projection[1][1] = 2.0 * self.near / height;
-        projection[2][2] = -(self.far + self.near) / (self.far - self.near);
-        projection[2][3] = -2.0 * self.far * self.near / (self.far - self.near);
-        projection[3][2] = -1.0;
-        projection
+                if infinite_far {
+                    Matrix4::perspective_infinite(fov, aspect, self.near, DepthRange::NegativeOneToOne)
+                } else {
+                    Matrix4::perspective(fov, aspect, self.near, self.far, DepthRange::NegativeOneToOne)
+                }
+            },
+            ProjectionMode::Orthographic { size, aspect } => {
+                let half_width = size * aspect;
+                Matrix4::orthographic(-half_width, half_width, -size, size, self.near, self.far)
+            },
+        }
+    }
+}
+
+#[test]
+fn projection_modes() {
+    use std::f32::consts::PI;
+
+    let mut camera = Camera::new(PI * 0.5, 1.0, 1.0, 3.0);
+    camera.position = Point::new(1.0, 2.0, 3.0);
+    let view = camera.view_matrix();
+
+    let mut perspective = Matrix4::new();
+    perspective[0][0] = 1.0;
+    perspective[1][1] = 1.0;
+    perspective[2][2] = -2.0;
+    perspective[2][3] = -3.0;
+    perspective[3][2] = -1.0;
+    assert_eq!(camera.projection_matrix(), perspective);
+
+    // Switching modes changes the projection but not the view.
+    camera.projection = ProjectionMode::Orthographic { size: 2.0, aspect: 2.0 };
+
+    let mut orthographic = Matrix4::identity();
+    orthographic[0][0] = 0.25;
+    orthographic[1][1] = 0.5;
+    orthographic[2][2] = -1.0;
+    orthographic[2][3] = -2.0;
+    assert_eq!(camera.projection_matrix(), orthographic);
+    assert_eq!(camera.view_matrix(), view);
+
+    assert_eq!(Camera::orthographic(2.0, 2.0, 1.0, 3.0).projection_matrix(), orthographic);
+}
+
+#[test]
+fn world_to_view_normal() {
+    use std::f32::consts::PI;
+    use math::IsZero;
+
+    // Cameras look down -Z by default, so turn to look down +X.
+    let mut camera = Camera::new(PI * 0.5, 1.0, 1.0, 100.0);
+    camera.position = Point::new(5.0, -3.0, 2.0);
+    camera.rotation = Quaternion::axis_angle(Vector3::up(), -PI * 0.5);
+
+    // Up stays up, world forward is view forward, and world +Z is to the camera's right.
+    assert!((camera.world_to_view_normal(Vector3::up()) - Vector3::up()).is_zero());
+    assert!((camera.world_to_view_normal(Vector3::new(1.0, 0.0, 0.0)) - Vector3::new(0.0, 0.0, -1.0)).is_zero());
+    assert!((camera.world_to_view_normal(Vector3::new(0.0, 0.0, 1.0)) - Vector3::new(1.0, 0.0, 0.0)).is_zero());
+
+    assert_eq!(camera.world_to_view_normal(Vector3::zero()), Vector3::zero());
+}
+
+#[test]
+fn infinite_far_projection() {
+    use std::f32::consts::PI;
+
+    let mut camera = Camera::new(PI * 0.5, 1.0, 0.1, 100.0);
+    let finite = camera.projection_matrix();
+
+    // Very distant points approach the far end of the depth range but never pass it.
+    camera.set_infinite_far();
+    let infinite = camera.projection_matrix();
+    assert_eq!(infinite, Matrix4::perspective_infinite(PI * 0.5, 1.0, 0.1, DepthRange::NegativeOneToOne));
+    for &distance in &[1000.0, 1.0e5, 1.0e9] {
+        let clip = Point::new(0.0, 0.0, -distance) * infinite;
+        let depth = clip.z / clip.w;
+        assert!(depth > 0.99 && depth <= 1.0);
     }
+
+    // Orthographic projections always use the finite far plane.
+    camera.projection = ProjectionMode::Orthographic { size: 2.0, aspect: 1.0 };
+    assert_eq!(camera.projection_matrix(), Matrix4::orthographic(-2.0, 2.0, -2.0, 2.0, 0.1, 100.0));
+
+    // Switching back restores the standard projection.
+    camera.projection = ProjectionMode::Perspective { fov: PI * 0.5, aspect: 1.0 };
+    camera.set_far(100.0);
+    assert_eq!(camera.projection_matrix(), finite);
+}
+
+#[test]
+#[should_panic(expected = "near plane must be positive")]
+fn perspective_near_must_be_positive() {
+    Camera::new(1.0, 1.0, 0.0, 100.0).projection_matrix();
+}
+
+#[test]
+#[should_panic(expected = "must be further than its near plane")]
+fn far_must_be_beyond_near() {
+    Camera::orthographic(1.0, 1.0, 10.0, 5.0).projection_matrix();
 }
@@ -7,7 +7,7 @@ pub mod geometry;
 pub mod gl_render;
 pub mod light;
 
-pub use camera::Camera;
+pub use camera::{Camera, ProjectionMode};
 pub use geometry::*;
 pub use gl_render::{GLRender, ShaderProgram};
 pub use light::{Light, PointLight};
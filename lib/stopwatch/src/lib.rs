@@ -5,7 +5,9 @@ use std::collections::HashMap;
 // use std::fs::OpenOptions;
 // use std::io::Write;
 use std::ptr;
+use std::time::Duration;
 
+use bootstrap::log;
 use bootstrap::time::{Timer, TimeMark};
 
 pub mod null;
@@ -14,6 +16,10 @@ pub mod null;
 /// anywhere without having to pass the Collector around.
 static mut COLLECTOR: *mut Collector = 0 as *mut Collector;
 
+/// A global access point for the profiler. Stopwatches automatically report their timings to the
+/// profiler if one exists.
+static mut PROFILER: *mut Profiler = 0 as *mut Profiler;
+
 pub struct Collector {
     nodes:      Vec<StackNode>,
     call_stack: Vec<usize>,
@@ -49,13 +55,14 @@ impl Collector {
         //     writeln!(file, "{}", log).unwrap();
         // }
 
-        // For now we're going to just print to the console.
+        // For now we're going to just send the timings to the log.
         self.print_node(&self.nodes[0], 0);
     }
 
     fn print_node<'a>(&'a self, node: &'a StackNode, depth: usize) {
+        let mut line = String::new();
         for _ in 0..depth {
-            print!("    ");
+            line.push_str("    ");
         }
 
         let mut sorted_data = node.data.clone();
@@ -67,7 +74,8 @@ impl Collector {
             }
         });
         let median = sorted_data[sorted_data.len() / 2];
-        println!("{}: median {:.6}ms", node.name, median);
+        line.push_str(&format!("{}: median {:.6}ms", node.name, median));
+        log::info(&line);
 
         for (_, child_index) in &node.children {
             self.print_node(&self.nodes[*child_index], depth + 1);
@@ -107,9 +115,112 @@ impl Drop for Stopwatch {
     fn drop(&mut self) {
         let elapsed = self.timer.elapsed_ms(self.start_time);
         pop_call_stack(self.name, elapsed);
+
+        unsafe {
+            if !PROFILER.is_null() {
+                (&mut *PROFILER).record(self.name, duration_from_ms(elapsed));
+            }
+        }
     }
 }
 
+/// Aggregates stopwatch timings into a per-frame profile.
+///
+/// # Details
+///
+/// While a `Profiler` exists every `Stopwatch` reports its elapsed time to the profiler when it's
+/// dropped. Timings are grouped by the stopwatch's name, so multiple stopwatches with the same name
+/// (including nested ones) are aggregated into a single section rather than overwriting each
+/// other. Call `reset()` at the end of each frame to start a new profile.
+///
+/// Only one `Profiler` may exist at a time.
+pub struct Profiler {
+    sections: Vec<ProfileSection>,
+    indices:  HashMap<&'static str, usize>,
+}
+
+impl Profiler {
+    pub fn new() -> Result<Box<Profiler>, ()> {
+        unsafe {
+            if !PROFILER.is_null() {
+                return Err(());
+            }
+        }
+
+        let mut boxed = Box::new(Profiler {
+            sections: Vec::new(),
+            indices:  HashMap::new(),
+        });
+
+        unsafe {
+            PROFILER = &mut *boxed;
+        }
+
+        Ok(boxed)
+    }
+
+    /// Records a single timing for the named section.
+    pub fn record(&mut self, name: &'static str, duration: Duration) {
+        if let Some(&index) = self.indices.get(name) {
+            let section = &mut self.sections[index];
+            section.count += 1;
+            section.total = section.total + duration;
+            if duration < section.min {
+                section.min = duration;
+            }
+            if duration > section.max {
+                section.max = duration;
+            }
+
+            return;
+        }
+
+        self.indices.insert(name, self.sections.len());
+        self.sections.push(ProfileSection {
+            name:  name,
+            count: 1,
+            total: duration,
+            min:   duration,
+            max:   duration,
+        });
+    }
+
+    /// Generates a report for the current frame.
+    ///
+    /// Each entry in the report is the section name followed by the min, max, and average duration
+    /// for that section. Sections are listed in the order they were first recorded.
+    pub fn frame_report(&self) -> Vec<(String, Duration, Duration, Duration)> {
+        self.sections.iter().map(|section| {
+            (section.name.into(), section.min, section.max, section.total / section.count)
+        }).collect()
+    }
+
+    /// Clears all recorded timings in preparation for the next frame.
+    pub fn reset(&mut self) {
+        self.sections.clear();
+        self.indices.clear();
+    }
+}
+
+impl Drop for Profiler {
+    fn drop(&mut self) {
+        unsafe { PROFILER = ptr::null_mut(); }
+    }
+}
+
+struct ProfileSection {
+    name:  &'static str,
+    count: u32,
+    total: Duration,
+    min:   Duration,
+    max:   Duration,
+}
+
+fn duration_from_ms(ms: f32) -> Duration {
+    let nanos = (ms as f64 * 1_000_000.0) as u64;
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
+
 fn push_call_stack(name: &'static str) {
     unsafe {
         debug_assert!(!COLLECTOR.is_null(), "Cannot push call stack without a collector instance.");
@@ -180,3 +291,38 @@ impl StackNode {
         }
     }
 }
+
+#[test]
+fn profiler_aggregates_sections() {
+    let _collector = Collector::new().unwrap();
+    let mut profiler = Profiler::new().unwrap();
+
+    // Nested stopwatches with the same name are aggregated into a single section.
+    {
+        let _outer = Stopwatch::new("section");
+        let _inner = Stopwatch::new("section");
+    }
+
+    {
+        let report = profiler.frame_report();
+        assert_eq!(report.len(), 1);
+
+        let (ref name, min, max, average) = report[0];
+        assert_eq!(name, "section");
+        assert!(min <= average && average <= max);
+    }
+
+    profiler.reset();
+    assert!(profiler.frame_report().is_empty());
+
+    // Recording the same section twice in a frame combines the timings.
+    profiler.record("manual", Duration::from_millis(1));
+    profiler.record("manual", Duration::from_millis(3));
+    profiler.record("other", Duration::from_millis(5));
+
+    let report = profiler.frame_report();
+    assert_eq!(report, vec![
+        ("manual".into(), Duration::from_millis(1), Duration::from_millis(3), Duration::from_millis(2)),
+        ("other".into(), Duration::from_millis(5), Duration::from_millis(5), Duration::from_millis(5)),
+    ]);
+}
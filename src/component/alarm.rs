@@ -102,6 +102,11 @@ impl ComponentManager for AlarmManager {
         self.marked_for_destroy.borrow_mut().insert(entity);
     }
 
+    /// Checks whether the entity has any pending alarms.
+    fn contains(&self, entity: Entity) -> bool {
+        self.alarms.iter().any(|alarm| alarm.entity == entity)
+    }
+
     fn destroy_marked(&mut self) {
         let mut marked_for_destroy = self.marked_for_destroy.borrow_mut();
         for entity in marked_for_destroy.drain() {
@@ -1,11 +1,14 @@
 use std::rc::Rc;
 use std::cell::{Ref, RefMut};
+use std::f32::consts::PI;
 
 use scene::Scene;
 use ecs::{Entity, ComponentManager, System};
 use resource::ResourceManager;
 use super::struct_component_manager::{StructComponentManager, ComponentIter, ComponentIterMut};
+use super::transform::Transform;
 use wav::Wave;
+use math::*;
 
 #[derive(Debug, Clone)]
 pub struct AudioSource {
@@ -13,6 +16,8 @@ pub struct AudioSource {
     offset:     usize,
     is_playing: bool,
     looping:    bool,
+    pan:        f32,
+    gain:       f32,
 }
 
 impl AudioSource {
@@ -41,6 +46,80 @@ impl AudioSource {
     pub fn is_playing(&self) -> bool {
         self.is_playing
     }
+
+    /// Retrieve the stereo pan of the audio source.
+    pub fn pan(&self) -> f32 {
+        self.pan
+    }
+
+    /// Set the stereo pan of the audio source.
+    ///
+    /// # Details
+    ///
+    /// -1.0 plays the clip fully in the left channel, 1.0 plays it fully in the right channel,
+    /// and 0.0 plays it equally in both. Values outside that range are clamped. Panning uses a
+    /// constant-power curve so the perceived loudness stays the same across the pan range. Pan
+    /// is ignored if the output device only has a single channel.
+    pub fn set_pan(&mut self, pan: f32) {
+        self.pan = pan.clamp(-1.0, 1.0);
+    }
+
+    /// Retrieve the volume of the audio source.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Set the volume of the audio source.
+    ///
+    /// 1.0 plays the clip at its original volume and 0.0 silences it. Values outside that range
+    /// are clamped.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.clamp(0.0, 1.0);
+    }
+}
+
+/// Calculates the gain and pan for a sound at `source_position` heard by `listener`.
+///
+/// # Details
+///
+/// The gain falls off linearly from 1.0 at the listener to 0.0 at `max_distance`, and is 0.0 for
+/// anything further away. The pan is the sine of the angle between the listener's forward
+/// direction and the direction to the source, so it's -1.0 for a source directly to the
+/// listener's left, 1.0 directly to its right, and 0.0 directly in front or behind. A source at
+/// the listener's position has full gain and a centered pan.
+///
+/// The listener's derived position and rotation are used, so the transform must be up to date.
+/// The results are meant to be passed to `AudioSource::set_gain()` and `AudioSource::set_pan()`.
+pub fn spatialize(listener: &Transform, source_position: Point, max_distance: f32) -> (f32, f32) {
+    let offset = source_position - listener.position_derived();
+    let distance = offset.magnitude();
+    if distance.is_zero() {
+        return (1.0, 0.0);
+    }
+
+    let gain = if distance >= max_distance {
+        0.0
+    } else {
+        1.0 - distance / max_distance
+    };
+
+    // +X is right for every coordinate convention.
+    let right = Vector3::new(1.0, 0.0, 0.0) * Matrix3::from_quaternion(listener.rotation_derived());
+    let pan = (offset / distance).dot(right).clamp(-1.0, 1.0);
+
+    (gain, pan)
+}
+
+/// Calculates the left and right channel gains for the given pan using constant-power panning.
+fn pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * PI * 0.25;
+    (angle.cos(), angle.sin())
+}
+
+/// Scales a 16 bit PCM sample by `gain`.
+fn apply_gain(sample: u16, gain: f32) -> u16 {
+    // Samples are signed even though the audio source streams them as u16.
+    ((sample as i16) as f32 * gain) as i16 as u16
 }
 
 pub struct AudioSourceManager {
@@ -70,6 +149,8 @@ impl AudioSourceManager {
             offset:     0,
             is_playing: false,
             looping:    false,
+            pan:        0.0,
+            gain:       1.0,
         })
     }
 
@@ -98,6 +179,10 @@ impl ComponentManager for AudioSourceManager {
     fn destroy_marked(&mut self) {
         self.inner.destroy_marked();
     }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.inner.contains(entity)
+    }
 }
 
 pub struct AudioSystem;
@@ -105,6 +190,7 @@ pub struct AudioSystem;
 impl System for AudioSystem {
     fn update(&mut self, scene: &Scene, delta: f32) {
         let audio_source_manager = scene.get_manager::<AudioSourceManager>();
+        let channels = scene.audio_source.channels() as usize;
 
         // TODO: Use a better method to filter out audio sources that aren't playing.
         for mut audio_source in audio_source_manager.iter_mut()
@@ -112,8 +198,23 @@ impl System for AudioSystem {
                                 .filter(|audio_source| audio_source.is_playing) {
             // Create an iterator over the samples using the data from the audio clip.
             let total_samples = {
+                // Pan is applied to the first two channels, any other channels only have the gain
+                // applied.
+                let gain = audio_source.gain;
+                let (left_gain, right_gain) = pan_gains(audio_source.pan);
                 let mut stream = audio_source.audio_clip.data.samples[audio_source.offset..].iter()
-                    .map(|sample| *sample);
+                    .enumerate()
+                    .map(|(index, &sample)| {
+                        if channels < 2 {
+                            return apply_gain(sample, gain);
+                        }
+
+                        match index % channels {
+                            0 => apply_gain(sample, left_gain * gain),
+                            1 => apply_gain(sample, right_gain * gain),
+                            _ => apply_gain(sample, gain),
+                        }
+                    });
 
                 // Sream the samples to the audio card.
                 let samples_written = scene.audio_source.stream(&mut stream, delta);
@@ -133,3 +234,70 @@ impl System for AudioSystem {
         }
     }
 }
+
+#[test]
+fn pan_gains_constant_power() {
+    use math::IsZero;
+
+    let (left, right) = pan_gains(-1.0);
+    assert!((left - 1.0).is_zero());
+    assert!(right.is_zero());
+
+    let (left, right) = pan_gains(1.0);
+    assert!(left.is_zero());
+    assert!((right - 1.0).is_zero());
+
+    for step in 0..21 {
+        let pan = -1.0 + step as f32 * 0.1;
+        let (left, right) = pan_gains(pan);
+        assert!((left * left + right * right - 1.0).is_zero(), "Power not constant for pan {}", pan);
+    }
+
+    // Out of range values are clamped.
+    assert_eq!(pan_gains(-5.0), pan_gains(-1.0));
+}
+
+#[test]
+fn apply_gain_silences_channel() {
+    assert_eq!(apply_gain(12345, 0.0), 0);
+    assert_eq!(apply_gain(-1000i16 as u16, 0.5), -500i16 as u16);
+    assert_eq!(apply_gain(1000, 1.0), 1000);
+}
+
+#[test]
+fn spatialize_gain_and_pan() {
+    use ecs::EntityManager;
+    use super::transform::TransformManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    // The listener faces down -Z so its right is +X.
+    let listener = entity_manager.create();
+    transform_manager.assign(listener).set_position(Point::new(1.0, 0.0, 0.0));
+    transform_manager.update_parallel(1);
+    let transform = transform_manager.get(listener);
+
+    assert_eq!(spatialize(&transform, Point::new(1.0, 0.0, 0.0), 10.0), (1.0, 0.0));
+
+    let (gain, pan) = spatialize(&transform, Point::new(6.0, 0.0, 0.0), 10.0);
+    assert!((gain - 0.5).abs() < 1e-5);
+    assert!((pan - 1.0).abs() < 1e-5);
+
+    let (gain, pan) = spatialize(&transform, Point::new(-9.0, 0.0, 0.0), 10.0);
+    assert!(gain.abs() < 1e-5);
+    assert!((pan + 1.0).abs() < 1e-5);
+
+    // Directly in front is centered, and anything past the max distance is silent.
+    let (gain, pan) = spatialize(&transform, Point::new(1.0, 0.0, -3.0), 10.0);
+    assert!((gain - 0.7).abs() < 1e-5);
+    assert!(pan.abs() < 1e-5);
+    assert_eq!(spatialize(&transform, Point::new(1.0, 50.0, 0.0), 10.0).0, 0.0);
+
+    // Turning the listener around swaps left and right.
+    drop(transform);
+    transform_manager.get_mut(listener).set_rotation(Quaternion::axis_angle(Vector3::up(), PI));
+    transform_manager.update_parallel(1);
+    let (_, pan) = spatialize(&transform_manager.get(listener), Point::new(6.0, 0.0, 0.0), 10.0);
+    assert!(pan < -0.99);
+}
@@ -1,4 +1,85 @@
+use std::cmp::Ordering;
+
+use math::Vector3;
+
+use ecs::Entity;
 use component::StructComponentManager;
+use super::transform::TransformManager;
 
-pub use polygon::camera::Camera;
+pub use polygon::camera::{Camera, ProjectionMode};
 pub type CameraManager = StructComponentManager<Camera>;
+
+/// Sorts entities by their distance from the camera, e.g. for drawing transparent objects.
+///
+/// # Details
+///
+/// Distances are measured between the derived positions of the camera's transform and each
+/// entity's transform, so the transforms must be up to date. If `back_to_front` is `true` the
+/// furthest entity comes first, otherwise the nearest entity comes first. Entities without a
+/// transform are always moved to the end regardless of the sort direction, and entities at the
+/// same distance keep their relative order. Panics if the camera doesn't have a transform.
+pub fn sort_by_distance(camera: Entity, entities: &mut Vec<Entity>, transforms: &TransformManager, back_to_front: bool) {
+    assert!(transforms.contains(camera), "Camera entity {:?} does not have a transform", camera);
+    let camera_position = transforms.get(camera).position_derived().as_vector3();
+
+    let mut keyed: Vec<(Option<f32>, Entity)> =
+        entities
+        .iter()
+        .map(|&entity| {
+            let distance = if transforms.contains(entity) {
+                let position = transforms.get(entity).position_derived().as_vector3();
+                Some(Vector3::distance_squared(camera_position, position))
+            } else {
+                None
+            };
+
+            (distance, entity)
+        })
+        .collect();
+
+    keyed.sort_by(|&(first, _), &(second, _)| {
+        match (first, second) {
+            (Some(first), Some(second)) => {
+                let ordering = first.partial_cmp(&second).unwrap_or(Ordering::Equal);
+                if back_to_front { ordering.reverse() } else { ordering }
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    });
+
+    for (entity, (_, sorted)) in entities.iter_mut().zip(keyed.into_iter()) {
+        *entity = sorted;
+    }
+}
+
+#[test]
+fn sort_by_camera_distance() {
+    use math::Point;
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let camera = entity_manager.create();
+    transform_manager.assign(camera).set_position(Point::new(0.0, 0.0, 5.0));
+
+    // The camera looks down -Z, `behind` is behind the camera but closer than `middle`.
+    let mut entities_at_depth = Vec::new();
+    for &depth in &[1.0, 10.0, 4.0, -2.0] {
+        let entity = entity_manager.create();
+        transform_manager.assign(entity).set_position(Point::new(1.0, 0.0, 5.0 - depth));
+        entities_at_depth.push(entity);
+    }
+    let (near, far, middle, behind) = (entities_at_depth[0], entities_at_depth[1], entities_at_depth[2], entities_at_depth[3]);
+    let untransformed = entity_manager.create();
+    transform_manager.update_parallel(1);
+
+    let mut entities = vec![untransformed, near, far, middle, behind];
+    sort_by_distance(camera, &mut entities, &transform_manager, true);
+    assert_eq!(entities, vec![far, middle, behind, near, untransformed]);
+
+    sort_by_distance(camera, &mut entities, &transform_manager, false);
+    assert_eq!(entities, vec![near, behind, middle, far, untransformed]);
+}
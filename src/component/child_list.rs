@@ -0,0 +1,139 @@
+use ecs::Entity;
+
+/// The number of children a `ChildList` can hold before it spills to the heap.
+pub const INLINE_CAPACITY: usize = 4;
+
+/// A list of an entity's direct children, stored inline for small child counts.
+///
+/// # Details
+///
+/// Most entities have only a handful of children, so up to `INLINE_CAPACITY` children are
+/// stored without a heap allocation. Adding more than that moves the children into a `Vec`,
+/// which is kept even if children are later removed. Children are kept in the order they were
+/// added.
+#[derive(Debug, Clone)]
+pub enum ChildList {
+    Inline {
+        len: usize,
+        children: [Entity; INLINE_CAPACITY],
+    },
+    Heap(Vec<Entity>),
+}
+
+impl ChildList {
+    pub fn new() -> ChildList {
+        ChildList::Inline {
+            len: 0,
+            children: [Entity::from_id(0); INLINE_CAPACITY],
+        }
+    }
+
+    pub fn as_slice(&self) -> &[Entity] {
+        match *self {
+            ChildList::Inline { len, ref children } => &children[..len],
+            ChildList::Heap(ref children) => &*children,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks whether the children have been moved to the heap.
+    pub fn is_spilled(&self) -> bool {
+        match *self {
+            ChildList::Inline { .. } => false,
+            ChildList::Heap(_) => true,
+        }
+    }
+
+    pub fn push(&mut self, child: Entity) {
+        let spilled = match *self {
+            ChildList::Inline { ref mut len, ref mut children } => {
+                if *len < INLINE_CAPACITY {
+                    children[*len] = child;
+                    *len += 1;
+                    return;
+                }
+
+                let mut spilled = Vec::with_capacity(INLINE_CAPACITY * 2);
+                spilled.extend(children.iter().cloned());
+                spilled.push(child);
+                spilled
+            },
+            ChildList::Heap(ref mut children) => {
+                children.push(child);
+                return;
+            },
+        };
+
+        *self = ChildList::Heap(spilled);
+    }
+
+    /// Removes the child from the list, preserving the order of the remaining children.
+    ///
+    /// Returns `false` if the entity isn't in the list.
+    pub fn remove(&mut self, child: Entity) -> bool {
+        let index = match self.as_slice().iter().position(|&existing| existing == child) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        match *self {
+            ChildList::Inline { ref mut len, ref mut children } => {
+                for shifted in index..*len - 1 {
+                    children[shifted] = children[shifted + 1];
+                }
+                *len -= 1;
+            },
+            ChildList::Heap(ref mut children) => {
+                children.remove(index);
+            },
+        }
+
+        true
+    }
+}
+
+#[test]
+fn child_list_spills() {
+    let entities: Vec<Entity> = (1..8).map(Entity::from_id).collect();
+
+    let mut children = ChildList::new();
+    for &entity in &entities[..INLINE_CAPACITY] {
+        children.push(entity);
+    }
+    assert!(!children.is_spilled());
+    assert_eq!(children.as_slice(), &entities[..INLINE_CAPACITY]);
+
+    // Overflowing the inline storage moves the children to the heap without losing any.
+    for &entity in &entities[INLINE_CAPACITY..] {
+        children.push(entity);
+    }
+    assert!(children.is_spilled());
+    assert_eq!(children.as_slice(), &*entities);
+
+    assert!(children.remove(entities[1]));
+    assert!(!children.remove(entities[1]));
+    assert_eq!(children.len(), entities.len() - 1);
+    assert_eq!(children.as_slice()[1], entities[2]);
+}
+
+#[test]
+fn child_list_inline_remove() {
+    let mut children = ChildList::new();
+    children.push(Entity::from_id(1));
+    children.push(Entity::from_id(2));
+    children.push(Entity::from_id(3));
+
+    assert!(children.remove(Entity::from_id(1)));
+    assert_eq!(children.as_slice(), &[Entity::from_id(2), Entity::from_id(3)]);
+    assert!(children.remove(Entity::from_id(3)));
+    assert!(children.remove(Entity::from_id(2)));
+    assert!(children.is_empty());
+    assert!(!children.is_spilled());
+}
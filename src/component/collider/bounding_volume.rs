@@ -339,6 +339,101 @@ impl AABB {
      && test_ranges((self.min.y, self.max.y), (other.min.y, other.max.y))
      && test_ranges((self.min.z, self.max.z), (other.min.z, other.max.z))
     }
+
+    /// Creates an empty AABB that doesn't contain any points.
+    ///
+    /// The empty AABB has its minimum above its maximum, so taking its union with any other AABB
+    /// gives the other AABB.
+    pub fn empty() -> AABB {
+        AABB {
+            min: Point::max(),
+            max: Point::min(),
+        }
+    }
+
+    /// Checks if the AABB doesn't contain any points, i.e. its minimum is above its maximum on
+    /// any axis.
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Calculates the smallest AABB containing both AABBs.
+    pub fn union(&self, other: &AABB) -> AABB {
+        AABB {
+            min: Point::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z)),
+            max: Point::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z)),
+        }
+    }
+
+    /// Finds where `ray` first enters the AABB, returning the ray parameter of the hit.
+    ///
+    /// # Details
+    ///
+    /// The result is measured in multiples of the ray's direction, so it's the distance along the
+    /// ray when the direction is normalized. A ray that starts inside the AABB hits it at 0. An
+    /// empty AABB is never hit.
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let origin = ray.origin.as_vector3();
+        let (min, max) = (self.min.as_vector3(), self.max.as_vector3());
+        let (mut t_min, mut t_max) = (0.0, ::std::f32::INFINITY);
+        for axis in 0..3 {
+            if ray.direction[axis].is_zero() {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+            } else {
+                let inverse = 1.0 / ray.direction[axis];
+                let mut near = (min[axis] - origin[axis]) * inverse;
+                let mut far = (max[axis] - origin[axis]) * inverse;
+                if near > far {
+                    ::std::mem::swap(&mut near, &mut far);
+                }
+
+                t_min = f32::max(t_min, near);
+                t_max = f32::min(t_max, far);
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+
+        Some(t_min)
+    }
+
+    /// Calculates the smallest AABB containing this AABB after it has been transformed by
+    /// `matrix`.
+    ///
+    /// Transforming an empty AABB gives an empty AABB.
+    pub fn transformed(&self, matrix: Matrix4) -> AABB {
+        if self.is_empty() {
+            return AABB::empty();
+        }
+
+        let mut result = AABB::empty();
+        for &x in &[self.min.x, self.max.x] {
+            for &y in &[self.min.y, self.max.y] {
+                for &z in &[self.min.z, self.max.z] {
+                    let corner = Point::new(x, y, z) * matrix;
+                    result = result.union(&AABB {
+                        min: corner,
+                        max: corner,
+                    });
+                }
+            }
+        }
+
+        result
+    }
 }
 
 pub fn bvh_update(scene: &Scene, _delta: f32) {
@@ -416,3 +511,52 @@ fn test_ranges(first: (f32, f32), second: (f32, f32)) -> bool {
     || max_a < min_b
     || max_b < min_a)
 }
+
+#[test]
+fn aabb_union_and_transform() {
+    let unit = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+
+    assert!(AABB::empty().is_empty());
+    assert!(!unit.is_empty());
+    assert!(AABB::empty().transformed(Matrix4::identity()).is_empty());
+
+    let union = AABB::empty().union(&unit);
+    assert_eq!((union.min, union.max), (unit.min, unit.max));
+
+    let moved = unit.transformed(Matrix4::translation(2.0, 0.0, 0.0) * Matrix4::scale(1.0, 2.0, 1.0));
+    assert_eq!((moved.min, moved.max), (Point::new(1.0, -2.0, -1.0), Point::new(3.0, 2.0, 1.0)));
+
+    let union = unit.union(&moved);
+    assert_eq!((union.min, union.max), (Point::new(-1.0, -2.0, -1.0), Point::new(3.0, 2.0, 1.0)));
+}
+
+#[test]
+fn aabb_intersect_ray() {
+    let unit = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+
+    assert_eq!(unit.intersect_ray(&Ray::new(Point::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0))), Some(4.0));
+    assert_eq!(unit.intersect_ray(&Ray::new(Point::new(-3.0, 0.5, 0.0), Vector3::new(1.0, 0.0, 0.0))), Some(2.0));
+
+    // Pointing away, passing to the side, and parallel to a face outside the box.
+    assert_eq!(unit.intersect_ray(&Ray::new(Point::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, 1.0))), None);
+    assert_eq!(unit.intersect_ray(&Ray::new(Point::new(0.0, 0.0, 5.0), Vector3::new(1.0, 0.0, -1.0))), None);
+    assert_eq!(unit.intersect_ray(&Ray::new(Point::new(0.0, 2.0, 5.0), Vector3::new(0.0, 0.0, -1.0))), None);
+
+    // Starting inside counts as an immediate hit.
+    assert_eq!(unit.intersect_ray(&Ray::new(Point::new(0.5, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0))), Some(0.0));
+
+    // The parameter is in multiples of the direction.
+    let scaled = Ray {
+        origin: Point::new(0.0, 0.0, 5.0),
+        direction: Vector3::new(0.0, 0.0, -2.0),
+    };
+    assert_eq!(unit.intersect_ray(&scaled), Some(2.0));
+
+    assert_eq!(AABB::empty().intersect_ray(&Ray::new(Point::origin(), Vector3::new(1.0, 0.0, 0.0))), None);
+}
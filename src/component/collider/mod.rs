@@ -131,7 +131,7 @@ use debug_draw;
 use super::{EntityMap, EntitySet};
 use super::struct_component_manager::{StructComponentManager, ComponentIter};
 use self::grid_collision::GridCollisionSystem;
-use self::bounding_volume::{BoundingVolumeManager, bvh_update};
+use self::bounding_volume::{AABB, BoundingVolumeManager, bvh_update};
 use component::transform::Transform;
 
 pub mod grid_collision;
@@ -277,6 +277,10 @@ impl ComponentManager for ColliderManager {
             self.bvh_manager.borrow_mut().destroy_immediate(entity);
         }
     }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.inner.contains(entity)
+    }
 }
 
 /// Combines collider data with calculated world position.
@@ -386,6 +390,256 @@ impl Sphere {
         let dist_sqr = obb.closest_distance_squared(self.center);
         dist_sqr < self.radius * self.radius
     }
+
+    /// Tests if the sphere intersects the axis-aligned box.
+    ///
+    /// A sphere that exactly touches a face of the box is considered to be intersecting.
+    pub fn test_aabb(&self, aabb: &AABB) -> bool {
+        let closest = Point::new(
+            self.center.x.clamp(aabb.min.x, aabb.max.x),
+            self.center.y.clamp(aabb.min.y, aabb.max.y),
+            self.center.z.clamp(aabb.min.z, aabb.max.z));
+        let diff = (self.center - closest).magnitude_squared() - self.radius * self.radius;
+
+        diff < 0.0 || diff.is_zero()
+    }
+
+    /// Calculates the minimal translation needed to push the sphere out of `other`.
+    ///
+    /// Details
+    /// =======
+    ///
+    /// Returns the direction to move `self` (pointing from `other` towards `self`) and the
+    /// distance to move it, or `None` if the spheres aren't overlapping. Spheres that are only
+    /// touching aren't considered to be overlapping. If the spheres are concentric there is no
+    /// preferred direction, so the Y axis is used.
+    pub fn penetration(&self, other: &Sphere) -> Option<(Vector3, f32)> {
+        let offset = self.center - other.center;
+        let distance = offset.magnitude();
+        let depth = self.radius + other.radius - distance;
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let normal = if distance.is_zero() {
+            Vector3::up()
+        } else {
+            offset / distance
+        };
+
+        Some((normal, depth))
+    }
+
+    /// Calculates the minimal translation needed to push the sphere out of the axis-aligned box.
+    ///
+    /// Details
+    /// =======
+    ///
+    /// Returns the direction to move the sphere (pointing away from the box) and the distance to
+    /// move it, or `None` if the sphere and box aren't overlapping. If the sphere's center is
+    /// outside the box the direction is from the closest point on the box to the center. If the
+    /// center is inside the box the sphere is pushed out through the nearest face.
+    pub fn penetration_aabb(&self, aabb: &AABB) -> Option<(Vector3, f32)> {
+        let closest = Point::new(
+            self.center.x.clamp(aabb.min.x, aabb.max.x),
+            self.center.y.clamp(aabb.min.y, aabb.max.y),
+            self.center.z.clamp(aabb.min.z, aabb.max.z));
+        let offset = self.center - closest;
+        let distance = offset.magnitude();
+
+        if !distance.is_zero() {
+            let depth = self.radius - distance;
+            return if depth > 0.0 {
+                Some((offset / distance, depth))
+            } else {
+                None
+            };
+        }
+
+        // The center is inside the box, find the face it is closest to.
+        let (center, min, max) = (self.center.as_vector3(), aabb.min.as_vector3(), aabb.max.as_vector3());
+        let mut normal = Vector3::zero();
+        let mut face_distance = ::std::f32::MAX;
+        for axis in 0..3 {
+            let to_min = center[axis] - min[axis];
+            let to_max = max[axis] - center[axis];
+
+            if to_min < face_distance {
+                face_distance = to_min;
+                normal = Vector3::zero();
+                normal[axis] = -1.0;
+            }
+
+            if to_max < face_distance {
+                face_distance = to_max;
+                normal = Vector3::zero();
+                normal[axis] = 1.0;
+            }
+        }
+
+        Some((normal, face_distance + self.radius))
+    }
+
+    /// Finds when the sphere first touches the box as it moves by `velocity`.
+    ///
+    /// Details
+    /// =======
+    ///
+    /// Returns the time of impact as a fraction of `velocity` in the range [0, 1], or `None` if
+    /// the sphere doesn't reach the box. A sphere that already intersects the box returns
+    /// `Some(0.0)`. This catches collisions that a discrete test would miss because the sphere
+    /// moved all the way through the box in a single step.
+    ///
+    /// Sweeping a sphere against a box is the same as sweeping its center against the box
+    /// expanded by the sphere's radius (the Minkowski sum). The expanded box has rounded edges and
+    /// corners, so the center's path is tested against the box expanded along each axis, a
+    /// cylinder around each edge, and a sphere at each corner, taking the earliest hit.
+    pub fn sweep(&self, velocity: Vector3, aabb: &AABB) -> Option<f32> {
+        if self.test_aabb(aabb) {
+            return Some(0.0);
+        }
+
+        let origin = self.center.as_vector3();
+        let (min, max) = (aabb.min.as_vector3(), aabb.max.as_vector3());
+        let mut earliest = None;
+
+        // The faces, i.e. the box expanded along a single axis.
+        for axis in 0..3 {
+            let (mut face_min, mut face_max) = (min, max);
+            face_min[axis] -= self.radius;
+            face_max[axis] += self.radius;
+            earliest = earliest_hit(earliest, segment_aabb(origin, velocity, face_min, face_max));
+        }
+
+        // The corners.
+        for corner in 0..8 {
+            let corner = Vector3::new(
+                if corner & 1 == 0 { min.x } else { max.x },
+                if corner & 2 == 0 { min.y } else { max.y },
+                if corner & 4 == 0 { min.z } else { max.z });
+            earliest = earliest_hit(earliest, first_root(origin - corner, velocity, self.radius));
+        }
+
+        // The edges. Each edge runs along `axis` and the cylinder around it is tested by
+        // projecting onto the plane of the other two axes.
+        for axis in 0..3 {
+            let (first, second) = ((axis + 1) % 3, (axis + 2) % 3);
+            for edge in 0..4 {
+                let mut offset = origin;
+                offset[first] -= if edge & 1 == 0 { min[first] } else { max[first] };
+                offset[second] -= if edge & 2 == 0 { min[second] } else { max[second] };
+                offset[axis] = 0.0;
+
+                let mut direction = velocity;
+                direction[axis] = 0.0;
+
+                let t = first_root(offset, direction, self.radius).and_then(|t| {
+                    let along_edge = origin[axis] + velocity[axis] * t;
+                    if along_edge >= min[axis] && along_edge <= max[axis] { Some(t) } else { None }
+                });
+                earliest = earliest_hit(earliest, t);
+            }
+        }
+
+        earliest
+    }
+}
+
+/// Picks the earlier of two hit times, ignoring hits outside of [0, 1].
+fn earliest_hit(earliest: Option<f32>, hit: Option<f32>) -> Option<f32> {
+    match hit {
+        Some(t) if t >= 0.0 && t <= 1.0 && earliest.map_or(true, |earliest| t < earliest) => Some(t),
+        _ => earliest,
+    }
+}
+
+/// Finds the first time in [0, 1] at which the segment `origin + direction * t` enters the box.
+fn segment_aabb(origin: Vector3, direction: Vector3, min: Vector3, max: Vector3) -> Option<f32> {
+    let (mut t_min, mut t_max) = (0.0, 1.0);
+    for axis in 0..3 {
+        if direction[axis].is_zero() {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+        } else {
+            let inverse = 1.0 / direction[axis];
+            let mut near = (min[axis] - origin[axis]) * inverse;
+            let mut far = (max[axis] - origin[axis]) * inverse;
+            if near > far {
+                ::std::mem::swap(&mut near, &mut far);
+            }
+
+            t_min = f32::max(t_min, near);
+            t_max = f32::min(t_max, far);
+            if t_min > t_max {
+                return None;
+            }
+        }
+    }
+
+    Some(t_min)
+}
+
+/// Finds the first time at which `offset + direction * t` is `radius` away from the origin.
+///
+/// Returns `None` if that never happens or only happens for negative `t`.
+fn first_root(offset: Vector3, direction: Vector3, radius: f32) -> Option<f32> {
+    let a = direction.dot(direction);
+    if a.is_zero() {
+        return None;
+    }
+
+    let b = 2.0 * offset.dot(direction);
+    let c = offset.dot(offset) - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if t >= 0.0 { Some(t) } else { None }
+}
+
+/// A capsule, represented as a line segment swept by a sphere.
+///
+/// Details
+/// =======
+///
+/// A capsule whose `start` and `end` points are the same is equivalent to a sphere centered at
+/// `start` and all tests on it will behave as such.
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+    pub start: Point,
+    pub end: Point,
+    pub radius: f32,
+}
+
+impl Capsule {
+    /// Calculates the point on the capsule's central segment that is closest to `point`.
+    pub fn closest_point(&self, point: Point) -> Point {
+        let segment = self.end - self.start;
+        let length_sqr = segment.magnitude_squared();
+
+        // Degenerate capsule, the whole segment is just the start point.
+        if length_sqr.is_zero() {
+            return self.start;
+        }
+
+        // Project the point onto the segment and clamp it to the segment's end points.
+        let t = (point - self.start).dot(segment) / length_sqr;
+        self.start + segment * t.clamp(0.0, 1.0)
+    }
+
+    /// Tests if the capsule intersects the sphere.
+    ///
+    /// A sphere that exactly touches the surface of the capsule is considered to be intersecting.
+    pub fn test_sphere(&self, sphere: &Sphere) -> bool {
+        let closest = self.closest_point(sphere.center);
+        Sphere {
+            center: closest,
+            radius: self.radius,
+        }.test_sphere(sphere)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -795,3 +1049,181 @@ fn obb_obb_tests() {
     obb_test!(unit, unit, true);
     obb_test!(unit, rot_z, true);
 }
+
+#[test]
+fn sphere_aabb_tests() {
+    let aabb = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+
+    let inside = Sphere {
+        center: Point::origin(),
+        radius: 0.5,
+    };
+
+    let touching = Sphere {
+        center: Point::new(2.0, 0.0, 0.0),
+        radius: 1.0,
+    };
+
+    let separate = Sphere {
+        center: Point::new(2.0, 0.0, 0.0),
+        radius: 0.9,
+    };
+
+    // Near the corner but outside of the box even though it overlaps the box's bounding planes.
+    let corner = Sphere {
+        center: Point::new(1.5, 1.5, 1.5),
+        radius: 0.8,
+    };
+
+    assert!(inside.test_aabb(&aabb));
+    assert!(touching.test_aabb(&aabb));
+    assert!(!separate.test_aabb(&aabb));
+    assert!(!corner.test_aabb(&aabb));
+}
+
+#[test]
+fn sphere_penetration_tests() {
+    let unit = Sphere {
+        center: Point::origin(),
+        radius: 1.0,
+    };
+
+    let overlapping = Sphere {
+        center: Point::new(1.5, 0.0, 0.0),
+        radius: 1.0,
+    };
+
+    let (normal, depth) = overlapping.penetration(&unit).unwrap();
+    assert_eq!(normal, Vector3::new(1.0, 0.0, 0.0));
+    assert!((depth - 0.5).is_zero());
+
+    let (normal, depth) = unit.penetration(&overlapping).unwrap();
+    assert_eq!(normal, Vector3::new(-1.0, 0.0, 0.0));
+    assert!((depth - 0.5).is_zero());
+
+    let separate = Sphere {
+        center: Point::new(3.0, 0.0, 0.0),
+        radius: 1.0,
+    };
+    assert!(unit.penetration(&separate).is_none());
+
+    let touching = Sphere {
+        center: Point::new(2.0, 0.0, 0.0),
+        radius: 1.0,
+    };
+    assert!(unit.penetration(&touching).is_none());
+
+    // Concentric spheres still produce a valid direction.
+    let (normal, depth) = unit.penetration(&unit).unwrap();
+    assert!(normal.is_normalized());
+    assert_eq!(depth, 2.0);
+}
+
+#[test]
+fn sphere_aabb_penetration_tests() {
+    let aabb = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+
+    let outside = Sphere {
+        center: Point::new(0.0, 1.5, 0.0),
+        radius: 1.0,
+    };
+    let (normal, depth) = outside.penetration_aabb(&aabb).unwrap();
+    assert_eq!(normal, Vector3::new(0.0, 1.0, 0.0));
+    assert!((depth - 0.5).is_zero());
+
+    // Center inside the box is pushed out through the nearest face.
+    let inside = Sphere {
+        center: Point::new(0.0, 0.0, -0.75),
+        radius: 0.5,
+    };
+    let (normal, depth) = inside.penetration_aabb(&aabb).unwrap();
+    assert_eq!(normal, Vector3::new(0.0, 0.0, -1.0));
+    assert!((depth - 0.75).is_zero());
+
+    let separate = Sphere {
+        center: Point::new(1.5, 1.5, 1.5),
+        radius: 0.8,
+    };
+    assert!(separate.penetration_aabb(&aabb).is_none());
+}
+
+#[test]
+fn capsule_sphere_tests() {
+    let capsule = Capsule {
+        start: Point::new(0.0, 0.0, 0.0),
+        end: Point::new(0.0, 4.0, 0.0),
+        radius: 1.0,
+    };
+
+    // Closest point is clamped to the ends of the segment.
+    assert_eq!(capsule.closest_point(Point::new(3.0, 2.0, 0.0)), Point::new(0.0, 2.0, 0.0));
+    assert_eq!(capsule.closest_point(Point::new(0.0, -3.0, 0.0)), Point::new(0.0, 0.0, 0.0));
+    assert_eq!(capsule.closest_point(Point::new(1.0, 7.0, 0.0)), Point::new(0.0, 4.0, 0.0));
+
+    // Contact exactly at the sum of the radii.
+    let touching_side = Sphere {
+        center: Point::new(3.0, 2.0, 0.0),
+        radius: 2.0,
+    };
+    let touching_cap = Sphere {
+        center: Point::new(0.0, 7.0, 0.0),
+        radius: 2.0,
+    };
+    let separate = Sphere {
+        center: Point::new(3.0, 2.0, 0.0),
+        radius: 1.9,
+    };
+
+    assert!(capsule.test_sphere(&touching_side));
+    assert!(capsule.test_sphere(&touching_cap));
+    assert!(!capsule.test_sphere(&separate));
+
+    // A degenerate capsule behaves like a sphere.
+    let degenerate = Capsule {
+        start: Point::new(1.0, 1.0, 1.0),
+        end: Point::new(1.0, 1.0, 1.0),
+        radius: 1.0,
+    };
+    assert_eq!(degenerate.closest_point(Point::new(5.0, 1.0, 1.0)), Point::new(1.0, 1.0, 1.0));
+    assert!(degenerate.test_sphere(&Sphere { center: Point::new(3.0, 1.0, 1.0), radius: 1.0 }));
+    assert!(!degenerate.test_sphere(&Sphere { center: Point::new(3.5, 1.0, 1.0), radius: 1.0 }));
+}
+
+#[test]
+fn sphere_sweep_tests() {
+    let aabb = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+    let sphere_at = |x: f32, y: f32, z: f32| Sphere { center: Point::new(x, y, z), radius: 0.5 };
+    let close = |actual: Option<f32>, expected: f32| actual.map_or(false, |t| (t - expected).abs() < 1e-4);
+
+    // Sweeping towards the box hits the face at x = -1.5, even if the sweep would pass all the
+    // way through the box.
+    let sphere = sphere_at(-5.0, 0.0, 0.0);
+    assert!(close(sphere.sweep(Vector3::new(10.0, 0.0, 0.0), &aabb), 0.35));
+    assert!(close(sphere.sweep(Vector3::new(7.0, 0.0, 0.0), &aabb), 0.5));
+
+    // Stopping short of the box, moving away from it, or passing beside it all miss.
+    assert_eq!(sphere.sweep(Vector3::new(2.0, 0.0, 0.0), &aabb), None);
+    assert_eq!(sphere.sweep(Vector3::new(-10.0, 0.0, 0.0), &aabb), None);
+    assert_eq!(sphere_at(-5.0, 1.6, 0.0).sweep(Vector3::new(10.0, 0.0, 0.0), &aabb), None);
+
+    // Already overlapping.
+    assert_eq!(sphere_at(1.2, 0.0, 0.0).sweep(Vector3::new(1.0, 0.0, 0.0), &aabb), Some(0.0));
+
+    // Grazing a corner hits the rounded corner of the expanded box, but passing close enough to
+    // be inside the square corner without touching the rounded one misses.
+    let offset = 0.25f32 - 0.18;
+    assert!(close(sphere_at(-5.0, 1.3, 1.3).sweep(Vector3::new(10.0, 0.0, 0.0), &aabb), (4.0 - offset.sqrt()) / 10.0));
+    assert_eq!(sphere_at(-5.0, 1.4, 1.4).sweep(Vector3::new(10.0, 0.0, 0.0), &aabb), None);
+
+    // Diagonally past an edge.
+    assert!(close(sphere_at(-3.0, 3.0, 0.0).sweep(Vector3::new(4.0, -4.0, 0.0), &aabb), (2.0 - 0.5 / 2.0f32.sqrt()) / 4.0));
+}
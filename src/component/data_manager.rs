@@ -0,0 +1,278 @@
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::any::Any;
+
+use super::{EntityMap, EntitySet};
+
+use ecs::{Entity, ComponentManager};
+use serialize::{Serialize, Deserialize, ByteReader, SerializeError};
+
+/// A ready-made manager for storing arbitrary per-entity data, e.g. health or ammo.
+///
+/// # Details
+///
+/// The data is stored densely in a `Vec<T>` so iterating over all of it is cache friendly.
+/// Unlike `StructComponentManager` the data isn't wrapped in a `RefCell`, so mutable access
+/// requires a mutable borrow of the manager (i.e. `Scene::get_manager_mut()`), and `T` doesn't
+/// need to implement `Clone`.
+#[derive(Debug, Clone)]
+pub struct DataManager<T: Any> {
+    data: Vec<T>,
+    entities: Vec<Entity>,
+    indices: EntityMap<usize>,
+
+    marked_for_destroy: RefCell<EntitySet>,
+}
+
+impl<T: Any> DataManager<T> {
+    pub fn new() -> DataManager<T> {
+        DataManager {
+            data: Vec::new(),
+            entities: Vec::new(),
+            indices: HashMap::default(),
+
+            marked_for_destroy: RefCell::new(HashSet::default()),
+        }
+    }
+
+    /// Associates `value` with the entity, returning the previous value if there was one.
+    pub fn insert(&mut self, entity: Entity, value: T) -> Option<T> {
+        if let Some(&index) = self.indices.get(&entity) {
+            return Some(::std::mem::replace(&mut self.data[index], value));
+        }
+
+        let index = self.data.len();
+        self.data.push(value);
+        self.entities.push(entity);
+        self.indices.insert(entity, index);
+
+        None
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.indices.get(&entity) {
+            Some(&index) => Some(&self.data[index]),
+            None => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.indices.get(&entity) {
+            Some(&index) => Some(&mut self.data[index]),
+            None => None,
+        }
+    }
+
+    /// Removes and returns the data associated with the entity.
+    ///
+    /// # Details
+    ///
+    /// The last element is swapped into the removed element's place, so the order of the data
+    /// is not preserved.
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let index = match self.indices.remove(&entity) {
+            Some(index) => index,
+            None => return None,
+        };
+
+        let removed_entity = self.entities.swap_remove(index);
+        debug_assert!(removed_entity == entity);
+
+        // Update the index mapping for the moved entity, but only if the one we removed
+        // wasn't the last one.
+        if index != self.entities.len() {
+            let moved_entity = self.entities[index];
+            self.indices.insert(moved_entity, index);
+        }
+
+        Some(self.data.swap_remove(index))
+    }
+
+    /// Rearranges the data so that it's stored in the same order as the entities in `order`.
+    ///
+    /// # Details
+    ///
+    /// This is useful when a system walks the entities of another manager (e.g.
+    /// `TransformManager::topological_order()`) and looks up each one's data, storing the data in
+    /// the same order makes those lookups sequential in memory. Entities in `order` that don't
+    /// have data are skipped, as are repeats of an entity already listed. Data for entities not in
+    /// `order` is moved after the reordered data, in no particular order. The data is permuted in
+    /// place, without cloning or reallocating.
+    pub fn reorder_to_match(&mut self, order: &[Entity]) {
+        let mut next = 0;
+        for &entity in order {
+            let index = match self.indices.get(&entity) {
+                Some(&index) => index,
+                None => continue,
+            };
+
+            // Entities before `next` have already been placed, so this entity is a repeat.
+            if index < next {
+                continue;
+            }
+
+            if index != next {
+                self.data.swap(index, next);
+                self.entities.swap(index, next);
+                self.indices.insert(self.entities[index], index);
+                self.indices.insert(entity, next);
+            }
+
+            next += 1;
+        }
+    }
+
+    pub fn data(&self) -> &[T] {
+        &*self.data
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &*self.entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl<T: Any> ComponentManager for DataManager<T> {
+    fn destroy_all(&self, entity: Entity) {
+        if self.indices.contains_key(&entity) {
+            self.marked_for_destroy.borrow_mut().insert(entity);
+        }
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.indices.contains_key(&entity)
+    }
+
+    fn destroy_marked(&mut self) {
+        let mut marked_for_destroy = RefCell::new(HashSet::default());
+        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
+        let mut marked_for_destroy = marked_for_destroy.into_inner();
+        for entity in marked_for_destroy.drain() {
+            self.remove(entity);
+        }
+    }
+}
+
+impl<T: Any + Serialize> Serialize for DataManager<T> {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        self.entities.serialize(bytes);
+        self.data.serialize(bytes);
+    }
+}
+
+impl<T: Any + Deserialize> Deserialize for DataManager<T> {
+    fn deserialize(reader: &mut ByteReader) -> Result<DataManager<T>, SerializeError> {
+        let offset = reader.offset();
+        let entities: Vec<Entity> = try!(Deserialize::deserialize(reader));
+        let data: Vec<T> = try!(Deserialize::deserialize(reader));
+        if entities.len() != data.len() {
+            return Err(SerializeError::InvalidData { offset: offset });
+        }
+
+        let mut data_manager = DataManager::new();
+        for (entity, value) in entities.into_iter().zip(data.into_iter()) {
+            if data_manager.insert(entity, value).is_some() {
+                return Err(SerializeError::InvalidData { offset: offset });
+            }
+        }
+
+        Ok(data_manager)
+    }
+}
+
+#[test]
+fn data_manager_round_trip() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut data_manager = DataManager::new();
+
+    let entity = entity_manager.create();
+    let missing = entity_manager.create();
+
+    assert_eq!(data_manager.insert(entity, 100), None);
+    assert_eq!(data_manager.get(entity), Some(&100));
+    assert_eq!(data_manager.get(missing), None);
+
+    *data_manager.get_mut(entity).unwrap() -= 25;
+    assert_eq!(data_manager.insert(entity, 50), Some(75));
+    assert_eq!(data_manager.len(), 1);
+
+    assert_eq!(data_manager.remove(entity), Some(50));
+    assert_eq!(data_manager.remove(entity), None);
+    assert_eq!(data_manager.get(entity), None);
+    assert!(data_manager.is_empty());
+}
+
+#[test]
+fn data_manager_swap_remove() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut data_manager = DataManager::new();
+
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    let third = entity_manager.create();
+
+    data_manager.insert(first, "first");
+    data_manager.insert(second, "second");
+    data_manager.insert(third, "third");
+
+    // Destroying the first entity moves the last one into its slot.
+    data_manager.destroy_all(first);
+    data_manager.destroy_marked();
+
+    assert_eq!(data_manager.get(first), None);
+    assert_eq!(data_manager.get(second), Some(&"second"));
+    assert_eq!(data_manager.get(third), Some(&"third"));
+    assert_eq!(data_manager.entities(), &[third, second]);
+
+    assert_eq!(data_manager.remove(third), Some("third"));
+    assert_eq!(data_manager.get(second), Some(&"second"));
+}
+
+#[test]
+fn data_manager_reorder_to_match() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut data_manager = DataManager::new();
+
+    let entities: Vec<Entity> = (0..6).map(|_| entity_manager.create()).collect();
+    for (value, &entity) in entities.iter().enumerate() {
+        data_manager.insert(entity, value);
+    }
+
+    // `missing` has no data, `entities[1]` and `entities[4]` aren't listed, and `entities[0]`
+    // is listed twice.
+    let missing = entity_manager.create();
+    let order = [entities[5], missing, entities[2], entities[0], entities[3], entities[0]];
+    data_manager.reorder_to_match(&order);
+
+    assert_eq!(&data_manager.entities()[..4], &[entities[5], entities[2], entities[0], entities[3]]);
+    assert_eq!(&data_manager.data()[..4], &[5, 2, 0, 3]);
+
+    let mut rest = data_manager.entities()[4..].to_vec();
+    rest.sort_by(|first, second| first.id().cmp(&second.id()));
+    assert_eq!(rest, vec![entities[1], entities[4]]);
+
+    // Every entity still maps to its own data.
+    assert_eq!(data_manager.len(), entities.len());
+    for (value, &entity) in entities.iter().enumerate() {
+        assert_eq!(data_manager.get(entity), Some(&value));
+    }
+    assert_eq!(data_manager.get(missing), None);
+
+    // Reordering to the current order changes nothing.
+    let current = data_manager.entities().to_vec();
+    data_manager.reorder_to_match(&current);
+    assert_eq!(data_manager.entities(), &*current);
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+
+use math::EPSILON;
+use ecs::{Entity, System, ComponentManager};
+use scene::Scene;
+use super::{EntityMap, EntitySet};
+
+/// Destroys entities once they have been alive for a set amount of time, e.g. for particles and
+/// other temporary effects.
+///
+/// # Details
+///
+/// Each entity's elapsed time accumulates in scaled game time, and once it reaches the entity's
+/// lifetime `LifetimeSystem` destroys the entity with `Scene::destroy_entity()`, removing all of
+/// its components.
+#[derive(Debug, Clone)]
+pub struct LifetimeManager {
+    lifetimes: EntityMap<Lifetime>,
+    marked_for_destroy: RefCell<EntitySet>,
+}
+
+impl LifetimeManager {
+    pub fn new() -> LifetimeManager {
+        LifetimeManager {
+            lifetimes: HashMap::default(),
+            marked_for_destroy: RefCell::new(HashSet::default()),
+        }
+    }
+
+    /// Sets the number of seconds until the entity is destroyed, replacing any existing lifetime.
+    ///
+    /// A lifetime of zero or less destroys the entity on the next update.
+    pub fn set_lifetime(&mut self, entity: Entity, seconds: f32) {
+        self.lifetimes.insert(entity, Lifetime {
+            lifetime: seconds,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Retrieves the number of seconds until the entity is destroyed, or `None` if the entity
+    /// doesn't have a lifetime.
+    pub fn remaining(&self, entity: Entity) -> Option<f32> {
+        self.lifetimes.get(&entity).map(|lifetime| lifetime.lifetime - lifetime.elapsed)
+    }
+
+    /// Removes the entity's lifetime so that it is no longer destroyed automatically.
+    pub fn clear_lifetime(&mut self, entity: Entity) {
+        self.lifetimes.remove(&entity);
+    }
+
+    /// Counts down every lifetime by `delta` seconds, returning the entities whose lifetimes
+    /// have expired.
+    ///
+    /// Expired entities are removed from the manager. This doesn't destroy them, that's done by
+    /// `LifetimeSystem`.
+    ///
+    /// A lifetime counts as expired once the elapsed time is within `EPSILON` of it, so stepping
+    /// in increments that aren't exactly representable (e.g. 0.025 four times for a lifetime of
+    /// 0.1) still expires the entity on the expected step.
+    pub fn advance(&mut self, delta: f32) -> Vec<Entity> {
+        let mut expired = Vec::new();
+        for (&entity, lifetime) in self.lifetimes.iter_mut() {
+            lifetime.elapsed += delta;
+            if lifetime.elapsed >= lifetime.lifetime - EPSILON {
+                expired.push(entity);
+            }
+        }
+
+        for entity in &expired {
+            self.lifetimes.remove(entity);
+        }
+
+        expired
+    }
+}
+
+impl ComponentManager for LifetimeManager {
+    fn destroy_all(&self, entity: Entity) {
+        if self.lifetimes.contains_key(&entity) {
+            self.marked_for_destroy.borrow_mut().insert(entity);
+        }
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.lifetimes.contains_key(&entity)
+    }
+
+    fn destroy_marked(&mut self) {
+        let mut marked_for_destroy = RefCell::new(HashSet::default());
+        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
+        let mut marked_for_destroy = marked_for_destroy.into_inner();
+        for entity in marked_for_destroy.drain() {
+            self.lifetimes.remove(&entity);
+        }
+    }
+}
+
+/// An entity's total lifetime and how much of it has passed, both in seconds.
+#[derive(Debug, Clone, Copy)]
+struct Lifetime {
+    lifetime: f32,
+    elapsed: f32,
+}
+
+/// Destroys the entities in the `LifetimeManager` whose lifetimes have expired.
+pub struct LifetimeSystem;
+
+impl System for LifetimeSystem {
+    fn update(&mut self, scene: &Scene, delta: f32) {
+        // Collect the expired entities first since destroying an entity borrows every manager,
+        // including the lifetime manager.
+        let expired = scene.get_manager_mut::<LifetimeManager>().advance(delta);
+        for entity in expired {
+            scene.destroy_entity(entity);
+        }
+    }
+}
+
+#[test]
+fn lifetime_expires() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut lifetime_manager = LifetimeManager::new();
+
+    let entity = entity_manager.create();
+    let long_lived = entity_manager.create();
+    lifetime_manager.set_lifetime(entity, 0.1);
+    lifetime_manager.set_lifetime(long_lived, 10.0);
+
+    // Not destroyed until the full 0.1 seconds have passed.
+    for _ in 0..3 {
+        assert!(lifetime_manager.advance(0.025).is_empty());
+        assert!(lifetime_manager.contains(entity));
+    }
+
+    assert_eq!(lifetime_manager.advance(0.025), vec![entity]);
+    assert!(!lifetime_manager.contains(entity));
+    assert!(lifetime_manager.contains(long_lived));
+    assert!(lifetime_manager.advance(0.025).is_empty());
+}
+
+#[test]
+fn lifetime_expires_with_inexact_steps() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut lifetime_manager = LifetimeManager::new();
+
+    // Neither 0.1 nor 1.0 / 60.0 is exactly representable, so the accumulated time lands slightly
+    // off the lifetime.
+    let entity = entity_manager.create();
+    lifetime_manager.set_lifetime(entity, 1.0);
+    for _ in 0..59 {
+        assert!(lifetime_manager.advance(1.0 / 60.0).is_empty());
+    }
+    assert_eq!(lifetime_manager.advance(1.0 / 60.0), vec![entity]);
+
+    let entity = entity_manager.create();
+    lifetime_manager.set_lifetime(entity, 0.3);
+    for _ in 0..2 {
+        assert!(lifetime_manager.advance(0.1).is_empty());
+    }
+    assert_eq!(lifetime_manager.advance(0.1), vec![entity]);
+}
+
+#[test]
+fn lifetime_zero_or_negative() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut lifetime_manager = LifetimeManager::new();
+
+    let zero = entity_manager.create();
+    let negative = entity_manager.create();
+    lifetime_manager.set_lifetime(zero, 0.0);
+    lifetime_manager.set_lifetime(negative, -1.0);
+
+    let mut expired = lifetime_manager.advance(0.0);
+    expired.sort_by(|first, second| first.id().cmp(&second.id()));
+    assert_eq!(expired, vec![zero, negative]);
+    assert_eq!(lifetime_manager.remaining(zero), None);
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+
+use math::*;
+
+use ecs::{Entity, ComponentManager};
+use super::{EntityMap, EntitySet, TransformManager};
+
+/// The default width of the band around each threshold in which the level of detail doesn't
+/// change, see `LodManager::set_hysteresis()`.
+pub const DEFAULT_HYSTERESIS: f32 = 0.5;
+
+#[derive(Debug, Clone)]
+struct Lod {
+    thresholds: Vec<f32>,
+
+    /// `None` until the first update after the thresholds are set, so that the first selection
+    /// isn't affected by hysteresis.
+    current: Option<usize>,
+}
+
+/// Selects a level of detail for each entity based on its distance from the camera.
+///
+/// # Details
+///
+/// Each entity has a list of increasing distance thresholds, where `n` thresholds give `n + 1`
+/// levels of detail. Level 0 is the most detailed and is used while the entity is closer than
+/// the first threshold, level 1 is used between the first and second thresholds, and so on, with
+/// the last level used beyond every threshold. The manager only picks the level, it's up to the
+/// caller to choose which mesh to draw for it.
+///
+/// To keep an entity sitting right on a threshold from flickering between two levels, the level
+/// only changes once the entity has moved past the threshold by half of the hysteresis band.
+#[derive(Debug, Clone)]
+pub struct LodManager {
+    lods: EntityMap<Lod>,
+    hysteresis: f32,
+    marked_for_destroy: RefCell<EntitySet>,
+}
+
+impl LodManager {
+    pub fn new() -> LodManager {
+        LodManager {
+            lods: HashMap::default(),
+            hysteresis: DEFAULT_HYSTERESIS,
+            marked_for_destroy: RefCell::new(HashSet::default()),
+        }
+    }
+
+    /// Sets the distances at which the entity switches to the next level of detail, replacing
+    /// any existing thresholds.
+    ///
+    /// # Details
+    ///
+    /// The entity starts at level 0 and its actual level is picked on the next `update()`. An
+    /// empty list of thresholds is allowed and always uses level 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thresholds are negative or aren't strictly increasing.
+    pub fn set_thresholds(&mut self, entity: Entity, thresholds: Vec<f32>) {
+        assert!(
+            thresholds.iter().all(|&threshold| threshold >= 0.0)
+                && thresholds.windows(2).all(|pair| pair[0] < pair[1]),
+            "LOD thresholds must be non-negative and strictly increasing, but were {:?}", thresholds);
+
+        self.lods.insert(entity, Lod {
+            thresholds: thresholds,
+            current: None,
+        });
+    }
+
+    /// Retrieves the entity's thresholds, or `None` if the entity doesn't have any.
+    pub fn thresholds(&self, entity: Entity) -> Option<&[f32]> {
+        self.lods.get(&entity).map(|lod| &*lod.thresholds)
+    }
+
+    /// Removes the entity's thresholds.
+    pub fn clear_thresholds(&mut self, entity: Entity) {
+        self.lods.remove(&entity);
+    }
+
+    /// Retrieves the entity's current level of detail, where 0 is the most detailed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity doesn't have LOD thresholds.
+    pub fn lod_index(&self, entity: Entity) -> usize {
+        self.lods.get(&entity)
+            .expect("LOD manager does not contain thresholds for the given entity")
+            .current
+            .unwrap_or(0)
+    }
+
+    pub fn hysteresis(&self) -> f32 {
+        self.hysteresis
+    }
+
+    /// Sets the width of the band around each threshold in which the level of detail doesn't
+    /// change.
+    ///
+    /// # Details
+    ///
+    /// With a band of `h` an entity switches to a less detailed level once it's `h / 2` beyond
+    /// the threshold and back once it's `h / 2` in front of it. The band should be narrower than
+    /// the gap between consecutive thresholds. A band of 0 disables hysteresis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hysteresis` is negative.
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        assert!(hysteresis >= 0.0, "LOD hysteresis cannot be negative, but was {}", hysteresis);
+        self.hysteresis = hysteresis;
+    }
+
+    /// Selects the level of detail for every entity based on its distance from `camera_position`.
+    ///
+    /// # Details
+    ///
+    /// Distances are measured to each entity's derived position, so the transforms must be up to
+    /// date. Entities without a transform keep their current level.
+    pub fn update(&mut self, camera_position: Point, transforms: &TransformManager) {
+        let half_band = self.hysteresis * 0.5;
+        for (&entity, lod) in self.lods.iter_mut() {
+            let position = match transforms.try_get(entity) {
+                Some(transform) => transform.position_derived(),
+                None => continue,
+            };
+            let distance = Vector3::distance(camera_position.as_vector3(), position.as_vector3());
+
+            let level = match lod.current {
+                None => lod.thresholds.iter().take_while(|&&threshold| distance >= threshold).count(),
+                Some(mut level) => {
+                    while level < lod.thresholds.len() && distance >= lod.thresholds[level] + half_band {
+                        level += 1;
+                    }
+
+                    while level > 0 && distance < lod.thresholds[level - 1] - half_band {
+                        level -= 1;
+                    }
+
+                    level
+                },
+            };
+
+            lod.current = Some(level);
+        }
+    }
+}
+
+impl ComponentManager for LodManager {
+    fn destroy_all(&self, entity: Entity) {
+        if self.lods.contains_key(&entity) {
+            self.marked_for_destroy.borrow_mut().insert(entity);
+        }
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.lods.contains_key(&entity)
+    }
+
+    fn destroy_marked(&mut self) {
+        let mut marked_for_destroy = RefCell::new(HashSet::default());
+        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
+        let mut marked_for_destroy = marked_for_destroy.into_inner();
+        for entity in marked_for_destroy.drain() {
+            self.lods.remove(&entity);
+        }
+    }
+}
+
+#[test]
+fn lod_threshold_selection() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut lod_manager = LodManager::new();
+
+    let near = entity_manager.create();
+    let middle = entity_manager.create();
+    let far = entity_manager.create();
+    let single = entity_manager.create();
+    transform_manager.assign(near).set_position(Point::new(0.0, 0.0, -5.0));
+    transform_manager.assign(middle).set_position(Point::new(0.0, 15.0, 0.0));
+    transform_manager.assign(far).set_position(Point::new(100.0, 0.0, 0.0));
+    transform_manager.assign(single).set_position(Point::new(100.0, 0.0, 0.0));
+    transform_manager.update_parallel(1);
+
+    for &entity in &[near, middle, far] {
+        lod_manager.set_thresholds(entity, vec![10.0, 20.0, 40.0]);
+    }
+    lod_manager.set_thresholds(single, Vec::new());
+
+    // Nothing is selected until the first update.
+    assert_eq!(lod_manager.lod_index(far), 0);
+
+    lod_manager.update(Point::origin(), &transform_manager);
+    assert_eq!(lod_manager.lod_index(near), 0);
+    assert_eq!(lod_manager.lod_index(middle), 1);
+    assert_eq!(lod_manager.lod_index(far), 3);
+    assert_eq!(lod_manager.lod_index(single), 0);
+
+    // Moving the camera changes the levels, even across several thresholds at once.
+    lod_manager.update(Point::new(100.0, 0.0, 0.0), &transform_manager);
+    assert_eq!(lod_manager.lod_index(near), 3);
+    assert_eq!(lod_manager.lod_index(far), 0);
+
+    // An entity exactly on the first threshold starts at the less detailed level.
+    lod_manager.set_thresholds(middle, vec![15.0]);
+    lod_manager.update(Point::origin(), &transform_manager);
+    assert_eq!(lod_manager.lod_index(middle), 1);
+
+    lod_manager.clear_thresholds(single);
+    assert!(!lod_manager.contains(single));
+}
+
+#[test]
+fn lod_hysteresis() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut lod_manager = LodManager::new();
+    lod_manager.set_hysteresis(2.0);
+
+    let entity = entity_manager.create();
+    transform_manager.assign(entity);
+    transform_manager.update_parallel(1);
+    lod_manager.set_thresholds(entity, vec![10.0, 20.0]);
+
+    let mut at_distance = |distance: f32| {
+        lod_manager.update(Point::new(distance, 0.0, 0.0), &transform_manager);
+        lod_manager.lod_index(entity)
+    };
+
+    assert_eq!(at_distance(9.5), 0);
+
+    // Crossing the threshold by less than half the band doesn't switch.
+    assert_eq!(at_distance(10.5), 0);
+    assert_eq!(at_distance(9.9), 0);
+    assert_eq!(at_distance(10.9), 0);
+    assert_eq!(at_distance(11.0), 1);
+
+    // Coming back, the entity stays at level 1 until it's half the band in front of the threshold.
+    assert_eq!(at_distance(9.5), 1);
+    assert_eq!(at_distance(10.1), 1);
+    assert_eq!(at_distance(9.0), 1);
+    assert_eq!(at_distance(8.9), 0);
+
+    // Jumping across both thresholds still respects the band on the far one.
+    assert_eq!(at_distance(20.5), 1);
+    assert_eq!(at_distance(21.0), 2);
+    assert_eq!(at_distance(0.0), 0);
+}
+
+#[test]
+#[should_panic]
+fn lod_unsorted_thresholds() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    LodManager::new().set_thresholds(entity_manager.create(), vec![10.0, 5.0]);
+}
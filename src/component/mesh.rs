@@ -102,6 +102,10 @@ impl ComponentManager for MeshManager {
         }
     }
 
+    fn contains(&self, entity: Entity) -> bool {
+        self.indices.contains_key(&entity)
+    }
+
     fn destroy_marked(&mut self) {
         let mut marked_for_destroy = RefCell::new(HashSet::default());
         ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
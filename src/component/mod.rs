@@ -4,18 +4,30 @@ pub mod mesh;
 pub mod light;
 pub mod audio;
 pub mod alarm;
+pub mod lifetime;
+pub mod lod;
+pub mod name;
+pub mod velocity;
 pub mod singleton_component_manager;
 pub mod struct_component_manager;
+pub mod data_manager;
 pub mod collider;
 
+mod child_list;
+
 pub use self::singleton_component_manager::SingletonComponentManager;
 pub use self::struct_component_manager::StructComponentManager;
-pub use self::transform::{Transform, TransformManager, transform_update};
-pub use self::camera::{Camera, CameraManager};
+pub use self::data_manager::DataManager;
+pub use self::transform::{Transform, TransformManager, CoordinateConvention, ParallelTransformUpdate, TransformWorkers, transform_update};
+pub use self::camera::{Camera, CameraManager, ProjectionMode, sort_by_distance};
 pub use self::mesh::{Mesh, MeshManager};
 pub use self::light::{Light, LightManager, LightUpdateSystem};
-pub use self::audio::{AudioSource, AudioSourceManager, AudioSystem};
+pub use self::audio::{AudioSource, AudioSourceManager, AudioSystem, spatialize};
 pub use self::alarm::{AlarmID, AlarmManager, AlarmSystem};
+pub use self::lifetime::{LifetimeManager, LifetimeSystem};
+pub use self::lod::LodManager;
+pub use self::name::NameManager;
+pub use self::velocity::{VelocityManager, VelocitySystem};
 pub use self::collider::{Collider, ColliderManager, CollisionSystem, bounding_volume, grid_collision};
 
 use std::collections::{HashMap, HashSet};
@@ -24,5 +36,52 @@ use hash::*;
 
 use ecs::Entity;
 
+/// A map keyed by entity, used by the component managers to find each entity's component.
+///
+/// # Details
+///
+/// The map uses the FNV hasher, which is faster than the default SipHash for small keys like
+/// entity IDs. FNV starts from a fixed offset rather than a random seed, so the iteration order
+/// depends only on the sequence of insertions and removals. Code that iterates a manager's map
+/// therefore behaves the same way on every run, which keeps replays and saved scenes
+/// reproducible.
 pub type EntityMap<T> = HashMap<Entity, T, FnvHashState>;
+
+/// A set of entities, hashed deterministically like `EntityMap`.
 pub type EntitySet = HashSet<Entity, FnvHashState>;
+
+#[test]
+fn entity_map_order_is_deterministic() {
+    use std::u32;
+
+    // Includes IDs at the top of the range so every bit of the ID goes through the hasher.
+    let ids: Vec<u32> = (0..200).chain(u32::MAX - 50..u32::MAX).chain(Some(u32::MAX)).collect();
+
+    let build = || {
+        let mut map: EntityMap<usize> = HashMap::default();
+        for (value, &id) in ids.iter().enumerate() {
+            map.insert(Entity::from_id(id), value);
+        }
+
+        // Removals also have to be replayed identically.
+        for &id in ids.iter().filter(|&&id| id % 3 == 1) {
+            map.remove(&Entity::from_id(id));
+        }
+
+        map
+    };
+
+    let first = build();
+    let second = build();
+    let first_order: Vec<(Entity, usize)> = first.iter().map(|(&entity, &value)| (entity, value)).collect();
+    let second_order: Vec<(Entity, usize)> = second.iter().map(|(&entity, &value)| (entity, value)).collect();
+    assert_eq!(first_order, second_order);
+
+    assert_eq!(first.get(&Entity::from_id(u32::MAX)), Some(&(ids.len() - 1)));
+    assert_eq!(first.get(&Entity::from_id(u32::MAX - 1)), Some(&(ids.len() - 2)));
+    assert_eq!(first.len(), ids.iter().filter(|&&id| id % 3 != 1).count());
+
+    let set: EntitySet = ids.iter().map(|&id| Entity::from_id(id)).collect();
+    let other: EntitySet = ids.iter().map(|&id| Entity::from_id(id)).collect();
+    assert_eq!(set.iter().collect::<Vec<_>>(), other.iter().collect::<Vec<_>>());
+}
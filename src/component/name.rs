@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+
+use ecs::{Entity, ComponentManager};
+use super::{EntityMap, EntitySet};
+
+/// Associates a display name with an entity, for logging and editor inspectors.
+///
+/// # Details
+///
+/// Names aren't required to be unique, if several entities share a name then `find_by_name()`
+/// returns whichever of them was named first. Names are purely descriptive and have no effect on
+/// the entity's behavior.
+#[derive(Debug, Clone)]
+pub struct NameManager {
+    names: EntityMap<String>,
+
+    /// Named entities in the order they were first given a name, used to make `find_by_name()`
+    /// deterministic.
+    order: Vec<Entity>,
+
+    marked_for_destroy: RefCell<EntitySet>,
+}
+
+impl NameManager {
+    pub fn new() -> NameManager {
+        NameManager {
+            names: HashMap::default(),
+            order: Vec::new(),
+            marked_for_destroy: RefCell::new(HashSet::default()),
+        }
+    }
+
+    /// Sets the name of the entity, replacing any existing name.
+    ///
+    /// Renaming an entity doesn't change its place in the search order of `find_by_name()`.
+    pub fn set_name<S: Into<String>>(&mut self, entity: Entity, name: S) {
+        if self.names.insert(entity, name.into()).is_none() {
+            self.order.push(entity);
+        }
+    }
+
+    /// Retrieves the name of the entity, or `None` if the entity hasn't been named.
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.names.get(&entity).map(|name| &**name)
+    }
+
+    /// Finds an entity with the specified name.
+    ///
+    /// # Details
+    ///
+    /// If more than one entity has the name, the one that was named first is returned. This is a
+    /// linear search over every named entity so it's meant for debugging and tooling, not for
+    /// per-frame lookups.
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.order.iter()
+            .find(|&&entity| self.names[&entity] == name)
+            .cloned()
+    }
+
+    /// Removes the name from the entity.
+    pub fn clear_name(&mut self, entity: Entity) {
+        if self.names.remove(&entity).is_some() {
+            self.order.retain(|&other| other != entity);
+        }
+    }
+}
+
+impl ComponentManager for NameManager {
+    fn destroy_all(&self, entity: Entity) {
+        if self.names.contains_key(&entity) {
+            self.marked_for_destroy.borrow_mut().insert(entity);
+        }
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.names.contains_key(&entity)
+    }
+
+    fn destroy_marked(&mut self) {
+        let mut marked_for_destroy = RefCell::new(HashSet::default());
+        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
+        let marked_for_destroy = marked_for_destroy.into_inner();
+        if marked_for_destroy.is_empty() {
+            return;
+        }
+
+        for entity in &marked_for_destroy {
+            self.names.remove(entity);
+        }
+        self.order.retain(|entity| !marked_for_destroy.contains(entity));
+    }
+}
+
+#[test]
+fn set_and_find_names() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut name_manager = NameManager::new();
+
+    let player = entity_manager.create();
+    let first_enemy = entity_manager.create();
+    let second_enemy = entity_manager.create();
+    let unnamed = entity_manager.create();
+
+    name_manager.set_name(player, "Player");
+    name_manager.set_name(first_enemy, "Enemy");
+    name_manager.set_name(second_enemy, String::from("Enemy"));
+
+    assert_eq!(name_manager.name(player), Some("Player"));
+    assert_eq!(name_manager.name(unnamed), None);
+    assert_eq!(name_manager.find_by_name("Player"), Some(player));
+    assert_eq!(name_manager.find_by_name("Camera"), None);
+
+    // Duplicate names find the entity named first, even after it's renamed back.
+    assert_eq!(name_manager.find_by_name("Enemy"), Some(first_enemy));
+    name_manager.set_name(first_enemy, "Boss");
+    assert_eq!(name_manager.find_by_name("Enemy"), Some(second_enemy));
+    name_manager.set_name(first_enemy, "Enemy");
+    assert_eq!(name_manager.find_by_name("Enemy"), Some(first_enemy));
+
+    name_manager.clear_name(player);
+    assert_eq!(name_manager.name(player), None);
+    assert_eq!(name_manager.find_by_name("Player"), None);
+}
+
+#[test]
+fn names_removed_on_destroy() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut name_manager = NameManager::new();
+
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    name_manager.set_name(first, "Crate");
+    name_manager.set_name(second, "Crate");
+
+    // Names stay until the marked entities are actually destroyed.
+    name_manager.destroy_all(first);
+    assert_eq!(name_manager.name(first), Some("Crate"));
+
+    name_manager.destroy_marked();
+    assert!(!name_manager.contains(first));
+    assert_eq!(name_manager.name(first), None);
+    assert_eq!(name_manager.find_by_name("Crate"), Some(second));
+}
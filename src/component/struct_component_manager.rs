@@ -5,14 +5,20 @@ use std::any::Any;
 
 use super::{EntityMap, EntitySet};
 
-use ecs::{Entity, ComponentManager};
+use ecs::{Entity, ComponentManager, Handle};
 
 /// A default manager for component types that can be represented as a single struct.
 #[derive(Debug, Clone)]
 pub struct StructComponentManager<T: Clone + Any> {
     components: Vec<RefCell<T>>,
     entities: Vec<Entity>,
-    indices: EntityMap<usize>,
+    indices: EntityMap<Handle<T>>,
+
+    /// The generation of each slot in `components`, used to detect stale handles.
+    ///
+    /// This is never shrunk so that a slot's generation keeps increasing even if the slot is
+    /// vacated and later reused.
+    generations: Vec<u32>,
 
     marked_for_destroy: RefCell<EntitySet>,
 }
@@ -23,6 +29,7 @@ impl<T: Clone + Any> StructComponentManager<T> {
             components: Vec::new(),
             entities: Vec::new(),
             indices: HashMap::default(),
+            generations: Vec::new(),
 
             marked_for_destroy: RefCell::new(HashSet::default()),
         }
@@ -32,29 +39,66 @@ impl<T: Clone + Any> StructComponentManager<T> {
         assert!(!self.indices.contains_key(&entity));
 
         let index = self.components.len();
+        let handle = Handle::new(index, self.bump_generation(index));
         self.components.push(RefCell::new(component));
         self.entities.push(entity);
-        self.indices.insert(entity, index);
+        self.indices.insert(entity, handle);
 
         self.components[index].borrow_mut()
     }
 
+    /// Checks whether the entity has a component.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.indices.contains_key(&entity)
+    }
+
     pub fn get(&self, entity: Entity) -> Option<Ref<T>> {
-        if let Some(index) = self.indices.get(&entity) {
-            Some(self.components[*index].borrow())
+        if let Some(handle) = self.indices.get(&entity) {
+            Some(self.components[handle.index()].borrow())
         } else {
             None
         }
     }
 
     pub fn get_mut(&self, entity: Entity) -> Option<RefMut<T>> {
-        if let Some(index) = self.indices.get(&entity) {
-            Some(self.components[*index].borrow_mut())
+        if let Some(handle) = self.indices.get(&entity) {
+            Some(self.components[handle.index()].borrow_mut())
+        } else {
+            None
+        }
+    }
+
+    /// Retrieves a handle to the entity's component.
+    ///
+    /// The handle stays valid until a component is destroyed, after which it may need to be
+    /// retrieved again.
+    pub fn handle(&self, entity: Entity) -> Option<Handle<T>> {
+        self.indices.get(&entity).cloned()
+    }
+
+    /// Retrieves the component referenced by the handle, or `None` if the handle is stale.
+    pub fn get_by_handle(&self, handle: Handle<T>) -> Option<Ref<T>> {
+        if self.is_valid(handle) {
+            Some(self.components[handle.index()].borrow())
         } else {
             None
         }
     }
 
+    /// Mutably retrieves the component referenced by the handle, or `None` if the handle is stale.
+    pub fn get_mut_by_handle(&self, handle: Handle<T>) -> Option<RefMut<T>> {
+        if self.is_valid(handle) {
+            Some(self.components[handle.index()].borrow_mut())
+        } else {
+            None
+        }
+    }
+
+    /// Checks if the handle still refers to the same component it did when it was retrieved.
+    pub fn is_valid(&self, handle: Handle<T>) -> bool {
+        handle.index() < self.components.len() && self.generations[handle.index()] == handle.generation()
+    }
+
     pub fn components(&self) -> &[RefCell<T>] {
         &*self.components
     }
@@ -79,7 +123,7 @@ impl<T: Clone + Any> StructComponentManager<T> {
 
     pub fn destroy_immediate(&mut self, entity: Entity) -> T {
         // Retrieve indices of removed entity and the one it's swapped with.
-        let index = self.indices.remove(&entity).unwrap();
+        let index = self.indices.remove(&entity).unwrap().index();
 
         // Remove transform and the associate entity.
         let removed_entity = self.entities.swap_remove(index);
@@ -88,8 +132,10 @@ impl<T: Clone + Any> StructComponentManager<T> {
         // Update the index mapping for the moved entity, but only if the one we removed
         // wasn't the only one in the row (or the last one in the row).
         if index != self.entities.len() {
+            // The slot now holds a different component, so handles to the removed one are stale.
             let moved_entity = self.entities[index];
-            self.indices.insert(moved_entity, index);
+            let handle = Handle::new(index, self.bump_generation(index));
+            self.indices.insert(moved_entity, handle);
         }
 
         // Defer removing the transform until the very end to avoid a bunch of memcpys.
@@ -98,6 +144,18 @@ impl<T: Clone + Any> StructComponentManager<T> {
         // once at the end (hopefully) means only a single memcpy.
         self.components.swap_remove(index).into_inner()
     }
+
+    /// Advances the generation of the slot at `index`, invalidating any existing handles to it.
+    fn bump_generation(&mut self, index: usize) -> u32 {
+        if index < self.generations.len() {
+            self.generations[index] = self.generations[index].wrapping_add(1);
+        } else {
+            debug_assert!(index == self.generations.len());
+            self.generations.push(0);
+        }
+
+        self.generations[index]
+    }
 }
 
 impl<T: Clone + Any> ComponentManager for StructComponentManager<T> {
@@ -107,6 +165,10 @@ impl<T: Clone + Any> ComponentManager for StructComponentManager<T> {
         }
     }
 
+    fn contains(&self, entity: Entity) -> bool {
+        StructComponentManager::contains(self, entity)
+    }
+
     fn destroy_marked(&mut self) {
         let mut marked_for_destroy = RefCell::new(HashSet::default());
         ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
@@ -149,3 +211,84 @@ impl<'a, T: 'a + Clone> Iterator for ComponentIterMut<'a, T> {
         }
     }
 }
+
+#[test]
+fn handle_round_trip() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut manager = StructComponentManager::<u32>::new();
+
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    manager.assign(first, 1);
+    manager.assign(second, 2);
+
+    let first_handle = manager.handle(first).unwrap();
+    let second_handle = manager.handle(second).unwrap();
+    assert!(first_handle != second_handle);
+    assert_eq!(*manager.get_by_handle(first_handle).unwrap(), 1);
+    assert_eq!(*manager.get_by_handle(second_handle).unwrap(), 2);
+
+    *manager.get_mut_by_handle(second_handle).unwrap() = 20;
+    assert_eq!(*manager.get(second).unwrap(), 20);
+
+    // Handles are typed by component, the following doesn't compile:
+    // let _: Option<Ref<f32>> = manager.get_by_handle(Handle::<f32>::new(0, 0));
+}
+
+#[test]
+fn stale_handles_detected() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut manager = StructComponentManager::<u32>::new();
+
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    manager.assign(first, 1);
+    manager.assign(second, 2);
+
+    let first_handle = manager.handle(first).unwrap();
+    let second_handle = manager.handle(second).unwrap();
+
+    // Destroying the first component moves the second into its slot, so neither of the old
+    // handles refer to a valid component any more.
+    manager.destroy_immediate(first);
+    assert!(manager.get_by_handle(first_handle).is_none());
+    assert!(manager.get_by_handle(second_handle).is_none());
+
+    // Retrieving the handle again gives one that works.
+    let moved_handle = manager.handle(second).unwrap();
+    assert_eq!(*manager.get_by_handle(moved_handle).unwrap(), 2);
+
+    // Reusing a vacated slot doesn't revive old handles to it.
+    let third = entity_manager.create();
+    manager.assign(third, 3);
+    assert_eq!(manager.handle(third).unwrap().index(), second_handle.index());
+    assert!(manager.get_by_handle(second_handle).is_none());
+    assert!(manager.handle(first).is_none());
+}
+
+#[test]
+fn contains_after_swap_remove() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut manager = StructComponentManager::<u32>::new();
+
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    assert!(!manager.contains(first));
+
+    manager.assign(first, 1);
+    manager.assign(second, 2);
+    assert!(manager.contains(first));
+    assert!(manager.contains(second));
+
+    // Removing the first component swaps the second into its place.
+    manager.destroy_immediate(first);
+    assert!(!manager.contains(first));
+    assert!(manager.contains(second));
+    assert_eq!(*manager.get(second).unwrap(), 2);
+}
@@ -1,12 +1,23 @@
 use std::collections::{HashMap, HashSet};
+use std::collections::hash_state::HashState;
 use std::cell::{Cell, RefCell, Ref, RefMut};
+#[cfg(debug_assertions)]
+use std::cell::BorrowState;
+use std::f32::consts::PI;
+use std::sync::mpsc::{self, SendError};
+use std::thread;
+use std::slice;
+use std::fmt::{self, Display, Formatter};
 
 use math::*;
 use stopwatch::Stopwatch;
 
 use ecs::{Entity, System, ComponentManager};
 use scene::Scene;
+use serialize::{Serialize, Deserialize, ByteReader, SerializeError};
 use super::{EntityMap, EntitySet};
+use super::child_list::ChildList;
+use super::collider::bounding_volume::AABB;
 
 #[derive(Debug, Clone)]
 pub struct TransformManager {
@@ -19,16 +30,198 @@ pub struct TransformManager {
     /// second is the index of the transform within that row.
     indices: EntityMap<(usize, usize)>,
 
+    /// The direct children of each transform, in the order they were parented.
+    ///
+    /// Only transforms with at least one child have an entry. This lets the `walk_*` functions
+    /// visit children without searching the whole of the next row.
+    children: EntityMap<ChildList>,
+
     marked_for_destroy: RefCell<EntitySet>,
+
+    convention: CoordinateConvention,
+
+    scale_constraint: ScaleConstraint,
+
+    /// The scene that each transform belongs to. Every transform has an entry.
+    scene_ids: EntityMap<u32>,
+
+    /// Change events that haven't been drained yet, see `drain_events()`.
+    events: RefCell<Vec<TransformEvent>>,
+
+    /// Whether change events are recorded at all, see `set_record_events()`.
+    record_events: bool,
+
+    /// Incremented whenever transforms are added, removed, or updated, so that snapshots taken
+    /// with `read_snapshot()` can tell when they're stale.
+    generation: Cell<u64>,
+}
+
+/// A change to a transform, emitted by `TransformManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformEvent {
+    /// A transform was assigned to the entity.
+    TransformAdded(Entity),
+
+    /// The entity's derived transform changed, either because the transform itself was modified
+    /// or because one of its ancestors was.
+    TransformMoved(Entity),
+
+    /// The entity's transform was destroyed.
+    TransformDestroyed(Entity),
+}
+
+/// An iterator over the root entities of a `TransformManager`, see `TransformManager::roots()`.
+pub struct Roots<'a> {
+    inner: slice::Iter<'a, (Entity, Option<Entity>)>,
+}
+
+impl<'a> Iterator for Roots<'a> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        self.inner.next().map(|&(entity, _)| entity)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A depth-first iterator over an entity and all of its descendants, see
+/// `TransformManager::iter_subtree()`.
+///
+/// # Details
+///
+/// The iterator doesn't keep a stack of pending entities. Instead it finds the next entity by
+/// following the parent links back up the hierarchy, so iterating never allocates.
+pub struct SubtreeIter<'a> {
+    manager: &'a TransformManager,
+    root: Entity,
+    next: Option<Entity>,
+}
+
+impl<'a> SubtreeIter<'a> {
+    /// Finds the entity visited after `current`: its first child if it has one, otherwise the
+    /// next sibling of the nearest ancestor (up to the root) that has one.
+    fn successor(&self, current: Entity) -> Option<Entity> {
+        let manager = self.manager;
+        if let Some(children) = manager.children.get(&current) {
+            if let Some(&first) = children.as_slice().first() {
+                return Some(first);
+            }
+        }
+
+        let mut node = current;
+        while node != self.root {
+            let (row, index) = manager.indices[&node];
+            let parent = manager.entities[row][index].1
+                .expect("Non-root entity in subtree has no parent");
+
+            let siblings = manager.children[&parent].as_slice();
+            let position = siblings.iter()
+                .position(|&sibling| sibling == node)
+                .expect("Entity missing from its parent's child list");
+            if let Some(&sibling) = siblings.get(position + 1) {
+                return Some(sibling);
+            }
+
+            node = parent;
+        }
+
+        None
+    }
+}
+
+impl<'a> Iterator for SubtreeIter<'a> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let current = match self.next {
+            Some(entity) => entity,
+            None => return None,
+        };
+
+        self.next = self.successor(current);
+        Some(current)
+    }
+}
+
+/// A copy of the derived values of every transform, see `TransformManager::read_snapshot()`.
+///
+/// # Details
+///
+/// The values are stored in flat arrays indexed in the same order as `entities()`, so read-only
+/// systems like rendering and culling can access them without borrowing each transform's
+/// `RefCell`. The snapshot doesn't update itself, use `TransformManager::is_snapshot_current()`
+/// to check if it's stale and `TransformManager::refresh_snapshot()` to regenerate it.
+#[derive(Debug, Clone)]
+pub struct TransformSnapshot {
+    entities: Vec<Entity>,
+    matrices: Vec<Matrix4>,
+    positions: Vec<Point>,
+    indices: EntityMap<usize>,
+    generation: u64,
+}
+
+impl TransformSnapshot {
+    /// The number of transforms in the snapshot.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Retrieves the index of the entity's values, or `None` if the entity didn't have a
+    /// transform when the snapshot was taken.
+    pub fn index_of(&self, entity: Entity) -> Option<usize> {
+        self.indices.get(&entity).cloned()
+    }
+
+    /// Retrieves the entity's derived matrix, or `None` if the entity isn't in the snapshot.
+    pub fn derived_matrix(&self, entity: Entity) -> Option<Matrix4> {
+        self.index_of(entity).map(|index| self.matrices[index])
+    }
+
+    /// Retrieves the entity's derived position, or `None` if the entity isn't in the snapshot.
+    pub fn position_derived(&self, entity: Entity) -> Option<Point> {
+        self.index_of(entity).map(|index| self.positions[index])
+    }
+
+    /// The entities in the snapshot, parents are always listed before their children.
+    pub fn entities(&self) -> &[Entity] {
+        &*self.entities
+    }
+
+    /// The derived matrices, in the same order as `entities()`.
+    pub fn matrices(&self) -> &[Matrix4] {
+        &*self.matrices
+    }
+
+    /// The derived positions, in the same order as `entities()`.
+    pub fn positions(&self) -> &[Point] {
+        &*self.positions
+    }
 }
 
+/// The scene that transforms are assigned to by default.
+pub const DEFAULT_SCENE_ID: u32 = 0;
+
 impl TransformManager {
     pub fn new() -> TransformManager {
         let mut transform_manager = TransformManager {
             transforms: Vec::new(),
             entities: Vec::new(),
             indices: HashMap::default(),
+            children: HashMap::default(),
             marked_for_destroy: RefCell::new(HashSet::default()),
+            convention: CoordinateConvention::YUp,
+            scale_constraint: ScaleConstraint::Free,
+            scene_ids: HashMap::default(),
+            events: RefCell::new(Vec::new()),
+            record_events: false,
+            generation: Cell::new(0),
         };
 
         transform_manager.transforms.push(Vec::new());
@@ -36,25 +229,184 @@ impl TransformManager {
         transform_manager
     }
 
+    /// Creates a transform manager with storage preallocated for a hierarchy of the given size.
+    ///
+    /// # Details
+    ///
+    /// `max_depth` rows are created up front, each with room for `root_entities` transforms, so
+    /// building a hierarchy within those bounds doesn't reallocate. Hierarchies that grow deeper
+    /// or wider than the reserved size still work, the rows are grown on demand as they would be
+    /// for `TransformManager::new()`.
+    pub fn with_capacity(root_entities: usize, max_depth: usize) -> TransformManager {
+        let mut transform_manager = TransformManager::new();
+
+        // `new()` already created the root row.
+        for _ in 1..max_depth {
+            transform_manager.transforms.push(Vec::new());
+            transform_manager.entities.push(Vec::new());
+        }
+
+        for (transform_row, entity_row) in transform_manager.transforms.iter_mut().zip(transform_manager.entities.iter_mut()) {
+            transform_row.reserve(root_entities);
+            entity_row.reserve(root_entities);
+        }
+
+        transform_manager.indices.reserve(root_entities);
+        transform_manager.scene_ids.reserve(root_entities);
+        transform_manager
+    }
+
+    /// Reserves capacity for at least `additional` more root transforms.
+    pub fn reserve(&mut self, additional: usize) {
+        self.transforms[0].reserve(additional);
+        self.entities[0].reserve(additional);
+        self.indices.reserve(additional);
+        self.scene_ids.reserve(additional);
+    }
+
     pub fn assign(&mut self, entity: Entity) -> RefMut<Transform> {
+        let mut transform = Transform::new();
+        transform.convention = self.convention;
+        transform.scale_constraint = self.scale_constraint;
+
         let index = self.transforms[0].len();
-        self.transforms[0].push(RefCell::new(Transform::new()));
+        self.transforms[0].push(RefCell::new(transform));
         self.entities[0].push((entity, None));
 
         assert!(self.transforms[0].len() == self.entities[0].len());
 
         self.indices.insert(entity, (0, index));
+        self.scene_ids.insert(entity, DEFAULT_SCENE_ID);
+        self.push_event(TransformEvent::TransformAdded(entity));
+        self.bump_generation();
         self.transforms[0][index].borrow_mut()
     }
 
+    /// Removes and returns the change events emitted since the events were last drained.
+    ///
+    /// # Details
+    ///
+    /// Events are emitted in the order the changes happened: `TransformAdded` when a transform is
+    /// assigned, `TransformDestroyed` when it's destroyed by `destroy_marked()` or
+    /// `destroy_immediate()`, and `TransformMoved` when the hierarchy is updated. Since moves are
+    /// detected during the update, a transform that is modified any number of times in a frame
+    /// only emits a single `TransformMoved`, and every descendant of a moved transform is reported
+    /// as moved too. Events accumulate until they're drained, so whoever consumes them should
+    /// drain them every frame.
+    ///
+    /// Events are only recorded after they've been enabled with `set_record_events()`, otherwise
+    /// this always returns an empty list.
+    pub fn drain_events(&self) -> Vec<TransformEvent> {
+        let mut events = self.events.borrow_mut();
+        ::std::mem::replace(&mut *events, Vec::new())
+    }
+
+    /// Sets whether change events are recorded for `drain_events()`.
+    ///
+    /// # Details
+    ///
+    /// Recording is off by default since recorded events are kept until they're drained, and
+    /// with nobody draining them they would pile up for the life of the scene. Turning recording
+    /// off discards any events that haven't been drained.
+    pub fn set_record_events(&mut self, record_events: bool) {
+        self.record_events = record_events;
+        if !record_events {
+            self.events.borrow_mut().clear();
+        }
+    }
+
+    fn push_event(&self, event: TransformEvent) {
+        if self.record_events {
+            self.events.borrow_mut().push(event);
+        }
+    }
+
+    /// Checks whether the entity has a transform.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.indices.contains_key(&entity)
+    }
+
+    /// Retrieves the entity's transform.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity doesn't have a transform or if its transform is already mutably
+    /// borrowed. In debug builds the panic message names the entity.
     pub fn get(&self, entity: Entity) -> Ref<Transform> {
         let (row, index) = *self.indices.get(&entity).expect("Transform manager does not contain a transform for the given entity.");
-        self.transforms[row][index].borrow()
+        borrow_transform(&self.transforms[row][index], entity)
     }
 
+    /// Retrieves the entity's transform mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity doesn't have a transform or if its transform is already borrowed. In
+    /// debug builds the panic message names the entity.
     pub fn get_mut(&self, entity: Entity) -> RefMut<Transform> {
         let (row, index) = *self.indices.get(&entity).expect("Transform manager does not contain a transform for the given entity.");
-        self.transforms[row][index].borrow_mut()
+        borrow_transform_mut(&self.transforms[row][index], entity)
+    }
+
+    /// Retrieves the entity's transform, or `None` if the entity doesn't have one.
+    ///
+    /// Use this instead of `get()` when the entity may not have a transform.
+    pub fn try_get(&self, entity: Entity) -> Option<Ref<Transform>> {
+        self.indices.get(&entity).map(|&(row, index)| borrow_transform(&self.transforms[row][index], entity))
+    }
+
+    /// Retrieves the entity's transform mutably, or `None` if the entity doesn't have one.
+    ///
+    /// Use this instead of `get_mut()` when the entity may not have a transform.
+    pub fn try_get_mut(&self, entity: Entity) -> Option<RefMut<Transform>> {
+        self.indices.get(&entity).map(|&(row, index)| borrow_transform_mut(&self.transforms[row][index], entity))
+    }
+
+    /// Retrieves the coordinate convention used by the transforms in the manager.
+    pub fn coordinate_convention(&self) -> CoordinateConvention {
+        self.convention
+    }
+
+    /// Sets the coordinate convention used by all transforms in the manager.
+    ///
+    /// # Details
+    ///
+    /// The convention determines which local axes are returned by `Transform::forward()`,
+    /// `Transform::up()`, and `Transform::right()`, and how `Transform::look_at()` builds
+    /// rotations. Changing the convention does not re-interpret the rotations already stored in
+    /// existing transforms, so a transform that was facing a direction under the old convention
+    /// will generally report a different `forward()` under the new one. Ideally the convention
+    /// should be set once before any transforms are assigned.
+    pub fn set_coordinate_convention(&mut self, convention: CoordinateConvention) {
+        self.convention = convention;
+
+        for row in &self.transforms {
+            for transform in row {
+                transform.borrow_mut().convention = convention;
+            }
+        }
+    }
+
+    /// Retrieves the scale constraint applied to the transforms in the manager.
+    pub fn scale_constraint(&self) -> ScaleConstraint {
+        self.scale_constraint
+    }
+
+    /// Sets the scale constraint applied to all transforms in the manager.
+    ///
+    /// # Details
+    ///
+    /// Use `ScaleConstraint::UniformAverage` or `ScaleConstraint::UniformAssert` to keep
+    /// non-uniform scales out of systems that can't handle them (e.g. physics). The constraint
+    /// only applies to scales set after the constraint is changed, existing scales are left as-is.
+    pub fn set_scale_constraint(&mut self, scale_constraint: ScaleConstraint) {
+        self.scale_constraint = scale_constraint;
+
+        for row in &self.transforms {
+            for transform in row {
+                transform.borrow_mut().scale_constraint = scale_constraint;
+            }
+        }
     }
 
     pub fn set_child(&mut self, parent: Entity, child: Entity) {
@@ -64,13 +416,125 @@ impl TransformManager {
 
         // Move the child and all of its children to the correct row.
         self.set_row_recursive(child, Some(parent), child_row);
+
+        // Children always belong to the same scene as their parent.
+        let scene_id = self.scene_id(parent);
+        self.assign_to_scene(child, scene_id);
+    }
+
+    /// Retrieves the ID of the scene that the entity's transform belongs to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity does not have a transform.
+    pub fn scene_id(&self, entity: Entity) -> u32 {
+        *self.scene_ids.get(&entity).expect("Transform manager does not contain a transform for the given entity.")
+    }
+
+    /// Assigns the entity and all of its children to the specified scene.
+    ///
+    /// # Details
+    ///
+    /// Scenes are a way to group transform hierarchies so that they can be destroyed together
+    /// with `destroy_scene()`, e.g. to unload a level. All transforms start in
+    /// `DEFAULT_SCENE_ID`. Parenting a transform with `set_child()` moves the child and its
+    /// children into the parent's scene.
+    pub fn assign_to_scene(&mut self, entity: Entity, scene_id: u32) {
+        let mut subtree = Vec::new();
+        self.walk_children(entity, &mut |entity| subtree.push(entity));
+
+        for entity in subtree {
+            self.scene_ids.insert(entity, scene_id);
+        }
+    }
+
+    /// Immediately destroys all transforms in the specified scene, along with their children.
+    ///
+    /// # Details
+    ///
+    /// Only the transforms are destroyed, any other components belonging to the entities are
+    /// unaffected. Use `Scene::destroy_entity()` to destroy all of an entity's components.
+    pub fn destroy_scene(&mut self, scene_id: u32) {
+        let mut to_destroy: EntitySet = HashSet::default();
+        for (&entity, _) in self.scene_ids.iter().filter(|&(_, &id)| id == scene_id) {
+            self.walk_children(entity, &mut |entity| { to_destroy.insert(entity); });
+        }
+
+        for entity in to_destroy {
+            self.destroy_immediate(entity);
+        }
+    }
+
+    /// Makes `child` a child of `parent` without changing its world transform.
+    ///
+    /// # Details
+    ///
+    /// `set_child()` keeps the child's local position, rotation, and scale, which are then
+    /// interpreted relative to the new parent, so the child will generally move in world space.
+    /// This instead recalculates the child's local values so that its derived position, rotation,
+    /// and scale are the same as before it was reparented.
+    ///
+    /// If the hierarchy contains non-uniform scale combined with rotation the world transform
+    /// can't always be represented exactly by a local position, rotation, and scale, in which
+    /// case the derived scale is preserved but the derived matrix may differ slightly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent's derived matrix is singular (i.e. it has a zero scale component).
+    pub fn set_child_keep_world(&mut self, parent: Entity, child: Entity) {
+        self.update_single(parent);
+        self.update_single(child);
+
+        let (position, rotation, scale) = {
+            let parent_transform = self.get(parent);
+            let child_transform = self.get(child);
+
+            let parent_inverse = parent_transform.derived_matrix().inverse()
+                .expect("Cannot keep world transform when reparenting to a transform with zero scale");
+            let parent_scale = parent_transform.scale_derived();
+            let child_scale = child_transform.scale_derived();
+
+            (
+                child_transform.position_derived() * parent_inverse,
+                parent_transform.rotation_derived().conjugate() * child_transform.rotation_derived(),
+                Vector3::new(
+                    child_scale.x / parent_scale.x,
+                    child_scale.y / parent_scale.y,
+                    child_scale.z / parent_scale.z),
+            )
+        };
+
+        self.set_child(parent, child);
+
+        let mut child_transform = self.get_mut(child);
+        child_transform.set_position(position);
+        child_transform.set_rotation(rotation);
+        child_transform.set_scale(scale);
+    }
+
+    /// Moves `child` to the root of the hierarchy without changing its world transform.
+    ///
+    /// See `set_child_keep_world()` for more information.
+    pub fn unparent_keep_world(&mut self, child: Entity) {
+        self.update_single(child);
+
+        let (position, rotation, scale) = {
+            let child_transform = self.get(child);
+            (child_transform.position_derived(), child_transform.rotation_derived(), child_transform.scale_derived())
+        };
+
+        self.set_row_recursive(child, None, 0);
+
+        let mut child_transform = self.get_mut(child);
+        child_transform.set_position(position);
+        child_transform.set_rotation(rotation);
+        child_transform.set_scale(scale);
     }
 
     fn set_row_recursive(&mut self, entity: Entity, parent: Option<Entity>, new_row: usize) {
         debug_assert!((new_row == 0 && parent.is_none()) || (new_row > 0 && parent.is_some()));
 
         // Remove old transform component.
-        let (old_row, _) = *self.indices.get(&entity).unwrap(); // TODO: Don't panic? If this fails an invariant somewhere else was broken.
         let transform = self.remove(entity);
 
         // Ensure that there are enough rows for the child.
@@ -86,31 +550,40 @@ impl TransformManager {
 
         // Update the index map.
         self.indices.insert(entity, (new_row, child_index));
+        if let Some(parent) = parent {
+            self.children.entry(parent).or_insert_with(ChildList::new).push(entity);
+        }
 
-        // Update all children.
-        // TODO: We shouldn't have to clone the list here, but Rust's ownership rules mean that we
-        // can't compile if we don't (which is completely valid in this case). Once we implement a
-        // more stable form of storage for transform nodes (where pointers to nodes are stable)
-        // then cloning should be able to go away.
-        for (child, maybe_parent) in self.entities[old_row + 1].clone() {
-            match maybe_parent {
-                Some(parent) if parent == entity => {
-                    self.set_row_recursive(child, Some(entity), new_row + 1);
-                },
-                _ => {},
-            }
+        // Update all children. The list is cloned since moving each child modifies it, but small
+        // lists are stored inline so this doesn't usually allocate.
+        let children = match self.children.get(&entity) {
+            Some(children) => children.clone(),
+            None => return,
+        };
+        for &child in children.as_slice() {
+            self.set_row_recursive(child, Some(entity), new_row + 1);
         }
     }
 
+    /// Resets the entity's transform to its default values, see `Transform::reset()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity doesn't have a transform.
+    pub fn reset(&self, entity: Entity) {
+        self.get_mut(entity).reset();
+    }
+
     pub fn update_single(&self, entity: Entity) {
         let transform = self.get(entity);
+        self.bump_generation();
 
         let (row, index) = *self.indices.get(&entity).expect("Transform manager does not contain a transform for the given entity.");
         let (_, parent) = self.entities[row][index];
         match parent {
             None => {
                 DUMMY_TRANSFORM.with(|parent| {
-                    transform.update(parent);
+                    transform.update(entity, parent);
                 })
             },
             Some(parent) => {
@@ -119,7 +592,7 @@ impl TransformManager {
 
                 // Now update self with the parent's updated transform.
                 let parent_transform = self.get(parent);
-                transform.update(&*parent_transform);
+                transform.update(entity, &*parent_transform);
             }
         }
     }
@@ -135,11 +608,9 @@ impl TransformManager {
             let mut transform = self.transforms[row][index].borrow_mut();
             callback(entity, &mut *transform);
 
-            let child_row = row + 1;
-            if self.transforms.len() > child_row {
-                for (child_index, _) in self.entities[child_row].iter().enumerate().filter(|&(_, &(_, parent))| parent.unwrap() == entity) {
-                    let (child_entity, _) = self.entities[child_row][child_index];
-                    self.walk_hierarchy(child_entity, callback);
+            if let Some(children) = self.children.get(&entity) {
+                for &child in children.as_slice() {
+                    self.walk_hierarchy(child, callback);
                 }
             }
         }
@@ -153,293 +624,3088 @@ impl TransformManager {
     /// the callback is never invoked. Note that the transform itself is not passed to the callback,
     /// if you need to access the transform use `walk_hierarchy()` instead.
     pub fn walk_children<F: FnMut(Entity)>(&self, entity: Entity, callback: &mut F) {
-        if let Some(&(row, _)) = self.indices.get(&entity) {
+        if self.indices.contains_key(&entity) {
             callback(entity);
 
-            let child_row = row + 1;
-            if self.transforms.len() > child_row {
-                for (child_index, _) in self.entities[child_row].iter().enumerate().filter(|&(_, &(_, parent))| parent.unwrap() == entity) {
-                    let (child_entity, _) = self.entities[child_row][child_index];
-                    self.walk_children(child_entity, callback);
+            if let Some(children) = self.children.get(&entity) {
+                for &child in children.as_slice() {
+                    self.walk_children(child, callback);
                 }
             }
         }
     }
 
-    /// Marks the transform associated with the entity for destruction.
+    /// Returns a depth-first iterator over the entity and all of its descendants.
     ///
     /// # Details
     ///
-    /// Components marked for destruction are destroyed at the end of every frame. It can be used
-    /// to destroy components without needing a mutable borrow on the component manager.
+    /// Entities are visited in the same order as `walk_children()`, starting with `root` itself.
+    /// If `root` doesn't have a transform the iterator is empty. Unlike `walk_children()` this
+    /// composes with iterator adaptors and can be stopped early, e.g. with `find()`.
     ///
-    /// TODO: Actually support deferred destruction.
-    pub fn destroy(&self, entity: Entity) {
-        let mut marked_for_destroy = self.marked_for_destroy.borrow_mut();
-        marked_for_destroy.insert(entity); // TODO: Warning, error if entity has already been marked?
+    /// The iterator only borrows the hierarchy, not the transforms, so transforms can be
+    /// accessed with `get()` or `get_mut()` while iterating.
+    pub fn iter_subtree(&self, root: Entity) -> SubtreeIter {
+        SubtreeIter {
+            manager: self,
+            root: root,
+            next: if self.indices.contains_key(&root) { Some(root) } else { None },
+        }
     }
 
-    pub fn destroy_immediate(&mut self, entity: Entity) {
-        self.remove(entity);
+    /// Retrieves how deep the entity is in the transform hierarchy.
+    ///
+    /// Root transforms have a depth of 0, their children have a depth of 1, and so on. Returns
+    /// `None` if the entity doesn't have a transform.
+    pub fn depth(&self, entity: Entity) -> Option<usize> {
+        self.indices.get(&entity).map(|&(row, _)| row)
     }
 
-    // Removes and returns the transform associated with the given entity.
-    //
-    // # Details
-    //
-    // NOTE: This does not handle updating/removing children. So be warned.
-    fn remove(&mut self, entity: Entity) -> Transform {
-        // Retrieve indices of removed entity and the one it's swapped with.
-        let (row, index) = self.indices.remove(&entity).unwrap();
-        debug_assert!(self.transforms[row].len() == self.entities[row].len());
-
-        // Remove transform and the associate entity.
-        let (removed_entity, _) = self.entities[row].swap_remove(index);
-        debug_assert!(removed_entity == entity);
+    /// Retrieves the root of the hierarchy containing the entity.
+    ///
+    /// A root transform is its own root. Returns `None` if the entity doesn't have a transform.
+    pub fn root_of(&self, entity: Entity) -> Option<Entity> {
+        let mut current = entity;
+        loop {
+            let &(row, index) = match self.indices.get(&current) {
+                Some(indices) => indices,
+                None => return None,
+            };
 
-        // Update the index mapping for the moved entity, but only if the one we removed
-        // wasn't the only one in the row (or the last one in the row).
-        if index != self.entities[row].len() {
-            let (moved_entity, _) = self.entities[row][index];
-            self.indices.insert(moved_entity, (row, index));
+            match self.entities[row][index].1 {
+                Some(parent) => current = parent,
+                None => return Some(current),
+            }
         }
+    }
 
-        // Defer removing the transform until the very end to avoid a bunch of memcpys.
-        // Transform is a pretty fat struct so if we remove it, cache it to a variable,
-        // and then return it at the end we wind up with 2 or 3 memcpys. Doing it all at
-        // once at the end (hopefully) means only a single memcpy.
-        self.transforms[row].swap_remove(index).into_inner()
+    /// Returns an iterator over every entity at the root of the transform hierarchy.
+    pub fn roots(&self) -> Roots {
+        Roots {
+            inner: self.entities[0].iter(),
+        }
     }
-}
 
-impl ComponentManager for TransformManager {
-    fn destroy_all(&self, entity: Entity) {
-        self.marked_for_destroy.borrow_mut().insert(entity);
+    /// Lists every entity with a transform, ordered so that each parent comes before its children.
+    ///
+    /// # Details
+    ///
+    /// Entities are listed by depth, every root first, then every child of a root, and so on.
+    /// This is the order `update_parallel()` and `for_each_mut()` use, so systems that need to
+    /// process a parent before its children can simply iterate the result. The relative order of
+    /// entities at the same depth is unspecified, so separate hierarchies are interleaved.
+    pub fn topological_order(&self) -> Vec<Entity> {
+        self.entities.iter()
+            .flat_map(|row| row.iter().map(|&(entity, _)| entity))
+            .collect()
     }
 
-    fn destroy_marked(&mut self) {
-        let mut marked_for_destroy = RefCell::new(HashSet::default());
-        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
-        let mut marked_for_destroy = marked_for_destroy.into_inner();
-        for entity in marked_for_destroy.drain() {
-            self.destroy_immediate(entity);
+    /// Invokes `callback` with each entity and a mutable reference to its transform.
+    ///
+    /// # Details
+    ///
+    /// Transforms are visited row by row, so parents are always visited before their children.
+    /// Each transform is only borrowed for the duration of the call to `callback`, so the
+    /// callback may freely access other transforms through `get()` and `get_mut()`. Attempting to
+    /// access the transform currently being visited (e.g. calling `get()` with the entity passed
+    /// to the callback) will panic because it is already mutably borrowed.
+    ///
+    /// It is safe to call `destroy()` from within the callback, destruction is deferred until the
+    /// end of the frame.
+    pub fn for_each_mut<F: FnMut(Entity, &mut Transform)>(&self, callback: &mut F) {
+        for (transform_row, entity_row) in self.transforms.iter().zip(self.entities.iter()) {
+            for (transform, &(entity, _)) in transform_row.iter().zip(entity_row.iter()) {
+                let mut transform = transform.borrow_mut();
+                callback(entity, &mut *transform);
+            }
         }
     }
-}
 
-thread_local!(static DUMMY_TRANSFORM: Transform = Transform::new());
+    /// Builds the bone matrix palette for the skeleton rooted at `root`.
+    ///
+    /// # Details
+    ///
+    /// Each bone's derived matrix is combined with its inverse bind matrix to produce the matrix
+    /// that transforms a vertex from bind-pose space to the bone's current pose, which is the data
+    /// a GPU skinning shader consumes. Bones are listed in the order they are visited by
+    /// `walk_hierarchy()` (depth-first starting at `root`), so the index of a bone's matrix in the
+    /// palette is stable as long as the hierarchy isn't modified.
+    ///
+    /// Bones that have no entry in `inverse_bind` use the identity matrix as their inverse bind
+    /// matrix and a warning is logged. The transforms in the hierarchy must be up to date.
+    pub fn bone_palette<S>(&self, root: Entity, inverse_bind: &HashMap<Entity, Matrix4, S>) -> Vec<Matrix4>
+        where S: HashState
+    {
+        let mut palette = Vec::new();
+        self.bone_palette_into(root, inverse_bind, &mut palette);
+        palette
+    }
 
-/// TODO: This should be module-level documentation.
-///
-/// A component representing the total transform (position, orientation,
-/// and scale) of an object in the world.
-///
-/// # Details
-///
-/// The `Transform` component is a fundamental part of the Gunship engine.
-/// It has the dual role of managing each individual entity's local transformation,
-/// as well as representing the individual nodes within the scene hierarchy.
-///
-/// ## Scene hierarchy
-///
-/// Each transform component may have one parent and any number of children. If a transform has
-/// a parent then its world transformation is the concatenation of its local transformation with
-/// its parent's world transformation. Using simple combinations of nested transforms can allow
-/// otherwise complex patterns of movement and positioning to be much easier to represent.
-///
-/// Transforms that have no parent are said to be at the root level and have the property
-/// that their local transformation is also their world transformation. If a transform is
-/// known to be at the root of the hierarchy it is recommended that its local values be modified
-/// directly to achieve best performance.
-#[derive(Debug, Clone)]
-pub struct Transform {
-    position:         Point,
-    rotation:         Quaternion,
-    scale:            Vector3,
-    local_matrix:     Cell<Matrix4>,
-    position_derived: Cell<Point>,
-    rotation_derived: Cell<Quaternion>,
-    scale_derived:    Cell<Vector3>,
-    matrix_derived:   Cell<Matrix4>,
-    out_of_date:      Cell<bool>,
-}
+    /// Builds the bone matrix palette for the skeleton rooted at `root` into `palette`.
+    ///
+    /// # Details
+    ///
+    /// `palette` is cleared before the bone matrices are written to it, so it ends up containing
+    /// exactly one matrix per bone regardless of its previous length. Reusing the same buffer
+    /// every frame means that once it has grown large enough for the skeleton no further
+    /// allocations are made. See `bone_palette()` for more information.
+    pub fn bone_palette_into<S>(
+        &self,
+        root: Entity,
+        inverse_bind: &HashMap<Entity, Matrix4, S>,
+        palette: &mut Vec<Matrix4>)
+        where S: HashState
+    {
+        palette.clear();
+        self.walk_hierarchy(root, &mut |entity, transform| {
+            let inverse_bind_matrix = match inverse_bind.get(&entity) {
+                Some(matrix) => *matrix,
+                None => {
+                    println!("WARNING: No inverse bind matrix for bone {:?}, using identity", entity);
+                    Matrix4::identity()
+                },
+            };
 
-impl Transform {
-    pub fn new() -> Transform {
-        Transform {
-            position:         Point::origin(),
-            rotation:         Quaternion::identity(),
-            scale:            Vector3::one(),
-            local_matrix:     Cell::new(Matrix4::identity()),
-            position_derived: Cell::new(Point::origin()),
-            rotation_derived: Cell::new(Quaternion::identity()),
-            scale_derived:    Cell::new(Vector3::one()),
-            matrix_derived:   Cell::new(Matrix4::identity()),
-            out_of_date:      Cell::new(false),
-        }
+            palette.push(transform.derived_matrix() * inverse_bind_matrix);
+        });
     }
 
-    pub fn position(&self) -> Point {
-        self.position
-    }
+    /// Transforms a world space point into the local space of `observer`.
+    ///
+    /// # Details
+    ///
+    /// Returns `None` if `observer` doesn't have a transform or if its derived matrix can't be
+    /// inverted (i.e. it has a zero scale component). The observer's transform must be up to
+    /// date.
+    pub fn local_point_in(&self, observer: Entity, world_point: Point) -> Option<Point> {
+        let &(row, index) = match self.indices.get(&observer) {
+            Some(indices) => indices,
+            None => return None,
+        };
 
-    pub fn set_position(&mut self, new_position: Point) {
-        self.position = new_position;
-        self.out_of_date.set(true);
+        let transform = self.transforms[row][index].borrow();
+        transform.derived_matrix().inverse().map(|inverse| world_point * inverse)
     }
 
-    pub fn rotation(&self) -> Quaternion {
-        self.rotation
-    }
+    /// Retrieves the position of `other` in the local space of `observer`.
+    ///
+    /// # Details
+    ///
+    /// Returns `None` if either entity doesn't have a transform. See `local_point_in()` for more
+    /// information.
+    pub fn point_relative_to(&self, observer: Entity, other: Entity) -> Option<Point> {
+        let &(row, index) = match self.indices.get(&other) {
+            Some(indices) => indices,
+            None => return None,
+        };
 
-    pub fn set_rotation(&mut self, new_rotation: Quaternion) {
-        self.rotation = new_rotation;
-        self.out_of_date.set(true);
+        let world_point = self.transforms[row][index].borrow().position_derived();
+        self.local_point_in(observer, world_point)
     }
 
-    pub fn scale(&self) -> Vector3 {
-        self.scale
-    }
+    /// Finds the candidate closest to `point`, returning it along with its distance from `point`.
+    ///
+    /// # Details
+    ///
+    /// Distances are measured to the candidates' derived positions, so the derived values must be
+    /// up to date. Candidates without a transform are skipped. If several candidates are equally
+    /// close the first one is returned. Returns `None` if none of the candidates have a transform.
+    ///
+    /// Every candidate is checked, so this is linear in the number of candidates.
+    pub fn nearest<I>(&self, point: Point, candidates: I) -> Option<(Entity, f32)>
+        where I: IntoIterator<Item = Entity>
+    {
+        let mut nearest = None;
+        for entity in candidates {
+            let &(row, index) = match self.indices.get(&entity) {
+                Some(indices) => indices,
+                None => continue,
+            };
 
-    pub fn set_scale(&mut self, new_scale: Vector3) {
-        self.scale = new_scale;
-        self.out_of_date.set(true);
+            let position = self.transforms[row][index].borrow().position_derived();
+            let distance_squared = Vector3::distance_squared(point.as_vector3(), position.as_vector3());
+            match nearest {
+                Some((_, nearest_distance_squared)) if nearest_distance_squared <= distance_squared => {},
+                _ => nearest = Some((entity, distance_squared)),
+            }
+        }
+
+        nearest.map(|(entity, distance_squared)| (entity, distance_squared.sqrt()))
     }
 
-    /// Retrieves the derived position of the transform.
+    /// Calculates the world-space bounds of `root` and all of its descendants.
     ///
-    /// In debug builds this method asserts if the transform is out of date.
-    pub fn position_derived(&self) -> Point {
-        assert!(!self.out_of_date.get());
+    /// # Details
+    ///
+    /// `local_bounds` contains the bounds of each entity in its own local space. Each entity's
+    /// bounds are transformed into world space by its derived matrix and merged into a single
+    /// AABB, so the derived matrices must be up to date. Entities without an entry in
+    /// `local_bounds` don't contribute to the result, and if none of the entities in the subtree
+    /// have bounds the result is empty (see `AABB::is_empty()`).
+    pub fn compute_subtree_bounds<S>(&self, root: Entity, local_bounds: &HashMap<Entity, AABB, S>) -> AABB
+        where S: HashState
+    {
+        let mut bounds = AABB::empty();
+        self.walk_children(root, &mut |entity| {
+            if let Some(local) = local_bounds.get(&entity) {
+                let world = local.transformed(self.get(entity).derived_matrix());
+                bounds = bounds.union(&world);
+            }
+        });
 
-        self.position_derived.get()
+        bounds
     }
 
-    /// Retrieves the derived rotation of the transform.
+    /// Finds the nearest entity whose bounds are hit by `ray`, returning the entity and the
+    /// distance along the ray to the hit.
     ///
-    /// In debug builds this method asserts if the transform is out of date.
-    pub fn rotation_derived(&self) -> Quaternion {
-        assert!(!self.out_of_date.get());
+    /// # Details
+    ///
+    /// `local_bounds` contains the bounds of each pickable entity in its own local space, as for
+    /// `compute_subtree_bounds()`. Rather than transforming the bounds into world space (which
+    /// would loosen them for rotated entities), the ray is transformed into each entity's local
+    /// space by the inverse of its derived matrix and tested against the exact local bounds, so
+    /// the derived matrices must be up to date. Entities without a transform, with empty bounds,
+    /// or with a derived matrix that can't be inverted (e.g. a zero scale) are ignored.
+    ///
+    /// A ray that starts inside an entity's bounds hits it at a distance of 0. Distances are
+    /// measured along `ray.direction`, so they're in world units when the direction is normalized.
+    pub fn raycast<S>(&self, ray: &Ray, local_bounds: &HashMap<Entity, AABB, S>) -> Option<(Entity, f32)>
+        where S: HashState
+    {
+        let mut nearest = None;
+        for (&entity, bounds) in local_bounds {
+            let &(row, index) = match self.indices.get(&entity) {
+                Some(indices) => indices,
+                None => continue,
+            };
 
-        self.rotation_derived.get()
+            let inverse = match self.transforms[row][index].borrow().derived_matrix().inverse() {
+                Some(inverse) => inverse,
+                None => continue,
+            };
+
+            // The ray's parameter is unchanged by the transformation, so a hit in local space is
+            // at the same distance along the world ray.
+            let local_ray = Ray {
+                origin: ray.origin * inverse,
+                direction: ray.direction * Matrix3::from_matrix4(&inverse),
+            };
+
+            let distance = match bounds.intersect_ray(&local_ray) {
+                Some(distance) => distance,
+                None => continue,
+            };
+
+            match nearest {
+                Some((_, nearest_distance)) if nearest_distance <= distance => {},
+                _ => nearest = Some((entity, distance)),
+            }
+        }
+
+        nearest
     }
 
-    /// Retrieves the derived scale of the transform.
+    /// Writes the transform hierarchy to a human-readable text format.
     ///
-    /// In debug builds this method asserts if the transform is out of date.
-    pub fn scale_derived(&self) -> Vector3 {
-        assert!(!self.out_of_date.get());
+    /// # Details
+    ///
+    /// Each transform is written on its own line as:
+    ///
+    /// ```text
+    /// id parent pos_x pos_y pos_z rot_w rot_x rot_y rot_z scale_x scale_y scale_z
+    /// ```
+    ///
+    /// where `id` and `parent` are entity IDs (see `Entity::id()`) and `parent` is `-` for
+    /// transforms at the root of the hierarchy. The output starts with a comment line describing
+    /// the columns. Use `from_text()` to load the hierarchy back.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# id parent pos_x pos_y pos_z rot_w rot_x rot_y rot_z scale_x scale_y scale_z\n");
+        for (transform_row, entity_row) in self.transforms.iter().zip(self.entities.iter()) {
+            for (transform, &(entity, parent)) in transform_row.iter().zip(entity_row.iter()) {
+                let transform = transform.borrow();
+                let parent = match parent {
+                    Some(parent) => parent.id().to_string(),
+                    None => String::from("-"),
+                };
+                let position = transform.position();
+                let rotation = transform.rotation();
+                let scale = transform.scale();
+
+                text.push_str(&format!(
+                    "{} {} {} {} {} {} {} {} {} {} {} {}\n",
+                    entity.id(), parent,
+                    position.x, position.y, position.z,
+                    rotation.w, rotation.x, rotation.y, rotation.z,
+                    scale.x, scale.y, scale.z));
+            }
+        }
 
-        self.scale_derived.get()
+        text
     }
 
-    /// Retrieves the composite matrix representing the local transform.
+    /// Loads a transform hierarchy from the text format written by `to_text()`.
     ///
     /// # Details
     ///
-    /// The composite matrix combines the affine matrices representing translation,
-    /// scale, and rotation into a single transformation matrix. The local maxtrix does
-    /// not include the parent's transformation. The local matrix transforms a local point
-    /// into the parent's coordinate system.
-    pub fn local_matrix(&self) -> Matrix4 {
-        if self.out_of_date.get() {
-            let local_matrix =
-                Matrix4::from_point(self.position)
-                * (self.rotation.as_matrix4() * Matrix4::from_scale_vector(self.scale));
-            self.local_matrix.set(local_matrix);
+    /// Blank lines and lines starting with `#` are ignored. Transforms may be listed in any
+    /// order, a transform's parent doesn't need to appear before it. Rotations are normalized
+    /// when loaded, so hand-edited values don't need to be exactly unit length.
+    pub fn from_text(text: &str) -> Result<TransformManager, ParseError> {
+        struct Record {
+            line: usize,
+            entity: Entity,
+            parent: Option<Entity>,
+            position: Point,
+            rotation: Quaternion,
+            scale: Vector3,
         }
 
-        self.local_matrix.get()
+        // First pass: Parse every line so that parents can be resolved regardless of order.
+        let mut records = Vec::new();
+        let mut parents = HashMap::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("#") {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 12 {
+                return Err(ParseError::WrongFieldCount { line: line_number, count: fields.len() });
+            }
+
+            let invalid_value = |field| ParseError::InvalidValue { line: line_number, field: field };
+
+            let entity = Entity::from_id(try!(fields[0].parse().map_err(|_| invalid_value(0))));
+            let parent = match fields[1] {
+                "-" => None,
+                parent => Some(Entity::from_id(try!(parent.parse().map_err(|_| invalid_value(1))))),
+            };
+
+            let mut values = [0.0; 10];
+            for (field_index, (value, field)) in values.iter_mut().zip(&fields[2..]).enumerate() {
+                *value = try!(field.parse::<f32>().map_err(|_| invalid_value(field_index + 2)));
+            }
+
+            let rotation = Quaternion { w: values[3], x: values[4], y: values[5], z: values[6] };
+            if rotation.is_zero() || !rotation.is_finite() {
+                return Err(ParseError::InvalidRotation { line: line_number });
+            }
+
+            if parents.insert(entity, parent).is_some() {
+                return Err(ParseError::DuplicateEntity { line: line_number, id: entity.id() });
+            }
+
+            records.push(Record {
+                line: line_number,
+                entity: entity,
+                parent: parent,
+                position: Point::new(values[0], values[1], values[2]),
+                rotation: rotation.normalized(),
+                scale: Vector3::new(values[7], values[8], values[9]),
+            });
+        }
+
+        // Validate that every parent exists and that the hierarchy doesn't contain any cycles.
+        for record in &records {
+            let mut ancestor = record.parent;
+            let mut depth = 0;
+            while let Some(parent) = ancestor {
+                ancestor = match parents.get(&parent) {
+                    Some(&grandparent) => grandparent,
+                    None => return Err(ParseError::MissingParent { line: record.line, parent: parent.id() }),
+                };
+
+                depth += 1;
+                if depth > records.len() {
+                    return Err(ParseError::ParentCycle { line: record.line });
+                }
+            }
+        }
+
+        // Second pass: Create all of the transforms, then link them once they all exist.
+        let mut transform_manager = TransformManager::new();
+        for record in &records {
+            let mut transform = transform_manager.assign(record.entity);
+            transform.set_position(record.position);
+            transform.set_rotation(record.rotation);
+            transform.set_scale(record.scale);
+        }
+
+        for record in &records {
+            if let Some(parent) = record.parent {
+                transform_manager.set_child(parent, record.entity);
+            }
+        }
+
+        Ok(transform_manager)
     }
 
-    pub fn derived_matrix(&self) -> Matrix4 {
-        assert!(!self.out_of_date.get());
+    /// Updates the derived values of every transform, splitting the work across up to
+    /// `num_threads` threads.
+    ///
+    /// # Details
+    ///
+    /// Each root-level transform and all of its descendants form an independent subtree, so the
+    /// subtrees are distributed between the threads and each thread only updates the transforms
+    /// it owns. Subtrees are assigned largest first to whichever thread has the least work so far.
+    /// A single subtree is never split between threads, so if the hierarchy can't be divided
+    /// between at least two threads (e.g. everything is parented to a single root, or
+    /// `num_threads` is less than 2) the transforms are updated sequentially on the calling thread
+    /// instead. The results are identical to those of `transform_update()` either way.
+    ///
+    /// Every transform is borrowed for the duration of the update, so this will panic if any
+    /// transform is currently mutably borrowed.
+    ///
+    /// The worker threads are started for this call and stopped before it returns, use
+    /// `update_parallel_with()` to reuse the same threads between updates.
+    pub fn update_parallel(&self, num_threads: usize) {
+        if num_threads < 2 || self.entities.get(0).map_or(0, |row| row.len()) < 2 {
+            self.update_sequential();
+            return;
+        }
 
-        self.matrix_derived.get()
+        let workers = TransformWorkers::new(num_threads - 1);
+        self.update_parallel_with(&workers);
     }
 
-    pub fn derived_normal_matrix(&self) -> Matrix4 {
-        assert!(!self.out_of_date.get());
+    /// Updates the derived values of every transform using a reusable set of worker threads.
+    ///
+    /// # Details
+    ///
+    /// This works the same way as `update_parallel()`, with the work divided between the
+    /// calling thread and each of the workers. `update_parallel()` has to start new threads each
+    /// time it's called, so code that updates the hierarchy every frame (like
+    /// `ParallelTransformUpdate`) should keep a `TransformWorkers` around and use this instead.
+    ///
+    /// Every worker is guaranteed to be done with the transforms before this returns, even if
+    /// updating a transform panics.
+    pub fn update_parallel_with(&self, workers: &TransformWorkers) {
+        if workers.len() == 0 || self.entities.get(0).map_or(0, |row| row.len()) < 2 {
+            self.update_sequential();
+            return;
+        }
 
-        let inverse =
-            Matrix4::from_scale_vector(1.0 / self.scale_derived.get())
-          * (self.rotation_derived.get().as_matrix4().transpose()
-          *  Matrix4::from_point(-self.position_derived.get()));
+        self.bump_generation();
+
+        // Borrow every transform up front. The worker threads access the transforms through raw
+        // pointers, so holding the borrows keeps anyone else from modifying them in the meantime.
+        let borrows: Vec<Vec<Ref<Transform>>> =
+            self.transforms
+            .iter()
+            .map(|row| row.iter().map(|transform| transform.borrow()).collect())
+            .collect();
+
+        // Group the transforms by subtree. Rows are visited in order so each subtree lists its
+        // transforms parents-first, the same order used by the sequential update.
+        let mut subtrees: Vec<Vec<TransformUpdateJob>> = Vec::new();
+        let mut subtree_of: EntityMap<usize> = HashMap::default();
+        let mut moved = HashSet::default();
+        for (row, (borrow_row, entity_row)) in borrows.iter().zip(self.entities.iter()).enumerate() {
+            for (transform, &(entity, parent)) in borrow_row.iter().zip(entity_row.iter()) {
+                debug_assert!(
+                    parent.is_some() || row == 0,
+                    "Transform for entity {:?} is not at the root level but has no parent", entity);
+
+                let (subtree, parent_transform) = match parent {
+                    None => {
+                        subtrees.push(Vec::new());
+                        (subtrees.len() - 1, None)
+                    },
+                    Some(parent) => {
+                        let &(parent_row, parent_index) = self.indices.get(&parent).expect("Parent transform does not exist");
+                        (subtree_of[&parent], Some(&*borrows[parent_row][parent_index] as *const Transform))
+                    },
+                };
+
+                self.record_moved(&mut moved, entity, parent, transform.out_of_date.get());
+
+                subtree_of.insert(entity, subtree);
+                subtrees[subtree].push(TransformUpdateJob {
+                    entity: entity,
+                    transform: &**transform,
+                    parent: parent_transform,
+                });
+            }
+        }
 
-        inverse.transpose()
+        // One partition for each worker plus one for the calling thread.
+        let mut partitions: Vec<Vec<TransformUpdateJob>> = (0..workers.len() + 1).map(|_| Vec::new()).collect();
+        subtrees.sort_by(|first, second| second.len().cmp(&first.len()));
+        for subtree in subtrees {
+            let partition = partitions.iter_mut().min_by_key(|partition| partition.len()).unwrap();
+            partition.extend(subtree);
+        }
+
+        // Declared after `borrows` so that it's dropped first: if anything below panics the
+        // guard still waits for the workers before the borrows they rely on are released.
+        let mut pending = PendingBatches {
+            done: Vec::new(),
+        };
+
+        // The calling thread takes the first partition. If a worker has died (because it panicked
+        // during an earlier update) its partition is processed on this thread instead.
+        let mut partitions = partitions.into_iter();
+        let mut local_jobs = partitions.next().unwrap();
+        for (jobs, sender) in partitions.zip(workers.senders.iter()) {
+            if jobs.is_empty() {
+                continue;
+            }
+
+            let (done_sender, done_receiver) = mpsc::channel();
+            match sender.send((jobs, done_sender)) {
+                Ok(()) => pending.done.push(done_receiver),
+                Err(SendError((jobs, _))) => local_jobs.extend(jobs),
+            }
+        }
+
+        for job in local_jobs {
+            unsafe { job.run(); }
+        }
+
+        let all_succeeded = pending.wait();
+        drop(borrows);
+        assert!(all_succeeded, "Transform update worker thread panicked");
     }
 
-    pub fn translate(&mut self, translation: Vector3) {
-        self.position = self.position + translation;
-        self.out_of_date.set(true);
+    /// Updates the derived values of every transform on the calling thread.
+    fn update_sequential(&self) {
+        self.bump_generation();
+        let mut moved = HashSet::default();
+        for (transform_row, entity_row) in self.transforms.iter().zip(self.entities.iter()) {
+            for (transform, &(entity, parent)) in transform_row.iter().zip(entity_row.iter()) {
+                debug_assert!(
+                    parent.is_some() || self.indices.get(&entity).map_or(false, |&(row, _)| row == 0),
+                    "Transform for entity {:?} is not at the root level but has no parent", entity);
+
+                self.record_moved(&mut moved, entity, parent, transform.borrow().out_of_date.get());
+
+                // Retrieve the parent's transformation matrix, using the identity
+                // matrix if the transform has no parent.
+                match parent {
+                    None => {
+                        DUMMY_TRANSFORM.with(|parent| {
+                            transform.borrow().update(entity, parent);
+                        });
+                    },
+                    Some(parent) => {
+                        let parent_transform = self.get(parent);
+                        transform.borrow().update(entity, &*parent_transform);
+                    }
+                };
+            }
+        }
     }
 
-    pub fn rotate(&mut self, rotation: Quaternion) {
-        self.rotation = self.rotation * rotation;
-        self.out_of_date.set(true);
+    /// Marks the transform associated with the entity for destruction.
+    ///
+    /// # Details
+    ///
+    /// Components marked for destruction are destroyed at the end of every frame. It can be used
+    /// to destroy components without needing a mutable borrow on the component manager.
+    ///
+    /// TODO: Actually support deferred destruction.
+    pub fn destroy(&self, entity: Entity) {
+        let mut marked_for_destroy = self.marked_for_destroy.borrow_mut();
+        marked_for_destroy.insert(entity); // TODO: Warning, error if entity has already been marked?
     }
 
-    pub fn look_at(&mut self, interest: Point, up: Vector3) {
-        let forward = interest - self.position;
-        self.rotation = Quaternion::look_rotation(forward, up);
-        self.out_of_date.set(true);
+    pub fn destroy_immediate(&mut self, entity: Entity) {
+        self.remove(entity);
+        self.children.remove(&entity);
+        self.scene_ids.remove(&entity);
+        self.push_event(TransformEvent::TransformDestroyed(entity));
+        self.bump_generation();
     }
 
-    pub fn look_direction(&mut self, forward: Vector3, up: Vector3) {
-        self.rotation = Quaternion::look_rotation(forward, up);
-        self.out_of_date.set(true);
+    /// Copies the derived matrix and position of every transform into a `TransformSnapshot`.
+    ///
+    /// # Details
+    ///
+    /// The snapshot holds the values calculated by the most recent update, so it should be taken
+    /// once after the transform update each frame and then shared by the systems that only read
+    /// transforms. Modifying a transform doesn't change its derived values until the next
+    /// update, but updating the hierarchy or adding or removing transforms makes the snapshot
+    /// stale, see `is_snapshot_current()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any transform is currently mutably borrowed.
+    pub fn read_snapshot(&self) -> TransformSnapshot {
+        let mut snapshot = TransformSnapshot {
+            entities: Vec::new(),
+            matrices: Vec::new(),
+            positions: Vec::new(),
+            indices: HashMap::default(),
+            generation: 0,
+        };
+        self.fill_snapshot(&mut snapshot);
+        snapshot
     }
 
-    pub fn forward(&self) -> Vector3 {
-        let matrix = Matrix3::from_quaternion(self.rotation);
-        -matrix.z_part()
+    /// Checks if `snapshot` still matches the transforms, i.e. no transforms have been added,
+    /// removed, or updated since it was taken.
+    pub fn is_snapshot_current(&self, snapshot: &TransformSnapshot) -> bool {
+        snapshot.generation == self.generation.get()
     }
 
-    pub fn right(&self) -> Vector3 {
-        let matrix = Matrix3::from_quaternion(self.rotation);
-        matrix.x_part()
+    /// Regenerates `snapshot` if it's stale, reusing its storage.
+    pub fn refresh_snapshot(&self, snapshot: &mut TransformSnapshot) {
+        if !self.is_snapshot_current(snapshot) {
+            self.fill_snapshot(snapshot);
+        }
     }
 
-    pub fn up(&self) -> Vector3 {
-        let matrix = Matrix3::from_quaternion(self.rotation);
-        matrix.y_part()
+    fn fill_snapshot(&self, snapshot: &mut TransformSnapshot) {
+        snapshot.entities.clear();
+        snapshot.matrices.clear();
+        snapshot.positions.clear();
+        snapshot.indices.clear();
+
+        for (transform_row, entity_row) in self.transforms.iter().zip(self.entities.iter()) {
+            for (transform, &(entity, _)) in transform_row.iter().zip(entity_row.iter()) {
+                let transform = borrow_transform(transform, entity);
+                snapshot.indices.insert(entity, snapshot.entities.len());
+                snapshot.entities.push(entity);
+                snapshot.matrices.push(transform.matrix_derived.get());
+                snapshot.positions.push(transform.position_derived.get());
+            }
+        }
+
+        snapshot.generation = self.generation.get();
     }
 
-    /// Updates the local and derived matrices for the transform.
-    fn update(&self, parent: &Transform) {
-        let local_matrix = self.local_matrix();
+    fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
 
-        let derived_matrix = parent.derived_matrix() * local_matrix;
-        self.matrix_derived.set(derived_matrix);
+    /// Emits `TransformMoved` for the entity if its transform was modified or its parent moved.
+    ///
+    /// Must be called for parents before their children, `moved` tracks the entities that have
+    /// moved so far during the current update.
+    fn record_moved(&self, moved: &mut EntitySet, entity: Entity, parent: Option<Entity>, out_of_date: bool) {
+        if out_of_date || parent.map_or(false, |parent| moved.contains(&parent)) {
+            moved.insert(entity);
+            self.push_event(TransformEvent::TransformMoved(entity));
+        }
+    }
 
-        self.position_derived.set(derived_matrix.translation_part());
-        self.rotation_derived.set(parent.rotation_derived() * self.rotation);
-        self.scale_derived.set(self.scale * parent.scale_derived());
+    /// Checks the consistency of the manager's internal bookkeeping, panicking if any of the
+    /// checks fail.
+    ///
+    /// # Details
+    ///
+    /// This is meant for tests, it walks every transform so it's too slow to call routinely.
+    /// The checks are:
+    ///
+    /// - Each row of transforms is the same length as its row of entities. A mismatch means a
+    ///   transform was added or removed without its entity (or vice versa), so the transforms
+    ///   no longer line up with the entities that own them.
+    /// - Every entry in the index map points at the entity it belongs to, and every entity in the
+    ///   rows has exactly one entry. Stale indices (e.g. after a swap remove) would make `get()`
+    ///   return another entity's transform.
+    /// - Root transforms have no parent and every other transform's parent exists exactly one row
+    ///   up. This guarantees that parents are always updated before their children, and catches
+    ///   orphans left behind when a parent is destroyed without its children.
+    /// - The child lists match the parents recorded in the rows, and no empty lists are kept.
+    ///   Otherwise the `walk_*` functions would miss or revisit children.
+    /// - Every transform belongs to a scene, and children belong to the same scene as their
+    ///   parent, which `destroy_scene()` relies on to destroy whole hierarchies.
+    ///
+    /// The invariants hold as long as destroying a transform also destroys its children, as
+    /// `Scene::destroy_entity()` does.
+    pub fn assert_invariants(&self) {
+        assert!(
+            self.transforms.len() == self.entities.len(),
+            "There are {} rows of transforms but {} rows of entities",
+            self.transforms.len(),
+            self.entities.len());
+
+        let mut count = 0;
+        for (row, (transform_row, entity_row)) in self.transforms.iter().zip(self.entities.iter()).enumerate() {
+            assert!(
+                transform_row.len() == entity_row.len(),
+                "Row {} has {} transforms but {} entities",
+                row,
+                transform_row.len(),
+                entity_row.len());
+
+            for (index, &(entity, parent)) in entity_row.iter().enumerate() {
+                count += 1;
+
+                assert!(
+                    self.indices.get(&entity) == Some(&(row, index)),
+                    "Entity {:?} is at {:?} but its index is {:?}",
+                    entity,
+                    (row, index),
+                    self.indices.get(&entity));
+
+                match parent {
+                    None => assert!(row == 0, "Entity {:?} in row {} has no parent", entity, row),
+                    Some(parent) => {
+                        let parent_row = self.indices.get(&parent).map(|&(parent_row, _)| parent_row);
+                        assert!(
+                            row > 0 && parent_row == Some(row - 1),
+                            "Entity {:?} in row {} has parent {:?} in row {:?}",
+                            entity,
+                            row,
+                            parent,
+                            parent_row);
+
+                        let listed = self.children.get(&parent).map_or(false, |children| children.as_slice().contains(&entity));
+                        assert!(listed, "Entity {:?} is missing from the child list of its parent {:?}", entity, parent);
+
+                        assert!(
+                            self.scene_ids.get(&entity) == self.scene_ids.get(&parent),
+                            "Entity {:?} is in scene {:?} but its parent {:?} is in scene {:?}",
+                            entity,
+                            self.scene_ids.get(&entity),
+                            parent,
+                            self.scene_ids.get(&parent));
+                    },
+                }
 
-        self.out_of_date.set(false);
+                assert!(self.scene_ids.contains_key(&entity), "Entity {:?} doesn't belong to a scene", entity);
+            }
+        }
+
+        assert!(
+            self.indices.len() == count,
+            "There are {} transforms but {} indices",
+            count,
+            self.indices.len());
+        assert!(
+            self.scene_ids.len() == count,
+            "There are {} transforms but {} scene IDs",
+            count,
+            self.scene_ids.len());
+
+        for (&parent, children) in &self.children {
+            assert!(!children.is_empty(), "Entity {:?} has an empty child list", parent);
+            for &child in children.as_slice() {
+                let recorded_parent = self.indices.get(&child).map(|&(row, index)| self.entities[row][index].1);
+                assert!(
+                    recorded_parent == Some(Some(parent)),
+                    "Entity {:?} is in the child list of {:?} but its parent is {:?}",
+                    child,
+                    parent,
+                    recorded_parent);
+            }
+        }
     }
-}
 
-pub fn transform_update(scene: &Scene, _: f32) {
-    let _stopwatch = Stopwatch::new("transform update");
+    // Removes and returns the transform associated with the given entity.
+    //
+    // # Details
+    //
+    // NOTE: This does not handle updating/removing children. So be warned. The entity is removed
+    // from its parent's child list, but its own child list is left alone.
+    fn remove(&mut self, entity: Entity) -> Transform {
+        // Retrieve indices of removed entity and the one it's swapped with.
+        let (row, index) = self.indices.remove(&entity).unwrap();
+        debug_assert!(self.transforms[row].len() == self.entities[row].len());
 
-    let transform_manager = scene.get_manager::<TransformManager>();
+        // Remove transform and the associate entity.
+        let (removed_entity, parent) = self.entities[row].swap_remove(index);
+        debug_assert!(removed_entity == entity);
 
-    for (transform_row, entity_row) in transform_manager.transforms.iter().zip(transform_manager.entities.iter()) {
-        for (transform, &(_, parent)) in transform_row.iter().zip(entity_row.iter()) {
-            // Retrieve the parent's transformation matrix, using the identity
-            // matrix if the transform has no parent.
-            match parent {
-                None => {
-                    DUMMY_TRANSFORM.with(|parent| {
-                        transform.borrow().update(parent);
-                    });
+        if let Some(parent) = parent {
+            let now_empty = match self.children.get_mut(&parent) {
+                Some(children) => {
+                    children.remove(entity);
+                    children.is_empty()
                 },
-                Some(parent) => {
-                    let parent_transform = transform_manager.get(parent);
-                    transform.borrow().update(&*parent_transform);
-                }
+                None => false,
             };
+
+            if now_empty {
+                self.children.remove(&parent);
+            }
         }
-    }
+
+        // Update the index mapping for the moved entity, but only if the one we removed
+        // wasn't the only one in the row (or the last one in the row).
+        if index != self.entities[row].len() {
+            let (moved_entity, _) = self.entities[row][index];
+            self.indices.insert(moved_entity, (row, index));
+        }
+
+        // Defer removing the transform until the very end to avoid a bunch of memcpys.
+        // Transform is a pretty fat struct so if we remove it, cache it to a variable,
+        // and then return it at the end we wind up with 2 or 3 memcpys. Doing it all at
+        // once at the end (hopefully) means only a single memcpy.
+        self.transforms[row].swap_remove(index).into_inner()
+    }
+}
+
+impl ComponentManager for TransformManager {
+    fn destroy_all(&self, entity: Entity) {
+        self.marked_for_destroy.borrow_mut().insert(entity);
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.indices.contains_key(&entity)
+    }
+
+    fn destroy_marked(&mut self) {
+        let mut marked_for_destroy = RefCell::new(HashSet::default());
+        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
+        let mut marked_for_destroy = marked_for_destroy.into_inner();
+        for entity in marked_for_destroy.drain() {
+            self.destroy_immediate(entity);
+        }
+    }
+}
+
+impl Serialize for TransformManager {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        let convention = match self.convention {
+            CoordinateConvention::YUp => 0u8,
+            CoordinateConvention::ZUp => 1u8,
+        };
+        let scale_constraint = match self.scale_constraint {
+            ScaleConstraint::Free => 0u8,
+            ScaleConstraint::UniformAverage => 1u8,
+            ScaleConstraint::UniformAssert => 2u8,
+        };
+        convention.serialize(bytes);
+        scale_constraint.serialize(bytes);
+
+        // Transforms are written a row at a time, so every parent is written before its children.
+        (self.indices.len() as u32).serialize(bytes);
+        for (transform_row, entity_row) in self.transforms.iter().zip(self.entities.iter()) {
+            for (transform, &(entity, parent)) in transform_row.iter().zip(entity_row.iter()) {
+                let transform = transform.borrow();
+                entity.serialize(bytes);
+                parent.serialize(bytes);
+                self.scene_id(entity).serialize(bytes);
+                transform.position().serialize(bytes);
+                transform.rotation().serialize(bytes);
+                transform.scale().serialize(bytes);
+            }
+        }
+    }
+}
+
+impl Deserialize for TransformManager {
+    fn deserialize(reader: &mut ByteReader) -> Result<TransformManager, SerializeError> {
+        let mut transform_manager = TransformManager::new();
+
+        let offset = reader.offset();
+        let convention = match try!(u8::deserialize(reader)) {
+            0 => CoordinateConvention::YUp,
+            1 => CoordinateConvention::ZUp,
+            _ => return Err(SerializeError::InvalidData { offset: offset }),
+        };
+        let offset = reader.offset();
+        let scale_constraint = match try!(u8::deserialize(reader)) {
+            0 => ScaleConstraint::Free,
+            1 => ScaleConstraint::UniformAverage,
+            2 => ScaleConstraint::UniformAssert,
+            _ => return Err(SerializeError::InvalidData { offset: offset }),
+        };
+        transform_manager.set_coordinate_convention(convention);
+
+        let count = try!(u32::deserialize(reader));
+        for _ in 0..count {
+            let offset = reader.offset();
+            let entity = try!(Entity::deserialize(reader));
+            let parent: Option<Entity> = try!(Deserialize::deserialize(reader));
+            let scene_id = try!(u32::deserialize(reader));
+            let position = try!(Point::deserialize(reader));
+            let rotation = try!(Quaternion::deserialize(reader));
+            let scale = try!(Vector3::deserialize(reader));
+
+            // Parents are always written before their children, so the parent must already exist.
+            let parent_missing = parent.map_or(false, |parent| !transform_manager.contains(parent));
+            if transform_manager.contains(entity) || parent_missing {
+                return Err(SerializeError::InvalidData { offset: offset });
+            }
+
+            {
+                let mut transform = transform_manager.assign(entity);
+                transform.set_position(position);
+                transform.set_rotation(rotation);
+                transform.set_scale(scale);
+            }
+
+            match parent {
+                Some(parent) => transform_manager.set_child(parent, entity),
+                None => transform_manager.assign_to_scene(entity, scene_id),
+            }
+        }
+
+        // The saved scales are restored as-is, they may predate the constraint, so the constraint
+        // is only applied once they've all been set.
+        transform_manager.set_scale_constraint(scale_constraint);
+
+        Ok(transform_manager)
+    }
+}
+
+/// Borrows the entity's transform, naming the entity in the panic message if the transform is
+/// already mutably borrowed.
+///
+/// The check is only done in debug builds, release builds use the default `RefCell` panic.
+#[cfg(debug_assertions)]
+fn borrow_transform(transform: &RefCell<Transform>, entity: Entity) -> Ref<Transform> {
+    if let BorrowState::Writing = transform.borrow_state() {
+        panic!("Transform for entity {} is already mutably borrowed", entity.id());
+    }
+
+    transform.borrow()
+}
+
+#[cfg(not(debug_assertions))]
+fn borrow_transform(transform: &RefCell<Transform>, _entity: Entity) -> Ref<Transform> {
+    transform.borrow()
+}
+
+/// Mutably borrows the entity's transform, naming the entity in the panic message if the
+/// transform is already borrowed.
+///
+/// The check is only done in debug builds, release builds use the default `RefCell` panic.
+#[cfg(debug_assertions)]
+fn borrow_transform_mut(transform: &RefCell<Transform>, entity: Entity) -> RefMut<Transform> {
+    match transform.borrow_state() {
+        BorrowState::Reading => panic!("Transform for entity {} is already immutably borrowed", entity.id()),
+        BorrowState::Writing => panic!("Transform for entity {} is already mutably borrowed", entity.id()),
+        BorrowState::Unused => {},
+    }
+
+    transform.borrow_mut()
+}
+
+#[cfg(not(debug_assertions))]
+fn borrow_transform_mut(transform: &RefCell<Transform>, _entity: Entity) -> RefMut<Transform> {
+    transform.borrow_mut()
+}
+
+thread_local!(static DUMMY_TRANSFORM: Transform = Transform::new());
+
+// Counts how many local matrices are built with the general (non-translation) path, so tests can
+// check that the translation-only fast path is taken.
+#[cfg(test)]
+thread_local!(static GENERAL_LOCAL_MATRICES: Cell<usize> = Cell::new(0));
+
+#[cfg(test)]
+fn count_general_local_matrix() {
+    GENERAL_LOCAL_MATRICES.with(|count| count.set(count.get() + 1));
+}
+
+#[cfg(not(test))]
+fn count_general_local_matrix() {}
+
+/// The convention for which local axes represent a transform's forward, up, and right directions.
+///
+/// # Details
+///
+/// Gunship natively uses a right-handed, Y-up coordinate system where the forward direction is
+/// -Z. Assets authored in Z-up tools (e.g. Blender and some CAD tools) instead treat +Z as up and
+/// +Y as forward. In both conventions +X is right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateConvention {
+    /// Y-up, -Z forward. This is the default.
+    YUp,
+
+    /// Z-up, +Y forward.
+    ZUp,
+}
+
+impl CoordinateConvention {
+    /// The local axis that represents forward under this convention.
+    pub fn forward_axis(self) -> Vector3 {
+        match self {
+            CoordinateConvention::YUp => Vector3::new(0.0, 0.0, -1.0),
+            CoordinateConvention::ZUp => Vector3::new(0.0, 1.0, 0.0),
+        }
+    }
+
+    /// The local axis that represents up under this convention.
+    pub fn up_axis(self) -> Vector3 {
+        match self {
+            CoordinateConvention::YUp => Vector3::new(0.0, 1.0, 0.0),
+            CoordinateConvention::ZUp => Vector3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    /// The local axis that represents right under this convention.
+    pub fn right_axis(self) -> Vector3 {
+        Vector3::new(1.0, 0.0, 0.0)
+    }
+
+    /// The rotation that maps this convention's forward and up axes onto the engine's native
+    /// forward (-Z) and up (+Y) axes.
+    pub fn basis_rotation(self) -> Quaternion {
+        match self {
+            CoordinateConvention::YUp => Quaternion::identity(),
+            CoordinateConvention::ZUp => Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), -PI * 0.5),
+        }
+    }
+}
+
+/// Restricts the scales that can be assigned to a transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleConstraint {
+    /// Any scale is allowed. This is the default.
+    Free,
+
+    /// Non-uniform scales are replaced with a uniform scale of the average of their components.
+    UniformAverage,
+
+    /// Setting a non-uniform scale panics in debug builds. Release builds average the components
+    /// like `UniformAverage`.
+    UniformAssert,
+}
+
+impl ScaleConstraint {
+    /// Applies the constraint to `scale`, returning the scale that should be used.
+    pub fn apply(self, scale: Vector3) -> Vector3 {
+        let is_uniform = (scale.x - scale.y).is_zero() && (scale.x - scale.z).is_zero();
+        if self == ScaleConstraint::Free || is_uniform {
+            return scale;
+        }
+
+        debug_assert!(
+            self != ScaleConstraint::UniformAssert,
+            "Non-uniform scale {:?} assigned to a transform that requires uniform scale", scale);
+
+        let average = (scale.x + scale.y + scale.z) / 3.0;
+        Vector3::new(average, average, average)
+    }
+}
+
+/// The ways in which loading a transform hierarchy with `TransformManager::from_text()` can fail.
+///
+/// Line numbers start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The line doesn't have exactly 12 fields.
+    WrongFieldCount {
+        line: usize,
+        count: usize,
+    },
+
+    /// A field couldn't be parsed. `field` is the zero-based index of the field in the line.
+    InvalidValue {
+        line: usize,
+        field: usize,
+    },
+
+    /// The rotation has zero length or isn't finite, so it can't be normalized.
+    InvalidRotation {
+        line: usize,
+    },
+
+    /// More than one line has the same entity ID.
+    DuplicateEntity {
+        line: usize,
+        id: u32,
+    },
+
+    /// The parent entity isn't listed anywhere in the text.
+    MissingParent {
+        line: usize,
+        parent: u32,
+    },
+
+    /// The transform is its own ancestor.
+    ParentCycle {
+        line: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            ParseError::WrongFieldCount { line, count } => write!(formatter, "line {}: expected 12 fields but found {}", line, count),
+            ParseError::InvalidValue { line, field } => write!(formatter, "line {}: field {} is not a valid value", line, field),
+            ParseError::InvalidRotation { line } => write!(formatter, "line {}: rotation must be finite and non-zero", line),
+            ParseError::DuplicateEntity { line, id } => write!(formatter, "line {}: entity {} is listed more than once", line, id),
+            ParseError::MissingParent { line, parent } => write!(formatter, "line {}: parent entity {} is not listed", line, parent),
+            ParseError::ParentCycle { line } => write!(formatter, "line {}: transform is its own ancestor", line),
+        }
+    }
+}
+
+/// TODO: This should be module-level documentation.
+///
+/// A component representing the total transform (position, orientation,
+/// and scale) of an object in the world.
+///
+/// # Details
+///
+/// The `Transform` component is a fundamental part of the Gunship engine.
+/// It has the dual role of managing each individual entity's local transformation,
+/// as well as representing the individual nodes within the scene hierarchy.
+///
+/// ## Scene hierarchy
+///
+/// Each transform component may have one parent and any number of children. If a transform has
+/// a parent then its world transformation is the concatenation of its local transformation with
+/// its parent's world transformation. Using simple combinations of nested transforms can allow
+/// otherwise complex patterns of movement and positioning to be much easier to represent.
+///
+/// Transforms that have no parent are said to be at the root level and have the property
+/// that their local transformation is also their world transformation. If a transform is
+/// known to be at the root of the hierarchy it is recommended that its local values be modified
+/// directly to achieve best performance.
+#[derive(Debug, Clone)]
+pub struct Transform {
+    position:         Point,
+    rotation:         Quaternion,
+    scale:            Vector3,
+    local_matrix:     Cell<Matrix4>,
+    position_derived: Cell<Point>,
+    rotation_derived: Cell<Quaternion>,
+    scale_derived:    Cell<Vector3>,
+    matrix_derived:   Cell<Matrix4>,
+    out_of_date:      Cell<bool>,
+    convention:       CoordinateConvention,
+    scale_constraint: ScaleConstraint,
+    frozen_position:  [bool; 3],
+    frozen_axis:      Option<Vector3>,
+
+    /// Set when the rotation is exactly identity and the scale is exactly one, so the local
+    /// matrix is a pure translation. Kept up to date by every method that changes the rotation or
+    /// scale.
+    translation_only: bool,
+}
+
+impl Transform {
+    pub fn new() -> Transform {
+        Transform {
+            position:         Point::origin(),
+            rotation:         Quaternion::identity(),
+            scale:            Vector3::one(),
+            local_matrix:     Cell::new(Matrix4::identity()),
+            position_derived: Cell::new(Point::origin()),
+            rotation_derived: Cell::new(Quaternion::identity()),
+            scale_derived:    Cell::new(Vector3::one()),
+            matrix_derived:   Cell::new(Matrix4::identity()),
+            out_of_date:      Cell::new(false),
+            convention:       CoordinateConvention::YUp,
+            scale_constraint: ScaleConstraint::Free,
+            frozen_position:  [false; 3],
+            frozen_axis:      None,
+            translation_only: true,
+        }
+    }
+
+    /// Resets the local position, rotation, and scale to their defaults, e.g. when reusing a
+    /// pooled entity.
+    ///
+    /// # Details
+    ///
+    /// The position is set to the origin, the rotation to identity, and the scale to one. The
+    /// transform's coordinate convention, scale constraint, and place in the hierarchy aren't
+    /// affected, so for a parented transform this moves it onto its parent.
+    pub fn reset(&mut self) {
+        self.position = Point::origin();
+        self.rotation = Quaternion::identity();
+        self.scale = Vector3::one();
+        self.translation_only = true;
+        self.out_of_date.set(true);
+    }
+
+    /// Checks if the local position, rotation, and scale are (approximately) the defaults set by
+    /// `reset()`.
+    ///
+    /// Both a quaternion and its negation represent the same rotation, so either sign of the
+    /// identity rotation counts.
+    pub fn is_identity(&self) -> bool {
+        let rotation = self.rotation;
+        self.position.as_vector3().is_zero()
+            && (rotation.w.abs() - 1.0).is_zero()
+            && Vector3::new(rotation.x, rotation.y, rotation.z).is_zero()
+            && (self.scale - Vector3::one()).is_zero()
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    /// Sets the local position of the transform.
+    ///
+    /// Components frozen with `freeze_position()` keep their current value.
+    pub fn set_position(&mut self, new_position: Point) {
+        self.position = self.constrain_position(new_position);
+        self.out_of_date.set(true);
+    }
+
+    pub fn rotation(&self) -> Quaternion {
+        self.rotation
+    }
+
+    /// Sets the local rotation of the transform.
+    ///
+    /// If an axis is frozen with `freeze_rotation_axis()` any change in rotation about that axis
+    /// is discarded.
+    pub fn set_rotation(&mut self, new_rotation: Quaternion) {
+        self.rotation = self.constrain_rotation(new_rotation);
+        self.update_translation_only();
+        self.out_of_date.set(true);
+    }
+
+    /// Locks components of the local position, e.g. to keep an object on a 2D plane.
+    ///
+    /// # Details
+    ///
+    /// Every method that moves the transform (`set_position()`, `translate()`,
+    /// `translate_local()`, `scale_around()`, and reparenting with `set_child_keep_world()`)
+    /// leaves frozen components at their current value and only changes the others. The
+    /// components are in the parent's space, like the local position. `reset()` still moves the
+    /// transform back to the origin. Pass `false` for every axis to unfreeze the position.
+    pub fn freeze_position(&mut self, x: bool, y: bool, z: bool) {
+        self.frozen_position = [x, y, z];
+    }
+
+    /// Checks which components of the local position are frozen, see `freeze_position()`.
+    pub fn frozen_position(&self) -> (bool, bool, bool) {
+        (self.frozen_position[0], self.frozen_position[1], self.frozen_position[2])
+    }
+
+    /// Prevents the transform from rotating about `axis`.
+    ///
+    /// # Details
+    ///
+    /// `axis` is in the transform's local space and doesn't need to be normalized. Whenever the
+    /// rotation is changed (`set_rotation()`, `rotate()`, `spin()`, `look_direction()`, etc.)
+    /// the change is split into a twist about `axis` and a swing about an axis perpendicular to
+    /// it, and only the swing is applied. Only one axis can be frozen at a time, freezing another
+    /// axis replaces it, and freezing the zero vector unfreezes the rotation. `reset()` still
+    /// resets the rotation to identity.
+    pub fn freeze_rotation_axis(&mut self, axis: Vector3) {
+        self.frozen_axis = if axis.is_zero() { None } else { Some(axis.normalized()) };
+    }
+
+    /// Retrieves the axis frozen with `freeze_rotation_axis()`, if any.
+    pub fn frozen_rotation_axis(&self) -> Option<Vector3> {
+        self.frozen_axis
+    }
+
+    pub fn scale(&self) -> Vector3 {
+        self.scale
+    }
+
+    /// Sets the local scale of the transform.
+    ///
+    /// The scale is subject to the transform manager's `ScaleConstraint`.
+    pub fn set_scale(&mut self, new_scale: Vector3) {
+        self.scale = self.scale_constraint.apply(new_scale);
+        self.update_translation_only();
+        self.out_of_date.set(true);
+    }
+
+    /// Sets all three components of the local scale to `scale`.
+    pub fn set_uniform_scale(&mut self, scale: f32) {
+        self.set_scale(Vector3::new(scale, scale, scale));
+    }
+
+    /// Retrieves the derived position of the transform.
+    ///
+    /// In debug builds this method asserts if the transform is out of date.
+    pub fn position_derived(&self) -> Point {
+        assert!(!self.out_of_date.get());
+
+        self.position_derived.get()
+    }
+
+    /// Retrieves the derived rotation of the transform.
+    ///
+    /// In debug builds this method asserts if the transform is out of date.
+    pub fn rotation_derived(&self) -> Quaternion {
+        assert!(!self.out_of_date.get());
+
+        self.rotation_derived.get()
+    }
+
+    /// Retrieves the derived scale of the transform.
+    ///
+    /// In debug builds this method asserts if the transform is out of date.
+    pub fn scale_derived(&self) -> Vector3 {
+        assert!(!self.out_of_date.get());
+
+        self.scale_derived.get()
+    }
+
+    /// Retrieves the composite matrix representing the local transform.
+    ///
+    /// # Details
+    ///
+    /// The composite matrix combines the affine matrices representing translation,
+    /// scale, and rotation into a single transformation matrix. The local maxtrix does
+    /// not include the parent's transformation. The local matrix transforms a local point
+    /// into the parent's coordinate system.
+    ///
+    /// Transforms that have only been translated (i.e. their rotation is exactly identity and
+    /// their scale exactly one) skip combining the matrices and use the translation matrix
+    /// directly, which is common for UI elements and static props.
+    pub fn local_matrix(&self) -> Matrix4 {
+        if self.out_of_date.get() {
+            let local_matrix = if self.translation_only {
+                Matrix4::from_point(self.position)
+            } else {
+                count_general_local_matrix();
+                Matrix4::from_point(self.position)
+                * (self.rotation.as_matrix4() * Matrix4::from_scale_vector(self.scale))
+            };
+            self.local_matrix.set(local_matrix);
+        }
+
+        self.local_matrix.get()
+    }
+
+    pub fn derived_matrix(&self) -> Matrix4 {
+        assert!(!self.out_of_date.get());
+
+        self.matrix_derived.get()
+    }
+
+    /// Retrieves the matrix used to transform the transform's normals into world space.
+    ///
+    /// # Details
+    ///
+    /// The normal matrix is the inverse-transpose of the derived matrix, which keeps normals
+    /// perpendicular to their surface even when the hierarchy contains non-uniform scaling. If
+    /// the derived matrix is singular (i.e. one of the scale components in the hierarchy is zero)
+    /// there is no meaningful normal matrix and the identity matrix is returned instead.
+    pub fn derived_normal_matrix(&self) -> Matrix4 {
+        assert!(!self.out_of_date.get());
+
+        match self.matrix_derived.get().inverse() {
+            Some(inverse) => inverse.transpose(),
+            None => Matrix4::identity(),
+        }
+    }
+
+    /// Moves the transform by `translation`.
+    ///
+    /// Components frozen with `freeze_position()` aren't changed.
+    pub fn translate(&mut self, translation: Vector3) {
+        self.position = self.constrain_position(self.position + translation);
+        self.out_of_date.set(true);
+    }
+
+    /// Moves the transform along its own axes, e.g. so that moving forward works regardless of
+    /// which way the transform is facing.
+    ///
+    /// # Details
+    ///
+    /// `local_offset` is rotated by the transform's local rotation before being added to its
+    /// position. To move along the transform's forward axis use the axis from its coordinate
+    /// convention, e.g. `translate_local(convention.forward_axis() * distance)`.
+    ///
+    /// For a parented transform the local rotation and position are both relative to the parent,
+    /// so the transform still moves along its own facing in world space. The distance moved is in
+    /// the parent's space though, so it's affected by the parent's scale.
+    pub fn translate_local(&mut self, local_offset: Vector3) {
+        let matrix = Matrix3::from_quaternion(self.rotation);
+        self.position = self.constrain_position(self.position + local_offset * matrix);
+        self.out_of_date.set(true);
+    }
+
+    pub fn rotate(&mut self, rotation: Quaternion) {
+        self.rotation = self.constrain_rotation(self.rotation * rotation);
+        self.update_translation_only();
+        self.out_of_date.set(true);
+    }
+
+    /// Rotates the transform about `axis` at a constant angular velocity for a frame of length `dt`.
+    ///
+    /// # Details
+    ///
+    /// The rotation is applied in local space like `rotate()`. The result is renormalized so that
+    /// spinning every frame doesn't accumulate error in the rotation. The angle is wrapped to a
+    /// single revolution before being applied, so a very large `dt` still gives a valid rotation.
+    /// Spinning about the zero vector does nothing.
+    pub fn spin(&mut self, axis: Vector3, radians_per_second: f32, dt: f32) {
+        if axis.is_zero() {
+            return;
+        }
+
+        let angle = (radians_per_second * dt) % (2.0 * PI);
+        let rotation = Quaternion::axis_angle(axis.normalized(), angle);
+        self.rotation = self.constrain_rotation((self.rotation * rotation).normalized());
+        self.update_translation_only();
+        self.out_of_date.set(true);
+    }
+
+    /// Scales the transform about `pivot` rather than about its own origin.
+    ///
+    /// # Details
+    ///
+    /// Both the local scale and the offset from the pivot to the transform's position are
+    /// multiplied by `scale_factor`, so the pivot stays fixed while the transform grows or shrinks
+    /// around it. `pivot` is in the same coordinate space as the transform's local position
+    /// (i.e. the parent's space). A component of `scale_factor` may be zero to flatten the
+    /// transform along that axis, but doing so makes the local and derived matrices singular.
+    pub fn scale_around(&mut self, pivot: Point, scale_factor: Vector3) {
+        let scale_factor = self.scale_constraint.apply(scale_factor);
+        let offset = (self.position - pivot) * scale_factor;
+        self.position = self.constrain_position(pivot + offset);
+        self.scale = self.scale_constraint.apply(self.scale * scale_factor);
+        self.update_translation_only();
+        self.out_of_date.set(true);
+    }
+
+    pub fn look_at(&mut self, interest: Point, up: Vector3) {
+        let forward = interest - self.position;
+        self.look_direction(forward, up);
+    }
+
+    /// Rotates the transform so that its forward axis points in the specified direction.
+    ///
+    /// The forward axis is determined by the transform's coordinate convention.
+    pub fn look_direction(&mut self, forward: Vector3, up: Vector3) {
+        self.rotation = self.constrain_rotation(Quaternion::look_rotation(forward, up) * self.convention.basis_rotation());
+        self.update_translation_only();
+        self.out_of_date.set(true);
+    }
+
+    pub fn forward(&self) -> Vector3 {
+        let matrix = Matrix3::from_quaternion(self.rotation);
+        self.convention.forward_axis() * matrix
+    }
+
+    pub fn right(&self) -> Vector3 {
+        let matrix = Matrix3::from_quaternion(self.rotation);
+        self.convention.right_axis() * matrix
+    }
+
+    pub fn up(&self) -> Vector3 {
+        let matrix = Matrix3::from_quaternion(self.rotation);
+        self.convention.up_axis() * matrix
+    }
+
+    fn update_translation_only(&mut self) {
+        self.translation_only = self.rotation == Quaternion::identity() && self.scale == Vector3::one();
+    }
+
+    /// Replaces the frozen components of `new_position` with the current position.
+    fn constrain_position(&self, new_position: Point) -> Point {
+        let mut position = new_position;
+        if self.frozen_position[0] { position.x = self.position.x; }
+        if self.frozen_position[1] { position.y = self.position.y; }
+        if self.frozen_position[2] { position.z = self.position.z; }
+        position
+    }
+
+    /// Removes the twist about the frozen axis from the change between the current rotation and
+    /// `new_rotation`.
+    fn constrain_rotation(&self, new_rotation: Quaternion) -> Quaternion {
+        let axis = match self.frozen_axis {
+            Some(axis) => axis,
+            None => return new_rotation,
+        };
+
+        // The change is applied on the right like `rotate()`, so it's in local space.
+        let change = self.rotation.conjugate() * new_rotation;
+        let projected = axis * axis.dot(Vector3::new(change.x, change.y, change.z));
+        let twist = Quaternion { w: change.w, x: projected.x, y: projected.y, z: projected.z };
+
+        // A half turn about a perpendicular axis has no twist component at all.
+        let twist_length = Quaternion::dot(twist, twist).sqrt();
+        if twist_length.is_zero() {
+            return new_rotation;
+        }
+
+        let twist = Quaternion {
+            w: twist.w / twist_length,
+            x: twist.x / twist_length,
+            y: twist.y / twist_length,
+            z: twist.z / twist_length,
+        };
+        let swing = change * twist.conjugate();
+        (self.rotation * swing).normalized()
+    }
+
+    /// Updates the local and derived matrices for the transform.
+    ///
+    /// `entity` is only used to report which transform is invalid if the derived matrix contains
+    /// NaN or infinite values.
+    fn update(&self, entity: Entity, parent: &Transform) {
+        let local_matrix = self.local_matrix();
+
+        let derived_matrix = parent.derived_matrix() * local_matrix;
+        debug_assert!(
+            derived_matrix.is_finite(),
+            "Derived matrix for entity {:?} is not finite (position: {:?}, rotation: {:?}, scale: {:?})",
+            entity, self.position, self.rotation, self.scale);
+        self.matrix_derived.set(derived_matrix);
+
+        self.position_derived.set(derived_matrix.translation_part());
+        self.rotation_derived.set(parent.rotation_derived() * self.rotation);
+        self.scale_derived.set(self.scale * parent.scale_derived());
+
+        self.out_of_date.set(false);
+    }
+}
+
+pub fn transform_update(scene: &Scene, _: f32) {
+    let _stopwatch = Stopwatch::new("transform update");
+
+    let transform_manager = scene.get_manager::<TransformManager>();
+    transform_manager.update_sequential();
+}
+
+/// A system that updates the transform hierarchy using multiple threads.
+///
+/// # Details
+///
+/// This can be used in place of `transform_update()` with `Engine::set_transform_update()`. See
+/// `TransformManager::update_parallel()` for how the work is divided. The worker threads are
+/// started when the system is created and reused every frame.
+#[derive(Debug)]
+pub struct ParallelTransformUpdate {
+    num_threads: usize,
+    workers: TransformWorkers,
+}
+
+impl ParallelTransformUpdate {
+    /// Creates the system, with the work divided between the engine's thread and
+    /// `num_threads - 1` worker threads.
+    pub fn new(num_threads: usize) -> ParallelTransformUpdate {
+        ParallelTransformUpdate {
+            num_threads: num_threads,
+            workers: TransformWorkers::new(num_threads.saturating_sub(1)),
+        }
+    }
+
+    /// The number of threads the system was created with, including the engine's thread.
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+}
+
+impl System for ParallelTransformUpdate {
+    fn update(&mut self, scene: &Scene, _: f32) {
+        let _stopwatch = Stopwatch::new("parallel transform update");
+
+        let transform_manager = scene.get_manager::<TransformManager>();
+        transform_manager.update_parallel_with(&self.workers);
+    }
+}
+
+/// A batch of jobs sent to a worker, along with the channel used to report that it's finished.
+type WorkerBatch = (Vec<TransformUpdateJob>, mpsc::Sender<()>);
+
+/// A set of threads that can be reused to update transforms, see
+/// `TransformManager::update_parallel_with()`.
+///
+/// The threads wait for work until the `TransformWorkers` is dropped, at which point they are
+/// stopped and joined.
+pub struct TransformWorkers {
+    senders: Vec<mpsc::Sender<WorkerBatch>>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl TransformWorkers {
+    /// Starts `num_workers` worker threads.
+    ///
+    /// If a thread can't be started there are simply fewer workers, and with no workers at all
+    /// updates are done sequentially on the calling thread.
+    pub fn new(num_workers: usize) -> TransformWorkers {
+        let mut senders = Vec::new();
+        let mut threads = Vec::new();
+        for _ in 0..num_workers {
+            let (sender, receiver) = mpsc::channel::<WorkerBatch>();
+            let spawn_result = thread::Builder::new().name("transform update".into()).spawn(move || {
+                for (jobs, done) in receiver.iter() {
+                    for job in jobs {
+                        unsafe { job.run(); }
+                    }
+
+                    // If the worker panics `done` is dropped without sending, which also tells
+                    // the waiting thread that the worker has stopped using the jobs.
+                    let _ = done.send(());
+                }
+            });
+
+            if let Ok(thread) = spawn_result {
+                senders.push(sender);
+                threads.push(thread);
+            }
+        }
+
+        TransformWorkers {
+            senders: senders,
+            threads: threads,
+        }
+    }
+
+    /// The number of worker threads, not counting the calling thread.
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+}
+
+impl Drop for TransformWorkers {
+    fn drop(&mut self) {
+        // Closing the channels ends each worker's loop.
+        self.senders.clear();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for TransformWorkers {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "TransformWorkers({})", self.senders.len())
+    }
+}
+
+/// Waits for the batches handed to workers by `update_parallel_with()` to finish.
+///
+/// The jobs hold raw pointers into transforms that are only borrowed for the duration of the
+/// update, so the workers must be done with them before the borrows are released. Waiting in
+/// `drop()` guarantees that, even when the calling thread unwinds from a panic.
+struct PendingBatches {
+    done: Vec<mpsc::Receiver<()>>,
+}
+
+impl PendingBatches {
+    /// Waits for every batch, returning `false` if any of the workers panicked.
+    fn wait(&mut self) -> bool {
+        self.done.drain(..).fold(true, |succeeded, done| done.recv().is_ok() && succeeded)
+    }
+}
+
+impl Drop for PendingBatches {
+    fn drop(&mut self) {
+        self.wait();
+    }
+}
+
+/// A single transform to be updated by `TransformManager::update_parallel()`.
+struct TransformUpdateJob {
+    entity: Entity,
+    transform: *const Transform,
+    parent: Option<*const Transform>,
+}
+
+/// `Transform` isn't `Sync` because of its `Cell` members, but each job is only ever run by the
+/// thread that owns its subtree so no transform is accessed from more than one thread at a time.
+/// The pointers are only valid while `update_parallel_with()` holds the borrows, which
+/// `PendingBatches` guarantees outlast every worker's use of them.
+unsafe impl Send for TransformUpdateJob {}
+
+impl TransformUpdateJob {
+    /// Updates the transform.
+    ///
+    /// The transform and its parent must still be borrowed, and the parent must already have
+    /// been updated.
+    unsafe fn run(&self) {
+        let transform = &*self.transform;
+        match self.parent {
+            None => DUMMY_TRANSFORM.with(|parent| transform.update(self.entity, parent)),
+            Some(parent) => transform.update(self.entity, &*parent),
+        }
+    }
+}
+
+#[test]
+fn walk_hierarchy_skips_orphaned_children() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let root = entity_manager.create();
+    let child = entity_manager.create();
+    let orphan = entity_manager.create();
+
+    transform_manager.assign(root);
+    transform_manager.assign(child);
+    transform_manager.set_child(root, child);
+
+    // Manually inject a transform into the child row without a parent, breaking the invariant
+    // that only root-level transforms have no parent.
+    let orphan_index = transform_manager.transforms[1].len();
+    transform_manager.transforms[1].push(RefCell::new(Transform::new()));
+    transform_manager.entities[1].push((orphan, None));
+    transform_manager.indices.insert(orphan, (1, orphan_index));
+
+    let mut visited = Vec::new();
+    transform_manager.walk_hierarchy(root, &mut |entity, _| visited.push(entity));
+    assert_eq!(visited, vec![root, child]);
+
+    let mut visited = Vec::new();
+    transform_manager.walk_children(root, &mut |entity| visited.push(entity));
+    assert_eq!(visited, vec![root, child]);
+}
+
+#[test]
+fn coordinate_convention_directions() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let entity = entity_manager.create();
+    transform_manager.assign(entity).set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5));
+
+    {
+        let transform = transform_manager.get(entity);
+        assert!((transform.forward() - Vector3::new(-1.0, 0.0, 0.0)).is_zero(), "{:?}", transform.forward());
+        assert!((transform.up() - Vector3::new(0.0, 1.0, 0.0)).is_zero(), "{:?}", transform.up());
+        assert!((transform.right() - Vector3::new(0.0, 0.0, -1.0)).is_zero(), "{:?}", transform.right());
+    }
+
+    transform_manager.set_coordinate_convention(CoordinateConvention::ZUp);
+
+    {
+        let transform = transform_manager.get(entity);
+        assert!((transform.forward() - Vector3::new(0.0, 1.0, 0.0)).is_zero(), "{:?}", transform.forward());
+        assert!((transform.up() - Vector3::new(1.0, 0.0, 0.0)).is_zero(), "{:?}", transform.up());
+        assert!((transform.right() - Vector3::new(0.0, 0.0, -1.0)).is_zero(), "{:?}", transform.right());
+    }
+
+    // `look_direction()` should point the convention's forward axis at the target.
+    let mut transform = transform_manager.get_mut(entity);
+    transform.look_direction(Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0));
+    assert!((transform.forward() - Vector3::new(1.0, 0.0, 0.0)).is_zero(), "{:?}", transform.forward());
+}
+
+#[test]
+fn bone_palette_two_bone_chain() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let root = entity_manager.create();
+    let child = entity_manager.create();
+
+    transform_manager.assign(root).set_position(Point::new(0.0, 1.0, 0.0));
+    transform_manager.assign(child).set_position(Point::new(0.0, 1.0, 0.0));
+    transform_manager.set_child(root, child);
+
+    // Bind pose has the root at <0, 1, 0> and the child at <0, 2, 0>.
+    let mut inverse_bind = HashMap::new();
+    inverse_bind.insert(root, Matrix4::translation(0.0, -1.0, 0.0));
+    inverse_bind.insert(child, Matrix4::translation(0.0, -2.0, 0.0));
+
+    // In the bind pose every skinning matrix is the identity.
+    transform_manager.update_single(child);
+    let palette = transform_manager.bone_palette(root, &inverse_bind);
+    assert_eq!(palette, vec![Matrix4::identity(), Matrix4::identity()]);
+
+    // Moving the root moves both bones.
+    transform_manager.get_mut(root).set_position(Point::new(1.0, 1.0, 0.0));
+    transform_manager.update_single(child);
+    let palette = transform_manager.bone_palette(root, &inverse_bind);
+    assert_eq!(palette, vec![Matrix4::translation(1.0, 0.0, 0.0), Matrix4::translation(1.0, 0.0, 0.0)]);
+
+    // Bones missing an inverse bind matrix use the identity.
+    inverse_bind.remove(&child);
+    let palette = transform_manager.bone_palette(root, &inverse_bind);
+    assert_eq!(palette, vec![Matrix4::translation(1.0, 0.0, 0.0), Matrix4::translation(1.0, 2.0, 0.0)]);
+}
+
+#[test]
+fn for_each_mut_moves_all_transforms() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let root = entity_manager.create();
+    let child = entity_manager.create();
+    let other = entity_manager.create();
+
+    transform_manager.assign(root);
+    transform_manager.assign(child);
+    transform_manager.assign(other);
+    transform_manager.set_child(root, child);
+
+    let mut visited = Vec::new();
+    transform_manager.for_each_mut(&mut |entity, transform| {
+        transform.translate(Vector3::new(1.0, 2.0, 3.0));
+        visited.push(entity);
+    });
+
+    assert_eq!(visited.len(), 3);
+    for entity in &[root, child, other] {
+        assert!(visited.contains(entity));
+        assert_eq!(transform_manager.get(*entity).position(), Point::new(1.0, 2.0, 3.0));
+    }
+
+    // Marking entities for destruction from within the callback is safe.
+    transform_manager.for_each_mut(&mut |entity, _| {
+        if entity == other {
+            transform_manager.destroy(entity);
+        }
+    });
+    transform_manager.destroy_marked();
+    assert!(transform_manager.indices.get(&other).is_none());
+}
+
+#[test]
+fn derived_normal_matrix_non_uniform_scale() {
+    use ecs::EntityManager;
+
+    // The old approximation of the normal matrix, which is only correct when the scale in the
+    // hierarchy is uniform.
+    fn approximate_normal_matrix(transform: &Transform) -> Matrix4 {
+        let inverse =
+            Matrix4::from_scale_vector(1.0 / transform.scale_derived())
+          * (transform.rotation_derived().as_matrix4().transpose()
+          *  Matrix4::from_point(-transform.position_derived()));
+
+        inverse.transpose()
+    }
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+
+    transform_manager.assign(parent);
+    transform_manager.assign(child);
+    transform_manager.set_child(parent, child);
+
+    // With uniform scale both approaches agree.
+    transform_manager.get_mut(parent).set_scale(Vector3::new(2.0, 2.0, 2.0));
+    transform_manager.get_mut(parent).set_position(Point::new(1.0, 2.0, 3.0));
+    transform_manager.get_mut(child).set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 0.0, 1.0), PI * 0.25));
+    transform_manager.update_single(child);
+    {
+        let transform = transform_manager.get(child);
+        assert_eq!(transform.derived_normal_matrix(), approximate_normal_matrix(&*transform));
+    }
+
+    // With non-uniform parent scale only the true inverse-transpose keeps normals perpendicular
+    // to the surface.
+    transform_manager.get_mut(parent).set_scale(Vector3::new(2.0, 1.0, 1.0));
+    transform_manager.update_single(child);
+
+    let transform = transform_manager.get(child);
+    let tangent = Vector3::new(1.0, 0.0, 0.0) * Matrix3::from_matrix4(&transform.derived_matrix());
+    let normal = Vector3::new(0.0, 1.0, 0.0) * Matrix3::from_matrix4(&transform.derived_normal_matrix());
+    let approximate_normal = Vector3::new(0.0, 1.0, 0.0) * Matrix3::from_matrix4(&approximate_normal_matrix(&*transform));
+
+    assert!(tangent.dot(normal).is_zero());
+    assert!(!tangent.dot(approximate_normal).is_zero());
+    assert!(transform.derived_normal_matrix() != approximate_normal_matrix(&*transform));
+}
+
+#[test]
+fn scale_around_pivot() {
+    let mut transform = Transform::new();
+    transform.set_position(Point::new(1.0, 2.0, 3.0));
+    transform.scale_around(Point::new(1.0, 1.0, 0.0), Vector3::new(2.0, 2.0, 2.0));
+
+    assert_eq!(transform.position(), Point::new(1.0, 3.0, 6.0));
+    assert_eq!(transform.scale(), Vector3::new(2.0, 2.0, 2.0));
+
+    // Zero components flatten the transform onto the pivot along that axis.
+    transform.scale_around(Point::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 1.0));
+    assert_eq!(transform.position(), Point::new(1.0, 0.0, 6.0));
+    assert_eq!(transform.scale(), Vector3::new(2.0, 0.0, 2.0));
+}
+
+#[test]
+fn point_relative_to_rotated_observer() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let observer = entity_manager.create();
+    let target = entity_manager.create();
+    let missing = entity_manager.create();
+
+    transform_manager.assign(observer);
+    transform_manager.assign(target);
+
+    let target_position = {
+        let mut transform = transform_manager.get_mut(observer);
+        transform.set_position(Point::new(1.0, 0.0, 1.0));
+        transform.set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5));
+        transform.position() + transform.forward() * 5.0
+    };
+    transform_manager.get_mut(target).set_position(target_position);
+
+    transform_manager.update_single(observer);
+    transform_manager.update_single(target);
+
+    let local = transform_manager.point_relative_to(observer, target).unwrap();
+    let forward_axis = CoordinateConvention::YUp.forward_axis();
+    assert!((local.as_vector3().dot(forward_axis) - 5.0).is_zero());
+    assert_eq!(local, Point::new(0.0, 0.0, -5.0));
+
+    assert_eq!(transform_manager.local_point_in(observer, target_position), Some(local));
+    assert_eq!(transform_manager.local_point_in(missing, target_position), None);
+    assert_eq!(transform_manager.point_relative_to(missing, target), None);
+    assert_eq!(transform_manager.point_relative_to(observer, missing), None);
+}
+
+#[test]
+fn destroy_scene_leaves_other_scenes() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let level_root = entity_manager.create();
+    let level_child = entity_manager.create();
+    let ui_root = entity_manager.create();
+    let ui_child = entity_manager.create();
+
+    transform_manager.assign(level_root);
+    transform_manager.assign(level_child);
+    transform_manager.assign(ui_root);
+    transform_manager.assign(ui_child);
+    transform_manager.set_child(level_root, level_child);
+
+    transform_manager.assign_to_scene(level_root, 1);
+    transform_manager.assign_to_scene(ui_root, 2);
+    assert_eq!(transform_manager.scene_id(level_child), 1);
+    assert_eq!(transform_manager.scene_id(ui_child), DEFAULT_SCENE_ID);
+
+    // Reparenting moves the child into the parent's scene.
+    transform_manager.set_child(ui_root, ui_child);
+    assert_eq!(transform_manager.scene_id(ui_child), 2);
+
+    transform_manager.destroy_scene(1);
+    assert!(!transform_manager.contains(level_root));
+    assert!(!transform_manager.contains(level_child));
+    assert!(transform_manager.contains(ui_root));
+    assert!(transform_manager.contains(ui_child));
+
+    let mut visited = Vec::new();
+    transform_manager.walk_children(ui_root, &mut |entity| visited.push(entity));
+    assert_eq!(visited, vec![ui_root, ui_child]);
+}
+
+#[test]
+#[should_panic(expected = "is not finite")]
+fn update_detects_nan_position() {
+    use std::f32::NAN;
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let entity = entity_manager.create();
+    transform_manager.assign(entity).set_position(Point::new(1.0, NAN, 3.0));
+    transform_manager.update_single(entity);
+}
+
+#[test]
+fn bone_palette_into_reuses_buffer() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let root = entity_manager.create();
+    let child = entity_manager.create();
+
+    transform_manager.assign(root);
+    transform_manager.assign(child).set_position(Point::new(0.0, 1.0, 0.0));
+    transform_manager.set_child(root, child);
+    transform_manager.update_single(child);
+
+    let mut inverse_bind = HashMap::new();
+    inverse_bind.insert(root, Matrix4::identity());
+    inverse_bind.insert(child, Matrix4::translation(0.0, -1.0, 0.0));
+
+    // Start with a buffer that has stale data in it.
+    let mut palette = vec![Matrix4::new(); 5];
+    transform_manager.bone_palette_into(root, &inverse_bind, &mut palette);
+    assert_eq!(palette, transform_manager.bone_palette(root, &inverse_bind));
+
+    let first = palette.clone();
+    let capacity = palette.capacity();
+    transform_manager.bone_palette_into(root, &inverse_bind, &mut palette);
+    assert_eq!(palette, first);
+    assert_eq!(palette.capacity(), capacity);
+}
+
+#[test]
+fn set_child_keep_world_preserves_transform() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+
+    {
+        let mut transform = transform_manager.assign(parent);
+        transform.set_position(Point::new(1.0, 2.0, 3.0));
+        transform.set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5));
+        transform.set_scale(Vector3::new(2.0, 2.0, 2.0));
+    }
+    {
+        let mut transform = transform_manager.assign(child);
+        transform.set_position(Point::new(5.0, 0.0, -1.0));
+        transform.set_rotation(Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), PI * 0.25));
+    }
+
+    transform_manager.update_single(child);
+    let (position, rotation) = {
+        let transform = transform_manager.get(child);
+        (transform.position_derived(), transform.rotation_derived())
+    };
+
+    let assert_world_unchanged = |transform_manager: &TransformManager| {
+        transform_manager.update_single(child);
+        let transform = transform_manager.get(child);
+        assert!((transform.position_derived() - position).is_zero(), "{:?} != {:?}", transform.position_derived(), position);
+        assert!((Quaternion::dot(transform.rotation_derived(), rotation).abs() - 1.0).is_zero());
+        assert!((transform.scale_derived() - Vector3::one()).is_zero());
+    };
+
+    transform_manager.set_child_keep_world(parent, child);
+    assert_world_unchanged(&transform_manager);
+
+    let mut children = Vec::new();
+    transform_manager.walk_children(parent, &mut |entity| children.push(entity));
+    assert_eq!(children, vec![parent, child]);
+
+    // Moving back to the root also preserves the world transform.
+    transform_manager.unparent_keep_world(child);
+    assert_world_unchanged(&transform_manager);
+    assert_eq!(transform_manager.get(child).position(), transform_manager.get(child).position_derived());
+}
+
+#[test]
+fn text_round_trip() {
+    // The grandchild is listed before its parent.
+    let text = "
+        # A three level hierarchy.
+        3 2 0 0 -1 1 0 0 0 1 1 1
+
+        1 - 1 2 3 1 0 0 0 2 2 2
+        2 1 0.5 0 0 0.7071068 0 0.7071068 0 1 1 1
+    ";
+
+    let transform_manager = TransformManager::from_text(text).unwrap();
+    let reloaded = TransformManager::from_text(&transform_manager.to_text()).unwrap();
+
+    let root = Entity::from_id(1);
+    let child = Entity::from_id(2);
+    let grandchild = Entity::from_id(3);
+
+    for manager in &[&transform_manager, &reloaded] {
+        let mut visited = Vec::new();
+        manager.walk_children(root, &mut |entity| visited.push(entity));
+        assert_eq!(visited, vec![root, child, grandchild]);
+
+        assert_eq!(manager.get(root).position(), Point::new(1.0, 2.0, 3.0));
+        assert_eq!(manager.get(root).scale(), Vector3::new(2.0, 2.0, 2.0));
+        assert_eq!(manager.get(child).position(), Point::new(0.5, 0.0, 0.0));
+        assert_eq!(manager.get(grandchild).position(), Point::new(0.0, 0.0, -1.0));
+    }
+
+    assert_eq!(reloaded.get(child).rotation(), transform_manager.get(child).rotation());
+    assert_eq!(reloaded.to_text().lines().count(), 4);
+}
+
+#[test]
+fn text_parse_errors() {
+    assert_eq!(
+        TransformManager::from_text("1 - 0 0 0 1 0 0 0 1 1").err(),
+        Some(ParseError::WrongFieldCount { line: 1, count: 11 }));
+    assert_eq!(
+        TransformManager::from_text("\n1 - 0 zero 0 1 0 0 0 1 1 1").err(),
+        Some(ParseError::InvalidValue { line: 2, field: 3 }));
+    assert_eq!(
+        TransformManager::from_text("1 - 0 0 0 0 0 0 0 1 1 1").err(),
+        Some(ParseError::InvalidRotation { line: 1 }));
+    assert_eq!(
+        TransformManager::from_text("1 5 0 0 0 1 0 0 0 1 1 1").err(),
+        Some(ParseError::MissingParent { line: 1, parent: 5 }));
+    assert_eq!(
+        TransformManager::from_text("1 - 0 0 0 1 0 0 0 1 1 1\n1 - 0 0 0 1 0 0 0 1 1 1").err(),
+        Some(ParseError::DuplicateEntity { line: 2, id: 1 }));
+    assert_eq!(
+        TransformManager::from_text("1 2 0 0 0 1 0 0 0 1 1 1\n2 1 0 0 0 1 0 0 0 1 1 1").err(),
+        Some(ParseError::ParentCycle { line: 1 }));
+}
+
+#[cfg(test)]
+fn build_test_forest(roots: usize, depth: usize) -> (TransformManager, Vec<Entity>) {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut entities = Vec::new();
+
+    for root_index in 0..roots {
+        let mut parent = None;
+        for level in 0..depth {
+            let entity = entity_manager.create();
+            let offset = (root_index * depth + level) as f32;
+            {
+                let mut transform = transform_manager.assign(entity);
+                transform.set_position(Point::new(offset, 1.0 - offset, 0.5 * offset));
+                transform.set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.1 * offset));
+                transform.set_scale(Vector3::new(1.0, 1.5, 0.5 + 0.1 * level as f32));
+            }
+
+            if let Some(parent) = parent {
+                transform_manager.set_child(parent, entity);
+            }
+
+            // Give some of the transforms a sibling so subtrees are wider than a single chain.
+            if let (Some(parent), 0) = (parent, level % 2) {
+                let sibling = entity_manager.create();
+                transform_manager.assign(sibling).set_position(Point::new(0.0, offset, -offset));
+                transform_manager.set_child(parent, sibling);
+                entities.push(sibling);
+            }
+
+            entities.push(entity);
+            parent = Some(entity);
+        }
+    }
+
+    (transform_manager, entities)
+}
+
+#[test]
+fn parallel_update_matches_sequential() {
+    let (sequential, entities) = build_test_forest(7, 5);
+    let parallel = sequential.clone();
+
+    sequential.update_sequential();
+    parallel.update_parallel(4);
+
+    for &entity in &entities {
+        assert_eq!(parallel.get(entity).derived_matrix(), sequential.get(entity).derived_matrix());
+        assert_eq!(parallel.get(entity).position_derived(), sequential.get(entity).position_derived());
+        assert_eq!(parallel.get(entity).rotation_derived(), sequential.get(entity).rotation_derived());
+        assert_eq!(parallel.get(entity).scale_derived(), sequential.get(entity).scale_derived());
+    }
+}
+
+#[test]
+fn parallel_update_reuses_workers() {
+    let (sequential, entities) = build_test_forest(7, 5);
+    let parallel = sequential.clone();
+    let workers = TransformWorkers::new(3);
+    assert_eq!(workers.len(), 3);
+
+    // The same workers are used for several frames of changes.
+    for frame in 0..3 {
+        for (index, &entity) in entities.iter().enumerate().filter(|&(index, _)| index % 4 == frame) {
+            let position = Point::new(index as f32, frame as f32, 1.0);
+            sequential.get_mut(entity).set_position(position);
+            parallel.get_mut(entity).set_position(position);
+        }
+
+        sequential.update_sequential();
+        parallel.update_parallel_with(&workers);
+
+        for &entity in &entities {
+            assert_eq!(parallel.get(entity).derived_matrix(), sequential.get(entity).derived_matrix());
+        }
+    }
+
+    // Without workers everything is done on the calling thread.
+    let no_workers = TransformWorkers::new(0);
+    parallel.get_mut(entities[0]).set_position(Point::new(-1.0, -1.0, -1.0));
+    sequential.get_mut(entities[0]).set_position(Point::new(-1.0, -1.0, -1.0));
+    sequential.update_sequential();
+    parallel.update_parallel_with(&no_workers);
+    assert_eq!(parallel.get(entities[0]).derived_matrix(), sequential.get(entities[0]).derived_matrix());
+}
+
+#[test]
+fn parallel_update_single_subtree_falls_back() {
+    let (sequential, entities) = build_test_forest(1, 6);
+    let parallel = sequential.clone();
+
+    sequential.update_sequential();
+    parallel.update_parallel(4);
+
+    for &entity in &entities {
+        assert_eq!(parallel.get(entity).derived_matrix(), sequential.get(entity).derived_matrix());
+    }
+}
+
+#[test]
+fn read_snapshot_matches_transforms() {
+    let (transform_manager, entities) = build_test_forest(4, 5);
+    transform_manager.update_parallel(1);
+
+    let mut snapshot = transform_manager.read_snapshot();
+    assert!(transform_manager.is_snapshot_current(&snapshot));
+    assert_eq!(snapshot.len(), entities.len());
+    for &entity in &entities {
+        let transform = transform_manager.get(entity);
+        assert_eq!(snapshot.derived_matrix(entity), Some(transform.derived_matrix()));
+        assert_eq!(snapshot.position_derived(entity), Some(transform.position_derived()));
+
+        let index = snapshot.index_of(entity).unwrap();
+        assert_eq!(snapshot.entities()[index], entity);
+        assert_eq!(snapshot.matrices()[index], transform.derived_matrix());
+    }
+
+    // Modifying a transform doesn't change the derived values until the next update.
+    transform_manager.get_mut(entities[0]).translate(Vector3::new(5.0, 0.0, 0.0));
+    assert!(transform_manager.is_snapshot_current(&snapshot));
+
+    transform_manager.update_parallel(1);
+    assert!(!transform_manager.is_snapshot_current(&snapshot));
+    assert!(snapshot.derived_matrix(entities[0]) != Some(transform_manager.get(entities[0]).derived_matrix()));
+
+    transform_manager.refresh_snapshot(&mut snapshot);
+    assert!(transform_manager.is_snapshot_current(&snapshot));
+    for &entity in &entities {
+        assert_eq!(snapshot.derived_matrix(entity), Some(transform_manager.get(entity).derived_matrix()));
+    }
+}
+
+#[test]
+fn snapshot_stale_after_hierarchy_changes() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let entity = entity_manager.create();
+    transform_manager.assign(entity);
+    transform_manager.update_parallel(1);
+
+    let snapshot = transform_manager.read_snapshot();
+    let other = entity_manager.create();
+    transform_manager.assign(other);
+    assert!(!transform_manager.is_snapshot_current(&snapshot));
+    assert_eq!(snapshot.index_of(other), None);
+
+    let snapshot = transform_manager.read_snapshot();
+    transform_manager.destroy_immediate(entity);
+    assert!(!transform_manager.is_snapshot_current(&snapshot));
+}
+
+#[test]
+fn subtree_bounds() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+    let lonely = entity_manager.create();
+
+    transform_manager.assign(parent).set_position(Point::new(1.0, 0.0, 0.0));
+    transform_manager.assign(child).set_position(Point::new(0.0, 3.0, 0.0));
+    transform_manager.assign(lonely);
+    transform_manager.set_child(parent, child);
+    transform_manager.update_sequential();
+
+    let unit = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+    let mut local_bounds: HashMap<Entity, AABB> = HashMap::new();
+    local_bounds.insert(parent, unit);
+    local_bounds.insert(child, unit);
+
+    // The child sits above the parent, so the union extends the parent's bounds upwards.
+    let bounds = transform_manager.compute_subtree_bounds(parent, &local_bounds);
+    assert_eq!(bounds.min, Point::new(0.0, -1.0, -1.0));
+    assert_eq!(bounds.max, Point::new(2.0, 4.0, 1.0));
+
+    // Only the child's bounds when starting from the child.
+    let bounds = transform_manager.compute_subtree_bounds(child, &local_bounds);
+    assert_eq!(bounds.min, Point::new(0.0, 2.0, -1.0));
+    assert_eq!(bounds.max, Point::new(2.0, 4.0, 1.0));
+
+    assert!(transform_manager.compute_subtree_bounds(lonely, &local_bounds).is_empty());
+}
+
+#[test]
+fn with_capacity_preallocates_rows() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::with_capacity(16, 3);
+    assert_eq!(transform_manager.transforms.len(), 3);
+
+    let root_capacity = transform_manager.transforms[0].capacity();
+    let child_capacity = transform_manager.transforms[1].capacity();
+    assert!(root_capacity >= 16);
+    assert!(child_capacity >= 16);
+
+    let mut roots = Vec::new();
+    for _ in 0..16 {
+        let entity = entity_manager.create();
+        transform_manager.assign(entity);
+        roots.push(entity);
+    }
+    assert_eq!(transform_manager.transforms[0].capacity(), root_capacity);
+    assert_eq!(transform_manager.entities[0].len(), 16);
+
+    // Parenting within the reserved depth doesn't grow the rows either.
+    let child = entity_manager.create();
+    transform_manager.assign(child);
+    transform_manager.set_child(roots[0], child);
+    assert_eq!(transform_manager.transforms.len(), 3);
+    assert_eq!(transform_manager.transforms[1].capacity(), child_capacity);
+
+    // Going deeper than the reserved depth falls back to growing on demand.
+    let mut parent = child;
+    for _ in 0..3 {
+        let entity = entity_manager.create();
+        transform_manager.assign(entity);
+        transform_manager.set_child(parent, entity);
+        parent = entity;
+    }
+    assert_eq!(transform_manager.transforms.len(), 5);
+
+    transform_manager.reserve(32);
+    assert!(transform_manager.transforms[0].capacity() >= transform_manager.transforms[0].len() + 32);
+}
+
+#[test]
+fn transform_event_lifecycle() {
+    use ecs::EntityManager;
+    use self::TransformEvent::*;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    transform_manager.set_record_events(true);
+
+    // Spawn.
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+    transform_manager.assign(parent);
+    transform_manager.assign(child).set_position(Point::new(0.0, 1.0, 0.0));
+    transform_manager.set_child(parent, child);
+    transform_manager.update_sequential();
+    assert_eq!(transform_manager.drain_events(), vec![TransformAdded(parent), TransformAdded(child), TransformMoved(child)]);
+
+    // Nothing changed, so nothing is emitted.
+    transform_manager.update_sequential();
+    assert_eq!(transform_manager.drain_events(), vec![]);
+
+    // Moving the parent several times in a frame emits a single event for it and its child.
+    transform_manager.get_mut(parent).set_position(Point::new(1.0, 0.0, 0.0));
+    transform_manager.get_mut(parent).translate(Vector3::new(0.0, 0.0, 1.0));
+    transform_manager.update_sequential();
+    assert_eq!(transform_manager.drain_events(), vec![TransformMoved(parent), TransformMoved(child)]);
+
+    // The parallel update reports moves the same way.
+    transform_manager.get_mut(child).set_scale(Vector3::new(2.0, 2.0, 2.0));
+    transform_manager.update_parallel(2);
+    assert_eq!(transform_manager.drain_events(), vec![TransformMoved(child)]);
+
+    // Destroy.
+    transform_manager.destroy_all(child);
+    transform_manager.destroy_marked();
+    transform_manager.destroy_all(parent);
+    transform_manager.destroy_marked();
+    assert_eq!(transform_manager.drain_events(), vec![TransformDestroyed(child), TransformDestroyed(parent)]);
+
+    // Adding and destroying in the same frame emits both events in order.
+    let temporary = entity_manager.create();
+    transform_manager.assign(temporary);
+    transform_manager.destroy_all(temporary);
+    transform_manager.destroy_marked();
+    assert_eq!(transform_manager.drain_events(), vec![TransformAdded(temporary), TransformDestroyed(temporary)]);
+}
+
+#[test]
+fn transform_events_off_by_default() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    // Nobody drains the events, so several frames of changes must not leave anything queued.
+    let entity = entity_manager.create();
+    transform_manager.assign(entity);
+    for frame in 0..10 {
+        transform_manager.get_mut(entity).set_position(Point::new(frame as f32, 0.0, 0.0));
+        transform_manager.update_sequential();
+        assert!(transform_manager.events.borrow().is_empty());
+    }
+
+    // Once enabled events are queued, and disabling recording drops anything still queued.
+    transform_manager.set_record_events(true);
+    transform_manager.get_mut(entity).set_position(Point::new(-1.0, 0.0, 0.0));
+    transform_manager.update_sequential();
+    assert_eq!(transform_manager.events.borrow().len(), 1);
+    transform_manager.set_record_events(false);
+    assert!(transform_manager.drain_events().is_empty());
+}
+
+#[test]
+fn depth_and_root() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let chain: Vec<Entity> = (0..4).map(|_| entity_manager.create()).collect();
+    for &entity in &chain {
+        transform_manager.assign(entity);
+    }
+    for pair in chain.windows(2) {
+        transform_manager.set_child(pair[0], pair[1]);
+    }
+
+    let other_root = entity_manager.create();
+    transform_manager.assign(other_root);
+
+    for (depth, &entity) in chain.iter().enumerate() {
+        assert_eq!(transform_manager.depth(entity), Some(depth));
+        assert_eq!(transform_manager.root_of(entity), Some(chain[0]));
+    }
+    assert_eq!(transform_manager.depth(other_root), Some(0));
+    assert_eq!(transform_manager.root_of(other_root), Some(other_root));
+
+    let missing = entity_manager.create();
+    assert_eq!(transform_manager.depth(missing), None);
+    assert_eq!(transform_manager.root_of(missing), None);
+
+    let mut roots: Vec<Entity> = transform_manager.roots().collect();
+    roots.sort_by(|first, second| first.id().cmp(&second.id()));
+    assert_eq!(roots, vec![chain[0], other_root]);
+}
+
+#[test]
+fn contains_and_try_get() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    assert!(!transform_manager.contains(first));
+    assert!(transform_manager.try_get(first).is_none());
+    assert!(transform_manager.try_get_mut(first).is_none());
+
+    transform_manager.assign(first);
+    transform_manager.assign(second).set_position(Point::new(1.0, 2.0, 3.0));
+    assert!(transform_manager.contains(first));
+    assert!(transform_manager.contains(second));
+
+    // Destroying the first transform swaps the second into its place.
+    transform_manager.destroy_immediate(first);
+    assert!(!transform_manager.contains(first));
+    assert!(transform_manager.try_get(first).is_none());
+    assert!(transform_manager.contains(second));
+    assert_eq!(transform_manager.try_get(second).unwrap().position(), Point::new(1.0, 2.0, 3.0));
+
+    transform_manager.try_get_mut(second).unwrap().set_position(Point::origin());
+    assert_eq!(transform_manager.get(second).position(), Point::origin());
+
+    // Marked transforms are only removed once destruction is processed.
+    transform_manager.destroy_all(second);
+    assert!(transform_manager.contains(second));
+    transform_manager.destroy_marked();
+    assert!(!transform_manager.contains(second));
+}
+
+#[test]
+fn uniform_scale() {
+    use ecs::EntityManager;
+
+    let mut transform = Transform::new();
+    transform.set_uniform_scale(2.5);
+    assert_eq!(transform.scale(), Vector3::new(2.5, 2.5, 2.5));
+
+    // Non-uniform scales are allowed by default.
+    transform.set_scale(Vector3::new(2.0, 1.0, 1.0));
+    assert_eq!(transform.scale(), Vector3::new(2.0, 1.0, 1.0));
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let existing = entity_manager.create();
+    transform_manager.assign(existing);
+    transform_manager.set_scale_constraint(ScaleConstraint::UniformAverage);
+
+    let entity = entity_manager.create();
+    transform_manager.assign(entity);
+    for &entity in &[existing, entity] {
+        let mut transform = transform_manager.get_mut(entity);
+        transform.set_scale(Vector3::new(2.0, 1.0, 1.0));
+        assert!((transform.scale() - Vector3::new(4.0 / 3.0, 4.0 / 3.0, 4.0 / 3.0)).is_zero());
+
+        transform.set_uniform_scale(3.0);
+        assert_eq!(transform.scale(), Vector3::new(3.0, 3.0, 3.0));
+    }
+
+    assert_eq!(ScaleConstraint::UniformAssert.apply(Vector3::one()), Vector3::one());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn uniform_scale_assert() {
+    let mut transform = Transform::new();
+    transform.scale_constraint = ScaleConstraint::UniformAssert;
+    transform.set_scale(Vector3::new(2.0, 1.0, 1.0));
+}
+
+#[test]
+fn spin_at_constant_rate() {
+    let mut transform = Transform::new();
+    let axis = Vector3::new(0.0, 2.0, 0.0);
+
+    // A quarter turn per second for one second at 60 frames per second.
+    for _ in 0..60 {
+        transform.spin(axis, PI * 0.5, 1.0 / 60.0);
+    }
+
+    let expected = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5);
+    assert!((Quaternion::dot(transform.rotation(), expected).abs() - 1.0).abs() < 1e-4);
+    assert!(transform.rotation().is_normalized());
+
+    // A huge time step still gives a valid rotation.
+    transform.spin(axis, 3.0, 1.0e7);
+    assert!(transform.rotation().is_normalized());
+
+    let before = transform.rotation();
+    transform.spin(Vector3::zero(), 1.0, 1.0);
+    assert_eq!(transform.rotation(), before);
+}
+
+#[test]
+fn translate_local_follows_facing() {
+    use ecs::EntityManager;
+
+    // Yawing a quarter turn to the left turns forward (-Z) to -X and right (+X) to -Z.
+    let yaw = Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), PI * 0.5);
+
+    let mut transform = Transform::new();
+    transform.set_position(Point::new(1.0, 2.0, 3.0));
+    transform.set_rotation(yaw);
+    transform.translate_local(CoordinateConvention::YUp.forward_axis());
+    assert!((transform.position() - Point::new(0.0, 2.0, 3.0)).is_zero());
+
+    transform.translate_local(Vector3::new(2.0, 0.0, 0.0));
+    assert!((transform.position() - Point::new(0.0, 2.0, 1.0)).is_zero());
+
+    // A child moves along its own facing in world space even though its rotation is relative to
+    // its parent.
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+    transform_manager.assign(parent).set_rotation(yaw);
+    transform_manager.assign(child);
+    transform_manager.set_child(parent, child);
+    transform_manager.update_parallel(1);
+
+    transform_manager.get_mut(child).translate_local(Vector3::new(0.0, 0.0, -1.0));
+    transform_manager.update_parallel(1);
+    let child_transform = transform_manager.get(child);
+    assert!((child_transform.position() - Point::new(0.0, 0.0, -1.0)).is_zero());
+    assert!((child_transform.position_derived() - Point::new(-1.0, 0.0, 0.0)).is_zero());
+}
+
+#[test]
+fn reset_restores_defaults() {
+    use ecs::EntityManager;
+
+    let mut transform = Transform::new();
+    assert!(transform.is_identity());
+
+    transform.set_position(Point::new(1.0, 2.0, 3.0));
+    assert!(!transform.is_identity());
+    transform.reset();
+    assert!(transform.is_identity());
+
+    transform.set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5));
+    assert!(!transform.is_identity());
+    transform.reset();
+    assert!(transform.is_identity());
+
+    transform.set_scale(Vector3::new(1.0, 2.0, 1.0));
+    assert!(!transform.is_identity());
+    transform.reset();
+    assert_eq!(transform.position(), Point::origin());
+    assert_eq!(transform.rotation(), Quaternion::identity());
+    assert_eq!(transform.scale(), Vector3::one());
+
+    // A negated identity rotation and tiny errors still count as identity.
+    transform.set_rotation(Quaternion { w: -1.0, x: 0.0, y: 0.0, z: 0.0 });
+    transform.set_position(Point::new(1e-8, 0.0, 0.0));
+    assert!(transform.is_identity());
+
+    // Resetting through the manager marks the transform for update.
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let entity = entity_manager.create();
+    transform_manager.assign(entity).set_position(Point::new(4.0, 0.0, 0.0));
+    transform_manager.update_parallel(1);
+
+    transform_manager.reset(entity);
+    assert!(transform_manager.get(entity).is_identity());
+    transform_manager.update_parallel(1);
+    assert_eq!(transform_manager.get(entity).position_derived(), Point::origin());
+}
+
+#[test]
+fn nearest_candidate() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    let positions = [
+        Point::new(10.0, 0.0, 0.0),
+        Point::new(0.0, 3.0, 4.0),
+        Point::new(-6.0, 0.0, 0.0),
+        Point::new(0.0, 0.0, -20.0),
+    ];
+
+    let mut entities = Vec::new();
+    for &position in &positions {
+        let entity = entity_manager.create();
+        transform_manager.assign(entity).set_position(position);
+        entities.push(entity);
+    }
+    let untransformed = entity_manager.create();
+    transform_manager.update_parallel(1);
+
+    let (nearest, distance) = transform_manager.nearest(Point::origin(), entities.iter().cloned()).unwrap();
+    assert_eq!(nearest, entities[1]);
+    assert!((distance - 5.0).abs() < 1e-5);
+
+    // Untransformed candidates are skipped.
+    let candidates = vec![untransformed, entities[0], entities[3]];
+    assert_eq!(transform_manager.nearest(Point::new(0.0, 0.0, -15.0), candidates), Some((entities[3], 5.0)));
+
+    assert_eq!(transform_manager.nearest(Point::origin(), Vec::new()), None);
+    assert_eq!(transform_manager.nearest(Point::origin(), vec![untransformed]), None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Transform for entity 1 is already immutably borrowed")]
+fn conflicting_borrow_names_entity() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let entity = entity_manager.create();
+    transform_manager.assign(entity);
+
+    let _transform = transform_manager.get(entity);
+    transform_manager.get_mut(entity);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Transform for entity 2 is already mutably borrowed")]
+fn conflicting_mut_borrow_names_entity() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    entity_manager.create();
+    let entity = entity_manager.create();
+    transform_manager.assign(entity);
+
+    let _transform = transform_manager.get_mut(entity);
+    transform_manager.try_get(entity);
+}
+
+#[test]
+fn shallow_hierarchy_children_stored_inline() {
+    use ecs::EntityManager;
+    use super::child_list::INLINE_CAPACITY;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    // A wide but shallow hierarchy where no transform has more children than fit inline.
+    let mut roots = Vec::new();
+    for _ in 0..64 {
+        let root = entity_manager.create();
+        transform_manager.assign(root);
+        let mut expected = vec![root];
+        for _ in 0..INLINE_CAPACITY {
+            let child = entity_manager.create();
+            transform_manager.assign(child);
+            transform_manager.set_child(root, child);
+            expected.push(child);
+        }
+
+        roots.push((root, expected));
+    }
+
+    // Walking visits children directly from the inline lists, so there are no per-node heap
+    // allocations for the child lists. Every hierarchy is the same size, so one output buffer
+    // sized for a single subtree is reused for every walk and its capacity never changes.
+    assert!(transform_manager.children.values().all(|children| !children.is_spilled()));
+    let mut visited = Vec::with_capacity(INLINE_CAPACITY + 1);
+    let capacity = visited.capacity();
+    for &(root, ref expected) in &roots {
+        visited.clear();
+        transform_manager.walk_children(root, &mut |entity| visited.push(entity));
+        assert_eq!(&visited, expected);
+        assert_eq!(visited.capacity(), capacity);
+
+        visited.clear();
+        visited.extend(transform_manager.iter_subtree(root));
+        assert_eq!(&visited, expected);
+        assert_eq!(visited.capacity(), capacity);
+    }
+
+    // Overflowing the inline storage spills to the heap transparently.
+    let (wide_root, ref expected) = roots[0];
+    let mut expected = expected.clone();
+    let extra = entity_manager.create();
+    transform_manager.assign(extra);
+    transform_manager.set_child(wide_root, extra);
+    expected.push(extra);
+    assert!(transform_manager.children[&wide_root].is_spilled());
+
+    let mut visited = Vec::new();
+    transform_manager.walk_hierarchy(wide_root, &mut |entity, _| visited.push(entity));
+    assert_eq!(visited, expected);
+
+    // Moving a child to another parent updates both child lists.
+    let (other_root, _) = roots[1];
+    transform_manager.set_child(other_root, extra);
+    assert_eq!(transform_manager.children[&wide_root].len(), INLINE_CAPACITY);
+    assert_eq!(transform_manager.children[&other_root].len(), INLINE_CAPACITY + 1);
+
+    // Unparenting the last child removes the list entirely.
+    let (last_root, ref last_expected) = roots[63];
+    for &child in &last_expected[1..] {
+        transform_manager.unparent_keep_world(child);
+    }
+    assert!(!transform_manager.children.contains_key(&last_root));
+}
+
+#[test]
+fn random_operations_keep_invariants() {
+    use ecs::EntityManager;
+    use math::random::Rng;
+
+    /// Picks a random element of `entities`, which must not be empty.
+    fn pick(rng: &mut Rng, entities: &[Entity]) -> Entity {
+        entities[rng.next_u32() as usize % entities.len()]
+    }
+
+    let mut rng = Rng::new(659);
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut live = Vec::new();
+
+    for _ in 0..5000 {
+        match rng.next_u32() % 8 {
+            // Assigning is the most common operation so that the hierarchy keeps growing.
+            0 ... 2 => {
+                let entity = entity_manager.create();
+                transform_manager.assign(entity);
+                live.push(entity);
+            },
+
+            // Reparent to a random transform, skipping moves that would create a cycle.
+            3 ... 4 if live.len() >= 2 => {
+                let child = pick(&mut rng, &live);
+                let parent = pick(&mut rng, &live);
+
+                let mut subtree = Vec::new();
+                transform_manager.walk_children(child, &mut |entity| subtree.push(entity));
+                if !subtree.contains(&parent) {
+                    transform_manager.set_child(parent, child);
+                }
+            },
+
+            5 if !live.is_empty() => {
+                let entity = pick(&mut rng, &live);
+                transform_manager.unparent_keep_world(entity);
+            },
+
+            // Destroy a transform and its children the same way `Scene::destroy_entity()` does.
+            6 ... 7 if !live.is_empty() => {
+                let entity = pick(&mut rng, &live);
+                let mut subtree = Vec::new();
+                transform_manager.walk_children(entity, &mut |entity| subtree.push(entity));
+                for &entity in &subtree {
+                    transform_manager.destroy_all(entity);
+                }
+
+                transform_manager.destroy_marked();
+                live.retain(|entity| !subtree.contains(entity));
+            },
+
+            _ => {},
+        }
+
+        transform_manager.assert_invariants();
+        assert_eq!(transform_manager.indices.len(), live.len());
+    }
+}
+
+#[test]
+fn iter_subtree_matches_walk_children() {
+    use ecs::EntityManager;
+    use super::child_list::INLINE_CAPACITY;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    // A root with a wide (spilled) child list, a deep chain, and a leaf with no children.
+    let root = entity_manager.create();
+    transform_manager.assign(root);
+    let mut parents = vec![root];
+    for depth in 0..4 {
+        let parent = parents[depth];
+        for _ in 0..(INLINE_CAPACITY + 1 - depth) {
+            let child = entity_manager.create();
+            transform_manager.assign(child);
+            transform_manager.set_child(parent, child);
+        }
+
+        let last_child = *transform_manager.children[&parent].as_slice().last().unwrap();
+        parents.push(last_child);
+    }
+
+    let unrelated = entity_manager.create();
+    transform_manager.assign(unrelated);
+
+    for &start in parents.iter().chain(Some(unrelated).iter()) {
+        let mut walked = Vec::new();
+        transform_manager.walk_children(start, &mut |entity| walked.push(entity));
+        let iterated: Vec<Entity> = transform_manager.iter_subtree(start).collect();
+        assert_eq!(iterated, walked);
+    }
+
+    // The whole subtree is visited, and it doesn't escape into the rest of the hierarchy.
+    let total: usize = transform_manager.iter_subtree(root).count();
+    assert_eq!(total, 1 + (5 + 4 + 3 + 2));
+    assert_eq!(transform_manager.iter_subtree(parents[2]).count(), 1 + 3 + 2);
+    assert_eq!(transform_manager.iter_subtree(unrelated).collect::<Vec<_>>(), vec![unrelated]);
+
+    // Early exit and transform access while iterating.
+    let deepest = parents[4];
+    let found = transform_manager.iter_subtree(root).find(|&entity| entity == deepest);
+    assert_eq!(found, Some(deepest));
+    for entity in transform_manager.iter_subtree(parents[3]).take(2) {
+        transform_manager.get_mut(entity).translate(Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    // An entity without a transform has an empty subtree.
+    let missing = entity_manager.create();
+    assert_eq!(transform_manager.iter_subtree(missing).next(), None);
+}
+
+#[test]
+fn topological_order_puts_parents_first() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    // Three separate hierarchies of different depths plus a lone root. Children are assigned
+    // before their parents and a subtree is moved to a deeper parent to shuffle the rows.
+    let entities: Vec<Entity> = (0..10).map(|_| entity_manager.create()).collect();
+    for &entity in entities.iter().rev() {
+        transform_manager.assign(entity);
+    }
+
+    transform_manager.set_child(entities[0], entities[1]);
+    transform_manager.set_child(entities[1], entities[2]);
+    transform_manager.set_child(entities[3], entities[4]);
+    transform_manager.set_child(entities[3], entities[5]);
+    transform_manager.set_child(entities[6], entities[7]);
+    transform_manager.set_child(entities[7], entities[8]);
+    transform_manager.set_child(entities[2], entities[6]);
+
+    let order = transform_manager.topological_order();
+    assert_eq!(order.len(), entities.len());
+
+    let position = |entity: Entity| order.iter().position(|&other| other == entity).unwrap();
+    let parent_of = |entity: Entity| {
+        let (row, index) = transform_manager.indices[&entity];
+        transform_manager.entities[row][index].1
+    };
+    for &entity in &entities {
+        // Every ancestor, not just the direct parent, comes first.
+        let mut current = entity;
+        while let Some(parent) = parent_of(current) {
+            assert!(position(parent) < position(entity));
+            current = parent;
+        }
+    }
+
+    assert_eq!(transform_manager.root_of(entities[8]), Some(entities[0]));
+    assert_eq!(transform_manager.depth(entities[8]), Some(5));
+}
+
+#[test]
+fn raycast_picks_nearest() {
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+
+    // A row of boxes along -Z. `rotated` is turned 45 degrees so its corner faces the ray, which
+    // makes it closer than its unrotated bounds would be, and `stretched` is scaled so it's
+    // closest of all. `offset` is off to the side and `parent` is only there to place `child`.
+    let far = entity_manager.create();
+    let rotated = entity_manager.create();
+    let stretched = entity_manager.create();
+    let offset = entity_manager.create();
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+
+    transform_manager.assign(far).set_position(Point::new(0.0, 0.0, -20.0));
+    {
+        let mut transform = transform_manager.assign(rotated);
+        transform.set_position(Point::new(0.0, 0.0, -10.0));
+        transform.set_rotation(Quaternion::axis_angle(Vector3::up(), PI / 4.0));
+    }
+    {
+        let mut transform = transform_manager.assign(stretched);
+        transform.set_position(Point::new(0.0, 0.0, -8.0));
+        transform.set_scale(Vector3::new(1.0, 1.0, 3.0));
+    }
+    transform_manager.assign(offset).set_position(Point::new(5.0, 0.0, -2.0));
+    transform_manager.assign(parent).set_position(Point::new(0.0, 10.0, 0.0));
+    transform_manager.assign(child).set_position(Point::new(0.0, -10.0, -30.0));
+    transform_manager.set_child(parent, child);
+    transform_manager.update_sequential();
+
+    let unit = AABB {
+        min: Point::new(-1.0, -1.0, -1.0),
+        max: Point::new(1.0, 1.0, 1.0),
+    };
+    let mut local_bounds: HashMap<Entity, AABB> = HashMap::new();
+    for &entity in &[far, rotated, offset, parent, child] {
+        local_bounds.insert(entity, unit);
+    }
+
+    let ray = Ray::new(Point::origin(), Vector3::forward());
+
+    // Without the stretched box, the rotated box's corner is hit at 10 - sqrt(2).
+    let (entity, distance) = transform_manager.raycast(&ray, &local_bounds).unwrap();
+    assert_eq!(entity, rotated);
+    assert!((distance - (10.0 - 2.0f32.sqrt())).abs() < 1e-4, "Hit at {}", distance);
+
+    local_bounds.insert(stretched, unit);
+    let (entity, distance) = transform_manager.raycast(&ray, &local_bounds).unwrap();
+    assert_eq!(entity, stretched);
+    assert!((distance - 5.0).abs() < 1e-4, "Hit at {}", distance);
+
+    // The child is placed through its parent's transform.
+    let (entity, distance) = transform_manager.raycast(&Ray::new(Point::new(0.0, 0.0, -25.0), Vector3::forward()), &local_bounds).unwrap();
+    assert_eq!(entity, child);
+    assert!((distance - 4.0).abs() < 1e-4, "Hit at {}", distance);
+
+    // Starting inside a box is a hit at 0, missing everything is `None`.
+    assert_eq!(transform_manager.raycast(&Ray::new(Point::new(5.0, 0.5, -2.0), Vector3::up()), &local_bounds), Some((offset, 0.0)));
+    assert_eq!(transform_manager.raycast(&Ray::new(Point::origin(), Vector3::new(1.0, 0.0, 0.0)), &local_bounds), None);
+}
+
+#[test]
+fn frozen_axes() {
+    let mut transform = Transform::new();
+    transform.set_position(Point::new(1.0, 2.0, 3.0));
+    transform.freeze_position(false, true, false);
+    assert_eq!(transform.frozen_position(), (false, true, false));
+
+    transform.set_position(Point::new(4.0, 5.0, 6.0));
+    assert_eq!(transform.position(), Point::new(4.0, 2.0, 6.0));
+
+    transform.translate(Vector3::new(1.0, 1.0, 1.0));
+    assert_eq!(transform.position(), Point::new(5.0, 2.0, 7.0));
+
+    // Moving along a tilted local axis only moves along the unfrozen axes.
+    transform.set_rotation(Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), PI / 4.0));
+    transform.translate_local(Vector3::new(0.0, 0.0, -2.0));
+    assert_eq!(transform.position().y, 2.0);
+
+    transform.freeze_position(false, false, false);
+    transform.translate(Vector3::new(0.0, 1.0, 0.0));
+    assert_eq!(transform.position().y, 3.0);
+
+    // With Y frozen a rotation about Y is discarded, a rotation about X is kept, and a combined
+    // rotation only keeps the part that doesn't twist about Y.
+    let mut transform = Transform::new();
+    transform.freeze_rotation_axis(Vector3::new(0.0, 2.0, 0.0));
+    assert_eq!(transform.frozen_rotation_axis(), Some(Vector3::new(0.0, 1.0, 0.0)));
+
+    transform.rotate(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 1.0));
+    assert!(transform.is_identity());
+
+    let tilt = Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.5);
+    transform.set_rotation(tilt);
+    assert!((Quaternion::dot(transform.rotation(), tilt).abs() - 1.0).is_zero());
+
+    transform.rotate(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.7));
+    assert!((Quaternion::dot(transform.rotation(), tilt).abs() - 1.0).is_zero());
+
+    // Spinning about the frozen axis does nothing, and the result is still a valid rotation.
+    transform.spin(Vector3::new(0.0, 1.0, 0.0), 3.0, 0.1);
+    assert!((Quaternion::dot(transform.rotation(), tilt).abs() - 1.0).is_zero());
+    assert!(transform.rotation().is_normalized());
+
+    // A change that both swings and twists only keeps the swing, whose axis is perpendicular
+    // to the frozen axis.
+    let mut transform = Transform::new();
+    transform.freeze_rotation_axis(Vector3::new(0.0, 1.0, 0.0));
+    transform.set_rotation(Quaternion::axis_angle(Vector3::new(1.0, 1.0, 0.0).normalized(), 1.0));
+    let rotation = transform.rotation();
+    assert!(rotation.y.abs() < 1e-5, "{:?} still twists about Y", rotation);
+    assert!(rotation.x.abs() > 0.1);
+
+    transform.freeze_rotation_axis(Vector3::zero());
+    assert_eq!(transform.frozen_rotation_axis(), None);
+    transform.set_rotation(Quaternion::axis_angle(Vector3::new(0.0, 1.0, 0.0), 1.0));
+    assert!(!transform.is_identity());
+}
+
+#[test]
+fn translation_only_fast_path() {
+    fn general_local_matrix(transform: &Transform) -> Matrix4 {
+        Matrix4::from_point(transform.position())
+        * (transform.rotation().as_matrix4() * Matrix4::from_scale_vector(transform.scale()))
+    }
+
+    fn general_count() -> usize {
+        GENERAL_LOCAL_MATRICES.with(|count| count.get())
+    }
+
+    let mut transform = Transform::new();
+    transform.set_position(Point::new(1.0, -2.0, 3.0));
+    transform.translate(Vector3::new(0.5, 0.5, 0.5));
+
+    // Tests run on separate threads, so the count is only affected by this test.
+    let before = general_count();
+    assert_eq!(transform.local_matrix(), general_local_matrix(&transform));
+    assert_eq!(general_count(), before);
+
+    // A non-identity rotation or scale takes the general path.
+    transform.set_rotation(Quaternion::axis_angle(Vector3::up(), 0.5));
+    assert_eq!(transform.local_matrix(), general_local_matrix(&transform));
+    assert_eq!(general_count(), before + 1);
+
+    transform.set_rotation(Quaternion::identity());
+    transform.set_scale(Vector3::new(1.0, 2.0, 1.0));
+    assert_eq!(transform.local_matrix(), general_local_matrix(&transform));
+    assert_eq!(general_count(), before + 2);
+
+    // Going back to identity re-enables the fast path.
+    transform.set_uniform_scale(1.0);
+    assert_eq!(transform.local_matrix(), general_local_matrix(&transform));
+    assert_eq!(general_count(), before + 2);
+
+    transform.rotate(Quaternion::axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.1));
+    transform.local_matrix();
+    assert_eq!(general_count(), before + 3);
+
+    transform.reset();
+    transform.set_position(Point::new(4.0, 0.0, 0.0));
+    assert_eq!(transform.local_matrix(), Matrix4::translation(4.0, 0.0, 0.0));
+    assert_eq!(general_count(), before + 3);
 }
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+
+use math::*;
+
+use ecs::{Entity, System, ComponentManager};
+use scene::Scene;
+use super::{EntityMap, EntitySet};
+use super::transform::TransformManager;
+
+/// The default distance an entity can move in a single update before it's considered to have
+/// teleported, see `VelocityManager::set_teleport_distance()`.
+pub const DEFAULT_TELEPORT_DISTANCE: f32 = 10.0;
+
+/// Tracks the velocities of entities by comparing their derived transforms between frames.
+///
+/// # Details
+///
+/// Only entities added with `track()` have their velocities calculated. The velocities are
+/// recalculated by `VelocitySystem` every frame after the transform update, using the change in
+/// derived position and rotation since the previous frame. An entity's velocities are zero for
+/// the first frame it's tracked, since there's no previous frame to compare against.
+///
+/// An entity that moves further than the teleport distance in a single frame was probably moved
+/// directly to a new location (e.g. respawned) rather than travelling there, so it's flagged as
+/// teleported for that frame. Its velocity is still reported but should generally be ignored,
+/// e.g. to avoid a burst of doppler shift.
+#[derive(Debug, Clone)]
+pub struct VelocityManager {
+    tracked: EntityMap<Velocity>,
+    teleport_distance: f32,
+    marked_for_destroy: RefCell<EntitySet>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Velocity {
+    previous: Option<(Point, Quaternion)>,
+    linear: Vector3,
+    angular: Vector3,
+    teleported: bool,
+}
+
+impl VelocityManager {
+    pub fn new() -> VelocityManager {
+        VelocityManager {
+            tracked: HashMap::default(),
+            teleport_distance: DEFAULT_TELEPORT_DISTANCE,
+            marked_for_destroy: RefCell::new(HashSet::default()),
+        }
+    }
+
+    /// Starts tracking the velocity of the entity.
+    ///
+    /// Tracking an entity that's already tracked resets it as though it had just been tracked.
+    pub fn track(&mut self, entity: Entity) {
+        self.tracked.insert(entity, Velocity {
+            previous: None,
+            linear: Vector3::zero(),
+            angular: Vector3::zero(),
+            teleported: false,
+        });
+    }
+
+    pub fn untrack(&mut self, entity: Entity) {
+        self.tracked.remove(&entity);
+    }
+
+    /// Sets how far an entity can move in a single update before it's flagged as teleported.
+    pub fn set_teleport_distance(&mut self, distance: f32) {
+        self.teleport_distance = distance;
+    }
+
+    /// Retrieves the entity's velocity in world units per second, or `None` if the entity isn't
+    /// tracked.
+    pub fn linear_velocity(&self, entity: Entity) -> Option<Vector3> {
+        self.tracked.get(&entity).map(|velocity| velocity.linear)
+    }
+
+    /// Retrieves the entity's angular velocity in world space, or `None` if the entity isn't
+    /// tracked.
+    ///
+    /// The direction is the axis of rotation and the magnitude is the rate of rotation in radians
+    /// per second. Rotations of more than half a turn in a single update can't be distinguished
+    /// from a shorter rotation in the opposite direction, so the shorter rotation is assumed.
+    pub fn angular_velocity(&self, entity: Entity) -> Option<Vector3> {
+        self.tracked.get(&entity).map(|velocity| velocity.angular)
+    }
+
+    /// Checks if the entity moved further than the teleport distance in the last update.
+    pub fn teleported(&self, entity: Entity) -> bool {
+        self.tracked.get(&entity).map_or(false, |velocity| velocity.teleported)
+    }
+
+    /// Recalculates the velocities of all tracked entities from their current derived transforms.
+    ///
+    /// # Details
+    ///
+    /// `delta` is the time elapsed since the last update, the derived transforms must already be
+    /// up to date. If `delta` isn't positive (e.g. while the game is paused) the velocities are
+    /// left unchanged. Tracked entities that don't have a transform have zero velocity.
+    pub fn update_velocities(&mut self, transform_manager: &TransformManager, delta: f32) {
+        if delta <= 0.0 {
+            return;
+        }
+
+        for (&entity, velocity) in self.tracked.iter_mut() {
+            let (position, rotation) = match transform_manager.try_get(entity) {
+                Some(transform) => (transform.position_derived(), transform.rotation_derived()),
+                None => {
+                    velocity.previous = None;
+                    velocity.linear = Vector3::zero();
+                    velocity.angular = Vector3::zero();
+                    velocity.teleported = false;
+                    continue;
+                },
+            };
+
+            if let Some((previous_position, previous_rotation)) = velocity.previous {
+                let offset = position - previous_position;
+                velocity.linear = offset / delta;
+                velocity.angular = angular_velocity(previous_rotation, rotation, delta);
+                velocity.teleported = offset.magnitude() > self.teleport_distance;
+            }
+
+            velocity.previous = Some((position, rotation));
+        }
+    }
+}
+
+/// Calculates the world space angular velocity needed to rotate from `previous` to `current` in
+/// `delta` seconds.
+fn angular_velocity(previous: Quaternion, current: Quaternion, delta: f32) -> Vector3 {
+    let mut difference = current * previous.conjugate();
+
+    // `q` and `-q` represent the same rotation, pick the one that takes the shorter path.
+    if difference.w < 0.0 {
+        difference = Quaternion { w: -difference.w, x: -difference.x, y: -difference.y, z: -difference.z };
+    }
+
+    let axis = Vector3::new(difference.x, difference.y, difference.z);
+    if axis.is_zero() {
+        return Vector3::zero();
+    }
+
+    let angle = 2.0 * difference.w.clamp(-1.0, 1.0).acos();
+    axis.normalized() * (angle / delta)
+}
+
+impl ComponentManager for VelocityManager {
+    fn destroy_all(&self, entity: Entity) {
+        if self.tracked.contains_key(&entity) {
+            self.marked_for_destroy.borrow_mut().insert(entity);
+        }
+    }
+
+    fn contains(&self, entity: Entity) -> bool {
+        self.tracked.contains_key(&entity)
+    }
+
+    fn destroy_marked(&mut self) {
+        let mut marked_for_destroy = RefCell::new(HashSet::default());
+        ::std::mem::swap(&mut marked_for_destroy, &mut self.marked_for_destroy);
+        let mut marked_for_destroy = marked_for_destroy.into_inner();
+        for entity in marked_for_destroy.drain() {
+            self.tracked.remove(&entity);
+        }
+    }
+}
+
+/// Updates the velocities in the `VelocityManager`, must run after the transform update.
+pub struct VelocitySystem;
+
+impl System for VelocitySystem {
+    fn update(&mut self, scene: &Scene, delta: f32) {
+        let transform_manager = scene.get_manager::<TransformManager>();
+        scene.get_manager_mut::<VelocityManager>().update_velocities(&transform_manager, delta);
+    }
+}
+
+#[test]
+fn velocity_from_movement() {
+    use std::f32::consts::PI;
+    use ecs::EntityManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut velocity_manager = VelocityManager::new();
+
+    let entity = entity_manager.create();
+    let untracked = entity_manager.create();
+    transform_manager.assign(entity).set_position(Point::new(1.0, 0.0, 0.0));
+    velocity_manager.track(entity);
+
+    // There's no previous frame to compare against at first.
+    transform_manager.update_parallel(1);
+    velocity_manager.update_velocities(&transform_manager, 0.5);
+    assert_eq!(velocity_manager.linear_velocity(entity), Some(Vector3::zero()));
+
+    // Moving 3 units along X and 1.5 along Y in half a second.
+    {
+        let mut transform = transform_manager.get_mut(entity);
+        transform.set_position(Point::new(4.0, 1.5, 0.0));
+        transform.set_rotation(Quaternion::axis_angle(Vector3::up(), PI * 0.25));
+    }
+    transform_manager.update_parallel(1);
+    velocity_manager.update_velocities(&transform_manager, 0.5);
+
+    let linear = velocity_manager.linear_velocity(entity).unwrap();
+    assert!((linear - Vector3::new(6.0, 3.0, 0.0)).is_zero_within(1e-4), "{:?}", linear);
+    let angular = velocity_manager.angular_velocity(entity).unwrap();
+    assert!((angular - Vector3::new(0.0, PI * 0.5, 0.0)).is_zero_within(1e-4), "{:?}", angular);
+    assert!(!velocity_manager.teleported(entity));
+
+    // A zero delta leaves the velocity alone.
+    velocity_manager.update_velocities(&transform_manager, 0.0);
+    assert_eq!(velocity_manager.linear_velocity(entity), Some(linear));
+
+    // A huge jump is flagged as a teleport.
+    transform_manager.get_mut(entity).set_position(Point::new(100.0, 0.0, 0.0));
+    transform_manager.update_parallel(1);
+    velocity_manager.update_velocities(&transform_manager, 0.5);
+    assert!(velocity_manager.teleported(entity));
+
+    assert_eq!(velocity_manager.linear_velocity(untracked), None);
+    assert!(!velocity_manager.teleported(untracked));
+}
@@ -1,38 +1,276 @@
 use std::collections::VecDeque;
 use std::fmt;
+use std::marker::PhantomData;
+use std::ops::BitOr;
 
 use scene::Scene;
+use serialize::{Serialize, Deserialize, ByteReader, SerializeError};
 
+/// The number of low bits of an entity's ID that hold its index, the remaining high bits hold
+/// its generation.
+pub const ENTITY_INDEX_BITS: u32 = 24;
+
+/// The largest index an entity can have, which limits how many entities can be alive at once.
+pub const MAX_ENTITY_INDEX: u32 = (1 << ENTITY_INDEX_BITS) - 1;
+
+/// A handle to an entity in the scene.
+///
+/// # Details
+///
+/// The ID is made up of an index, which identifies the entity's slot in the `EntityAllocator`,
+/// and a generation, which counts how many times that slot has been reused. When an entity is
+/// destroyed its index is reused for a later entity with the next generation, so the stale
+/// handle doesn't compare equal to the new entity even though they share a slot.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Entity(u32);
 
+impl Entity {
+    fn new(index: u32, generation: u8) -> Entity {
+        debug_assert!(index <= MAX_ENTITY_INDEX, "Entity index {} is too large", index);
+        Entity(((generation as u32) << ENTITY_INDEX_BITS) | index)
+    }
+
+    /// Retrieves the unique ID of the entity, which includes both its index and generation.
+    pub fn id(&self) -> u32 {
+        self.0
+    }
+
+    /// Retrieves the index of the entity's slot in the allocator.
+    pub fn index(&self) -> u32 {
+        self.0 & MAX_ENTITY_INDEX
+    }
+
+    /// Retrieves the number of times the entity's slot had been reused when it was created.
+    pub fn generation(&self) -> u8 {
+        (self.0 >> ENTITY_INDEX_BITS) as u8
+    }
+
+    /// Recreates an entity from an ID previously retrieved with `id()`.
+    ///
+    /// # Details
+    ///
+    /// This is meant for loading serialized scenes, normally entities should only be created
+    /// through `EntityManager::create()`. The caller is responsible for making sure the ID
+    /// doesn't collide with entities created by the entity manager.
+    pub fn from_id(id: u32) -> Entity {
+        Entity(id)
+    }
+}
+
+/// A typed reference to a component stored in a component manager.
+///
+/// # Details
+///
+/// Handles are retrieved from a manager (e.g. `StructComponentManager::handle()`) and allow the
+/// component to be accessed without looking up the entity. The type parameter is the component
+/// type, so a `Handle<Camera>` can't accidentally be used where a `Handle<Light>` is expected.
+///
+/// Components can move within their manager when other components are destroyed, so a handle
+/// may become stale. Each handle records the generation of the slot it refers to, which lets
+/// the manager detect stale handles (including handles to components of destroyed entities)
+/// and return `None` instead of another entity's component.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Handle<T> {
+    pub fn new(index: usize, generation: u32) -> Handle<T> {
+        debug_assert!(index <= ::std::u32::MAX as usize, "Handle index {} is too large", index);
+
+        Handle {
+            index: index as u32,
+            generation: generation,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// These are implemented manually because deriving them would require `T` to implement them too.
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Handle<T>) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle({}, generation {})", self.index, self.generation)
+    }
+}
+
+/// Mints entity IDs, reusing the indices of freed entities with a new generation.
+///
+/// # Details
+///
+/// Freed indices are reused in the order they were freed, so an index sits unused for as long as
+/// possible before being handed out again. Each reuse increments the slot's generation, which
+/// wraps around after 256 reuses, at which point a very old handle could alias a new entity.
+///
+/// Index 0 is never allocated, so no valid entity has an ID of 0.
+#[derive(Debug, Clone)]
+pub struct EntityAllocator {
+    /// The generation of the entity currently using (or that will next use) each index.
+    generations: Vec<u8>,
+
+    /// Whether each index is in use by a live entity.
+    alive: Vec<bool>,
+
+    /// Freed indices waiting to be reused, oldest first.
+    free_indices: VecDeque<u32>,
+}
+
+impl EntityAllocator {
+    pub fn new() -> EntityAllocator {
+        EntityAllocator {
+            // Reserve index 0.
+            generations: vec![0],
+            alive: vec![false],
+            free_indices: VecDeque::new(),
+        }
+    }
+
+    /// Creates a new entity, reusing a freed index if there is one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every index up to `MAX_ENTITY_INDEX` is in use.
+    pub fn allocate(&mut self) -> Entity {
+        if let Some(index) = self.free_indices.pop_front() {
+            self.alive[index as usize] = true;
+            return Entity::new(index, self.generations[index as usize]);
+        }
+
+        let index = self.generations.len() as u32;
+        assert!(index <= MAX_ENTITY_INDEX, "Cannot allocate more than {} entities", MAX_ENTITY_INDEX);
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity::new(index, 0)
+    }
+
+    /// Frees the entity so that its index can be reused by a later entity.
+    ///
+    /// # Details
+    ///
+    /// Freeing an entity that isn't alive (because it was already freed, or it's a stale handle
+    /// to an earlier generation) is a bug and panics in debug builds. Release builds ignore it.
+    pub fn free(&mut self, entity: Entity) {
+        debug_assert!(self.is_alive(entity), "Cannot free entity {:?} because it isn't alive", entity);
+        if !self.is_alive(entity) {
+            return;
+        }
+
+        let index = entity.index() as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free_indices.push_back(entity.index());
+    }
+
+    /// Checks if the entity has been allocated and not yet freed.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index() as usize;
+        index < self.alive.len() && self.alive[index] && self.generations[index] == entity.generation()
+    }
+
+    /// Retrieves every live entity, ordered by index.
+    pub fn live_entities(&self) -> Vec<Entity> {
+        self.alive.iter()
+            .enumerate()
+            .filter(|&(_, &alive)| alive)
+            .map(|(index, _)| Entity::new(index as u32, self.generations[index]))
+            .collect()
+    }
+}
+
+impl Serialize for EntityAllocator {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        self.generations.serialize(bytes);
+        self.alive.serialize(bytes);
+        self.free_indices.iter().cloned().collect::<Vec<_>>().serialize(bytes);
+    }
+}
+
+impl Deserialize for EntityAllocator {
+    fn deserialize(reader: &mut ByteReader) -> Result<EntityAllocator, SerializeError> {
+        let offset = reader.offset();
+        let generations: Vec<u8> = try!(Vec::deserialize(reader));
+        let alive: Vec<bool> = try!(Vec::deserialize(reader));
+        let free_indices: Vec<u32> = try!(Vec::deserialize(reader));
+
+        // Every free index must be a dead slot, listed once, and there can't be dead slots
+        // missing from the free list (other than the reserved index 0) or they'd never be reused.
+        let mut listed = vec![false; alive.len()];
+        for &index in &free_indices {
+            let index = index as usize;
+            if index == 0 || index >= alive.len() || alive[index] || listed[index] {
+                return Err(SerializeError::InvalidData { offset: offset });
+            }
+            listed[index] = true;
+        }
+
+        let dead = alive.iter().skip(1).filter(|&&alive| !alive).count();
+        if generations.len() != alive.len() || alive.is_empty() || alive[0] || dead != free_indices.len() {
+            return Err(SerializeError::InvalidData { offset: offset });
+        }
+
+        Ok(EntityAllocator {
+            generations: generations,
+            alive: alive,
+            free_indices: free_indices.into_iter().collect(),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EntityManager {
-    entities: Vec<Entity>,
-    recycled_entities: VecDeque<Entity>,
+    allocator: EntityAllocator,
     marked_for_destroy: Vec<Entity>,
-    id_counter: u32
 }
 
 impl EntityManager {
     pub fn new() -> EntityManager {
         EntityManager {
-            entities: Vec::new(),
-            recycled_entities: VecDeque::new(),
+            allocator: EntityAllocator::new(),
             marked_for_destroy: Vec::new(),
-            id_counter: 1
         }
     }
 
+    /// Creates a new entity. See `EntityAllocator::allocate()` for how IDs are reused.
     pub fn create(&mut self) -> Entity {
-        if let Some(entity) = self.recycled_entities.pop_front() {
-            return entity;
-        }
+        self.allocator.allocate()
+    }
+
+    /// Checks if the entity has been created and not yet destroyed.
+    ///
+    /// Entities that are marked for destruction are still alive until the end of the frame.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.allocator.is_alive(entity)
+    }
 
-        let entity = Entity(self.id_counter);
-        self.id_counter += 1;
-        self.entities.push(entity);
-        entity
+    /// Retrieves every entity that has been created and not yet destroyed.
+    ///
+    /// Entities that are marked for destruction are still alive until the end of the frame.
+    pub fn live_entities(&self) -> Vec<Entity> {
+        self.allocator.live_entities()
     }
 
     pub fn mark_for_destroy(&mut self, entity: Entity) {
@@ -42,15 +280,31 @@ impl EntityManager {
 
     pub fn destroy_marked(&mut self) {
         for entity in self.marked_for_destroy.drain(0..) {
-            debug_assert!(!self.recycled_entities.iter().any(|existing| &entity == existing), "Trying to recycle entity {:?} but it is already recycled");
-            self.recycled_entities.push_back(entity)
+            self.allocator.free(entity);
         }
     }
 
     pub fn destroy_immediate(&mut self, entity: Entity) {
-        debug_assert!(!self.recycled_entities.iter().any(|existing| &entity == existing), "Trying to recycle entity {:?} but it is already recycled");
+        self.allocator.free(entity);
+    }
+}
 
-        self.recycled_entities.push_back(entity);
+impl Serialize for EntityManager {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        self.allocator.serialize(bytes);
+        self.marked_for_destroy.serialize(bytes);
+    }
+}
+
+impl Deserialize for EntityManager {
+    fn deserialize(reader: &mut ByteReader) -> Result<EntityManager, SerializeError> {
+        let allocator = try!(EntityAllocator::deserialize(reader));
+        let marked_for_destroy = try!(Vec::deserialize(reader));
+
+        Ok(EntityManager {
+            allocator: allocator,
+            marked_for_destroy: marked_for_destroy,
+        })
     }
 }
 
@@ -70,6 +324,85 @@ pub trait ComponentManager: ::std::any::Any {
 
     /// Destroy all previously marked components.
     fn destroy_marked(&mut self);
+
+    /// Performs any per-frame processing needed by the manager.
+    ///
+    /// # Details
+    ///
+    /// This is called once per frame for every registered manager by `Scene::update_managers()`,
+    /// after the user systems have run and before the transform update. The default
+    /// implementation does nothing.
+    ///
+    /// The manager is mutably borrowed from the scene for the duration of the call, so
+    /// `scene.get_manager()` and `scene.get_manager_mut()` may be used to access any *other*
+    /// manager, but trying to access the manager being updated through the scene will panic. Use
+    /// `self` instead.
+    fn update(&mut self, _scene: &Scene, _delta: f32) {}
+
+    /// Checks whether the entity has a component in this manager.
+    ///
+    /// # Details
+    ///
+    /// This is used by `Scene::query()`. The default implementation always returns `false`, so
+    /// managers that don't override it are never matched by a query.
+    fn contains(&self, _entity: Entity) -> bool {
+        false
+    }
+}
+
+/// The maximum number of component managers that can be registered with a single scene.
+pub const MAX_COMPONENT_MANAGERS: usize = 63;
+
+/// A set of component types, used to query the scene for entities that have all of them.
+///
+/// # Details
+///
+/// Each component manager registered with a scene is assigned its own bit, retrieved with
+/// `Scene::component_mask()`, and masks are combined with `|`:
+///
+/// ```ignore
+/// let mask = scene.component_mask::<TransformManager>() | scene.component_mask::<CameraManager>();
+/// for entity in scene.query(mask) {
+///     // ...
+/// }
+/// ```
+///
+/// Bits are assigned in the order managers are registered, so a mask is only meaningful for the
+/// scene that created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentMask(u64);
+
+impl ComponentMask {
+    /// Creates a mask that doesn't contain any component types.
+    pub fn empty() -> ComponentMask {
+        ComponentMask(0)
+    }
+
+    /// Creates a mask containing only the specified bit.
+    ///
+    /// `bit` may be at most `MAX_COMPONENT_MANAGERS`. Bits are normally assigned by the scene so
+    /// there should be little need to call this directly.
+    pub fn from_bit(bit: usize) -> ComponentMask {
+        assert!(bit <= MAX_COMPONENT_MANAGERS, "Component mask bit {} is out of range", bit);
+        ComponentMask(1 << bit)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Checks if every component type in `other` is also in `self`.
+    pub fn contains(self, other: ComponentMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ComponentMask {
+    type Output = ComponentMask;
+
+    fn bitor(self, rhs: ComponentMask) -> ComponentMask {
+        ComponentMask(self.0 | rhs.0)
+    }
 }
 
 impl ::std::fmt::Debug for ComponentManager {
@@ -77,3 +410,84 @@ impl ::std::fmt::Debug for ComponentManager {
         f.pad("ComponentManager")
     }
 }
+
+#[test]
+fn component_mask() {
+    let first = ComponentMask::from_bit(0);
+    let second = ComponentMask::from_bit(5);
+    let both = first | second;
+
+    assert!(ComponentMask::empty().is_empty());
+    assert!(both.contains(first));
+    assert!(both.contains(second));
+    assert!(both.contains(ComponentMask::empty()));
+    assert!(!first.contains(both));
+    assert!(!first.contains(second));
+}
+
+#[test]
+fn live_entities() {
+    let mut entity_manager = EntityManager::new();
+    let first = entity_manager.create();
+    let second = entity_manager.create();
+    let third = entity_manager.create();
+
+    entity_manager.destroy_immediate(second);
+    assert_eq!(entity_manager.live_entities(), vec![first, third]);
+
+    // The recycled slot is alive again once it's reused, but by a new entity.
+    let recycled = entity_manager.create();
+    assert_eq!(recycled.index(), second.index());
+    assert_eq!(entity_manager.live_entities(), vec![first, recycled, third]);
+    assert!(!entity_manager.is_alive(second));
+}
+
+#[test]
+fn allocator_reuses_with_new_generation() {
+    let mut allocator = EntityAllocator::new();
+    let first = allocator.allocate();
+    let second = allocator.allocate();
+    assert_eq!((first.index(), first.generation()), (1, 0));
+    assert_eq!((second.index(), second.generation()), (2, 0));
+
+    allocator.free(first);
+    assert!(!allocator.is_alive(first));
+    assert!(allocator.is_alive(second));
+
+    let reused = allocator.allocate();
+    assert_eq!((reused.index(), reused.generation()), (1, 1));
+    assert!(reused != first);
+    assert!(allocator.is_alive(reused));
+    assert!(!allocator.is_alive(first));
+
+    // Freed indices are reused oldest first, and fresh indices are used once they run out.
+    allocator.free(second);
+    allocator.free(reused);
+    assert_eq!(allocator.allocate(), Entity::new(2, 1));
+    assert_eq!(allocator.allocate(), Entity::new(1, 2));
+    assert_eq!(allocator.allocate(), Entity::new(3, 0));
+}
+
+#[test]
+fn allocator_generation_wraps() {
+    let mut allocator = EntityAllocator::new();
+    let original = allocator.allocate();
+    let mut entity = original;
+    for _ in 0..256 {
+        allocator.free(entity);
+        entity = allocator.allocate();
+    }
+
+    assert_eq!(entity.index(), original.index());
+    assert_eq!(entity.generation(), 0);
+}
+
+#[test]
+#[should_panic]
+#[cfg(debug_assertions)]
+fn allocator_double_free() {
+    let mut allocator = EntityAllocator::new();
+    let entity = allocator.allocate();
+    allocator.free(entity);
+    allocator.free(entity);
+}
@@ -10,6 +10,7 @@ use std::mem;
 use std::time::Duration;
 
 use bootstrap;
+use bootstrap::log;
 use bootstrap::input::ScanCode;
 use bootstrap::window::Window;
 use bootstrap::window::Message::*;
@@ -23,6 +24,7 @@ use resource::ResourceManager;
 use ecs::System;
 use component::*;
 use debug_draw::DebugDraw;
+use input::{InputRecorder, InputPlayer};
 
 pub const TARGET_FRAME_TIME_SECONDS: f32 = 1.0 / 60.0;
 pub const TARGET_FRAME_TIME_MS: f32 = TARGET_FRAME_TIME_SECONDS * 1000.0;
@@ -38,15 +40,21 @@ pub struct Engine {
     system_names: HashMap<String, TypeId>,
 
     transform_update: Box<System>,
+    transform_update_kind: TransformUpdateKind,
     light_update: Box<System>,
     audio_update: Box<System>,
     alarm_update: Box<System>,
+    lifetime_update: Box<System>,
+    velocity_update: Box<System>,
     collision_update: Box<System>,
 
     scene: Scene,
 
     debug_draw: DebugDraw,
 
+    input_recorder: Option<InputRecorder>,
+    input_player: Option<InputPlayer>,
+
     close: bool,
     debug_pause: bool,
 }
@@ -77,15 +85,21 @@ impl Engine {
             system_names: HashMap::new(),
 
             transform_update: Box::new(transform_update),
+            transform_update_kind: TransformUpdateKind::Sequential,
             light_update: Box::new(LightUpdateSystem),
             audio_update: Box::new(AudioSystem),
             alarm_update: Box::new(AlarmSystem),
+            lifetime_update: Box::new(LifetimeSystem),
+            velocity_update: Box::new(VelocitySystem),
             collision_update: Box::new(CollisionSystem::new()),
 
             scene: Scene::new(&resource_manager, audio_source),
 
             debug_draw: DebugDraw::new(renderer.clone(), &*resource_manager),
 
+            input_recorder: None,
+            input_player: None,
+
             close: false,
             debug_pause: false,
         }
@@ -107,6 +121,8 @@ impl Engine {
                         Close => self.close = true,
                         Destroy => (),
                         Paint => (),
+                        ScaleFactorChanged(_) => (),
+                        Minimized | Maximized | Restored => (),
 
                         // Handle inputs.
                         KeyDown(_)
@@ -115,24 +131,55 @@ impl Engine {
                       | MousePos(_, _)
                       | MouseButtonPressed(_)
                       | MouseButtonReleased(_)
-                      | MouseWheel(_) => scene.input.push_input(message),
+                      | MouseWheel { .. }
+                      | TextInput(_) => {
+                            // Live input is ignored while recorded input is being played back.
+                            if self.input_player.is_none() {
+                                scene.input.push_input(message);
+
+                                if let Some(ref mut recorder) = self.input_recorder {
+                                    recorder.record(message);
+                                }
+                            }
+                        },
                     }
                 },
                 None => break
             }
         }
 
+        let playback_finished = match self.input_player {
+            Some(ref mut player) => {
+                player.play_frame(&mut scene.input);
+                player.is_finished()
+            },
+            None => false,
+        };
+        if playback_finished {
+            self.input_player = None;
+        }
+
+        if let Some(ref mut recorder) = self.input_recorder {
+            recorder.next_frame();
+        }
+
         // TODO: More efficient handling of debug pause (i.e. something that doesn't have any
         // overhead when doing a release build).
         if !self.debug_pause || scene.input.key_pressed(ScanCode::F11) {
             self.debug_draw.clear_buffer();
 
-            self.alarm_update.update(scene, TARGET_FRAME_TIME_SECONDS);
+            // Gameplay systems use scaled time so that they respect slow motion and pausing.
+            let delta = scene.time.delta_seconds();
+
+            self.alarm_update.update(scene, delta);
+            self.lifetime_update.update(scene, delta);
 
             // Update systems.
             for system in self.systems.iter_mut() {
-                system.update(scene, TARGET_FRAME_TIME_SECONDS);
+                system.update(scene, delta);
             }
+
+            scene.update_managers(delta);
         }
 
         // Update debug systems always forever.
@@ -143,6 +190,9 @@ impl Engine {
         self.transform_update.update(scene, TARGET_FRAME_TIME_SECONDS);
 
         if !self.debug_pause || scene.input.key_pressed(ScanCode::F11) {
+            // Velocities are derived from the movement caused by the gameplay systems, so they use
+            // the same scaled time.
+            self.velocity_update.update(scene, scene.time.delta_seconds());
             self.collision_update.update(scene, TARGET_FRAME_TIME_SECONDS);
             self.light_update.update(scene, TARGET_FRAME_TIME_SECONDS);
             self.audio_update.update(scene, TARGET_FRAME_TIME_SECONDS);
@@ -215,7 +265,12 @@ impl Engine {
             let start_time = timer.now();
 
             self.update();
-            self.draw();
+
+            // Nothing is visible while the window is minimized so don't bother drawing.
+            let is_minimized = self.window.borrow().is_minimized();
+            if !is_minimized {
+                self.draw();
+            }
 
             if self.close {
                 break;
@@ -243,6 +298,31 @@ impl Engine {
         collector.flush_to_file("stopwatch.csv");
     }
 
+    /// Starts recording input, discarding any recording already in progress.
+    pub fn start_input_recording(&mut self) {
+        self.input_recorder = Some(InputRecorder::new());
+    }
+
+    /// Stops recording input, returning the recording if one was in progress.
+    pub fn stop_input_recording(&mut self) -> Option<InputRecorder> {
+        self.input_recorder.take()
+    }
+
+    /// Plays back recorded input in place of live input.
+    ///
+    /// # Details
+    ///
+    /// One recorded frame is played each time the engine updates, and live input is ignored
+    /// until every recorded frame has been played.
+    pub fn play_input(&mut self, player: InputPlayer) {
+        self.input_player = Some(player);
+    }
+
+    /// Returns true if recorded input is currently being played back.
+    pub fn is_playing_input(&self) -> bool {
+        self.input_player.is_some()
+    }
+
     pub fn register_system<T: Any + System>(&mut self, system: T) {
         let system_id = TypeId::of::<T>();
         assert!(!self.system_indices.contains_key(&system_id),
@@ -254,6 +334,24 @@ impl Engine {
         self.system_names.insert(type_name::<T>().into(), system_id);
     }
 
+    /// Replaces the system used to update the transform hierarchy each frame.
+    ///
+    /// # Details
+    ///
+    /// By default the engine uses `transform_update()`. This is mainly useful for switching to
+    /// `ParallelTransformUpdate` for scenes with many independent hierarchies.
+    ///
+    /// When the engine is reloaded a `ParallelTransformUpdate` is recreated with the same number
+    /// of threads. Any other system can't be recreated by the engine, so the reloaded engine goes
+    /// back to `transform_update()` and the game has to set it again.
+    pub fn set_transform_update<T: Any + System>(&mut self, system: T) {
+        self.transform_update_kind = match (&system as &Any).downcast_ref::<ParallelTransformUpdate>() {
+            Some(parallel) => TransformUpdateKind::Parallel(parallel.num_threads()),
+            None => TransformUpdateKind::Custom,
+        };
+        self.transform_update = Box::new(system);
+    }
+
     pub fn register_debug_system<T: Any + System>(&mut self, system: T) {
         self.debug_systems.push(Box::new(system));
     }
@@ -317,16 +415,25 @@ impl Clone for Engine {
             system_indices: HashMap::new(),
             system_names: HashMap::new(),
 
-            transform_update: Box::new(transform_update),
+            transform_update: self.transform_update_kind.create(),
+            transform_update_kind: match self.transform_update_kind {
+                TransformUpdateKind::Custom => TransformUpdateKind::Sequential,
+                kind => kind,
+            },
             light_update: Box::new(LightUpdateSystem),
             audio_update: Box::new(AudioSystem),
             alarm_update: Box::new(AlarmSystem),
+            lifetime_update: Box::new(LifetimeSystem),
+            velocity_update: Box::new(VelocitySystem),
             collision_update: Box::new(CollisionSystem::new()),
 
             scene: self.scene.clone(&resource_manager),
 
             debug_draw: DebugDraw::new(self.renderer.clone(), &*resource_manager),
 
+            input_recorder: None,
+            input_player: None,
+
             close: false,
             debug_pause: false,
         };
@@ -335,6 +442,28 @@ impl Clone for Engine {
     }
 }
 
+/// Which transform update system was chosen with `Engine::set_transform_update()`, so that the
+/// same one can be recreated when the engine is reloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransformUpdateKind {
+    Sequential,
+    Parallel(usize),
+    Custom,
+}
+
+impl TransformUpdateKind {
+    fn create(self) -> Box<System> {
+        match self {
+            TransformUpdateKind::Sequential => Box::new(transform_update),
+            TransformUpdateKind::Parallel(num_threads) => Box::new(ParallelTransformUpdate::new(num_threads)),
+            TransformUpdateKind::Custom => {
+                log::warn("Custom transform update system can't be recreated on reload, using transform_update()");
+                Box::new(transform_update)
+            },
+        }
+    }
+}
+
 fn type_name<T>() -> &'static str {
     unsafe {
         intrinsics::type_name::<T>()
@@ -367,15 +496,21 @@ pub fn engine_init(window: Rc<RefCell<Window>>) -> Box<Engine> {
         system_names: HashMap::new(),
 
         transform_update: Box::new(transform_update),
+        transform_update_kind: TransformUpdateKind::Sequential,
         light_update: Box::new(LightUpdateSystem),
         audio_update: Box::new(AudioSystem),
         alarm_update: Box::new(AlarmSystem),
+        lifetime_update: Box::new(LifetimeSystem),
+        velocity_update: Box::new(VelocitySystem),
         collision_update: Box::new(CollisionSystem::new()),
 
         scene: Scene::new(&resource_manager, audio_source),
 
         debug_draw: DebugDraw::new(renderer.clone(), &*resource_manager),
 
+        input_recorder: None,
+        input_player: None,
+
         close: false,
         debug_pause: false,
     })
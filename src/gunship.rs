@@ -1,4 +1,4 @@
-#![feature(core, core_intrinsics, raw, drain, unboxed_closures, hashmap_hasher, augmented_assignments)]
+#![feature(core, core_intrinsics, raw, drain, unboxed_closures, hashmap_hasher, augmented_assignments, borrow_state)]
 
 extern crate bootstrap_rs as bootstrap;
 extern crate bootstrap_audio as bs_audio;
@@ -10,37 +10,49 @@ pub mod stopwatch {
     extern crate stopwatch;
 
     #[cfg(feature="timing")]
-    pub use self::stopwatch::{Collector, Stopwatch};
+    pub use self::stopwatch::{Collector, Profiler, Stopwatch};
 
     #[cfg(not(feature="timing"))]
-    pub use self::stopwatch::null::{Collector, Stopwatch};
+    pub use self::stopwatch::null::{Collector, Profiler, Stopwatch};
 }
 
 pub mod engine;
 pub mod scene;
 pub mod input;
+pub mod time;
 pub mod resource;
 pub mod ecs;
+pub mod schedule;
+pub mod serialize;
 pub mod component;
 pub mod debug_draw;
 
 mod wav;
 
 pub use math::*;
+pub use bootstrap::log;
 pub use self::engine::Engine;
-pub use self::scene::Scene;
-pub use self::input::{Input, ScanCode};
+pub use self::scene::{Scene, EntityBuilder, BuildEntityError};
+pub use self::input::{Input, ScanCode, ActionMap, Binding, InputRecorder, InputPlayer, RecordingError};
+pub use self::time::Time;
+pub use self::schedule::{Schedule, PeriodicSystem, run_every, run_every_seconds};
+pub use self::serialize::{Serialize, Deserialize, ByteReader, SerializeError};
 pub use self::resource::ResourceManager;
-pub use self::ecs::{Entity, EntityManager, System, ComponentManager};
-pub use self::component::transform::{TransformManager, Transform};
-pub use self::component::camera::{CameraManager, Camera};
+pub use self::ecs::{Entity, EntityAllocator, EntityManager, System, ComponentManager, ComponentMask, Handle};
+pub use self::component::transform::{TransformManager, Transform, TransformEvent, Roots, SubtreeIter, TransformSnapshot, CoordinateConvention, ScaleConstraint, ParallelTransformUpdate, TransformWorkers};
+pub use self::component::camera::{CameraManager, Camera, ProjectionMode, sort_by_distance};
 pub use self::component::mesh::{MeshManager, Mesh};
 pub use self::component::light::{LightManager, Light, PointLight};
-pub use self::component::audio::{AudioSourceManager, AudioSource};
+pub use self::component::audio::{AudioSourceManager, AudioSource, spatialize};
 pub use self::component::alarm::{AlarmID, AlarmManager};
+pub use self::component::lifetime::{LifetimeManager, LifetimeSystem};
+pub use self::component::lod::LodManager;
+pub use self::component::name::NameManager;
+pub use self::component::velocity::{VelocityManager, VelocitySystem};
 pub use self::component::collider::{ColliderManager, Collider};
 pub use self::component::singleton_component_manager::SingletonComponentManager;
 pub use self::component::struct_component_manager::StructComponentManager;
+pub use self::component::data_manager::DataManager;
 
 // TODO: These are only needed for hotloading support.
 pub use self::engine::{engine_init, engine_update_and_render};
@@ -1,8 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::char;
+use std::fmt::{self, Display, Formatter};
+use std::mem;
 
 use bootstrap::window::Message;
 use bootstrap::window::Message::*;
 pub use bootstrap::input::ScanCode;
+use math::Vector2;
+use math::bytes::{ByteReader, ByteWriter, UnexpectedEnd};
 
 pub const MAX_SUPPORTED_MOUSE_BUTTONS: usize = 5;
 
@@ -16,7 +21,9 @@ pub struct Input {
     mouse_down: [bool; MAX_SUPPORTED_MOUSE_BUTTONS],
     mouse_pressed: [bool; MAX_SUPPORTED_MOUSE_BUTTONS],
     mouse_released: [bool; MAX_SUPPORTED_MOUSE_BUTTONS],
-    mouse_scroll: i32,
+    scroll_delta: Vector2,
+    typed_chars: String,
+    action_map: ActionMap,
 }
 
 impl Input {
@@ -30,7 +37,9 @@ impl Input {
             mouse_down: [false; MAX_SUPPORTED_MOUSE_BUTTONS],
             mouse_pressed: [false; MAX_SUPPORTED_MOUSE_BUTTONS],
             mouse_released: [false; MAX_SUPPORTED_MOUSE_BUTTONS],
-            mouse_scroll: 0,
+            scroll_delta: Vector2::new(0.0, 0.0),
+            typed_chars: String::new(),
+            action_map: ActionMap::new(),
         }
     }
 
@@ -40,7 +49,8 @@ impl Input {
         self.mouse_delta = (0, 0);
         self.mouse_pressed = [false; MAX_SUPPORTED_MOUSE_BUTTONS];
         self.mouse_released = [false; MAX_SUPPORTED_MOUSE_BUTTONS];
-        self.mouse_scroll = 0;
+        self.scroll_delta = Vector2::new(0.0, 0.0);
+        self.typed_chars.clear();
     }
 
     pub fn push_input(&mut self, message: Message) {
@@ -76,13 +86,28 @@ impl Input {
                 self.mouse_pressed[index] = true ^ self.mouse_down[index];
                 self.mouse_down[index] = true;
             },
-            MouseWheel(scroll_amount) => {
-                self.mouse_scroll = scroll_amount;
-            }
+            MouseWheel { delta_x, delta_y } => {
+                self.scroll_delta.x += delta_x;
+                self.scroll_delta.y += delta_y;
+            },
+            TextInput(character) => {
+                self.typed_chars.push(character);
+            },
             _ => panic!("Unhandled message {:?} passed to Input::push_input()", message) // TODO: Don't panic? Should be unreachable in release.
         }
     }
 
+    /// Retrieves the text typed by the user this frame.
+    ///
+    /// # Details
+    ///
+    /// The text comes from the OS so it respects the user's keyboard layout and modifier keys
+    /// (e.g. Shift+A produces "A"). Control characters such as backspace and enter are not
+    /// included, use `key_pressed()` to handle those.
+    pub fn typed_chars(&self) -> &str {
+        &*self.typed_chars
+    }
+
     pub fn key_down(&self, key: ScanCode) -> bool {
         self.keys_down.contains(&key)
     }
@@ -121,7 +146,636 @@ impl Input {
         self.mouse_released[button]
     }
 
-    pub fn mouse_scroll(&self) -> i32 {
-        self.mouse_scroll
+    /// Retrieves how far the mouse wheel was scrolled this frame.
+    ///
+    /// # Details
+    ///
+    /// The delta is the sum of every wheel message received this frame, measured in notches of
+    /// a standard mouse wheel. Positive `y` is scrolling up (away from the user) and positive `x`
+    /// is scrolling right. High-resolution wheels and touchpads produce fractional values.
+    pub fn scroll_delta(&self) -> Vector2 {
+        self.scroll_delta
+    }
+
+    pub fn action_map(&self) -> &ActionMap {
+        &self.action_map
+    }
+
+    pub fn action_map_mut(&mut self) -> &mut ActionMap {
+        &mut self.action_map
+    }
+
+    /// Returns true if any of the bindings for the action was pressed this frame.
+    pub fn action_pressed(&self, action: &str) -> bool {
+        self.action_map.buttons(action).iter().any(|binding| binding.pressed(self))
+    }
+
+    /// Returns true if any of the bindings for the action is currently held down.
+    pub fn action_down(&self, action: &str) -> bool {
+        self.action_map.buttons(action).iter().any(|binding| binding.down(self))
+    }
+
+    /// Returns the value of an axis action in the range [-1, 1].
+    ///
+    /// The axis is 1.0 if any positive binding is held, -1.0 if any negative binding is held, and
+    /// 0.0 if both or neither are held.
+    pub fn action_axis(&self, action: &str) -> f32 {
+        let (positive, negative) = self.action_map.axis(action);
+
+        let mut value = 0.0;
+        if positive.iter().any(|binding| binding.down(self)) {
+            value += 1.0;
+        }
+        if negative.iter().any(|binding| binding.down(self)) {
+            value -= 1.0;
+        }
+
+        value
+    }
+}
+
+/// A physical input that can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(ScanCode),
+    MouseButton(usize),
+}
+
+impl Binding {
+    fn pressed(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.key_pressed(key),
+            Binding::MouseButton(button) => input.mouse_button_pressed(button),
+        }
+    }
+
+    fn down(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.key_down(key),
+            Binding::MouseButton(button) => input.mouse_button_down(button),
+        }
+    }
+}
+
+/// Maps named actions to one or more physical inputs.
+///
+/// # Details
+///
+/// Game code should query actions through `Input::action_pressed()`, `Input::action_down()`, and
+/// `Input::action_axis()` rather than checking specific keys so that controls can be rebound at
+/// runtime. An action bound to multiple inputs is active if any of its bindings are active.
+#[derive(Debug, Clone)]
+pub struct ActionMap {
+    actions: HashMap<String, ActionBindings>,
+}
+
+impl ActionMap {
+    pub fn new() -> ActionMap {
+        ActionMap {
+            actions: HashMap::new(),
+        }
+    }
+
+    /// Adds a binding to the action, keeping any existing bindings.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.actions
+            .entry(action.into())
+            .or_insert_with(ActionBindings::new)
+            .buttons
+            .push(binding);
+    }
+
+    /// Adds a pair of bindings to an axis action, one for each direction.
+    pub fn bind_axis(&mut self, action: &str, positive: Binding, negative: Binding) {
+        let bindings = self.actions.entry(action.into()).or_insert_with(ActionBindings::new);
+        bindings.positive.push(positive);
+        bindings.negative.push(negative);
+    }
+
+    /// Replaces all existing bindings for the action with `binding`.
+    pub fn rebind(&mut self, action: &str, binding: Binding) {
+        self.clear_bindings(action);
+        self.bind(action, binding);
+    }
+
+    /// Removes all bindings for the action.
+    pub fn clear_bindings(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    fn buttons(&self, action: &str) -> &[Binding] {
+        match self.actions.get(action) {
+            Some(bindings) => &*bindings.buttons,
+            None => &[],
+        }
+    }
+
+    fn axis(&self, action: &str) -> (&[Binding], &[Binding]) {
+        match self.actions.get(action) {
+            Some(bindings) => (&*bindings.positive, &*bindings.negative),
+            None => (&[], &[]),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ActionBindings {
+    buttons: Vec<Binding>,
+    positive: Vec<Binding>,
+    negative: Vec<Binding>,
+}
+
+impl ActionBindings {
+    fn new() -> ActionBindings {
+        ActionBindings {
+            buttons: Vec::new(),
+            positive: Vec::new(),
+            negative: Vec::new(),
+        }
+    }
+}
+
+/// Identifies the start of serialized input recordings.
+///
+/// Recordings made before the format was versioned started with `GINP` and encoded mouse wheel
+/// deltas differently, using a different magic number means they're rejected instead of misread.
+const RECORDING_MAGIC: &'static [u8] = b"GINR";
+
+/// The version of the recording format, written after the magic number. Must be incremented
+/// whenever the encoding of any event changes.
+const RECORDING_VERSION: u32 = 1;
+
+const TAG_KEY_DOWN: u8 = 0;
+const TAG_KEY_UP: u8 = 1;
+const TAG_MOUSE_MOVE: u8 = 2;
+const TAG_MOUSE_POS: u8 = 3;
+const TAG_MOUSE_BUTTON_PRESSED: u8 = 4;
+const TAG_MOUSE_BUTTON_RELEASED: u8 = 5;
+const TAG_MOUSE_WHEEL: u8 = 6;
+const TAG_TEXT_INPUT: u8 = 7;
+
+/// Records the input messages received each frame so they can be played back later.
+///
+/// # Details
+///
+/// Messages are tagged with the frame they were received on rather than a timestamp, so playing
+/// them back with `InputPlayer` reproduces the same per-frame input state regardless of how
+/// long each frame takes. Call `record()` for each input message and `next_frame()` once all of
+/// the messages for the current frame have been recorded.
+#[derive(Debug, Clone)]
+pub struct InputRecorder {
+    frame: u32,
+    events: Vec<(u32, Message)>,
+}
+
+impl InputRecorder {
+    pub fn new() -> InputRecorder {
+        InputRecorder {
+            frame: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Records a message for the current frame. Messages that aren't input messages are ignored.
+    pub fn record(&mut self, message: Message) {
+        if is_input_message(message) {
+            self.events.push((self.frame, message));
+        }
+    }
+
+    /// Finishes the current frame, later messages are recorded for the next frame.
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Retrieves the number of frames that have been recorded.
+    pub fn frame_count(&self) -> u32 {
+        self.frame
+    }
+
+    /// Serializes the recording so that it can be loaded with `InputPlayer::from_bytes()`.
+    ///
+    /// # Details
+    ///
+    /// The format is a magic number, the format version, the frame count, and the event count
+    /// followed by the events, each one being its frame number, a message tag, and the message's
+    /// payload. All values are stored little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.write_bytes(RECORDING_MAGIC);
+        writer.write_u32(RECORDING_VERSION);
+        writer.write_u32(self.frame);
+        writer.write_u32(self.events.len() as u32);
+
+        for &(frame, message) in &self.events {
+            writer.write_u32(frame);
+            match message {
+                KeyDown(key) => {
+                    writer.write_u8(TAG_KEY_DOWN);
+                    writer.write_u32(key as u32);
+                },
+                KeyUp(key) => {
+                    writer.write_u8(TAG_KEY_UP);
+                    writer.write_u32(key as u32);
+                },
+                MouseMove(x, y) => {
+                    writer.write_u8(TAG_MOUSE_MOVE);
+                    writer.write_u32(x as u32);
+                    writer.write_u32(y as u32);
+                },
+                MousePos(x, y) => {
+                    writer.write_u8(TAG_MOUSE_POS);
+                    writer.write_u32(x as u32);
+                    writer.write_u32(y as u32);
+                },
+                MouseButtonPressed(button) => {
+                    writer.write_u8(TAG_MOUSE_BUTTON_PRESSED);
+                    writer.write_u8(button);
+                },
+                MouseButtonReleased(button) => {
+                    writer.write_u8(TAG_MOUSE_BUTTON_RELEASED);
+                    writer.write_u8(button);
+                },
+                MouseWheel { delta_x, delta_y } => {
+                    writer.write_u8(TAG_MOUSE_WHEEL);
+                    writer.write_f32(delta_x);
+                    writer.write_f32(delta_y);
+                },
+                TextInput(character) => {
+                    writer.write_u8(TAG_TEXT_INPUT);
+                    writer.write_u32(character as u32);
+                },
+                _ => unreachable!(),
+            }
+        }
+
+        writer.into_bytes()
+    }
+
+    /// Creates a player that plays back the recording from the first frame.
+    pub fn into_player(self) -> InputPlayer {
+        InputPlayer {
+            frame: 0,
+            frame_count: self.frame,
+            events: self.events,
+            next_event: 0,
+        }
+    }
+}
+
+/// Plays back input recorded by an `InputRecorder`.
+#[derive(Debug, Clone)]
+pub struct InputPlayer {
+    frame: u32,
+    frame_count: u32,
+    events: Vec<(u32, Message)>,
+    next_event: usize,
+}
+
+impl InputPlayer {
+    /// Loads a recording serialized with `InputRecorder::to_bytes()`.
+    ///
+    /// Recordings made with a different version of the format are rejected with
+    /// `RecordingError::InvalidHeader`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<InputPlayer, RecordingError> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.read_bytes(RECORDING_MAGIC.len()) != Ok(RECORDING_MAGIC) {
+            return Err(RecordingError::InvalidHeader);
+        }
+        if reader.read_u32() != Ok(RECORDING_VERSION) {
+            return Err(RecordingError::InvalidHeader);
+        }
+
+        let frame_count = try!(reader.read_u32());
+        let event_count = try!(reader.read_u32());
+
+        let mut events = Vec::new();
+        let mut last_frame = 0;
+        for _ in 0..event_count {
+            let offset = reader.offset();
+            let frame = try!(reader.read_u32());
+            if frame < last_frame || frame >= frame_count {
+                return Err(RecordingError::InvalidFrame { offset: offset, frame: frame });
+            }
+            last_frame = frame;
+
+            let tag = try!(reader.read_u8());
+            let message = match tag {
+                TAG_KEY_DOWN => KeyDown(scan_code_from_u32(try!(reader.read_u32()))),
+                TAG_KEY_UP => KeyUp(scan_code_from_u32(try!(reader.read_u32()))),
+                TAG_MOUSE_MOVE => MouseMove(try!(reader.read_u32()) as i32, try!(reader.read_u32()) as i32),
+                TAG_MOUSE_POS => MousePos(try!(reader.read_u32()) as i32, try!(reader.read_u32()) as i32),
+                TAG_MOUSE_BUTTON_PRESSED => MouseButtonPressed(try!(reader.read_u8())),
+                TAG_MOUSE_BUTTON_RELEASED => MouseButtonReleased(try!(reader.read_u8())),
+                TAG_MOUSE_WHEEL => MouseWheel {
+                    delta_x: try!(reader.read_f32()),
+                    delta_y: try!(reader.read_f32()),
+                },
+                TAG_TEXT_INPUT => {
+                    let value = try!(reader.read_u32());
+                    match char::from_u32(value) {
+                        Some(character) => TextInput(character),
+                        None => return Err(RecordingError::InvalidMessage { offset: offset }),
+                    }
+                },
+                _ => return Err(RecordingError::InvalidMessage { offset: offset }),
+            };
+
+            events.push((frame, message));
+        }
+
+        Ok(InputPlayer {
+            frame: 0,
+            frame_count: frame_count,
+            events: events,
+            next_event: 0,
+        })
+    }
+
+    /// Pushes the messages recorded for the current frame into `input` and advances to the next
+    /// frame.
+    ///
+    /// # Details
+    ///
+    /// `input` should be cleared before each frame is played, just like it is for live input.
+    /// Returns `false` without modifying `input` once every recorded frame has been played.
+    pub fn play_frame(&mut self, input: &mut Input) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        while self.next_event < self.events.len() && self.events[self.next_event].0 == self.frame {
+            input.push_input(self.events[self.next_event].1);
+            self.next_event += 1;
+        }
+
+        self.frame += 1;
+        true
+    }
+
+    /// Returns true once every recorded frame has been played.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.frame_count
+    }
+
+    /// Retrieves the index of the next frame to be played.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+}
+
+/// An error loading an input recording. `offset` is the byte offset of the offending event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingError {
+    /// The data doesn't start with the recording magic number, or was recorded with a different
+    /// version of the format.
+    InvalidHeader,
+
+    /// The data ended partway through the recording.
+    UnexpectedEnd,
+
+    /// The event's frame is past the end of the recording or before the previous event's frame.
+    InvalidFrame {
+        offset: usize,
+        frame: u32,
+    },
+
+    /// The event has an unknown message tag or an invalid payload.
+    InvalidMessage {
+        offset: usize,
+    },
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            RecordingError::InvalidHeader => write!(formatter, "data is not an input recording"),
+            RecordingError::UnexpectedEnd => write!(formatter, "recording ended unexpectedly"),
+            RecordingError::InvalidFrame { offset, frame } => write!(formatter, "byte {}: event has invalid frame {}", offset, frame),
+            RecordingError::InvalidMessage { offset } => write!(formatter, "byte {}: event is not a valid input message", offset),
+        }
+    }
+}
+
+impl From<UnexpectedEnd> for RecordingError {
+    fn from(_: UnexpectedEnd) -> RecordingError {
+        RecordingError::UnexpectedEnd
+    }
+}
+
+fn is_input_message(message: Message) -> bool {
+    match message {
+        KeyDown(_)
+      | KeyUp(_)
+      | MouseMove(_, _)
+      | MousePos(_, _)
+      | MouseButtonPressed(_)
+      | MouseButtonReleased(_)
+      | MouseWheel { .. }
+      | TextInput(_) => true,
+        _ => false,
+    }
+}
+
+/// Converts a serialized scancode back to a `ScanCode`, unknown values become `Unsupported`.
+fn scan_code_from_u32(value: u32) -> ScanCode {
+    const A: u32 = 'A' as u32;
+    const Z: u32 = 'Z' as u32;
+    const CHAR_0: u32 = '0' as u32;
+    const CHAR_9: u32 = '9' as u32;
+
+    match value {
+        A ... Z
+      | CHAR_0 ... CHAR_9
+      | 32
+      | 192
+      | 120 ... 122 => unsafe { mem::transmute(value) },
+        _ => ScanCode::Unsupported,
+    }
+}
+
+#[test]
+fn action_rebinding() {
+    let mut input = Input::new();
+    input.action_map_mut().bind("jump", Binding::Key(ScanCode::Space));
+
+    input.push_input(KeyDown(ScanCode::Space));
+    assert!(input.action_pressed("jump"));
+    assert!(input.action_down("jump"));
+
+    // After rebinding the old key no longer triggers the action.
+    input.clear();
+    input.push_input(KeyUp(ScanCode::Space));
+    input.action_map_mut().rebind("jump", Binding::Key(ScanCode::W));
+
+    input.clear();
+    input.push_input(KeyDown(ScanCode::Space));
+    assert!(!input.action_pressed("jump"));
+    assert!(!input.action_down("jump"));
+
+    input.push_input(KeyDown(ScanCode::W));
+    assert!(input.action_pressed("jump"));
+    assert!(input.action_down("jump"));
+
+    // Unbound actions are never active.
+    assert!(!input.action_down("fire"));
+}
+
+#[test]
+fn action_multiple_bindings() {
+    let mut input = Input::new();
+    input.action_map_mut().bind("fire", Binding::Key(ScanCode::F));
+    input.action_map_mut().bind("fire", Binding::Key(ScanCode::Space));
+
+    input.push_input(KeyDown(ScanCode::Space));
+    assert!(input.action_down("fire"));
+
+    input.push_input(KeyDown(ScanCode::F));
+    input.push_input(KeyUp(ScanCode::Space));
+    assert!(input.action_down("fire"));
+}
+
+#[test]
+fn action_axis() {
+    let mut input = Input::new();
+    input.action_map_mut().bind_axis("move", Binding::Key(ScanCode::D), Binding::Key(ScanCode::A));
+    assert_eq!(input.action_axis("move"), 0.0);
+
+    input.push_input(KeyDown(ScanCode::D));
+    assert_eq!(input.action_axis("move"), 1.0);
+
+    input.push_input(KeyDown(ScanCode::A));
+    assert_eq!(input.action_axis("move"), 0.0);
+
+    input.push_input(KeyUp(ScanCode::D));
+    assert_eq!(input.action_axis("move"), -1.0);
+}
+
+#[test]
+fn typed_chars_buffered_per_frame() {
+    let mut input = Input::new();
+    assert_eq!(input.typed_chars(), "");
+
+    input.push_input(TextInput('H'));
+    input.push_input(TextInput('i'));
+    input.push_input(TextInput('\u{e9}'));
+    assert_eq!(input.typed_chars(), "Hi\u{e9}");
+
+    input.clear();
+    assert_eq!(input.typed_chars(), "");
+
+    input.push_input(TextInput('!'));
+    assert_eq!(input.typed_chars(), "!");
+}
+
+#[test]
+fn scroll_delta_accumulated_per_frame() {
+    let mut input = Input::new();
+    assert_eq!(input.scroll_delta(), Vector2::new(0.0, 0.0));
+
+    // Touchpads send many small fractional deltas in a single frame.
+    input.push_input(MouseWheel { delta_x: 0.0, delta_y: 1.0 });
+    input.push_input(MouseWheel { delta_x: 0.0, delta_y: 0.25 });
+    input.push_input(MouseWheel { delta_x: -0.5, delta_y: 0.0 });
+    input.push_input(MouseWheel { delta_x: 0.125, delta_y: -0.5 });
+    assert_eq!(input.scroll_delta(), Vector2::new(-0.375, 0.75));
+
+    input.clear();
+    assert_eq!(input.scroll_delta(), Vector2::new(0.0, 0.0));
+
+    input.push_input(MouseWheel { delta_x: 0.0, delta_y: -2.0 });
+    assert_eq!(input.scroll_delta(), Vector2::new(0.0, -2.0));
+}
+
+#[test]
+fn input_recording_playback() {
+    type FrameState = (bool, bool, bool, bool, bool, (i32, i32), Vector2, String, bool);
+
+    fn frame_state(input: &Input) -> FrameState {
+        (
+            input.key_down(ScanCode::W),
+            input.key_pressed(ScanCode::W),
+            input.key_released(ScanCode::W),
+            input.key_down(ScanCode::Space),
+            input.key_pressed(ScanCode::Space),
+            input.mouse_pos(),
+            input.scroll_delta(),
+            input.typed_chars().into(),
+            input.mouse_button_down(0),
+        )
+    }
+
+    let frames = vec![
+        vec![KeyDown(ScanCode::W), TextInput('w'), Activate],
+        vec![],
+        vec![MousePos(10, 20), MouseButtonReleased(0), MouseWheel { delta_x: 0.25, delta_y: -3.0 }],
+        vec![KeyUp(ScanCode::W), KeyDown(ScanCode::Space), KeyDown(ScanCode::Space)],
+        vec![MouseButtonPressed(0), TextInput('\u{e9}')],
+        vec![],
+    ];
+
+    let mut live = Input::new();
+    let mut recorder = InputRecorder::new();
+    let mut expected = Vec::new();
+    for messages in &frames {
+        live.clear();
+        for &message in messages {
+            recorder.record(message);
+            if is_input_message(message) {
+                live.push_input(message);
+            }
+        }
+        recorder.next_frame();
+        expected.push(frame_state(&live));
     }
+    assert_eq!(recorder.frame_count(), frames.len() as u32);
+
+    // Playback is driven by frame number, so trailing empty frames are preserved.
+    let mut player = InputPlayer::from_bytes(&recorder.to_bytes()).unwrap();
+    let mut replayed = Input::new();
+    let mut actual = Vec::new();
+    while !player.is_finished() {
+        replayed.clear();
+        assert!(player.play_frame(&mut replayed));
+        actual.push(frame_state(&replayed));
+    }
+    assert_eq!(actual, expected);
+    assert!(!player.play_frame(&mut replayed));
+
+    // Playing directly from the recorder gives the same result.
+    let mut player = recorder.clone().into_player();
+    let mut replayed = Input::new();
+    for state in &expected {
+        replayed.clear();
+        player.play_frame(&mut replayed);
+        assert_eq!(&frame_state(&replayed), state);
+    }
+}
+
+#[test]
+fn input_recording_errors() {
+    let mut recorder = InputRecorder::new();
+    recorder.record(KeyDown(ScanCode::A));
+    recorder.next_frame();
+    let bytes = recorder.to_bytes();
+
+    assert_eq!(InputPlayer::from_bytes(b"nope").unwrap_err(), RecordingError::InvalidHeader);
+    assert_eq!(InputPlayer::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(), RecordingError::UnexpectedEnd);
+
+    let mut bad_tag = bytes.clone();
+    bad_tag[20] = 200;
+    assert_eq!(InputPlayer::from_bytes(&bad_tag).unwrap_err(), RecordingError::InvalidMessage { offset: 16 });
+
+    let mut bad_frame = bytes.clone();
+    bad_frame[16] = 5;
+    assert_eq!(InputPlayer::from_bytes(&bad_frame).unwrap_err(), RecordingError::InvalidFrame { offset: 16, frame: 5 });
+
+    // Recordings from another version of the format are rejected rather than misread.
+    let mut other_version = bytes.clone();
+    other_version[4] = RECORDING_VERSION as u8 + 1;
+    assert_eq!(InputPlayer::from_bytes(&other_version).unwrap_err(), RecordingError::InvalidHeader);
+
+    let mut unversioned = b"GINP".to_vec();
+    unversioned.extend(&bytes[8..]);
+    assert_eq!(InputPlayer::from_bytes(&unversioned).unwrap_err(), RecordingError::InvalidHeader);
 }
@@ -20,7 +20,7 @@ pub mod shader;
 
 #[derive(Debug, Clone)]
 pub struct ResourceManager {
-    renderer: Rc<GLRender>,
+    renderer: Option<Rc<GLRender>>,
     meshes: RefCell<HashMap<String, Mesh>>,
     gpu_meshes: RefCell<HashMap<String, GLMeshData>>,
     mesh_nodes: RefCell<HashMap<String, MeshNode>>,
@@ -32,6 +32,20 @@ pub struct ResourceManager {
 
 impl ResourceManager {
     pub fn new(renderer: Rc<GLRender>) -> ResourceManager {
+        ResourceManager::with_renderer(Some(renderer))
+    }
+
+    /// Creates a resource manager without a renderer, e.g. for tests and tools that don't draw.
+    ///
+    /// # Panics
+    ///
+    /// Any operation that needs to upload data to the GPU, such as loading a mesh or a shader,
+    /// will panic.
+    pub fn headless() -> ResourceManager {
+        ResourceManager::with_renderer(None)
+    }
+
+    fn with_renderer(renderer: Option<Rc<GLRender>>) -> ResourceManager {
         ResourceManager {
             renderer: renderer,
             meshes: RefCell::new(HashMap::new()),
@@ -44,6 +58,10 @@ impl ResourceManager {
         }
     }
 
+    fn renderer(&self) -> &GLRender {
+        self.renderer.as_ref().expect("Resource manager was created without a renderer")
+    }
+
     pub fn load_resource_file<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
         let mut full_path = self.resource_path.borrow().clone();
         full_path.push(path);
@@ -181,7 +199,7 @@ impl ResourceManager {
                 Some(program) => program.src,
             };
 
-            let shader = self.renderer.compile_shader_program(vert_src, frag_src);
+            let shader = self.renderer().compile_shader_program(vert_src, frag_src);
             self.shaders.borrow_mut().insert(path_string, shader.clone());
 
             Ok(shader)
@@ -231,7 +249,7 @@ impl ResourceManager {
             None => return None,
         };
 
-        let mesh_data = self.renderer.gen_mesh(mesh);
+        let mesh_data = self.renderer().gen_mesh(mesh);
         self.gpu_meshes
         .borrow_mut()
         .insert(uri.into(), mesh_data);
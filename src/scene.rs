@@ -1,19 +1,25 @@
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::cell::{RefCell, Ref, RefMut};
+use std::cell::{Cell, RefCell, Ref, RefMut};
 use std::ops::{Deref, DerefMut};
 use std::intrinsics;
 use std::mem;
 use std::raw::TraitObject;
 use std::marker::PhantomData;
+use std::fmt::{self, Debug, Formatter};
 
 use bs_audio::AudioSource;
+use bootstrap::log;
 
-use ecs::{Entity, EntityManager, ComponentManager};
+use ecs::{Entity, EntityManager, ComponentManager, ComponentMask, MAX_COMPONENT_MANAGERS};
 use input::Input;
-use component::{TransformManager, CameraManager, MeshManager, LightManager, AudioSourceManager,
-                AlarmManager, ColliderManager};
+use time::Time;
+use engine::TARGET_FRAME_TIME_SECONDS;
+use component::{Transform, TransformManager, Camera, CameraManager, MeshManager, LightManager,
+                AudioSourceManager, AlarmManager, LifetimeManager, LodManager, NameManager,
+                VelocityManager, ColliderManager};
 use resource::ResourceManager;
+use serialize::{Serialize, Deserialize, ByteReader, SerializeError, write_u32};
 
 #[cfg(not(feature = "hotloading"))]
 type ManagerId = ::std::any::TypeId;
@@ -21,6 +27,9 @@ type ManagerId = ::std::any::TypeId;
 #[cfg(feature = "hotloading")]
 type ManagerId = &'static str;
 
+/// The header written at the start of the data produced by `Scene::save()`.
+const SCENE_MAGIC: &'static [u8] = b"GSCN";
+
 /// Contains all the data that defines the current state of the world.
 ///
 /// This is passed into systems in System::update(). It can be used access component
@@ -29,7 +38,18 @@ type ManagerId = &'static str;
 pub struct Scene {
     entity_manager: RefCell<EntityManager>,
     component_managers: HashMap<ManagerId, RefCell<Box<ComponentManager>>>,
+    component_masks: HashMap<ManagerId, ComponentMask>,
+    serializers: HashMap<ManagerId, ManagerSerializer>,
+
+    /// The type name of each registered manager, used when warning about managers that can't be
+    /// saved.
+    manager_names: HashMap<ManagerId, &'static str>,
+
+    /// Whether `save()` has already warned about the managers that aren't serializable.
+    warned_unsaved: Cell<bool>,
+
     pub input: Input,
+    pub time: Time,
     pub audio_source: AudioSource,
     resource_manager: Rc<ResourceManager>,
 }
@@ -39,17 +59,26 @@ impl Scene {
         let mut scene = Scene {
             entity_manager: RefCell::new(EntityManager::new()),
             component_managers: HashMap::new(),
+            component_masks: HashMap::new(),
+            serializers: HashMap::new(),
+            manager_names: HashMap::new(),
+            warned_unsaved: Cell::new(false),
             input: Input::new(),
+            time: Time::new(TARGET_FRAME_TIME_SECONDS),
             audio_source: audio_source,
             resource_manager: resource_manager.clone(),
         };
 
-        scene.register_manager(TransformManager::new());
+        scene.register_serializable_manager(TransformManager::new());
         scene.register_manager(CameraManager::new());
         scene.register_manager(LightManager::new());
         scene.register_manager(MeshManager::new(resource_manager.clone()));
         scene.register_manager(AudioSourceManager::new(resource_manager.clone()));
         scene.register_manager(AlarmManager::new());
+        scene.register_manager(LifetimeManager::new());
+        scene.register_manager(LodManager::new());
+        scene.register_manager(NameManager::new());
+        scene.register_manager(VelocityManager::new());
         scene.register_manager(ColliderManager::new());
 
         scene
@@ -59,29 +88,185 @@ impl Scene {
         let mut scene = Scene {
             entity_manager: RefCell::new(self.entity_manager.borrow().clone()),
             component_managers: HashMap::new(),
+            component_masks: HashMap::new(),
+            serializers: HashMap::new(),
+            manager_names: HashMap::new(),
+            warned_unsaved: Cell::new(self.warned_unsaved.get()),
             input: self.input.clone(),
+            time: self.time.clone(),
             audio_source: self.audio_source.clone(),
             resource_manager: resource_manager.clone(),
         };
 
-        // Reload internal component managers.
-        scene.reload_manager::<TransformManager>(self);
+        // Reload internal component managers. The serializers are rebuilt rather than copied since
+        // the old scene's refer to functions in the library that's being unloaded.
+        scene.reload_serializable_manager::<TransformManager>(self);
         scene.reload_manager::<CameraManager>(self);
         scene.reload_manager::<LightManager>(self);
         scene.reload_manager::<AlarmManager>(self);
+        scene.reload_manager::<LifetimeManager>(self);
+        scene.reload_manager::<LodManager>(self);
+        scene.reload_manager::<NameManager>(self);
+        scene.reload_manager::<VelocityManager>(self);
         scene.reload_manager::<ColliderManager>(self);
-        scene.register_manager(self.get_manager::<MeshManager>().clone(resource_manager.clone()));
-        scene.register_manager(self.get_manager::<AudioSourceManager>().clone(resource_manager.clone()));
+        scene.reregister_manager(self.get_manager::<MeshManager>().clone(resource_manager.clone()), self);
+        scene.reregister_manager(self.get_manager::<AudioSourceManager>().clone(resource_manager.clone()), self);
 
         scene
     }
 
+    /// Registers a manager with the scene.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a manager of the same type is already registered, or if the maximum number of
+    /// managers (`MAX_COMPONENT_MANAGERS`) is already registered.
     pub fn register_manager<T: ComponentManager>(&mut self, manager: T) {
+        self.add_manager(manager, None);
+    }
+
+    /// Registers the manager, using `mask` as its component mask if provided, otherwise the
+    /// lowest bit not already used by another manager.
+    fn add_manager<T: ComponentManager>(&mut self, manager: T, mask: Option<ComponentMask>) {
         let manager_id = manager_id::<T>();
         assert!(!self.component_managers.contains_key(&manager_id),
                 "Manager {} with ID {:?} already registered", type_name::<T>(), manager_id);
 
+        let used = self.component_masks.values().fold(ComponentMask::empty(), |all, &mask| all | mask);
+        let mask = match mask {
+            Some(mask) => {
+                assert!(!used.contains(mask),
+                        "Cannot reload manager {}, its component mask is already used by another manager", type_name::<T>());
+                mask
+            },
+            None => {
+                match (0..MAX_COMPONENT_MANAGERS).find(|&bit| !used.contains(ComponentMask::from_bit(bit))) {
+                    Some(bit) => ComponentMask::from_bit(bit),
+                    None => panic!("Cannot register manager {}, only {} managers are supported", type_name::<T>(), MAX_COMPONENT_MANAGERS),
+                }
+            },
+        };
+
         self.component_managers.insert(manager_id, RefCell::new(Box::new(manager)));
+        self.component_masks.insert(manager_id, mask);
+        self.manager_names.insert(manager_id, type_name::<T>());
+    }
+
+    /// Registers a manager copied from `old_scene`, keeping the component mask it had there.
+    ///
+    /// Masks are looked up by manager ID rather than copied wholesale since under hotloading the
+    /// old scene's IDs belong to the library being unloaded.
+    fn reregister_manager<T: ComponentManager>(&mut self, manager: T, old_scene: &Scene) {
+        let mask = old_scene.component_masks.get(&manager_id::<T>()).cloned();
+        self.add_manager(manager, mask);
+    }
+
+    /// Registers a manager whose components are included in `save()` and `load()`.
+    ///
+    /// # Details
+    ///
+    /// The manager's data is tagged with the full type name of `T`, so the same manager must be
+    /// registered in the scene that loads the data.
+    pub fn register_serializable_manager<T>(&mut self, manager: T)
+        where T: ComponentManager + Serialize + Deserialize
+    {
+        self.register_manager(manager);
+        self.serializers.insert(manager_id::<T>(), ManagerSerializer::of::<T>());
+    }
+
+    /// Serializes the entities and components of every serializable manager in the scene.
+    ///
+    /// # Details
+    ///
+    /// Only managers registered with `register_serializable_manager()` are saved, any other
+    /// managers are skipped. The first save logs a warning naming each skipped manager. The data
+    /// can be loaded back with `load()`.
+    pub fn save(&self) -> Vec<u8> {
+        if !self.warned_unsaved.get() {
+            self.warned_unsaved.set(true);
+            for (manager_id, name) in &self.manager_names {
+                if !self.serializers.contains_key(manager_id) {
+                    log::warn(&format!("Manager {} is not serializable, it will not be saved", name));
+                }
+            }
+        }
+
+        let mut bytes = SCENE_MAGIC.to_vec();
+        self.entity_manager.borrow().serialize(&mut bytes);
+        save_managers(&self.component_managers, &self.serializers, &mut bytes);
+        bytes
+    }
+
+    /// Replaces the scene's entities and components with the data written by `save()`.
+    ///
+    /// # Details
+    ///
+    /// A scene can't be created from the saved data alone since it also needs the resource
+    /// manager and audio source, so the data is loaded into an existing scene. Every manager in
+    /// the data replaces the registered manager of the same type, serializable managers that
+    /// aren't in the data and managers that aren't serializable are left unchanged.
+    ///
+    /// # Failures
+    ///
+    /// Returns `SerializeError::UnknownTypeTag` if the data contains a manager that isn't
+    /// registered as serializable in this scene, or another `SerializeError` if the data is
+    /// malformed. The scene is only modified if the whole of the data loads successfully.
+    pub fn load(&mut self, bytes: &[u8]) -> Result<(), SerializeError> {
+        let mut reader = ByteReader::new(bytes);
+        if try!(reader.read_bytes(SCENE_MAGIC.len()).map_err(|_| SerializeError::InvalidHeader)) != SCENE_MAGIC {
+            return Err(SerializeError::InvalidHeader);
+        }
+
+        let entity_manager = try!(EntityManager::deserialize(&mut reader));
+        let managers = try!(load_managers(&self.serializers, &mut reader));
+        if !reader.is_empty() {
+            return Err(SerializeError::InvalidData { offset: reader.offset() });
+        }
+
+        *self.entity_manager.borrow_mut() = entity_manager;
+        for (manager_id, manager) in managers {
+            *self.component_managers[&manager_id].borrow_mut() = manager;
+        }
+
+        Ok(())
+    }
+
+    /// Retrieves the mask representing the components managed by `T`.
+    ///
+    /// # Details
+    ///
+    /// If no manager of type `T` is registered the returned mask doesn't match any entity, so
+    /// querying with it always yields nothing.
+    pub fn component_mask<T: ComponentManager>(&self) -> ComponentMask {
+        match self.component_masks.get(&manager_id::<T>()) {
+            Some(&mask) => mask,
+            None => ComponentMask::from_bit(MAX_COMPONENT_MANAGERS),
+        }
+    }
+
+    /// Retrieves every entity that has all of the components in `mask`.
+    ///
+    /// # Details
+    ///
+    /// Each manager in the mask is checked with `ComponentManager::contains()`. The managers are
+    /// only borrowed while building the result, so they may be freely accessed while iterating
+    /// over it. An empty mask matches every live entity.
+    pub fn query(&self, mask: ComponentMask) -> ::std::vec::IntoIter<Entity> {
+        let registered = self.component_masks.values().fold(ComponentMask::empty(), |all, &mask| all | mask);
+        if !registered.contains(mask) {
+            return Vec::new().into_iter();
+        }
+
+        let managers: Vec<Ref<Box<ComponentManager>>> =
+            self.component_managers
+            .iter()
+            .filter(|&(manager_id, _)| mask.contains(self.component_masks[manager_id]))
+            .map(|(_, manager)| manager.borrow())
+            .collect();
+
+        let managers: Vec<&ComponentManager> = managers.iter().map(|manager| &***manager).collect();
+        let candidates = self.entity_manager.borrow().live_entities();
+        matching_entities(candidates, &*managers)
     }
 
     pub fn get_manager<T: ComponentManager>(&self) -> ManagerRef<T> {
@@ -114,14 +299,50 @@ impl Scene {
         }
     }
 
+    /// Registers a copy of `old_scene`'s manager of type `T`, e.g. when reloading the scene.
+    ///
+    /// # Details
+    ///
+    /// The manager keeps the same component mask it had in `old_scene`, so masks retrieved with
+    /// `component_mask()` before the reload remain valid.
     pub fn reload_manager<T: ComponentManager + Clone>(&mut self, old_scene: &Scene) {
-        self.register_manager(old_scene.get_manager::<T>().clone());
+        self.reregister_manager(old_scene.get_manager::<T>().clone(), old_scene);
+    }
+
+    /// Like `reload_manager()`, but also registers the manager's serializer, see
+    /// `register_serializable_manager()`.
+    pub fn reload_serializable_manager<T>(&mut self, old_scene: &Scene)
+        where T: ComponentManager + Clone + Serialize + Deserialize
+    {
+        self.reregister_manager(old_scene.get_manager::<T>().clone(), old_scene);
+        self.serializers.insert(manager_id::<T>(), ManagerSerializer::of::<T>());
     }
 
     pub fn create_entity(&self) -> Entity {
         self.entity_manager.borrow_mut().create()
     }
 
+    /// Checks if the entity has been created and not yet destroyed.
+    ///
+    /// # Details
+    ///
+    /// Destroyed entities stay alive until the end of the frame. After that the entity is no
+    /// longer alive even once its slot is reused by a new entity, see `EntityAllocator` for the
+    /// limits of this.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entity_manager.borrow().is_alive(entity)
+    }
+
+    /// Begins building a new entity with a set of components.
+    ///
+    /// The entity is not created until `EntityBuilder::build()` is called.
+    pub fn build_entity(&self) -> EntityBuilder {
+        EntityBuilder {
+            scene: self,
+            components: EntityComponents::default(),
+        }
+    }
+
     pub fn resource_manager(&self) -> &ResourceManager {
         &*self.resource_manager
     }
@@ -146,6 +367,18 @@ impl Scene {
         self.entity_manager.borrow_mut().mark_for_destroy(entity);
     }
 
+    /// Invokes `ComponentManager::update()` on every registered manager.
+    ///
+    /// # Details
+    ///
+    /// Managers are updated in no particular order, so managers should not depend on other
+    /// managers having been updated first.
+    pub fn update_managers(&self, delta: f32) {
+        for (_, manager) in self.component_managers.iter() {
+            manager.borrow_mut().update(self, delta);
+        }
+    }
+
     pub fn destroy_marked(&self) {
         for (_, manager) in self.component_managers.iter() {
             manager.borrow_mut().destroy_marked();
@@ -155,6 +388,222 @@ impl Scene {
     }
 }
 
+/// Filters `candidates` down to the entities that have a component in every one of `managers`.
+fn matching_entities(candidates: Vec<Entity>, managers: &[&ComponentManager]) -> ::std::vec::IntoIter<Entity> {
+    candidates
+        .into_iter()
+        .filter(|&entity| managers.iter().all(|manager| manager.contains(entity)))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// The functions used to save and load a manager registered with
+/// `Scene::register_serializable_manager()`.
+struct ManagerSerializer {
+    /// Identifies the manager's data so that `load()` can route it back to the right manager.
+    tag: &'static str,
+    save: fn(&ComponentManager, &mut Vec<u8>),
+    load: fn(&mut ByteReader) -> Result<Box<ComponentManager>, SerializeError>,
+}
+
+impl ManagerSerializer {
+    fn of<T: ComponentManager + Serialize + Deserialize>() -> ManagerSerializer {
+        ManagerSerializer {
+            tag: type_name::<T>(),
+            save: save_manager::<T>,
+            load: load_manager::<T>,
+        }
+    }
+}
+
+impl Debug for ManagerSerializer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "ManagerSerializer({})", self.tag)
+    }
+}
+
+fn save_manager<T: ComponentManager + Serialize>(manager: &ComponentManager, bytes: &mut Vec<u8>) {
+    let manager: &T = unsafe { downcast_manager(manager) };
+    manager.serialize(bytes);
+}
+
+fn load_manager<T: ComponentManager + Deserialize>(reader: &mut ByteReader) -> Result<Box<ComponentManager>, SerializeError> {
+    let manager = try!(T::deserialize(reader));
+    Ok(Box::new(manager))
+}
+
+/// Writes every manager that has a serializer, each prefixed with its type tag and length.
+///
+/// Managers without a serializer are skipped, `Scene::save()` is responsible for warning about
+/// them.
+fn save_managers(
+    managers: &HashMap<ManagerId, RefCell<Box<ComponentManager>>>,
+    serializers: &HashMap<ManagerId, ManagerSerializer>,
+    bytes: &mut Vec<u8>,
+) {
+    let saved: Vec<_> =
+        managers
+        .iter()
+        .filter_map(|(manager_id, manager)| serializers.get(manager_id).map(|serializer| (serializer, manager)))
+        .collect();
+
+    write_u32(bytes, saved.len() as u32);
+    for (serializer, manager) in saved {
+        let mut manager_bytes = Vec::new();
+        (serializer.save)(&**manager.borrow(), &mut manager_bytes);
+
+        String::from(serializer.tag).serialize(bytes);
+        write_u32(bytes, manager_bytes.len() as u32);
+        bytes.extend(&manager_bytes);
+    }
+}
+
+/// Reads the managers written by `save_managers()`, matching each one to a serializer by its tag.
+fn load_managers(
+    serializers: &HashMap<ManagerId, ManagerSerializer>,
+    reader: &mut ByteReader,
+) -> Result<Vec<(ManagerId, Box<ComponentManager>)>, SerializeError> {
+    let count = try!(reader.read_u32());
+
+    let mut managers: Vec<(ManagerId, Box<ComponentManager>)> = Vec::new();
+    for _ in 0..count {
+        let tag_offset = reader.offset();
+        let tag = try!(String::deserialize(reader));
+        let entry = serializers.iter().find(|&(_, serializer)| serializer.tag == tag);
+        let (&manager_id, serializer) = match entry {
+            Some(entry) => entry,
+            None => return Err(SerializeError::UnknownTypeTag(tag)),
+        };
+
+        if managers.iter().any(|&(loaded_id, _)| loaded_id == manager_id) {
+            return Err(SerializeError::InvalidData { offset: tag_offset });
+        }
+
+        // The manager must use exactly the number of bytes that were written for it.
+        let len = try!(reader.read_u32()) as usize;
+        let start = reader.offset();
+        let manager = try!((serializer.load)(reader));
+        if reader.offset() != start + len {
+            return Err(SerializeError::InvalidData { offset: start });
+        }
+
+        managers.push((manager_id, manager));
+    }
+
+    Ok(managers)
+}
+
+/// Builds an entity and its components in a single expression.
+///
+/// # Details
+///
+/// Created with `Scene::build_entity()`. None of the components are added to the scene until
+/// `build()` is called, so a builder that is dropped without being built has no effect on the
+/// scene.
+///
+/// ```ignore
+/// let camera = scene.build_entity()
+///     .with_transform(transform)
+///     .with_camera(PI / 3.0, 1.0, 0.001, 100.0)
+///     .child_of(player)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct EntityBuilder<'a> {
+    scene: &'a Scene,
+    components: EntityComponents,
+}
+
+impl<'a> EntityBuilder<'a> {
+    /// Gives the entity a transform with the position, rotation, and scale of `transform`.
+    pub fn with_transform(mut self, transform: Transform) -> EntityBuilder<'a> {
+        self.components.transform = Some(transform);
+        self
+    }
+
+    /// Gives the entity a camera with the specified perspective projection.
+    pub fn with_camera(mut self, fov: f32, aspect: f32, near: f32, far: f32) -> EntityBuilder<'a> {
+        self.components.camera = Some(Camera::new(fov, aspect, near, far));
+        self
+    }
+
+    /// Gives the entity a camera with the specified orthographic projection.
+    pub fn with_orthographic_camera(mut self, size: f32, aspect: f32, near: f32, far: f32) -> EntityBuilder<'a> {
+        self.components.camera = Some(Camera::orthographic(size, aspect, near, far));
+        self
+    }
+
+    /// Makes the entity a child of `parent` in the transform hierarchy.
+    ///
+    /// If no transform has been specified with `with_transform()` the entity is given a default
+    /// transform.
+    pub fn child_of(mut self, parent: Entity) -> EntityBuilder<'a> {
+        self.components.parent = Some(parent);
+        self
+    }
+
+    /// Creates the entity and assigns all of its components.
+    ///
+    /// # Failures
+    ///
+    /// Returns `BuildEntityError::ParentNotFound` if the parent specified with `child_of()` does
+    /// not have a transform. In that case no entity is created.
+    pub fn build(self) -> Result<Entity, BuildEntityError> {
+        let mut transform_manager = self.scene.get_manager_mut::<TransformManager>();
+        let mut camera_manager = self.scene.get_manager_mut::<CameraManager>();
+
+        try!(self.components.validate(&transform_manager));
+
+        let entity = self.scene.create_entity();
+        self.components.assign(entity, &mut transform_manager, &mut camera_manager);
+        Ok(entity)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildEntityError {
+    /// Indicates that the entity passed to `EntityBuilder::child_of()` does not have a transform.
+    ParentNotFound(Entity),
+}
+
+/// The components that an `EntityBuilder` will assign to the entity.
+#[derive(Default)]
+struct EntityComponents {
+    transform: Option<Transform>,
+    camera: Option<Camera>,
+    parent: Option<Entity>,
+}
+
+impl EntityComponents {
+    fn validate(&self, transform_manager: &TransformManager) -> Result<(), BuildEntityError> {
+        match self.parent {
+            Some(parent) if !transform_manager.contains(parent) => {
+                Err(BuildEntityError::ParentNotFound(parent))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn assign(&self, entity: Entity, transform_manager: &mut TransformManager, camera_manager: &mut CameraManager) {
+        if self.transform.is_some() || self.parent.is_some() {
+            let mut transform = transform_manager.assign(entity);
+            if let Some(ref source) = self.transform {
+                transform.set_position(source.position());
+                transform.set_rotation(source.rotation());
+                transform.set_scale(source.scale());
+            }
+        }
+
+        if let Some(camera) = self.camera {
+            camera_manager.assign(entity, camera);
+        }
+
+        if let Some(parent) = self.parent {
+            transform_manager.set_child(parent, entity);
+        }
+    }
+}
+
 #[cfg(not(feature = "hotloading"))]
 fn manager_id<T: ComponentManager>() -> ManagerId {
     ::std::any::TypeId::of::<T>()
@@ -234,3 +683,245 @@ unsafe fn downcast_manager_mut<'a, T: ComponentManager>(manager: &'a mut Compone
     // Extract the data pointer.
     mem::transmute(to.data)
 }
+
+/// Creates a scene that doesn't need a window or renderer, so that tests can use a full scene.
+#[cfg(test)]
+fn headless_scene() -> Scene {
+    let audio_source = ::bs_audio::init().expect("Failed to initialize audio for test scene");
+    Scene::new(&Rc::new(ResourceManager::headless()), audio_source)
+}
+
+#[test]
+fn entity_components_parented_camera() {
+    use math::*;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut camera_manager = CameraManager::new();
+
+    let parent = entity_manager.create();
+    let entity = entity_manager.create();
+    transform_manager.assign(parent);
+
+    let mut transform = Transform::new();
+    transform.set_position(Point::new(1.0, 2.0, 3.0));
+
+    let components = EntityComponents {
+        transform: Some(transform),
+        camera: Some(Camera::new(1.0, 1.0, 0.001, 100.0)),
+        parent: Some(parent),
+    };
+    assert_eq!(components.validate(&transform_manager), Ok(()));
+    components.assign(entity, &mut transform_manager, &mut camera_manager);
+
+    assert!(transform_manager.contains(entity));
+    assert_eq!(transform_manager.get(entity).position(), Point::new(1.0, 2.0, 3.0));
+    assert!(camera_manager.get(entity).is_some());
+
+    let mut children = Vec::new();
+    transform_manager.walk_children(parent, &mut |child| children.push(child));
+    assert_eq!(children, vec![parent, entity]);
+}
+
+#[test]
+fn entity_components_missing_parent() {
+    let mut entity_manager = EntityManager::new();
+    let transform_manager = TransformManager::new();
+
+    let parent = entity_manager.create();
+    let components = EntityComponents {
+        parent: Some(parent),
+        .. EntityComponents::default()
+    };
+    assert_eq!(components.validate(&transform_manager), Err(BuildEntityError::ParentNotFound(parent)));
+}
+
+#[test]
+fn query_transform_and_camera() {
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut camera_manager = CameraManager::new();
+
+    let transform_only = entity_manager.create();
+    let camera_only = entity_manager.create();
+    let both = entity_manager.create();
+    let neither = entity_manager.create();
+
+    transform_manager.assign(transform_only);
+    transform_manager.assign(both);
+    camera_manager.assign(camera_only, Camera::new(1.0, 1.0, 0.001, 100.0));
+    camera_manager.assign(both, Camera::new(1.0, 1.0, 0.001, 100.0));
+
+    let candidates = entity_manager.live_entities();
+    let matches: Vec<Entity> = matching_entities(candidates.clone(), &[&transform_manager as &ComponentManager, &camera_manager as &ComponentManager]).collect();
+    assert_eq!(matches, vec![both]);
+
+    let matches: Vec<Entity> = matching_entities(candidates.clone(), &[&transform_manager as &ComponentManager]).collect();
+    assert_eq!(matches, vec![transform_only, both]);
+
+    // No managers matches everything.
+    let matches: Vec<Entity> = matching_entities(candidates, &[]).collect();
+    assert_eq!(matches, vec![transform_only, camera_only, both, neither]);
+}
+
+#[test]
+fn save_and_load_managers() {
+    use math::*;
+    use component::DataManager;
+
+    let mut entity_manager = EntityManager::new();
+    let mut transform_manager = TransformManager::new();
+    let mut health_manager = DataManager::<u32>::new();
+    let camera_manager = CameraManager::new();
+
+    let parent = entity_manager.create();
+    let child = entity_manager.create();
+    transform_manager.assign(parent).set_position(Point::new(1.0, 2.0, 3.0));
+    transform_manager.assign(child).set_scale(Vector3::new(2.0, 2.0, 2.0));
+    transform_manager.set_child(parent, child);
+    health_manager.insert(parent, 100);
+    health_manager.insert(child, 25);
+
+    let mut managers: HashMap<ManagerId, RefCell<Box<ComponentManager>>> = HashMap::new();
+    managers.insert(manager_id::<TransformManager>(), RefCell::new(Box::new(transform_manager)));
+    managers.insert(manager_id::<DataManager<u32>>(), RefCell::new(Box::new(health_manager)));
+    managers.insert(manager_id::<CameraManager>(), RefCell::new(Box::new(camera_manager)));
+
+    let mut serializers = HashMap::new();
+    serializers.insert(manager_id::<TransformManager>(), ManagerSerializer::of::<TransformManager>());
+    serializers.insert(manager_id::<DataManager<u32>>(), ManagerSerializer::of::<DataManager<u32>>());
+
+    let mut bytes = Vec::new();
+    save_managers(&managers, &serializers, &mut bytes);
+
+    // The camera manager isn't serializable so only the other two are loaded.
+    let mut reader = ByteReader::new(&bytes);
+    let loaded = load_managers(&serializers, &mut reader).unwrap();
+    assert!(reader.is_empty());
+    assert_eq!(loaded.len(), 2);
+
+    for (loaded_id, manager) in loaded {
+        if loaded_id == manager_id::<TransformManager>() {
+            let transform_manager: &TransformManager = unsafe { downcast_manager(&*manager) };
+            assert_eq!(transform_manager.get(parent).position(), Point::new(1.0, 2.0, 3.0));
+            assert_eq!(transform_manager.get(child).scale(), Vector3::new(2.0, 2.0, 2.0));
+            assert_eq!(transform_manager.root_of(child), Some(parent));
+            assert_eq!(transform_manager.depth(child), Some(1));
+        } else {
+            let health_manager: &DataManager<u32> = unsafe { downcast_manager(&*manager) };
+            assert_eq!(health_manager.get(parent), Some(&100));
+            assert_eq!(health_manager.get(child), Some(&25));
+            assert_eq!(health_manager.len(), 2);
+        }
+    }
+
+    // Loading data for a manager that isn't registered is an error rather than a panic.
+    serializers.remove(&manager_id::<DataManager<u32>>());
+    let result = load_managers(&serializers, &mut ByteReader::new(&bytes));
+    assert_eq!(result.err(), Some(SerializeError::UnknownTypeTag(String::from(type_name::<DataManager<u32>>()))));
+}
+
+#[test]
+fn update_managers_passes_delta() {
+    #[derive(Debug, Default)]
+    struct DeltaManager {
+        deltas: Vec<f32>,
+        transform_counts: Vec<usize>,
+    }
+
+    impl ComponentManager for DeltaManager {
+        fn destroy_all(&self, _: Entity) {}
+        fn destroy_marked(&mut self) {}
+
+        fn update(&mut self, scene: &Scene, delta: f32) {
+            self.deltas.push(delta);
+
+            // Other managers can be accessed through the scene during the update.
+            self.transform_counts.push(scene.get_manager::<TransformManager>().roots().count());
+        }
+    }
+
+    let mut scene = headless_scene();
+    scene.register_manager(DeltaManager::default());
+
+    let entity = scene.create_entity();
+    scene.get_manager_mut::<TransformManager>().assign(entity);
+
+    scene.update_managers(0.016);
+    scene.update_managers(0.5);
+
+    let manager = scene.get_manager::<DeltaManager>();
+    assert_eq!(manager.deltas, vec![0.016, 0.5]);
+    assert_eq!(manager.transform_counts, vec![1, 1]);
+}
+
+#[test]
+fn scene_save_and_load() {
+    use math::*;
+    use component::transform::ScaleConstraint;
+
+    let scene = headless_scene();
+    let parent = scene.create_entity();
+    let child = scene.create_entity();
+    {
+        let mut transform_manager = scene.get_manager_mut::<TransformManager>();
+        transform_manager.assign(parent).set_position(Point::new(1.0, 2.0, 3.0));
+        transform_manager.assign(child).set_scale(Vector3::new(1.0, 2.0, 3.0));
+        transform_manager.set_child(parent, child);
+
+        // The constraint is set after the scale, so the saved scale is still non-uniform.
+        transform_manager.set_scale_constraint(ScaleConstraint::UniformAssert);
+    }
+    let bytes = scene.save();
+
+    let mut loaded = headless_scene();
+    loaded.load(&bytes).unwrap();
+    assert!(loaded.is_alive(parent));
+    assert!(loaded.is_alive(child));
+    {
+        let transform_manager = loaded.get_manager::<TransformManager>();
+        assert_eq!(transform_manager.get(parent).position(), Point::new(1.0, 2.0, 3.0));
+        assert_eq!(transform_manager.get(child).scale(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(transform_manager.root_of(child), Some(parent));
+        assert_eq!(transform_manager.scale_constraint(), ScaleConstraint::UniformAssert);
+    }
+
+    // Data that wasn't written by `save()` is rejected without modifying the scene.
+    assert_eq!(loaded.load(b"not a scene"), Err(SerializeError::InvalidHeader));
+    assert!(loaded.is_alive(parent));
+}
+
+#[test]
+fn component_masks_survive_clone() {
+    #[derive(Debug, Clone)]
+    struct CustomManager;
+
+    impl ComponentManager for CustomManager {
+        fn destroy_all(&self, _: Entity) {}
+        fn destroy_marked(&mut self) {}
+    }
+
+    let mut scene = headless_scene();
+    scene.register_manager(CustomManager);
+
+    let mut cloned = scene.clone(&Rc::new(ResourceManager::headless()));
+    cloned.reload_manager::<CustomManager>(&scene);
+
+    assert_eq!(cloned.component_masks, scene.component_masks);
+    assert_eq!(cloned.component_mask::<MeshManager>(), scene.component_mask::<MeshManager>());
+    assert_eq!(cloned.component_mask::<AudioSourceManager>(), scene.component_mask::<AudioSourceManager>());
+    assert_eq!(cloned.component_mask::<CustomManager>(), scene.component_mask::<CustomManager>());
+
+    // Managers registered after the clone get a bit that isn't used by any reloaded manager.
+    #[derive(Debug)]
+    struct NewManager;
+
+    impl ComponentManager for NewManager {
+        fn destroy_all(&self, _: Entity) {}
+        fn destroy_marked(&mut self) {}
+    }
+
+    cloned.register_manager(NewManager);
+    let new_mask = cloned.component_mask::<NewManager>();
+    assert!(scene.component_masks.values().all(|&mask| mask != new_mask));
+}
@@ -0,0 +1,212 @@
+use ecs::System;
+use scene::Scene;
+
+/// How often a `Schedule` triggers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interval {
+    /// Trigger once every N frames.
+    Frames(u32),
+
+    /// Trigger each time the specified number of seconds has elapsed.
+    Seconds(f32),
+}
+
+/// The result of advancing a `Schedule` by one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledRuns {
+    /// The number of times to run this frame.
+    pub count: u32,
+
+    /// The delta time to use for each run.
+    pub delta: f32,
+}
+
+/// Tracks when something that runs periodically should next run.
+///
+/// # Details
+///
+/// Frame intervals count calls to `advance()` so they're fully deterministic. Time intervals
+/// accumulate the delta passed to `advance()`. If a single long frame covers several time
+/// intervals the schedule only triggers once, with the time since it last triggered as the delta,
+/// and the skipped intervals are dropped. With catch up enabled it instead triggers once for
+/// every elapsed interval, each with the interval as the delta. Catch up has no effect on frame
+/// intervals since a frame can never cover more than one of them.
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    interval: Interval,
+    catch_up: bool,
+    frames: u32,
+    elapsed: f32,
+    since_last_run: f32,
+}
+
+impl Schedule {
+    /// Creates a schedule that triggers every `frames` frames, starting on frame `frames`.
+    ///
+    /// Panics if `frames` is zero.
+    pub fn every_frames(frames: u32) -> Schedule {
+        assert!(frames > 0, "Schedule interval must be at least one frame");
+        Schedule::new(Interval::Frames(frames))
+    }
+
+    /// Creates a schedule that triggers every `seconds` seconds.
+    ///
+    /// Panics if `seconds` isn't positive.
+    pub fn every_seconds(seconds: f32) -> Schedule {
+        assert!(seconds > 0.0, "Schedule interval must be positive but was {}", seconds);
+        Schedule::new(Interval::Seconds(seconds))
+    }
+
+    fn new(interval: Interval) -> Schedule {
+        Schedule {
+            interval: interval,
+            catch_up: false,
+            frames: 0,
+            elapsed: 0.0,
+            since_last_run: 0.0,
+        }
+    }
+
+    pub fn interval(&self) -> Interval {
+        self.interval
+    }
+
+    pub fn catch_up(&self) -> bool {
+        self.catch_up
+    }
+
+    /// Sets whether a long frame triggers once for every time interval it covers.
+    pub fn set_catch_up(&mut self, catch_up: bool) {
+        self.catch_up = catch_up;
+    }
+
+    /// Advances the schedule by one frame that took `delta` seconds.
+    pub fn advance(&mut self, delta: f32) -> ScheduledRuns {
+        self.since_last_run += delta;
+
+        let runs = match self.interval {
+            Interval::Frames(frames) => {
+                self.frames += 1;
+                if self.frames >= frames {
+                    self.frames = 0;
+                    ScheduledRuns { count: 1, delta: self.since_last_run }
+                } else {
+                    ScheduledRuns { count: 0, delta: 0.0 }
+                }
+            },
+            Interval::Seconds(seconds) => {
+                self.elapsed += delta;
+                let count = (self.elapsed / seconds) as u32;
+                self.elapsed -= count as f32 * seconds;
+
+                if count == 0 {
+                    ScheduledRuns { count: 0, delta: 0.0 }
+                } else if self.catch_up {
+                    ScheduledRuns { count: count, delta: seconds }
+                } else {
+                    ScheduledRuns { count: 1, delta: self.since_last_run }
+                }
+            },
+        };
+
+        if runs.count > 0 {
+            self.since_last_run = 0.0;
+        }
+
+        runs
+    }
+}
+
+/// A system that only runs on a `Schedule` rather than every frame.
+///
+/// # Details
+///
+/// Register the wrapper in place of the system itself, e.g.
+/// `engine.register_system(run_every(10, repath_system))`. The wrapped system receives the time
+/// since it last ran as its delta, see `Schedule` for the details.
+pub struct PeriodicSystem<T: System> {
+    system: T,
+    schedule: Schedule,
+}
+
+impl<T: System> PeriodicSystem<T> {
+    pub fn new(system: T, schedule: Schedule) -> PeriodicSystem<T> {
+        PeriodicSystem {
+            system: system,
+            schedule: schedule,
+        }
+    }
+
+    /// Enables catch up on the system's schedule, see `Schedule::set_catch_up()`.
+    pub fn with_catch_up(mut self) -> PeriodicSystem<T> {
+        self.schedule.set_catch_up(true);
+        self
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    pub fn system(&self) -> &T {
+        &self.system
+    }
+
+    pub fn system_mut(&mut self) -> &mut T {
+        &mut self.system
+    }
+}
+
+impl<T: System> System for PeriodicSystem<T> {
+    fn update(&mut self, scene: &Scene, delta: f32) {
+        let runs = self.schedule.advance(delta);
+        for _ in 0..runs.count {
+            self.system.update(scene, runs.delta);
+        }
+    }
+}
+
+/// Wraps `system` so that it only runs once every `frames` frames.
+pub fn run_every<T: System>(frames: u32, system: T) -> PeriodicSystem<T> {
+    PeriodicSystem::new(system, Schedule::every_frames(frames))
+}
+
+/// Wraps `system` so that it only runs once every `seconds` seconds.
+pub fn run_every_seconds<T: System>(seconds: f32, system: T) -> PeriodicSystem<T> {
+    PeriodicSystem::new(system, Schedule::every_seconds(seconds))
+}
+
+#[test]
+fn frame_interval() {
+    let mut every_frame = Schedule::every_frames(1);
+    for _ in 0..5 {
+        assert_eq!(every_frame.advance(0.5), ScheduledRuns { count: 1, delta: 0.5 });
+    }
+
+    let mut every_third = Schedule::every_frames(3);
+    let counts: Vec<u32> = (0..9).map(|_| every_third.advance(0.25).count).collect();
+    assert_eq!(counts, vec![0, 0, 1, 0, 0, 1, 0, 0, 1]);
+
+    // The system receives the time since it last ran.
+    every_third.advance(0.25);
+    every_third.advance(0.5);
+    assert_eq!(every_third.advance(1.0), ScheduledRuns { count: 1, delta: 1.75 });
+}
+
+#[test]
+fn time_interval() {
+    let mut schedule = Schedule::every_seconds(1.0);
+    let counts: Vec<u32> = [0.5, 0.25, 0.5, 0.5, 0.25].iter().map(|&delta| schedule.advance(delta).count).collect();
+    assert_eq!(counts, vec![0, 0, 1, 0, 1]);
+
+    // A long frame covering several intervals only runs once.
+    assert_eq!(schedule.advance(3.5), ScheduledRuns { count: 1, delta: 3.5 });
+
+    // The leftover half interval carries over.
+    assert_eq!(schedule.advance(0.5).count, 1);
+
+    let mut schedule = Schedule::every_seconds(1.0);
+    schedule.set_catch_up(true);
+    assert_eq!(schedule.advance(3.5), ScheduledRuns { count: 3, delta: 1.0 });
+    assert_eq!(schedule.advance(0.25).count, 0);
+    assert_eq!(schedule.advance(0.25), ScheduledRuns { count: 1, delta: 1.0 });
+}
@@ -0,0 +1,281 @@
+//! A simple byte-based serialization format for saving and loading scene data.
+//!
+//! All values are written little-endian without any padding or type information, so data must
+//! be read back in exactly the order it was written. See `Scene::save()` and `Scene::load()`.
+
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+
+use math::*;
+use math::bytes::UnexpectedEnd;
+
+use ecs::Entity;
+
+pub use math::bytes::ByteReader;
+
+/// A value that can be written to a byte buffer.
+pub trait Serialize {
+    /// Appends the serialized form of `self` to `bytes`.
+    fn serialize(&self, bytes: &mut Vec<u8>);
+}
+
+/// A value that can be read back from the bytes written by `Serialize`.
+pub trait Deserialize: Sized {
+    fn deserialize(reader: &mut ByteReader) -> Result<Self, SerializeError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializeError {
+    /// The data doesn't start with the expected header.
+    InvalidHeader,
+
+    /// The data ended before a complete value could be read.
+    UnexpectedEnd,
+
+    /// The value starting at `offset` is malformed.
+    InvalidData {
+        offset: usize,
+    },
+
+    /// The data contains a manager with type tag `tag`, but no manager with that tag is
+    /// registered as serializable.
+    UnknownTypeTag(String),
+}
+
+impl Display for SerializeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SerializeError::InvalidHeader =>
+                write!(f, "Data does not start with the expected header"),
+            SerializeError::UnexpectedEnd =>
+                write!(f, "Data ended unexpectedly"),
+            SerializeError::InvalidData { offset } =>
+                write!(f, "Invalid data at offset {}", offset),
+            SerializeError::UnknownTypeTag(ref tag) =>
+                write!(f, "No serializable manager is registered with type tag {:?}", tag),
+        }
+    }
+}
+
+impl From<UnexpectedEnd> for SerializeError {
+    fn from(_: UnexpectedEnd) -> SerializeError {
+        SerializeError::UnexpectedEnd
+    }
+}
+
+/// Writes `value` to `bytes` in little-endian order.
+pub fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    for shift in 0..4 {
+        bytes.push((value >> (shift * 8)) as u8);
+    }
+}
+
+impl Serialize for u8 {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self);
+    }
+}
+
+impl Deserialize for u8 {
+    fn deserialize(reader: &mut ByteReader) -> Result<u8, SerializeError> {
+        Ok(try!(reader.read_u8()))
+    }
+}
+
+impl Serialize for bool {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self as u8);
+    }
+}
+
+impl Deserialize for bool {
+    fn deserialize(reader: &mut ByteReader) -> Result<bool, SerializeError> {
+        let offset = reader.offset();
+        match try!(reader.read_u8()) {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SerializeError::InvalidData { offset: offset }),
+        }
+    }
+}
+
+impl Serialize for u32 {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        write_u32(bytes, *self);
+    }
+}
+
+impl Deserialize for u32 {
+    fn deserialize(reader: &mut ByteReader) -> Result<u32, SerializeError> {
+        Ok(try!(reader.read_u32()))
+    }
+}
+
+impl Serialize for i32 {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        write_u32(bytes, *self as u32);
+    }
+}
+
+impl Deserialize for i32 {
+    fn deserialize(reader: &mut ByteReader) -> Result<i32, SerializeError> {
+        Ok(try!(reader.read_i32()))
+    }
+}
+
+impl Serialize for f32 {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        let bits: u32 = unsafe { mem::transmute(*self) };
+        write_u32(bytes, bits);
+    }
+}
+
+impl Deserialize for f32 {
+    fn deserialize(reader: &mut ByteReader) -> Result<f32, SerializeError> {
+        Ok(try!(reader.read_f32()))
+    }
+}
+
+impl Serialize for String {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        write_u32(bytes, self.len() as u32);
+        bytes.extend(self.as_bytes());
+    }
+}
+
+impl Deserialize for String {
+    fn deserialize(reader: &mut ByteReader) -> Result<String, SerializeError> {
+        let offset = reader.offset();
+        let len = try!(reader.read_u32()) as usize;
+        let bytes = try!(reader.read_bytes(len));
+        String::from_utf8(bytes.to_vec()).map_err(|_| SerializeError::InvalidData { offset: offset })
+    }
+}
+
+impl<T: Serialize> Serialize for Option<T> {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        match *self {
+            Some(ref value) => {
+                true.serialize(bytes);
+                value.serialize(bytes);
+            },
+            None => false.serialize(bytes),
+        }
+    }
+}
+
+impl<T: Deserialize> Deserialize for Option<T> {
+    fn deserialize(reader: &mut ByteReader) -> Result<Option<T>, SerializeError> {
+        if try!(bool::deserialize(reader)) {
+            Ok(Some(try!(T::deserialize(reader))))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        write_u32(bytes, self.len() as u32);
+        for value in self {
+            value.serialize(bytes);
+        }
+    }
+}
+
+impl<T: Deserialize> Deserialize for Vec<T> {
+    fn deserialize(reader: &mut ByteReader) -> Result<Vec<T>, SerializeError> {
+        let len = try!(reader.read_u32());
+
+        // Don't trust the length for preallocating, a corrupted length would allocate a huge
+        // amount of memory before failing.
+        let mut values = Vec::new();
+        for _ in 0..len {
+            values.push(try!(T::deserialize(reader)));
+        }
+
+        Ok(values)
+    }
+}
+
+impl Serialize for Entity {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        write_u32(bytes, self.id());
+    }
+}
+
+impl Deserialize for Entity {
+    fn deserialize(reader: &mut ByteReader) -> Result<Entity, SerializeError> {
+        Ok(Entity::from_id(try!(reader.read_u32())))
+    }
+}
+
+impl Serialize for Vector3 {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        self.x.serialize(bytes);
+        self.y.serialize(bytes);
+        self.z.serialize(bytes);
+    }
+}
+
+impl Deserialize for Vector3 {
+    fn deserialize(reader: &mut ByteReader) -> Result<Vector3, SerializeError> {
+        Ok(try!(reader.read_vector3()))
+    }
+}
+
+impl Serialize for Point {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        self.x.serialize(bytes);
+        self.y.serialize(bytes);
+        self.z.serialize(bytes);
+    }
+}
+
+impl Deserialize for Point {
+    fn deserialize(reader: &mut ByteReader) -> Result<Point, SerializeError> {
+        Ok(try!(reader.read_point()))
+    }
+}
+
+impl Serialize for Quaternion {
+    fn serialize(&self, bytes: &mut Vec<u8>) {
+        self.w.serialize(bytes);
+        self.x.serialize(bytes);
+        self.y.serialize(bytes);
+        self.z.serialize(bytes);
+    }
+}
+
+impl Deserialize for Quaternion {
+    fn deserialize(reader: &mut ByteReader) -> Result<Quaternion, SerializeError> {
+        Ok(try!(reader.read_quaternion()))
+    }
+}
+
+#[test]
+fn primitive_round_trip() {
+    let mut bytes = Vec::new();
+    7u8.serialize(&mut bytes);
+    true.serialize(&mut bytes);
+    0xdeadbeefu32.serialize(&mut bytes);
+    (-12i32).serialize(&mut bytes);
+    1.5f32.serialize(&mut bytes);
+    String::from("gunship").serialize(&mut bytes);
+    Some(Entity::from_id(3)).serialize(&mut bytes);
+    vec![Vector3::new(1.0, 2.0, 3.0)].serialize(&mut bytes);
+
+    let mut reader = ByteReader::new(&bytes);
+    assert_eq!(u8::deserialize(&mut reader), Ok(7));
+    assert_eq!(bool::deserialize(&mut reader), Ok(true));
+    assert_eq!(u32::deserialize(&mut reader), Ok(0xdeadbeef));
+    assert_eq!(i32::deserialize(&mut reader), Ok(-12));
+    assert_eq!(f32::deserialize(&mut reader), Ok(1.5));
+    assert_eq!(String::deserialize(&mut reader), Ok(String::from("gunship")));
+    assert_eq!(Option::<Entity>::deserialize(&mut reader), Ok(Some(Entity::from_id(3))));
+    assert_eq!(Vec::<Vector3>::deserialize(&mut reader), Ok(vec![Vector3::new(1.0, 2.0, 3.0)]));
+    assert!(reader.is_empty());
+
+    // Reading past the end is an error.
+    assert_eq!(u32::deserialize(&mut reader), Err(SerializeError::UnexpectedEnd));
+}
@@ -0,0 +1,116 @@
+/// Tracks the game time for the current frame, supporting slow motion and pausing.
+///
+/// # Details
+///
+/// Gameplay systems should use `delta_seconds()`, which is scaled by the current time scale.
+/// Anything that should keep running at real time regardless of slow motion or pausing (e.g.
+/// menus, debug cameras) should use `unscaled_delta_seconds()` instead.
+#[derive(Debug, Clone)]
+pub struct Time {
+    unscaled_delta: f32,
+    time_scale: f32,
+
+    /// The time scale from before `pause()` was called, if the clock is paused.
+    paused_scale: Option<f32>,
+}
+
+impl Time {
+    pub fn new(unscaled_delta: f32) -> Time {
+        Time {
+            unscaled_delta: unscaled_delta,
+            time_scale: 1.0,
+            paused_scale: None,
+        }
+    }
+
+    /// Retrieves the time in seconds since the last frame, scaled by the time scale.
+    pub fn delta_seconds(&self) -> f32 {
+        self.unscaled_delta * self.time_scale
+    }
+
+    /// Retrieves the real time in seconds since the last frame, ignoring the time scale.
+    pub fn unscaled_delta_seconds(&self) -> f32 {
+        self.unscaled_delta
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets the rate at which game time passes relative to real time.
+    ///
+    /// # Details
+    ///
+    /// A scale of 1.0 is real time, 0.5 is half speed, and 0.0 freezes game time. Game time
+    /// can't run backwards, so negative scales are not allowed. If the clock is paused the new
+    /// scale takes effect when the clock is resumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is negative.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        assert!(scale >= 0.0, "Time scale must not be negative, got {}", scale);
+
+        match self.paused_scale {
+            Some(ref mut paused_scale) => *paused_scale = scale,
+            None => self.time_scale = scale,
+        }
+    }
+
+    /// Freezes game time, remembering the current time scale so that it can be restored by
+    /// `resume()`. Has no effect if the clock is already paused.
+    pub fn pause(&mut self) {
+        if self.paused_scale.is_none() {
+            self.paused_scale = Some(self.time_scale);
+            self.time_scale = 0.0;
+        }
+    }
+
+    /// Restores the time scale from before the clock was paused. Has no effect if the clock
+    /// isn't paused.
+    pub fn resume(&mut self) {
+        if let Some(paused_scale) = self.paused_scale.take() {
+            self.time_scale = paused_scale;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_scale.is_some()
+    }
+}
+
+#[test]
+fn time_scale() {
+    let mut time = Time::new(0.1);
+    assert_eq!(time.delta_seconds(), 0.1);
+
+    time.set_time_scale(0.5);
+    assert_eq!(time.delta_seconds(), 0.05);
+    assert_eq!(time.unscaled_delta_seconds(), 0.1);
+}
+
+#[test]
+fn time_pause() {
+    let mut time = Time::new(0.1);
+    time.set_time_scale(0.5);
+
+    time.pause();
+    assert!(time.is_paused());
+    assert_eq!(time.delta_seconds(), 0.0);
+    assert_eq!(time.unscaled_delta_seconds(), 0.1);
+
+    // Changing the scale while paused applies once resumed.
+    time.set_time_scale(2.0);
+    assert_eq!(time.delta_seconds(), 0.0);
+
+    time.resume();
+    assert!(!time.is_paused());
+    assert_eq!(time.delta_seconds(), 0.2);
+}
+
+#[test]
+#[should_panic]
+fn time_negative_scale() {
+    let mut time = Time::new(0.1);
+    time.set_time_scale(-1.0);
+}